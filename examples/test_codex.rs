@@ -10,7 +10,8 @@ async fn main() {
             continue;
         }
         for prof in detector.list_profiles(*browser) {
-            if let Ok(cookies) = CookieReader::read_cookies(&prof, "chatgpt.com") {
+            if let Ok(decrypted) = CookieReader::read_cookies(&prof, "chatgpt.com") {
+                let cookies = decrypted.cookies;
                 let has_session = cookies
                     .iter()
                     .any(|c| c.name.starts_with("__Secure-next-auth.session-token"));
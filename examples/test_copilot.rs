@@ -10,7 +10,8 @@ async fn main() {
             continue;
         }
         for prof in detector.list_profiles(*browser) {
-            if let Ok(cookies) = CookieReader::read_cookies(&prof, "github.com") {
+            if let Ok(decrypted) = CookieReader::read_cookies(&prof, "github.com") {
+                let cookies = decrypted.cookies;
                 let has_user_session = cookies
                     .iter()
                     .any(|c| c.name == "user_session" || c.name == "__Host-user_session_same_site");
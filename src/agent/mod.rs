@@ -1,11 +1,57 @@
+mod output;
+mod signals;
+
+pub use output::{OutputCapture, OutputMatches};
+
 use crate::Cookie;
+use crate::browser::Profile;
 use crate::config::AgentConfig;
+use crate::cookie_store::CookieStore;
+use crate::error::SeherError;
 use chrono::{DateTime, Utc};
 use serde::Serialize;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
 
+#[derive(Clone)]
 pub struct Agent {
     pub config: AgentConfig,
     pub cookies: Vec<Cookie>,
+    /// Name of the `accounts` entry this agent runs as, if any.
+    pub account: Option<String>,
+    /// Directory the agent runs in, instead of seher's own.
+    pub working_dir: Option<PathBuf>,
+    /// Where to re-read the cookies when the provider rejects them.
+    cookie_store: Option<Arc<dyn CookieStore>>,
+    /// Profiles whose sessions are tried when the provider rejects the cookies.
+    fallback_profiles: Vec<Profile>,
+}
+
+type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// `(name, value)` of each cookie, to tell cookie sets apart.
+fn cookie_pairs(cookies: &[Cookie]) -> Vec<(String, String)> {
+    cookies
+        .iter()
+        .map(|c| (c.name.clone(), c.value.clone()))
+        .collect()
+}
+
+/// The live session `profile` holds for `domain`, read on a blocking thread.
+async fn read_profile_session(profile: Profile, domain: &str) -> Option<Vec<Cookie>> {
+    let domain = domain.to_string();
+    tokio::task::spawn_blocking(move || {
+        crate::session::read_session(
+            &profile,
+            &domain,
+            crate::session::session_cookie_names(&domain),
+        )
+    })
+    .await
+    .ok()
+    .flatten()
 }
 
 #[derive(Debug)]
@@ -21,15 +67,105 @@ pub struct UsageEntry {
     pub limited: bool,
     pub utilization: f64,
     pub resets_at: Option<DateTime<Utc>>,
+    /// Absolute amount left in this entry, in the provider's native unit.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining: Option<f64>,
+    /// Absolute allowance for this entry, in the same unit as `remaining`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<f64>,
+    /// Estimated time the window reaches 100% at the recent pace, from recorded history.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit_eta: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct AgentStatus {
     pub command: String,
     pub provider: Option<String>,
+    /// Subscription tier reported by the provider (e.g. `"Pro"`, `"Max 20x"`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan: Option<String>,
+    /// Signed-in user the usage belongs to (e.g. the claude.ai email), to tell accounts apart.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
     pub usage: Vec<UsageEntry>,
 }
 
+/// Record a usage sample in the history and the telemetry gauges.
+fn record_sample(provider: &str, account: &str, entries: &[UsageEntry]) {
+    crate::history::record(provider, account, entries);
+    #[cfg(feature = "cli")]
+    for entry in entries {
+        crate::telemetry::record_usage(
+            provider,
+            account,
+            &entry.entry_type,
+            entry.utilization,
+            entry.limited,
+        );
+    }
+}
+
+/// A cached "limited" response is stale once its reset time has passed.
+fn reset_has_passed(limited: bool, reset_time: Option<DateTime<Utc>>) -> bool {
+    limited && reset_time.is_some_and(|t| t <= Utc::now())
+}
+
+fn monthly_spend_entry(spend: &crate::budget::MonthlySpend) -> UsageEntry {
+    UsageEntry {
+        entry_type: "monthly_spend".to_string(),
+        limited: spend.is_limited(),
+        utilization: spend.utilization(),
+        resets_at: spend.resets_at,
+        remaining: Some(spend.remaining()),
+        limit: Some(spend.limit),
+        limit_eta: None,
+    }
+}
+
+/// Limited if any entry is, until the latest reset among the limited entries.
+fn limit_from_entries(entries: &[UsageEntry]) -> AgentLimit {
+    if entries.iter().any(|e| e.limited) {
+        let reset_time = entries
+            .iter()
+            .filter(|e| e.limited)
+            .map(|e| e.resets_at)
+            .max()
+            .flatten();
+        AgentLimit::Limited { reset_time }
+    } else {
+        AgentLimit::NotLimited
+    }
+}
+
+/// Run `fetch`, answering from the last cached snapshot for `key` instead when offline
+/// mode is on or the provider cannot be reached. Stale answers are announced as
+/// [warnings](crate::warning).
+async fn with_offline_fallback<T: serde::de::DeserializeOwned>(
+    provider: &str,
+    key: &str,
+    fetch: impl Future<Output = Result<T, SeherError>>,
+) -> Result<T, SeherError> {
+    let snapshot = || {
+        let (value, fetched_at) = crate::cache::UsageCache::current()?.last_snapshot::<T>(key)?;
+        crate::warning::warn(format_args!(
+            "{provider} is unreachable; using usage cached at {}",
+            fetched_at
+                .with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M")
+        ));
+        Some(value)
+    };
+    if crate::cache::offline() {
+        return snapshot()
+            .ok_or_else(|| format!("offline and no cached {provider} usage is available").into());
+    }
+    match fetch.await {
+        Err(e) if e.is_network_error() => snapshot().ok_or(e),
+        result => result,
+    }
+}
+
 fn codex_usage_entries(prefix: &str, limit: &crate::codex::CodexRateLimit) -> Vec<UsageEntry> {
     let has_limited_window = [
         limit.primary_window.as_ref(),
@@ -58,6 +194,9 @@ fn codex_usage_entries(prefix: &str, limit: &crate::codex::CodexRateLimit) -> Ve
                     || (fallback_reset.is_some() && resets_at == fallback_reset),
                 utilization: window.used_percent,
                 resets_at,
+                remaining: None,
+                limit: None,
+                limit_eta: None,
             });
         }
     }
@@ -68,16 +207,189 @@ fn codex_usage_entries(prefix: &str, limit: &crate::codex::CodexRateLimit) -> Ve
             limited: true,
             utilization: 100.0,
             resets_at: limit.next_reset_time(),
+            remaining: None,
+            limit: None,
+            limit_eta: None,
         });
     }
 
     entries
 }
 
+fn codex_response_entries(usage: &crate::codex::CodexUsageResponse) -> Vec<UsageEntry> {
+    let mut entries = codex_usage_entries("rate_limit", &usage.rate_limit);
+    if let Some(ref cr) = usage.code_review_rate_limit {
+        entries.extend(codex_usage_entries("code_review_rate_limit", cr));
+    }
+    entries
+}
+
+fn claude_usage_entries(usage: &crate::claude::UsageResponse) -> Vec<UsageEntry> {
+    let mut entries: Vec<UsageEntry> = usage
+        .all_windows()
+        .into_iter()
+        .map(|(name, w)| UsageEntry {
+            entry_type: name.to_string(),
+            limited: w.is_limited(),
+            utilization: w.utilization.unwrap_or(0.0),
+            resets_at: w.resets_at,
+            remaining: None,
+            limit: None,
+            limit_eta: None,
+        })
+        .collect();
+    if let Some(extra) = usage.extra_usage.as_ref().filter(|e| e.is_enabled) {
+        entries.push(UsageEntry {
+            entry_type: "extra_usage".to_string(),
+            limited: !extra.has_balance(),
+            utilization: extra.utilization.unwrap_or(0.0),
+            resets_at: None,
+            remaining: extra.remaining_credits(),
+            limit: extra.monthly_limit,
+            limit_eta: None,
+        });
+    }
+    entries
+}
+
+fn copilot_usage_entries(quota: &crate::copilot::CopilotQuota) -> Vec<UsageEntry> {
+    vec![
+        UsageEntry {
+            entry_type: "chat_utilization".to_string(),
+            limited: quota.chat_utilization >= 100.0,
+            utilization: quota.chat_utilization,
+            resets_at: quota.reset_time,
+            remaining: quota.chat_remaining,
+            limit: quota.chat_entitlement,
+            limit_eta: None,
+        },
+        UsageEntry {
+            entry_type: "premium_utilization".to_string(),
+            limited: quota.premium_utilization >= 100.0,
+            utilization: quota.premium_utilization,
+            resets_at: quota.reset_time,
+            remaining: quota.premium_remaining,
+            limit: quota.premium_entitlement,
+            limit_eta: None,
+        },
+    ]
+}
+
 impl Agent {
     #[must_use]
     pub fn new(config: AgentConfig, cookies: Vec<Cookie>) -> Self {
-        Self { config, cookies }
+        Self {
+            config,
+            cookies,
+            account: None,
+            working_dir: None,
+            cookie_store: None,
+            fallback_profiles: Vec::new(),
+        }
+    }
+
+    /// Build an agent whose cookies come from `store`. Providers that are not queried
+    /// through a website get no cookies and never touch the store.
+    ///
+    /// # Errors
+    ///
+    /// Returns the store's error if it cannot produce cookies for the provider's domain.
+    pub async fn from_store(
+        config: AgentConfig,
+        store: &impl CookieStore,
+    ) -> Result<Self, SeherError> {
+        let cookies = match config.resolve_domain() {
+            Some(domain) => store.cookies(domain).await?,
+            None => Vec::new(),
+        };
+        Ok(Self::new(config, cookies))
+    }
+
+    /// Re-read the cookies from `store` and retry once when the provider answers 401 or 403,
+    /// as the browser may have rotated the session since the cookies were read.
+    #[must_use]
+    pub fn with_cookie_store(mut self, store: Arc<dyn CookieStore>) -> Self {
+        self.cookie_store = Some(store);
+        self
+    }
+
+    /// Profiles to read another session from when the provider rejects the current one,
+    /// e.g. when the first profile with a session cookie holds a dead session.
+    #[must_use]
+    pub fn with_fallback_profiles(mut self, profiles: Vec<Profile>) -> Self {
+        self.fallback_profiles = profiles;
+        self
+    }
+
+    /// This agent with `cookies` in place of its own.
+    fn with_cookies(&self, cookies: Vec<Cookie>) -> Self {
+        Self {
+            config: self.config.clone(),
+            cookies,
+            account: self.account.clone(),
+            working_dir: self.working_dir.clone(),
+            cookie_store: None,
+            fallback_profiles: Vec::new(),
+        }
+    }
+
+    /// Run `call` on this agent and, while the provider rejects the cookies (401/403),
+    /// again with the cookies re-read from the cookie store and then with the session of
+    /// each fallback profile. Cookie sets that were already tried are skipped.
+    async fn with_auth_fallback<T>(
+        &self,
+        call: impl for<'a> Fn(&'a Self) -> BoxFuture<'a, Result<T, SeherError>>,
+    ) -> Result<T, SeherError> {
+        let mut result = call(self).await;
+        let Some(domain) = self.config.resolve_domain() else {
+            return result;
+        };
+        if result.as_ref().is_err_and(SeherError::is_auth_error)
+            && let Some(cache) = crate::cache::SessionCache::current()
+        {
+            cache.invalidate(domain);
+        }
+        let mut tried = vec![cookie_pairs(&self.cookies)];
+        let mut read_store = self.cookie_store.is_some();
+        let mut profiles = self.fallback_profiles.iter();
+        while result.as_ref().is_err_and(SeherError::is_auth_error) {
+            let cookies = if read_store {
+                read_store = false;
+                match &self.cookie_store {
+                    Some(store) => store.cookies(domain).await.ok(),
+                    None => None,
+                }
+            } else if let Some(profile) = profiles.next() {
+                read_profile_session(profile.clone(), domain).await
+            } else {
+                break;
+            };
+            let Some(cookies) = cookies else {
+                continue;
+            };
+            let pairs = cookie_pairs(&cookies);
+            if tried.contains(&pairs) {
+                continue;
+            }
+            tried.push(pairs);
+            let agent = self.with_cookies(cookies);
+            result = call(&agent).await;
+        }
+        result
+    }
+
+    /// Mark this agent as running under the named account.
+    #[must_use]
+    pub fn with_account(mut self, name: impl Into<String>) -> Self {
+        self.account = Some(name.into());
+        self
+    }
+
+    /// Run the agent (and its `pre_command`) in `dir`.
+    #[must_use]
+    pub fn with_working_dir(mut self, dir: PathBuf) -> Self {
+        self.working_dir = Some(dir);
+        self
     }
 
     #[must_use]
@@ -85,6 +397,25 @@ impl Agent {
         &self.config.command
     }
 
+    /// The GitHub username of a Copilot agent's browser session, when its cookies name one.
+    #[must_use]
+    pub fn github_login(&self) -> Option<&str> {
+        if self.config.resolve_provider() != Some("copilot") {
+            return None;
+        }
+        crate::copilot::CopilotClient::login(&self.cookies)
+    }
+
+    /// The command, followed by the account name (or the GitHub user of a Copilot session)
+    /// when there is one (`claude (work)`, `copilot (octocat)`).
+    #[must_use]
+    pub fn label(&self) -> String {
+        match self.account.as_deref().or_else(|| self.github_login()) {
+            Some(account) => format!("{} ({account})", self.command()),
+            None => self.command().to_string(),
+        }
+    }
+
     #[must_use]
     pub fn args(&self) -> &[String] {
         &self.config.args
@@ -93,12 +424,19 @@ impl Agent {
     /// # Errors
     ///
     /// Returns an error if fetching usage from the provider API fails or the domain is unknown.
-    pub async fn check_limit(&self) -> Result<AgentLimit, Box<dyn std::error::Error>> {
+    pub async fn check_limit(&self) -> Result<AgentLimit, SeherError> {
+        self.with_auth_fallback(|agent| Box::pin(agent.check_limit_once()))
+            .await
+    }
+
+    async fn check_limit_once(&self) -> Result<AgentLimit, SeherError> {
         match self.config.resolve_provider() {
             Some("claude") => self.check_claude_limit().await,
             Some("codex") => self.check_codex_limit().await,
             Some("copilot") => self.check_copilot_limit().await,
             Some("openrouter") => self.check_openrouter_limit().await,
+            Some("anthropic") => self.check_anthropic_limit().await,
+            Some("openai") => self.check_openai_limit().await,
             Some("glm") => self.check_glm_limit().await,
             Some("zai") => self.check_zai_limit().await,
             Some("kimi-k2") => self.check_kimik2_limit().await,
@@ -113,64 +451,66 @@ impl Agent {
     /// # Errors
     ///
     /// Returns an error if fetching usage from the provider API fails or the domain is unknown.
+    pub async fn fetch_status(&self) -> Result<AgentStatus, SeherError> {
+        self.with_auth_fallback(|agent| Box::pin(agent.fetch_status_once()))
+            .await
+    }
+
     #[expect(clippy::too_many_lines)]
-    pub async fn fetch_status(&self) -> Result<AgentStatus, Box<dyn std::error::Error>> {
+    async fn fetch_status_once(&self) -> Result<AgentStatus, SeherError> {
         let command = self.config.command.clone();
         let provider = self.config.resolve_provider().map(ToString::to_string);
-        let usage = match provider.as_deref() {
+        let mut plan = None;
+        let mut identity = None;
+        let mut usage = match provider.as_deref() {
             None => vec![],
             Some("claude") => {
-                let usage = crate::claude::ClaudeClient::fetch_usage(&self.cookies).await?;
-                usage
-                    .all_windows()
-                    .into_iter()
-                    .map(|(name, w)| UsageEntry {
-                        entry_type: name.to_string(),
-                        limited: w.is_limited(),
-                        utilization: w.utilization.unwrap_or(0.0),
-                        resets_at: w.resets_at,
-                    })
-                    .collect()
+                // The three claude.ai lookups of one status share a single throttle slot.
+                let slot = crate::cache::SharedSlot::new("claude");
+                let usage = self.fetch_claude_usage(&slot).await?;
+                // Plan metadata is informational; don't fail the status if it's unavailable.
+                plan = self
+                    .fetch_claude_organization(&slot)
+                    .await
+                    .and_then(|org| org.plan_name());
+                identity = self
+                    .fetch_claude_account(&slot)
+                    .await
+                    .and_then(|account| account.identity().map(ToString::to_string));
+                claude_usage_entries(&usage)
             }
-            Some("codex") => {
-                let usage = crate::codex::CodexClient::fetch_usage(&self.cookies).await?;
-                let mut entries = codex_usage_entries("rate_limit", &usage.rate_limit);
-                if let Some(ref cr) = usage.code_review_rate_limit {
-                    entries.extend(codex_usage_entries("code_review_rate_limit", cr));
+            Some("codex") => match self.fetch_codex_usage().await? {
+                Some(usage) => {
+                    plan = Some(usage.plan_type.clone());
+                    codex_response_entries(&usage)
                 }
-                entries
-            }
+                None => vec![],
+            },
             Some("copilot") => {
-                let quota = crate::copilot::CopilotClient::fetch_quota(&self.cookies).await?;
-                vec![
-                    UsageEntry {
-                        entry_type: "chat_utilization".to_string(),
-                        limited: quota.chat_utilization >= 100.0,
-                        utilization: quota.chat_utilization,
-                        resets_at: quota.reset_time,
-                    },
-                    UsageEntry {
-                        entry_type: "premium_utilization".to_string(),
-                        limited: quota.premium_utilization >= 100.0,
-                        utilization: quota.premium_utilization,
-                        resets_at: quota.reset_time,
-                    },
-                ]
+                let quota = self.fetch_copilot_quota().await?;
+                plan.clone_from(&quota.plan);
+                identity.clone_from(&quota.login);
+                copilot_usage_entries(&quota)
             }
             Some("openrouter") => {
                 let management_key = self.openrouter_management_key()?;
                 let credits =
-                    crate::openrouter::OpenRouterClient::fetch_credits(management_key).await?;
+                    crate::openrouter::OpenRouterClient::fetch_credits(&management_key).await?;
                 vec![UsageEntry {
                     entry_type: "credits".to_string(),
                     limited: credits.data.is_limited(),
                     utilization: credits.data.utilization(),
                     resets_at: None,
+                    remaining: None,
+                    limit: None,
+                    limit_eta: None,
                 }]
             }
+            Some("anthropic") => self.anthropic_usage_entries().await?,
+            Some("openai") => self.openai_usage_entries().await?,
             Some("glm") => {
                 let api_key = self.glm_api_key()?;
-                let quota = crate::glm::GlmClient::fetch_quota(api_key).await?;
+                let quota = crate::glm::GlmClient::fetch_quota(&api_key).await?;
                 match quota.data {
                     Some(data) => data
                         .limits
@@ -180,6 +520,9 @@ impl Agent {
                             limited: l.percentage >= 100,
                             utilization: f64::from(l.percentage),
                             resets_at: l.next_reset_time.and_then(DateTime::from_timestamp_millis),
+                            remaining: None,
+                            limit: None,
+                            limit_eta: None,
                         })
                         .collect(),
                     None => vec![],
@@ -199,6 +542,9 @@ impl Agent {
                             limited: l.percentage >= 100,
                             utilization: f64::from(l.percentage),
                             resets_at: l.next_reset_time.and_then(DateTime::from_timestamp_millis),
+                            remaining: None,
+                            limit: None,
+                            limit_eta: None,
                         })
                         .collect(),
                     None => vec![],
@@ -212,6 +558,9 @@ impl Agent {
                     limited: credits.is_limited(),
                     utilization: credits.utilization(),
                     resets_at: None,
+                    remaining: None,
+                    limit: None,
+                    limit_eta: None,
                 }]
             }
             Some("warp") => {
@@ -223,6 +572,9 @@ impl Agent {
                     limited: limit_info.is_limited(),
                     utilization: limit_info.utilization(),
                     resets_at: Self::reset_time_from_seconds(limit_info.reset_in_seconds),
+                    remaining: None,
+                    limit: None,
+                    limit_eta: None,
                 }]
             }
             Some("kiro") => {
@@ -232,6 +584,9 @@ impl Agent {
                     limited: info.is_limited(),
                     utilization: info.utilization(),
                     resets_at: Self::reset_time_from_seconds(info.reset_in_seconds),
+                    remaining: None,
+                    limit: None,
+                    limit_eta: None,
                 }]
             }
             Some("opencode-go") => self
@@ -243,41 +598,45 @@ impl Agent {
                     limited: window.is_limited(),
                     utilization: window.utilization(),
                     resets_at: window.resets_at,
+                    remaining: None,
+                    limit: None,
+                    limit_eta: None,
                 })
                 .collect(),
             Some(p) => return Err(format!("Unknown provider: {p}").into()),
         };
+        if let Some(p) = provider.as_deref() {
+            // Claude, Codex and Copilot record their samples when they are fetched.
+            if !matches!(p, "claude" | "codex" | "copilot") {
+                record_sample(p, &self.cache_key(p), &usage);
+            }
+            crate::history::annotate(p, &self.cache_key(p), &mut usage);
+        }
         Ok(AgentStatus {
             command,
             provider,
+            plan,
+            identity,
             usage,
         })
     }
 
-    async fn check_claude_limit(&self) -> Result<AgentLimit, Box<dyn std::error::Error>> {
-        let usage = crate::claude::ClaudeClient::fetch_usage(&self.cookies).await?;
-        let windows = usage.all_windows();
+    async fn check_claude_limit(&self) -> Result<AgentLimit, SeherError> {
+        let usage = self
+            .fetch_claude_usage(&crate::cache::SharedSlot::new("claude"))
+            .await?;
 
-        let (has_limited, reset_time) =
-            windows
-                .iter()
-                .fold((false, None), |(has_lim, max_t), (_, w)| {
-                    if w.is_limited() {
-                        (true, max_t.max(w.resets_at))
-                    } else {
-                        (has_lim, max_t)
-                    }
-                });
-
-        if has_limited {
-            Ok(AgentLimit::Limited { reset_time })
+        if usage.is_limited() {
+            Ok(AgentLimit::Limited {
+                reset_time: usage.next_reset_time(),
+            })
         } else {
             Ok(AgentLimit::NotLimited)
         }
     }
 
-    async fn check_copilot_limit(&self) -> Result<AgentLimit, Box<dyn std::error::Error>> {
-        let quota = crate::copilot::CopilotClient::fetch_quota(&self.cookies).await?;
+    async fn check_copilot_limit(&self) -> Result<AgentLimit, SeherError> {
+        let quota = self.fetch_copilot_quota().await?;
 
         if quota.is_limited() {
             Ok(AgentLimit::Limited {
@@ -288,10 +647,153 @@ impl Agent {
         }
     }
 
-    fn openrouter_management_key(&self) -> Result<&str, Box<dyn std::error::Error>> {
+    /// Cache key for this agent's account, derived from its credentials.
+    fn cache_key(&self, kind: &str) -> String {
+        let account = self
+            .cookies
+            .iter()
+            .map(|c| format!("{}={}", c.name, c.value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        crate::cache::cache_key(kind, &account)
+    }
+
+    /// Fetch Claude usage, serving a fresh cached response when the usage cache is enabled.
+    async fn fetch_claude_usage(
+        &self,
+        slot: &crate::cache::SharedSlot<'_>,
+    ) -> Result<crate::claude::UsageResponse, SeherError> {
+        let cache = crate::cache::UsageCache::current();
+        let key = self.cache_key("claude");
+        if let Some(usage) = cache.and_then(|c| c.get::<crate::claude::UsageResponse>(&key))
+            && !reset_has_passed(usage.is_limited(), usage.next_reset_time())
+        {
+            return Ok(usage);
+        }
+        with_offline_fallback("claude", &key, async {
+            slot.wait().await;
+            let usage = crate::claude::ClaudeClient::fetch_usage(&self.cookies).await?;
+            record_sample("claude", &key, &claude_usage_entries(&usage));
+            if let Some(cache) = cache {
+                cache.put(&key, &usage);
+            }
+            Ok(usage)
+        })
+        .await
+    }
+
+    async fn fetch_claude_organization(
+        &self,
+        slot: &crate::cache::SharedSlot<'_>,
+    ) -> Option<crate::claude::Organization> {
+        let cache = crate::cache::UsageCache::current();
+        let key = self.cache_key("claude-org");
+        if crate::cache::offline() {
+            return cache
+                .and_then(|c| c.last_snapshot(&key))
+                .map(|(org, _)| org);
+        }
+        if let Some(org) = cache.and_then(|c| c.get(&key)) {
+            return Some(org);
+        }
+        slot.wait().await;
+        let org = crate::claude::ClaudeClient::fetch_organization(&self.cookies)
+            .await
+            .ok()?;
+        if let Some(cache) = cache {
+            cache.put(&key, &org);
+        }
+        Some(org)
+    }
+
+    async fn fetch_claude_account(
+        &self,
+        slot: &crate::cache::SharedSlot<'_>,
+    ) -> Option<crate::claude::Account> {
+        let cache = crate::cache::UsageCache::current();
+        let key = self.cache_key("claude-account");
+        if crate::cache::offline() {
+            return cache
+                .and_then(|c| c.last_snapshot(&key))
+                .map(|(account, _)| account);
+        }
+        if let Some(account) = cache.and_then(|c| c.get(&key)) {
+            return Some(account);
+        }
+        slot.wait().await;
+        let account = crate::claude::ClaudeClient::fetch_account(&self.cookies)
+            .await
+            .ok()?;
+        if let Some(cache) = cache {
+            cache.put(&key, &account);
+        }
+        Some(account)
+    }
+
+    /// Fetch Copilot quota, serving a fresh cached response when the usage cache is enabled.
+    async fn fetch_copilot_quota(&self) -> Result<crate::copilot::CopilotQuota, SeherError> {
+        let cache = crate::cache::UsageCache::current();
+        let key = self.cache_key("copilot");
+        if let Some(quota) = cache.and_then(|c| c.get::<crate::copilot::CopilotQuota>(&key))
+            && !reset_has_passed(quota.is_limited(), quota.next_reset_time())
+        {
+            return Ok(quota);
+        }
+        with_offline_fallback("copilot", &key, async {
+            crate::cache::throttle("copilot").await;
+            let quota = self.fetch_copilot_quota_uncached().await?;
+            record_sample("copilot", &key, &copilot_usage_entries(&quota));
+            if let Some(cache) = cache {
+                cache.put(&key, &quota);
+            }
+            Ok(quota)
+        })
+        .await
+    }
+
+    /// Fetch Copilot quota from browser cookies, falling back to the `gh` CLI token.
+    ///
+    /// Business/Enterprise seats are not reported on the github.com chat page, so those
+    /// always go through the entitlement API.
+    async fn fetch_copilot_quota_uncached(
+        &self,
+    ) -> Result<crate::copilot::CopilotQuota, SeherError> {
+        if !self.cookies.is_empty()
+            && let Some(quota) =
+                crate::copilot::CopilotClient::fetch_chat_quota(&self.cookies).await?
+        {
+            return Ok(quota);
+        }
+        let token = self.github_token()?;
+        crate::copilot::CopilotClient::fetch_quota_with_token(&token).await
+    }
+
+    /// Resolve a GitHub token from the agent's `env`, then the token stored by
+    /// `seher login github`, falling back to the `gh` CLI.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no GitHub token can be found.
+    pub fn github_token(&self) -> Result<String, SeherError> {
+        if let Some(token) = self
+            .config
+            .env
+            .as_ref()
+            .and_then(|env| env.get("GH_TOKEN").or_else(|| env.get("GITHUB_TOKEN")))
+        {
+            return Ok(token.clone());
+        }
+        if let Ok(Some(token)) = crate::keyring::Keyring::get(crate::keyring::GITHUB_ACCOUNT) {
+            return Ok(token);
+        }
+        Ok(crate::copilot::GhAuth::token()?)
+    }
+
+    fn openrouter_management_key(&self) -> Result<String, SeherError> {
         self.config
             .openrouter_management_key
-            .as_deref()
+            .clone()
+            .or_else(|| self.resolve_secret("OPENROUTER_MANAGEMENT_KEY"))
             .ok_or_else(|| {
                 "openrouter_management_key is required for OpenRouter provider"
                     .to_string()
@@ -299,9 +801,9 @@ impl Agent {
             })
     }
 
-    async fn check_openrouter_limit(&self) -> Result<AgentLimit, Box<dyn std::error::Error>> {
+    async fn check_openrouter_limit(&self) -> Result<AgentLimit, SeherError> {
         let management_key = self.openrouter_management_key()?;
-        let credits = crate::openrouter::OpenRouterClient::fetch_credits(management_key).await?;
+        let credits = crate::openrouter::OpenRouterClient::fetch_credits(&management_key).await?;
         if credits.data.is_limited() {
             Ok(AgentLimit::Limited { reset_time: None })
         } else {
@@ -309,17 +811,131 @@ impl Agent {
         }
     }
 
-    fn glm_api_key(&self) -> Result<&str, Box<dyn std::error::Error>> {
-        self.config.glm_api_key.as_deref().ok_or_else(|| {
-            "glm_api_key is required for GLM provider"
-                .to_string()
-                .into()
-        })
+    /// The Anthropic Admin API key (`ANTHROPIC_ADMIN_KEY`).
+    #[must_use]
+    pub fn anthropic_admin_key(&self) -> Option<String> {
+        self.resolve_secret("ANTHROPIC_ADMIN_KEY")
+    }
+
+    /// Prepaid credits from console.anthropic.com cookies and, with an Admin API key and
+    /// `ANTHROPIC_MONTHLY_LIMIT_USD`, month-to-date spend against that budget.
+    async fn anthropic_usage_entries(&self) -> Result<Vec<UsageEntry>, SeherError> {
+        let admin_key = self.anthropic_admin_key();
+        if self.cookies.is_empty() && admin_key.is_none() {
+            return Err(
+                "console.anthropic.com cookies or ANTHROPIC_ADMIN_KEY is required \
+                        for the Anthropic provider"
+                    .into(),
+            );
+        }
+        let mut entries = vec![];
+        if !self.cookies.is_empty() {
+            let credits =
+                crate::anthropic::AnthropicConsoleClient::fetch_prepaid_credits(&self.cookies)
+                    .await?;
+            entries.push(UsageEntry {
+                entry_type: "credits".to_string(),
+                limited: credits.is_limited(),
+                utilization: if credits.is_limited() { 100.0 } else { 0.0 },
+                resets_at: None,
+                remaining: Some(credits.balance()),
+                limit: None,
+                limit_eta: None,
+            });
+        }
+        if let Some(admin_key) = admin_key {
+            let limit = self.monthly_limit("ANTHROPIC_MONTHLY_LIMIT_USD")?;
+            let cost =
+                crate::anthropic::AnthropicConsoleClient::fetch_month_cost(&admin_key).await?;
+            entries.push(monthly_spend_entry(&crate::budget::MonthlySpend::new(
+                cost,
+                limit,
+                Utc::now(),
+            )));
+        }
+        Ok(entries)
+    }
+
+    async fn check_anthropic_limit(&self) -> Result<AgentLimit, SeherError> {
+        Ok(limit_from_entries(&self.anthropic_usage_entries().await?))
+    }
+
+    /// The `OpenAI` API key (`OPENAI_API_KEY`), also stored by `seher login openai`.
+    #[must_use]
+    pub fn openai_api_key(&self) -> Option<String> {
+        self.resolve_secret("OPENAI_API_KEY")
+    }
+
+    /// Rate-limit headroom for the API key and, with an Admin API key
+    /// (`OPENAI_ADMIN_KEY`) and `OPENAI_MONTHLY_LIMIT_USD`, month-to-date organization
+    /// spend against that budget.
+    async fn openai_usage_entries(&self) -> Result<Vec<UsageEntry>, SeherError> {
+        let api_key = self.openai_api_key();
+        let admin_key = self.resolve_secret("OPENAI_ADMIN_KEY");
+        if api_key.is_none() && admin_key.is_none() {
+            return Err(
+                "OPENAI_API_KEY (or `seher login openai`) or OPENAI_ADMIN_KEY is \
+                        required for the OpenAI provider"
+                    .into(),
+            );
+        }
+        let mut entries = vec![];
+        if let Some(api_key) = api_key {
+            let now = Utc::now();
+            for headroom in crate::openai::OpenAiClient::fetch_rate_limits(&api_key).await? {
+                entries.push(UsageEntry {
+                    entry_type: headroom.kind.to_string(),
+                    limited: headroom.is_limited(),
+                    utilization: headroom.utilization(),
+                    resets_at: headroom
+                        .reset_in
+                        .and_then(|d| chrono::Duration::from_std(d).ok())
+                        .and_then(|d| now.checked_add_signed(d)),
+                    remaining: Some(headroom.remaining),
+                    limit: Some(headroom.limit),
+                    limit_eta: None,
+                });
+            }
+        }
+        if let Some(admin_key) = admin_key {
+            let limit = self.monthly_limit("OPENAI_MONTHLY_LIMIT_USD")?;
+            let cost = crate::openai::OpenAiClient::fetch_month_cost(&admin_key).await?;
+            entries.push(monthly_spend_entry(&crate::budget::MonthlySpend::new(
+                cost,
+                limit,
+                Utc::now(),
+            )));
+        }
+        Ok(entries)
+    }
+
+    async fn check_openai_limit(&self) -> Result<AgentLimit, SeherError> {
+        Ok(limit_from_entries(&self.openai_usage_entries().await?))
+    }
+
+    /// A monthly budget in dollars from the environment variable `key`.
+    fn monthly_limit(&self, key: &str) -> Result<f64, SeherError> {
+        self.resolve_env_key(key)?
+            .trim()
+            .parse::<f64>()
+            .map_err(|e| format!("invalid {key}: {e}").into())
+    }
+
+    fn glm_api_key(&self) -> Result<String, SeherError> {
+        self.config
+            .glm_api_key
+            .clone()
+            .or_else(|| self.resolve_secret("GLM_API_KEY"))
+            .ok_or_else(|| {
+                "glm_api_key is required for GLM provider"
+                    .to_string()
+                    .into()
+            })
     }
 
-    async fn check_glm_limit(&self) -> Result<AgentLimit, Box<dyn std::error::Error>> {
+    async fn check_glm_limit(&self) -> Result<AgentLimit, SeherError> {
         let api_key = self.glm_api_key()?;
-        let quota = crate::glm::GlmClient::fetch_quota(api_key).await?;
+        let quota = crate::glm::GlmClient::fetch_quota(&api_key).await?;
         match quota.data {
             Some(data) if data.is_limited() => {
                 let reset_time = data
@@ -338,7 +954,7 @@ impl Agent {
         secs.and_then(|s| Utc::now().checked_add_signed(chrono::Duration::seconds(s)))
     }
 
-    fn resolve_env_key(&self, key: &str) -> Result<String, Box<dyn std::error::Error>> {
+    fn resolve_env_key(&self, key: &str) -> Result<String, SeherError> {
         // 1. Check agent config env
         if let Some(env) = &self.config.env
             && let Some(val) = env.get(key)
@@ -349,9 +965,21 @@ impl Agent {
         if let Ok(val) = std::env::var(key) {
             return Ok(val);
         }
+        // 3. Check secrets stored by `seher secrets set`
+        if let Some(val) = crate::secrets::get(key) {
+            return Ok(val);
+        }
         Err(format!("{key} is required for this provider").into())
     }
 
+    /// A non-empty API key from the agent's `env`, the process environment, or the
+    /// secrets stored by `seher secrets set`.
+    fn resolve_secret(&self, key: &str) -> Option<String> {
+        self.resolve_optional_env(key)
+            .filter(|val| !val.is_empty())
+            .or_else(|| crate::secrets::get(key))
+    }
+
     fn resolve_optional_env(&self, key: &str) -> Option<String> {
         self.config
             .env
@@ -360,7 +988,7 @@ impl Agent {
             .or_else(|| std::env::var(key).ok())
     }
 
-    async fn check_zai_limit(&self) -> Result<AgentLimit, Box<dyn std::error::Error>> {
+    async fn check_zai_limit(&self) -> Result<AgentLimit, SeherError> {
         let api_key = self.resolve_env_key("Z_AI_API_KEY")?;
         let quota_url = self.resolve_optional_env("Z_AI_QUOTA_URL");
         let quota = crate::zai::ZaiClient::fetch_quota(&api_key, quota_url.as_deref()).await?;
@@ -378,7 +1006,7 @@ impl Agent {
         }
     }
 
-    async fn check_kimik2_limit(&self) -> Result<AgentLimit, Box<dyn std::error::Error>> {
+    async fn check_kimik2_limit(&self) -> Result<AgentLimit, SeherError> {
         let api_key = self.resolve_env_key("KIMI_K2_API_KEY")?;
         let credits = crate::kimik2::KimiK2Client::fetch_credits(&api_key).await?;
         if credits.is_limited() {
@@ -388,7 +1016,7 @@ impl Agent {
         }
     }
 
-    async fn check_warp_limit(&self) -> Result<AgentLimit, Box<dyn std::error::Error>> {
+    async fn check_warp_limit(&self) -> Result<AgentLimit, SeherError> {
         let api_key = self.resolve_env_key("WARP_API_KEY")?;
         let info = crate::warp::WarpClient::fetch_limit_info(&api_key).await?;
         let limit_info = &info.data.get_request_limit_info;
@@ -401,7 +1029,7 @@ impl Agent {
         }
     }
 
-    async fn check_kiro_limit(&self) -> Result<AgentLimit, Box<dyn std::error::Error>> {
+    async fn check_kiro_limit(&self) -> Result<AgentLimit, SeherError> {
         let info = crate::kiro::KiroClient::fetch_usage().await?;
         if info.is_limited() {
             Ok(AgentLimit::Limited {
@@ -412,7 +1040,7 @@ impl Agent {
         }
     }
 
-    fn check_opencode_go_limit(&self) -> Result<AgentLimit, Box<dyn std::error::Error>> {
+    fn check_opencode_go_limit(&self) -> Result<AgentLimit, SeherError> {
         let snapshot = self.opencode_go_usage_snapshot()?;
         if snapshot
             .windows
@@ -427,44 +1055,219 @@ impl Agent {
         }
     }
 
-    async fn check_codex_limit(&self) -> Result<AgentLimit, Box<dyn std::error::Error>> {
-        let usage = crate::codex::CodexClient::fetch_usage(&self.cookies).await?;
-
-        if usage.rate_limit.is_limited() {
-            Ok(AgentLimit::Limited {
+    async fn check_codex_limit(&self) -> Result<AgentLimit, SeherError> {
+        match self.fetch_codex_usage().await? {
+            Some(usage) if usage.rate_limit.is_limited() => Ok(AgentLimit::Limited {
                 reset_time: usage.rate_limit.next_reset_time(),
-            })
-        } else {
-            Ok(AgentLimit::NotLimited)
+            }),
+            _ => Ok(AgentLimit::NotLimited),
         }
     }
 
+    /// Resolve Codex CLI credentials, honoring `SEHER_CODEX_AUTH_PATH` and `OPENAI_API_KEY`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no `auth.json` credentials or API key are available.
+    pub fn codex_credentials(&self) -> Result<crate::codex::CodexCredentials, SeherError> {
+        let result = match self.resolve_optional_env("SEHER_CODEX_AUTH_PATH") {
+            Some(path) => {
+                crate::codex::CodexAuth::read_credentials_from(std::path::Path::new(&path))
+            }
+            None => crate::codex::CodexAuth::read_credentials(),
+        };
+        match result {
+            Ok(creds) => Ok(creds),
+            Err(e) => self
+                .resolve_optional_env("OPENAI_API_KEY")
+                .filter(|key| !key.is_empty())
+                .map(crate::codex::CodexCredentials::ApiKey)
+                .ok_or_else(|| e.into()),
+        }
+    }
+
+    async fn fetch_codex_usage(
+        &self,
+    ) -> Result<Option<crate::codex::CodexUsageResponse>, SeherError> {
+        let key = self.cache_key("codex");
+        with_offline_fallback("codex", &key, async {
+            crate::cache::throttle("codex").await;
+            let usage = self.fetch_codex_usage_unrecorded().await?;
+            if let Some(usage) = &usage {
+                record_sample("codex", &key, &codex_response_entries(usage));
+                if let Some(cache) = crate::cache::UsageCache::current() {
+                    cache.put(&key, usage);
+                }
+            }
+            Ok(usage)
+        })
+        .await
+    }
+
+    /// Fetch Codex usage from browser cookies, falling back to the Codex CLI's `auth.json`.
+    ///
+    /// Returns `None` for API-key authentication, which is billed per token and has no
+    /// plan windows to report.
+    async fn fetch_codex_usage_unrecorded(
+        &self,
+    ) -> Result<Option<crate::codex::CodexUsageResponse>, SeherError> {
+        if !self.cookies.is_empty() {
+            return Ok(Some(
+                crate::codex::CodexClient::fetch_usage(&self.cookies).await?,
+            ));
+        }
+        match self.codex_credentials()? {
+            crate::codex::CodexCredentials::ChatGpt {
+                access_token,
+                account_id,
+            } => Ok(Some(
+                crate::codex::CodexClient::fetch_usage_with_token(
+                    &access_token,
+                    account_id.as_deref(),
+                )
+                .await?,
+            )),
+            crate::codex::CodexCredentials::ApiKey(_) => Ok(None),
+        }
+    }
+
+    /// Run the agent, writing `stdin` to its standard input when given and killing it if
+    /// it runs longer than `timeout` (reported as [`Stop::TimedOut`]).
+    ///
+    /// If `stop` completes while the agent is running, the agent is sent SIGTERM (and
+    /// killed if it has not exited after a grace period) and [`Stop::Stopped`] is returned
+    /// with the reason `stop` resolved to. Pass [`std::future::pending`] to never stop it
+    /// this way.
+    ///
     /// # Errors
     ///
     /// Returns an error if spawning or waiting on the child process fails.
-    pub fn execute(
+    pub async fn execute(
+        &self,
+        resolved_args: &[String],
+        extra_args: &[String],
+        stdin: Option<&str>,
+        timeout: Option<std::time::Duration>,
+        stop: impl Future<Output = String>,
+    ) -> std::io::Result<Stop> {
+        if let Some(status) = self.run_pre_command()? {
+            return Ok(Stop::Exited(status));
+        }
+        let mut cmd =
+            tokio::process::Command::from(self.command_with_args(resolved_args, extra_args));
+        if stdin.is_some() {
+            cmd.stdin(std::process::Stdio::piped());
+        }
+        let mut child = cmd.spawn()?;
+        let _signals = signals::SignalForwarder::install(child.id());
+        let feed = feed_stdin(child.stdin.take(), stdin);
+        let (status, ()) = tokio::join!(wait_supervised(&mut child, timeout, stop), feed);
+        status
+    }
+
+    /// Replace the current process with the agent (`execvp`), so nothing sits between it
+    /// and the terminal. Returns only if `pre_command` fails (with its status) or the exec
+    /// itself fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `pre_command` cannot be run or the agent cannot be executed.
+    #[cfg(unix)]
+    pub fn exec(
         &self,
         resolved_args: &[String],
         extra_args: &[String],
     ) -> std::io::Result<std::process::ExitStatus> {
+        use std::os::unix::process::CommandExt;
+        if let Some(status) = self.run_pre_command()? {
+            return Ok(status);
+        }
+        Err(self.command_with_args(resolved_args, extra_args).exec())
+    }
+
+    /// Like [`Agent::execute`], but pipes the agent's stdout/stderr through seher so they
+    /// can be prefixed, copied to a log and scanned for rate-limit messages.
+    ///
+    /// Returns how the run ended and which of the capture's patterns appeared in the output.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if spawning or waiting on the child process fails, or the log
+    /// file cannot be opened.
+    pub async fn execute_captured(
+        &self,
+        resolved_args: &[String],
+        extra_args: &[String],
+        stdin: Option<&str>,
+        timeout: Option<std::time::Duration>,
+        stop: impl Future<Output = String>,
+        capture: &OutputCapture,
+    ) -> std::io::Result<(Stop, OutputMatches)> {
+        if let Some(status) = self.run_pre_command()? {
+            return Ok((Stop::Exited(status), OutputMatches::default()));
+        }
+        let mut cmd =
+            tokio::process::Command::from(self.command_with_args(resolved_args, extra_args));
+        cmd.stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped());
+        if stdin.is_some() {
+            cmd.stdin(std::process::Stdio::piped());
+        }
+        let mut child = cmd.spawn()?;
+        let _signals = signals::SignalForwarder::install(child.id());
+        let feed = feed_stdin(child.stdin.take(), stdin);
+        let (result, ()) = tokio::join!(
+            Box::pin(output::wait_forwarding(child, capture, timeout, stop)),
+            feed
+        );
+        result
+    }
+
+    /// Output substrings that indicate this agent hit its provider limit: the built-in
+    /// messages for its provider plus any configured `rate_limit_patterns`.
+    #[must_use]
+    pub fn rate_limit_patterns(&self) -> Vec<String> {
+        output::default_rate_limit_patterns(self.config.resolve_provider())
+            .iter()
+            .map(ToString::to_string)
+            .chain(self.config.rate_limit_patterns.iter().cloned())
+            .collect()
+    }
+
+    /// Run `pre_command`, returning its status if it failed.
+    fn run_pre_command(&self) -> std::io::Result<Option<std::process::ExitStatus>> {
         if let Some((cmd, args)) = self.config.pre_command.split_first() {
             let mut pre_cmd = std::process::Command::new(cmd);
             pre_cmd.args(args);
             if let Some(env) = &self.config.env {
                 pre_cmd.envs(env);
             }
+            if let Some(dir) = &self.working_dir {
+                pre_cmd.current_dir(dir);
+            }
             let status = pre_cmd.status()?;
             if !status.success() {
-                return Ok(status);
+                return Ok(Some(status));
             }
         }
+        Ok(None)
+    }
+
+    fn command_with_args(
+        &self,
+        resolved_args: &[String],
+        extra_args: &[String],
+    ) -> std::process::Command {
         let mut cmd = std::process::Command::new(self.command());
         cmd.args(resolved_args);
         cmd.args(extra_args);
         if let Some(env) = &self.config.env {
             cmd.envs(env);
         }
-        cmd.status()
+        if let Some(dir) = &self.working_dir {
+            cmd.current_dir(dir);
+        }
+        cmd
     }
 
     #[must_use]
@@ -521,7 +1324,7 @@ impl Agent {
 
     fn opencode_go_usage_snapshot(
         &self,
-    ) -> Result<crate::opencode_go::OpencodeGoUsageSnapshot, Box<dyn std::error::Error>> {
+    ) -> Result<crate::opencode_go::OpencodeGoUsageSnapshot, SeherError> {
         let db_path = self.resolve_optional_env("SEHER_OPENCODE_DB_PATH");
         let auth_path = self.resolve_optional_env("SEHER_OPENCODE_AUTH_PATH");
         Ok(
@@ -534,13 +1337,90 @@ impl Agent {
     }
 }
 
+/// How a supervised agent run ([`Agent::execute`], [`Agent::execute_captured`]) ended.
+#[derive(Debug)]
+pub enum Stop {
+    /// The agent (or a failing `pre_command`) exited on its own.
+    Exited(std::process::ExitStatus),
+    /// The agent ran longer than its timeout and was killed.
+    TimedOut,
+    /// The `stop` future completed with this reason and the agent was stopped.
+    Stopped(String),
+}
+
+impl Stop {
+    /// `true` if the agent exited successfully.
+    #[must_use]
+    pub fn success(&self) -> bool {
+        matches!(self, Self::Exited(status) if status.success())
+    }
+}
+
+/// Write `input` to the child's stdin and close it.
+///
+/// A broken pipe just means the agent stopped reading early, which is its call to make.
+async fn feed_stdin(pipe: Option<tokio::process::ChildStdin>, input: Option<&str>) {
+    use tokio::io::AsyncWriteExt;
+    if let (Some(mut pipe), Some(input)) = (pipe, input) {
+        let _ = pipe.write_all(input.as_bytes()).await;
+    }
+}
+
+/// How long a stopped agent gets to exit after SIGTERM before it is killed.
+const STOP_GRACE_PERIOD: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Wait for `child`, killing it if it runs longer than `timeout` and stopping it with the
+/// reason `stop` resolves to if that happens first.
+async fn wait_supervised(
+    child: &mut tokio::process::Child,
+    timeout: Option<std::time::Duration>,
+    stop: impl Future<Output = String>,
+) -> std::io::Result<Stop> {
+    let deadline = async {
+        match timeout {
+            Some(limit) => tokio::time::sleep(limit).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::select! {
+        status = child.wait() => status.map(Stop::Exited),
+        () = deadline => {
+            child.kill().await?;
+            Ok(Stop::TimedOut)
+        }
+        reason = stop => {
+            terminate(child).await?;
+            Ok(Stop::Stopped(reason))
+        }
+    }
+}
+
+/// Ask `child` to exit with SIGTERM, killing it if it is still running after
+/// [`STOP_GRACE_PERIOD`]. Kills it straight away on non-Unix platforms.
+async fn terminate(child: &mut tokio::process::Child) -> std::io::Result<()> {
+    #[cfg(unix)]
+    if let Some(pid) = child.id().and_then(|pid| libc::pid_t::try_from(pid).ok()) {
+        // SAFETY: `kill` takes plain integers; `pid` is our child, which has not been reaped.
+        unsafe {
+            libc::kill(pid, libc::SIGTERM);
+        }
+        if tokio::time::timeout(STOP_GRACE_PERIOD, child.wait())
+            .await
+            .is_ok()
+        {
+            return Ok(());
+        }
+    }
+    child.kill().await
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
 
     use super::*;
     use crate::codex::{CodexRateLimit, CodexWindow};
-    use crate::config::AgentConfig;
+    use crate::config::{AgentConfig, PromptInput};
 
     fn make_agent(
         models: Option<HashMap<String, String>>,
@@ -549,6 +1429,7 @@ mod tests {
         Agent::new(
             AgentConfig {
                 command: "claude".to_string(),
+                name: None,
                 args: vec![],
                 models,
                 arg_maps,
@@ -559,6 +1440,13 @@ mod tests {
                 pre_command: vec![],
                 active: None,
                 inactive: None,
+                detect_rate_limit: false,
+                rate_limit_patterns: vec![],
+                input: PromptInput::Arg,
+                resume_args: vec![],
+                accounts: vec![],
+                retry: None,
+                cooldown: None,
             },
             vec![],
         )
@@ -684,6 +1572,7 @@ mod tests {
         Agent::new(
             AgentConfig {
                 command: "myai".to_string(),
+                name: None,
                 args: vec![],
                 models: None,
                 arg_maps: HashMap::new(),
@@ -696,6 +1585,13 @@ mod tests {
                 pre_command: vec![],
                 active: None,
                 inactive: None,
+                detect_rate_limit: false,
+                rate_limit_patterns: vec![],
+                input: PromptInput::Arg,
+                resume_args: vec![],
+                accounts: vec![],
+                retry: None,
+                cooldown: None,
             },
             vec![],
         )
@@ -705,6 +1601,7 @@ mod tests {
         Agent::new(
             AgentConfig {
                 command: main_command.to_string(),
+                name: None,
                 args: vec![],
                 models: None,
                 arg_maps: HashMap::new(),
@@ -715,31 +1612,127 @@ mod tests {
                 pre_command,
                 active: None,
                 inactive: None,
+                detect_rate_limit: false,
+                rate_limit_patterns: vec![],
+                input: PromptInput::Arg,
+                resume_args: vec![],
+                accounts: vec![],
+                retry: None,
+                cooldown: None,
             },
             vec![],
         )
     }
 
-    #[test]
+    #[tokio::test(flavor = "current_thread")]
     #[cfg(unix)]
-    fn execute_runs_main_command_when_pre_command_succeeds() -> TestResult {
+    async fn execute_runs_main_command_when_pre_command_succeeds() -> TestResult {
         // pre_command: true (always exits 0), main: true
         let agent = make_agent_with_pre_command(vec!["true".to_string()], "true");
-        let status = agent.execute(&[], &[])?;
+        let status = agent
+            .execute(&[], &[], None, None, std::future::pending())
+            .await?;
         assert!(status.success());
         Ok(())
     }
 
-    #[test]
+    #[tokio::test(flavor = "current_thread")]
     #[cfg(unix)]
-    fn execute_skips_main_command_when_pre_command_fails() -> TestResult {
+    async fn execute_skips_main_command_when_pre_command_fails() -> TestResult {
         // pre_command: false (always exits non-0), main: true
         let agent = make_agent_with_pre_command(vec!["false".to_string()], "true");
-        let status = agent.execute(&[], &[])?;
+        let status = agent
+            .execute(&[], &[], None, None, std::future::pending())
+            .await?;
         assert!(!status.success());
         Ok(())
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    #[cfg(unix)]
+    async fn execute_writes_prompt_to_stdin() -> TestResult {
+        let mut agent = make_agent_with_pre_command(vec![], "sh");
+        agent.config.args = vec!["-c".to_string(), r#"test "$(cat)" = hello"#.to_string()];
+        let args = agent.resolved_args(None);
+        assert!(
+            agent
+                .execute(&args, &[], Some("hello"), None, std::future::pending())
+                .await?
+                .success()
+        );
+        assert!(
+            !agent
+                .execute(&args, &[], Some("bye"), None, std::future::pending())
+                .await?
+                .success()
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn exec_returns_pre_command_failure_without_replacing_the_process() -> TestResult {
+        let agent = make_agent_with_pre_command(vec!["false".to_string()], "true");
+        assert!(!agent.exec(&[], &[])?.success());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    #[cfg(unix)]
+    async fn execute_kills_agent_after_timeout() -> TestResult {
+        let agent = make_agent_with_pre_command(vec![], "sleep");
+        let started = std::time::Instant::now();
+        let result = agent
+            .execute(
+                &["5".to_string()],
+                &[],
+                None,
+                Some(std::time::Duration::from_millis(100)),
+                std::future::pending(),
+            )
+            .await?;
+        assert!(matches!(result, Stop::TimedOut));
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    #[cfg(unix)]
+    async fn execute_stops_agent_when_stop_completes() -> TestResult {
+        let agent = make_agent_with_pre_command(vec![], "sleep");
+        let started = std::time::Instant::now();
+        let stop = async {
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            "budget".to_string()
+        };
+        let result = agent
+            .execute(&["5".to_string()], &[], None, None, stop)
+            .await?;
+        assert!(matches!(result, Stop::Stopped(reason) if reason == "budget"));
+        assert!(started.elapsed() < std::time::Duration::from_secs(5));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    #[cfg(unix)]
+    async fn execute_captured_writes_prompt_to_stdin() -> TestResult {
+        let mut agent = make_agent_with_pre_command(vec![], "sh");
+        agent.config.args = vec!["-c".to_string(), r#"test "$(cat)" = hello"#.to_string()];
+        let args = agent.resolved_args(None);
+        let (status, _) = agent
+            .execute_captured(
+                &args,
+                &[],
+                Some("hello"),
+                None,
+                std::future::pending(),
+                &OutputCapture::default(),
+            )
+            .await?;
+        assert!(status.success());
+        Ok(())
+    }
+
     type TestResult = Result<(), Box<dyn std::error::Error>>;
 
     #[tokio::test(flavor = "current_thread")]
@@ -774,6 +1767,7 @@ mod tests {
         Agent::new(
             AgentConfig {
                 command: "myai".to_string(),
+                name: None,
                 args: vec![],
                 models: None,
                 arg_maps: HashMap::new(),
@@ -786,11 +1780,92 @@ mod tests {
                 pre_command: vec![],
                 active: None,
                 inactive: None,
+                detect_rate_limit: false,
+                rate_limit_patterns: vec![],
+                input: PromptInput::Arg,
+                resume_args: vec![],
+                accounts: vec![],
+                retry: None,
+                cooldown: None,
             },
             vec![],
         )
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn from_store_reads_cookies_only_for_website_providers() -> TestResult {
+        let store = vec![Cookie {
+            name: "sessionKey".to_string(),
+            value: "sk-ant".to_string(),
+            domain: ".claude.ai".to_string(),
+            path: "/".to_string(),
+            expires_utc: 0,
+            is_secure: true,
+            is_httponly: true,
+            same_site: 0,
+        }];
+        let claude = Agent::from_store(make_api_key_agent("claude").config, &store).await?;
+        assert_eq!(claude.cookies.len(), 1);
+        let zai = Agent::from_store(make_api_key_agent("zai").config, &store).await?;
+        assert!(zai.cookies.is_empty());
+        Ok(())
+    }
+
+    /// Fails with 401 for the `old` session key and succeeds with any other.
+    fn reject_old_session(agent: &Agent) -> BoxFuture<'_, Result<String, SeherError>> {
+        let value = agent.cookies[0].value.clone();
+        Box::pin(async move {
+            if value == "old" {
+                Err(SeherError::Status {
+                    service: "test",
+                    status: 401,
+                    body: String::new(),
+                })
+            } else {
+                Ok(value)
+            }
+        })
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn auth_failures_retry_with_new_cookies_from_the_store() {
+        let cookie = |value: &str| Cookie {
+            name: "sessionKey".to_string(),
+            value: value.to_string(),
+            domain: ".claude.ai".to_string(),
+            path: "/".to_string(),
+            expires_utc: 0,
+            is_secure: true,
+            is_httponly: true,
+            same_site: 0,
+        };
+        let config = make_api_key_agent("claude").config;
+
+        let without_store = Agent::new(config.clone(), vec![cookie("old")]);
+        assert!(
+            without_store
+                .with_auth_fallback(reject_old_session)
+                .await
+                .is_err()
+        );
+
+        let rotated = Agent::new(config.clone(), vec![cookie("old")])
+            .with_cookie_store(Arc::new(vec![cookie("new")]));
+        assert_eq!(
+            rotated.with_auth_fallback(reject_old_session).await.ok(),
+            Some("new".to_string())
+        );
+
+        let unchanged = Agent::new(config, vec![cookie("old")])
+            .with_cookie_store(Arc::new(vec![cookie("old")]));
+        assert!(
+            unchanged
+                .with_auth_fallback(reject_old_session)
+                .await
+                .is_err_and(|e| e.is_auth_error())
+        );
+    }
+
     // -- zai --
 
     #[tokio::test(flavor = "current_thread")]
@@ -954,6 +2029,36 @@ mod tests {
         Ok(())
     }
 
+    // -- codex (auth.json fallback when no cookies are available) --
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn check_limit_codex_treats_api_key_auth_as_not_limited() -> TestResult {
+        let tmp = tempfile::NamedTempFile::new()?;
+        std::fs::write(tmp.path(), r#"{"OPENAI_API_KEY": "sk-test"}"#)?;
+
+        let mut agent = make_api_key_agent("codex");
+        agent.config.env = Some(HashMap::from([(
+            "SEHER_CODEX_AUTH_PATH".to_string(),
+            tmp.path().display().to_string(),
+        )]));
+        let result = agent.check_limit().await?;
+        assert!(matches!(result, AgentLimit::NotLimited));
+        Ok(())
+    }
+
+    #[test]
+    fn codex_credentials_errors_when_auth_file_is_missing() {
+        let mut agent = make_api_key_agent("codex");
+        agent.config.env = Some(HashMap::from([(
+            "SEHER_CODEX_AUTH_PATH".to_string(),
+            "/nonexistent/seher/auth.json".to_string(),
+        )]));
+        // OPENAI_API_KEY may be set in the environment running the tests.
+        if std::env::var_os("OPENAI_API_KEY").is_none() {
+            assert!(agent.codex_credentials().is_err());
+        }
+    }
+
     // -- unknown provider still errors --
 
     #[tokio::test(flavor = "current_thread")]
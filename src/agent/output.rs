@@ -0,0 +1,288 @@
+use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::process::Child;
+
+/// Messages agents print when they hit a provider limit, matched case-insensitively.
+pub(crate) fn default_rate_limit_patterns(provider: Option<&str>) -> &'static [&'static str] {
+    match provider {
+        Some("claude") => &[
+            "usage limit reached",
+            "5-hour limit reached",
+            "weekly limit reached",
+            "you've hit your limit",
+        ],
+        Some("codex") => &["you've hit your usage limit", "rate_limit_exceeded"],
+        Some("copilot") => &[
+            "you have exceeded your premium request allowance",
+            "quota exceeded",
+        ],
+        _ => &["rate limit exceeded", "429 too many requests"],
+    }
+}
+
+/// Scans a byte stream line by line for any of a set of lowercase patterns.
+pub(crate) struct LineMatcher {
+    patterns: Vec<String>,
+    line: Vec<u8>,
+}
+
+/// Longest partial line kept while waiting for a newline.
+const MAX_LINE: usize = 16 * 1024;
+
+impl LineMatcher {
+    pub(crate) fn new(patterns: Vec<String>) -> Self {
+        Self {
+            patterns: patterns.into_iter().map(|p| p.to_lowercase()).collect(),
+            line: Vec::new(),
+        }
+    }
+
+    /// Feed a chunk of output; returns `true` if a completed line matched.
+    pub(crate) fn feed(&mut self, chunk: &[u8]) -> bool {
+        let mut matched = false;
+        for &byte in chunk {
+            if byte == b'\n' || byte == b'\r' {
+                matched |= self.finish_line();
+            } else if self.line.len() < MAX_LINE {
+                self.line.push(byte);
+            }
+        }
+        matched
+    }
+
+    /// Check any trailing output that did not end with a newline.
+    pub(crate) fn finish(&mut self) -> bool {
+        self.finish_line()
+    }
+
+    fn finish_line(&mut self) -> bool {
+        let line = String::from_utf8_lossy(&self.line).to_lowercase();
+        self.line.clear();
+        !line.is_empty() && self.patterns.iter().any(|p| line.contains(p.as_str()))
+    }
+}
+
+/// Where captured agent output goes besides the terminal.
+#[derive(Debug, Clone, Default)]
+pub struct OutputCapture {
+    /// Prepended to every output line, e.g. `"[claude] "`.
+    pub prefix: Option<String>,
+    /// File that receives a copy of the output (appended).
+    pub log: Option<std::path::PathBuf>,
+    /// Substrings that mark a rate limit; empty disables detection.
+    pub patterns: Vec<String>,
+    /// Substrings that mark a successful run; empty disables detection.
+    pub success_patterns: Vec<String>,
+}
+
+/// Which kinds of pattern appeared in captured output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OutputMatches {
+    /// A line matched [`OutputCapture::patterns`].
+    pub rate_limited: bool,
+    /// A line matched [`OutputCapture::success_patterns`].
+    pub success: bool,
+}
+
+/// [`OutputMatches`] shared between the stdout and stderr pumps.
+#[derive(Default)]
+struct Detected {
+    rate_limited: AtomicBool,
+    success: AtomicBool,
+}
+
+impl Detected {
+    fn record(&self, rate_limited: bool, success: bool) {
+        if rate_limited {
+            self.rate_limited.store(true, Ordering::Relaxed);
+        }
+        if success {
+            self.success.store(true, Ordering::Relaxed);
+        }
+    }
+
+    fn matches(&self) -> OutputMatches {
+        OutputMatches {
+            rate_limited: self.rate_limited.load(Ordering::Relaxed),
+            success: self.success.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Copies output to a sink, inserting a prefix at the start of every line.
+struct Prefixer<'a> {
+    prefix: Option<&'a str>,
+    at_line_start: bool,
+}
+
+impl Prefixer<'_> {
+    fn write<W: Write>(&mut self, sink: &mut W, chunk: &[u8]) -> std::io::Result<()> {
+        let Some(prefix) = self.prefix else {
+            return sink.write_all(chunk);
+        };
+        for piece in chunk.split_inclusive(|&b| b == b'\n') {
+            if self.at_line_start {
+                sink.write_all(prefix.as_bytes())?;
+            }
+            sink.write_all(piece)?;
+            self.at_line_start = piece.ends_with(b"\n");
+        }
+        Ok(())
+    }
+}
+
+type SharedLog = Option<Arc<Mutex<std::fs::File>>>;
+
+/// Copy `source` to `sink` (and the log) as it arrives, recording pattern matches in
+/// `detected`.
+async fn pump<R, W>(
+    mut source: R,
+    mut sink: W,
+    capture: &OutputCapture,
+    log: &SharedLog,
+    detected: &Detected,
+) where
+    R: AsyncRead + Unpin,
+    W: Write,
+{
+    let mut rate_limit = LineMatcher::new(capture.patterns.clone());
+    let mut success = LineMatcher::new(capture.success_patterns.clone());
+    let mut to_sink = Prefixer {
+        prefix: capture.prefix.as_deref(),
+        at_line_start: true,
+    };
+    let mut to_log = Prefixer {
+        prefix: capture.prefix.as_deref(),
+        at_line_start: true,
+    };
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = match source.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        let chunk = &buf[..n];
+        let _ = to_sink.write(&mut sink, chunk);
+        let _ = sink.flush();
+        if let Some(log) = log
+            && let Ok(mut file) = log.lock()
+        {
+            let _ = to_log.write(&mut *file, chunk);
+        }
+        detected.record(rate_limit.feed(chunk), success.feed(chunk));
+    }
+    detected.record(rate_limit.finish(), success.finish());
+}
+
+/// Wait for `child`, forwarding its piped stdout/stderr to ours as described by `capture`.
+///
+/// Returns the exit status and which of the capture's patterns appeared in the output.
+pub(crate) async fn wait_forwarding(
+    mut child: Child,
+    capture: &OutputCapture,
+    timeout: Option<std::time::Duration>,
+    stop: impl Future<Output = String>,
+) -> std::io::Result<(super::Stop, OutputMatches)> {
+    let log: SharedLog = match &capture.log {
+        Some(path) => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+            Some(Arc::new(Mutex::new(file)))
+        }
+        None => None,
+    };
+    let detected = Detected::default();
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let forward_stdout = async {
+        if let Some(stdout) = stdout {
+            pump(stdout, std::io::stdout(), capture, &log, &detected).await;
+        }
+    };
+    let forward_stderr = async {
+        if let Some(stderr) = stderr {
+            pump(stderr, std::io::stderr(), capture, &log, &detected).await;
+        }
+    };
+    let (status, (), ()) = tokio::join!(
+        super::wait_supervised(&mut child, timeout, stop),
+        forward_stdout,
+        forward_stderr
+    );
+    Ok((status?, detected.matches()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_matcher_matches_across_chunks_case_insensitively() {
+        let mut matcher = LineMatcher::new(vec!["Usage Limit Reached".to_string()]);
+        assert!(!matcher.feed(b"Claude usage lim"));
+        assert!(matcher.feed(b"it reached | resets 5pm\n"));
+        assert!(!matcher.feed(b"all good\n"));
+    }
+
+    #[test]
+    fn line_matcher_checks_unterminated_trailing_line() {
+        let mut matcher = LineMatcher::new(vec!["quota exceeded".to_string()]);
+        assert!(!matcher.feed(b"error: quota exceeded"));
+        assert!(matcher.finish());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn pump_forwards_output_and_flags_matches() {
+        let detected = Detected::default();
+        let mut sink = Vec::new();
+        let capture = OutputCapture {
+            patterns: vec!["rate limit exceeded".to_string()],
+            success_patterns: vec!["all tests pass".to_string()],
+            ..OutputCapture::default()
+        };
+        pump(
+            &b"working\nRate limit exceeded\n"[..],
+            &mut sink,
+            &capture,
+            &None,
+            &detected,
+        )
+        .await;
+        assert_eq!(sink, b"working\nRate limit exceeded\n");
+        assert_eq!(
+            detected.matches(),
+            OutputMatches {
+                rate_limited: true,
+                success: false
+            }
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn pump_prefixes_lines_and_tees_to_log() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let log_path = dir.path().join("agent.log");
+        let log: SharedLog = Some(Arc::new(Mutex::new(std::fs::File::create(&log_path)?)));
+        let capture = OutputCapture {
+            prefix: Some("[claude] ".to_string()),
+            ..OutputCapture::default()
+        };
+        let mut sink = Vec::new();
+        let detected = Detected::default();
+        pump(&b"one\ntwo\nthr"[..], &mut sink, &capture, &log, &detected).await;
+        assert_eq!(sink, b"[claude] one\n[claude] two\n[claude] thr");
+        assert_eq!(
+            std::fs::read(&log_path)?,
+            b"[claude] one\n[claude] two\n[claude] thr"
+        );
+        assert_eq!(detected.matches(), OutputMatches::default());
+        Ok(())
+    }
+}
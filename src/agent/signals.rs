@@ -0,0 +1,183 @@
+//! Forward termination signals to running agents.
+//!
+//! Agents stay in seher's process group so they keep the terminal's foreground for
+//! interactive use. A terminal Ctrl+C therefore already reaches them; seher only has to
+//! survive that SIGINT and keep waiting. Signals sent to seher alone (SIGTERM, SIGHUP,
+//! or SIGINT when seher is not in the foreground) are passed on, and seher exits once
+//! the agent does.
+
+/// Forwards signals to one child until dropped. The handlers are installed while at
+/// least one forwarder is alive and the previous ones are restored afterwards. Does
+/// nothing on non-Unix platforms.
+pub(crate) struct SignalForwarder {
+    #[cfg(unix)]
+    slot: Option<usize>,
+}
+
+impl SignalForwarder {
+    pub(crate) fn install(child_pid: Option<u32>) -> Self {
+        #[cfg(unix)]
+        {
+            let slot = child_pid
+                .and_then(|pid| libc::pid_t::try_from(pid).ok())
+                .and_then(unix::register);
+            Self { slot }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = child_pid;
+            Self {}
+        }
+    }
+}
+
+impl Drop for SignalForwarder {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(slot) = self.slot.take() {
+            unix::unregister(slot);
+        }
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    const SLOTS: usize = 32;
+
+    /// Pids of running agents; 0 marks a free slot. Read from the signal handler, so
+    /// this has to be lock-free.
+    static CHILDREN: [AtomicI32; SLOTS] = [const { AtomicI32::new(0) }; SLOTS];
+
+    struct Installed {
+        forwarders: usize,
+        saved: Vec<(libc::c_int, libc::sigaction)>,
+    }
+
+    static INSTALLED: Mutex<Installed> = Mutex::new(Installed {
+        forwarders: 0,
+        saved: Vec::new(),
+    });
+
+    pub(super) fn signal_children(children: &[AtomicI32], signal: libc::c_int) {
+        for child in children {
+            let pid = child.load(Ordering::Relaxed);
+            if pid > 0 {
+                // SAFETY: `kill` is async-signal-safe and takes plain integers.
+                unsafe {
+                    libc::kill(pid, signal);
+                }
+            }
+        }
+    }
+
+    extern "C" fn forward(signal: libc::c_int) {
+        signal_children(&CHILDREN, signal);
+    }
+
+    /// `true` if seher is the terminal's foreground process group, so terminal-generated
+    /// signals reach the agent directly.
+    fn in_terminal_foreground() -> bool {
+        // SAFETY: both calls only read process state; fd 0 stays open for the process.
+        unsafe { libc::tcgetpgrp(libc::STDIN_FILENO) == libc::getpgrp() }
+    }
+
+    fn set_handler(signal: libc::c_int, handler: libc::sighandler_t) -> Option<libc::sigaction> {
+        // SAFETY: `sigaction` is plain data; zeroed is a valid empty mask with no flags.
+        let mut action: libc::sigaction = unsafe { std::mem::zeroed() };
+        action.sa_sigaction = handler;
+        action.sa_flags = libc::SA_RESTART;
+        // SAFETY: as above.
+        let mut previous: libc::sigaction = unsafe { std::mem::zeroed() };
+        // SAFETY: both pointers refer to valid `sigaction` values for the whole call.
+        let ok = unsafe { libc::sigaction(signal, &raw const action, &raw mut previous) } == 0;
+        ok.then_some(previous)
+    }
+
+    fn install_handlers() -> Vec<(libc::c_int, libc::sigaction)> {
+        let forward = forward as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        let interrupt = if in_terminal_foreground() {
+            libc::SIG_IGN
+        } else {
+            forward
+        };
+        [
+            (libc::SIGINT, interrupt),
+            (libc::SIGTERM, forward),
+            (libc::SIGHUP, forward),
+        ]
+        .into_iter()
+        .filter_map(|(signal, handler)| Some((signal, set_handler(signal, handler)?)))
+        .collect()
+    }
+
+    pub(super) fn register(pid: libc::pid_t) -> Option<usize> {
+        let slot = CHILDREN.iter().position(|child| {
+            child
+                .compare_exchange(0, pid, Ordering::Relaxed, Ordering::Relaxed)
+                .is_ok()
+        })?;
+        let mut installed = INSTALLED
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if installed.forwarders == 0 {
+            installed.saved = install_handlers();
+        }
+        installed.forwarders += 1;
+        Some(slot)
+    }
+
+    pub(super) fn unregister(slot: usize) {
+        CHILDREN[slot].store(0, Ordering::Relaxed);
+        let mut installed = INSTALLED
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        installed.forwarders -= 1;
+        if installed.forwarders == 0 {
+            for (signal, previous) in installed.saved.drain(..) {
+                // SAFETY: `previous` was filled in by a successful `sigaction` call.
+                unsafe {
+                    libc::sigaction(signal, &raw const previous, std::ptr::null_mut());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicI32;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn signals_reach_registered_children() -> TestResult {
+        use std::os::unix::process::ExitStatusExt;
+
+        let mut child = tokio::process::Command::new("sleep").arg("5").spawn()?;
+        let pid = child.id().and_then(|p| libc::pid_t::try_from(p).ok());
+        let children = [AtomicI32::new(0), AtomicI32::new(pid.unwrap_or(0))];
+        unix::signal_children(&children, libc::SIGTERM);
+        let status =
+            tokio::time::timeout(std::time::Duration::from_secs(4), child.wait()).await??;
+        assert_eq!(status.signal(), Some(libc::SIGTERM));
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn forwarders_can_overlap() -> TestResult {
+        let mut first = tokio::process::Command::new("true").spawn()?;
+        let mut second = tokio::process::Command::new("true").spawn()?;
+        let a = SignalForwarder::install(first.id());
+        let b = SignalForwarder::install(second.id());
+        assert_ne!(a.slot, b.slot);
+        drop(a);
+        drop(b);
+        first.wait().await?;
+        second.wait().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,203 @@
+use crate::agent::UsageEntry;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// Percentage points utilization must fall below a threshold before its alert re-arms.
+pub const DEFAULT_HYSTERESIS: f64 = 5.0;
+
+/// An alert rule such as `five_hour > 80%` or `copilot premium > 95%`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    /// Provider (or agent command) the rule applies to; any provider when `None`.
+    pub provider: Option<String>,
+    /// Usage window name; also matches entries starting with `<window>_`.
+    pub window: String,
+    /// Utilization percentage that triggers the alert.
+    pub threshold: f64,
+}
+
+impl AlertRule {
+    fn matches(&self, provider: Option<&str>, command: &str, entry_type: &str) -> bool {
+        let provider_matches = self
+            .provider
+            .as_deref()
+            .is_none_or(|p| provider == Some(p) || command == p);
+        provider_matches
+            && (entry_type == self.window
+                || entry_type
+                    .strip_prefix(self.window.as_str())
+                    .is_some_and(|rest| rest.starts_with('_')))
+    }
+}
+
+impl FromStr for AlertRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (target, value) = s
+            .split_once('>')
+            .ok_or_else(|| format!("alert rule {s:?} must look like \"five_hour > 80%\""))?;
+        let value = value.trim_start_matches('=').trim();
+        let threshold: f64 = value
+            .strip_suffix('%')
+            .unwrap_or(value)
+            .trim()
+            .parse()
+            .map_err(|_| format!("invalid threshold {value:?} in alert rule {s:?}"))?;
+        let mut words = target.split_whitespace();
+        let (provider, window) = match (words.next(), words.next(), words.next()) {
+            (Some(window), None, None) => (None, window),
+            (Some(provider), Some(window), None) => (Some(provider.to_string()), window),
+            _ => return Err(format!("alert rule {s:?} must name [provider] window")),
+        };
+        Ok(Self {
+            provider,
+            window: window.to_string(),
+            threshold,
+        })
+    }
+}
+
+impl fmt::Display for AlertRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(provider) = &self.provider {
+            write!(f, "{provider} ")?;
+        }
+        write!(f, "{} > {}%", self.window, self.threshold)
+    }
+}
+
+/// A rule that crossed its threshold.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub command: String,
+    pub entry_type: String,
+    pub utilization: f64,
+    pub rule: AlertRule,
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} at {:.1}% (rule: {})",
+            self.command, self.entry_type, self.utilization, self.rule
+        )
+    }
+}
+
+/// Fires each rule once per upward crossing, re-arming only after utilization drops
+/// `hysteresis` points below the threshold.
+#[derive(Debug)]
+pub struct AlertTracker {
+    rules: Vec<AlertRule>,
+    hysteresis: f64,
+    fired: HashMap<(usize, String, String), bool>,
+}
+
+impl AlertTracker {
+    #[must_use]
+    pub fn new(rules: Vec<AlertRule>, hysteresis: f64) -> Self {
+        Self {
+            rules,
+            hysteresis,
+            fired: HashMap::new(),
+        }
+    }
+
+    /// Evaluate one agent's usage and return the alerts that just fired.
+    pub fn evaluate(
+        &mut self,
+        command: &str,
+        provider: Option<&str>,
+        entries: &[UsageEntry],
+    ) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            for entry in entries
+                .iter()
+                .filter(|e| rule.matches(provider, command, &e.entry_type))
+            {
+                let key = (index, command.to_string(), entry.entry_type.clone());
+                let fired = self.fired.entry(key).or_insert(false);
+                if !*fired && entry.utilization > rule.threshold {
+                    *fired = true;
+                    alerts.push(Alert {
+                        command: command.to_string(),
+                        entry_type: entry.entry_type.clone(),
+                        utilization: entry.utilization,
+                        rule: rule.clone(),
+                    });
+                } else if *fired && entry.utilization < rule.threshold - self.hysteresis {
+                    *fired = false;
+                }
+            }
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(entry_type: &str, utilization: f64) -> UsageEntry {
+        UsageEntry {
+            entry_type: entry_type.to_string(),
+            limited: utilization >= 100.0,
+            utilization,
+            resets_at: None,
+            remaining: None,
+            limit: None,
+            limit_eta: None,
+        }
+    }
+
+    #[test]
+    fn parses_rules_with_and_without_provider() -> Result<(), String> {
+        let rule: AlertRule = "five_hour > 80%".parse()?;
+        assert_eq!(rule.provider, None);
+        assert_eq!(rule.window, "five_hour");
+        assert!((rule.threshold - 80.0).abs() < f64::EPSILON);
+
+        let rule: AlertRule = "copilot premium >= 95".parse()?;
+        assert_eq!(rule.provider.as_deref(), Some("copilot"));
+        assert_eq!(rule.window, "premium");
+        assert!((rule.threshold - 95.0).abs() < f64::EPSILON);
+
+        assert!("five_hour 80%".parse::<AlertRule>().is_err());
+        assert!("a b c > 1".parse::<AlertRule>().is_err());
+        assert!("five_hour > lots".parse::<AlertRule>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn rule_window_matches_prefixed_entries() -> Result<(), String> {
+        let rule: AlertRule = "copilot premium > 95%".parse()?;
+        assert!(rule.matches(Some("copilot"), "copilot", "premium_utilization"));
+        assert!(!rule.matches(Some("copilot"), "copilot", "premiumx"));
+        assert!(!rule.matches(Some("claude"), "claude", "premium_utilization"));
+        Ok(())
+    }
+
+    #[test]
+    fn fires_once_per_crossing_with_hysteresis() -> Result<(), String> {
+        let mut tracker = AlertTracker::new(vec!["five_hour > 80%".parse()?], 5.0);
+        let mut check = |u| {
+            tracker
+                .evaluate("claude", Some("claude"), &[entry("five_hour", u)])
+                .len()
+        };
+        assert_eq!(check(70.0), 0);
+        assert_eq!(check(85.0), 1);
+        assert_eq!(check(90.0), 0);
+        // Dipping just below the threshold does not re-arm.
+        assert_eq!(check(78.0), 0);
+        assert_eq!(check(85.0), 0);
+        // Falling past the hysteresis band does.
+        assert_eq!(check(60.0), 0);
+        assert_eq!(check(81.0), 1);
+        Ok(())
+    }
+}
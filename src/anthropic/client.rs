@@ -0,0 +1,220 @@
+use chrono::{DateTime, Utc};
+
+use super::types::{ConsoleOrganization, CostReport, PrepaidCredits};
+use crate::budget::month_start;
+use crate::error::SeherError;
+use crate::http::{Endpoint, HttpRequest, HttpResponse, HttpTransport, ReqwestTransport, base_url};
+
+#[cfg(feature = "browser")]
+use crate::Cookie;
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+/// Pages of daily buckets are capped by the API; a month never needs more than this.
+const MAX_COST_PAGES: usize = 12;
+
+pub struct AnthropicConsoleClient;
+
+impl AnthropicConsoleClient {
+    /// Fetch the prepaid credit balance of `org_id` using a Console cookie header.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Console request fails or the response cannot be parsed.
+    pub async fn fetch_prepaid_credits_with_header(
+        cookie_header: &str,
+        org_id: &str,
+    ) -> Result<PrepaidCredits, SeherError> {
+        Self::fetch_prepaid_credits_via(&ReqwestTransport, cookie_header, org_id).await
+    }
+
+    /// [`Self::fetch_prepaid_credits_with_header`] over a caller-supplied transport.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Console request fails or the response cannot be parsed.
+    pub async fn fetch_prepaid_credits_via(
+        transport: &dyn HttpTransport,
+        cookie_header: &str,
+        org_id: &str,
+    ) -> Result<PrepaidCredits, SeherError> {
+        let url = format!(
+            "{}/api/organizations/{org_id}/prepaid/credits",
+            base_url(Endpoint::AnthropicConsole)
+        );
+        let response = Self::send(transport, Self::console_request(url, cookie_header)).await?;
+        Ok(response.json()?)
+    }
+
+    /// List the organizations the Console session belongs to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Console request fails or the response cannot be parsed.
+    pub async fn fetch_organizations_via(
+        transport: &dyn HttpTransport,
+        cookie_header: &str,
+    ) -> Result<Vec<ConsoleOrganization>, SeherError> {
+        let url = format!("{}/api/organizations", base_url(Endpoint::AnthropicConsole));
+        let response = Self::send(transport, Self::console_request(url, cookie_header)).await?;
+        Ok(response.json()?)
+    }
+
+    /// Month-to-date API spend in dollars from the Admin API cost report.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Admin API request fails or the response cannot be parsed.
+    pub async fn fetch_month_cost(admin_key: &str) -> Result<f64, SeherError> {
+        Self::fetch_month_cost_via(&ReqwestTransport, admin_key, Utc::now()).await
+    }
+
+    /// [`Self::fetch_month_cost`] over a caller-supplied transport, for the month of `now`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Admin API request fails or the response cannot be parsed.
+    pub async fn fetch_month_cost_via(
+        transport: &dyn HttpTransport,
+        admin_key: &str,
+        now: DateTime<Utc>,
+    ) -> Result<f64, SeherError> {
+        let starting_at = month_start(now).format("%Y-%m-%dT%H:%M:%SZ");
+        let base = format!(
+            "{}/v1/organizations/cost_report?starting_at={starting_at}&bucket_width=1d&limit=31",
+            base_url(Endpoint::AnthropicApi)
+        );
+        let mut total = 0.0;
+        let mut url = base.clone();
+        for _ in 0..MAX_COST_PAGES {
+            let request = HttpRequest::get(&url)
+                .header("x-api-key", admin_key)
+                .header("anthropic-version", ANTHROPIC_VERSION)
+                .header("accept", "application/json");
+            let report: CostReport = Self::send(transport, request).await?.json()?;
+            total += report.total();
+            match report.next_page.filter(|_| report.has_more) {
+                Some(page) => url = format!("{base}&page={page}"),
+                None => break,
+            }
+        }
+        Ok(total)
+    }
+
+    /// Fetch the prepaid credit balance from browser cookies, using the `lastActiveOrg`
+    /// cookie or else the session's first organization.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Console request fails, the response cannot be parsed, or
+    /// the session belongs to no organization.
+    #[cfg(feature = "browser")]
+    pub async fn fetch_prepaid_credits(cookies: &[Cookie]) -> Result<PrepaidCredits, SeherError> {
+        let cookie_header = Self::build_cookie_header(cookies);
+        let org_id = match Self::last_active_org(cookies) {
+            Some(org_id) => org_id,
+            None => Self::fetch_organizations_via(&ReqwestTransport, &cookie_header)
+                .await?
+                .into_iter()
+                .next()
+                .map(|org| org.uuid)
+                .ok_or("No organization found for this Console session")?,
+        };
+        Self::fetch_prepaid_credits_with_header(&cookie_header, &org_id).await
+    }
+
+    #[cfg(feature = "browser")]
+    fn last_active_org(cookies: &[Cookie]) -> Option<String> {
+        cookies
+            .iter()
+            .find(|c| c.name == "lastActiveOrg")
+            .map(|c| c.value.replace("%22", "").trim_matches('"').to_string())
+            .filter(|org| !org.is_empty())
+    }
+
+    #[cfg(feature = "browser")]
+    fn build_cookie_header(cookies: &[Cookie]) -> String {
+        crate::browser::cookie_header(cookies, "console.anthropic.com", "/api/")
+    }
+
+    fn console_request(url: String, cookie_header: &str) -> HttpRequest {
+        let origin = base_url(Endpoint::AnthropicConsole);
+        HttpRequest::get(url)
+            .header("Cookie", cookie_header)
+            .header("Accept", "application/json")
+            .header("Referer", format!("{origin}/"))
+            .header("Origin", origin)
+    }
+
+    async fn send(
+        transport: &dyn HttpTransport,
+        request: HttpRequest,
+    ) -> Result<HttpResponse, SeherError> {
+        let response = transport.send(request).await?;
+        if !response.is_success() {
+            return Err(SeherError::Status {
+                service: "Anthropic Console API",
+                status: response.status,
+                body: response.body,
+            });
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::transport::mock::MockTransport;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn month_cost_follows_pages_from_the_start_of_the_month() -> TestResult {
+        let base = format!(
+            "{}/v1/organizations/cost_report?starting_at=2026-10-01T00:00:00Z&bucket_width=1d&limit=31",
+            base_url(Endpoint::AnthropicApi)
+        );
+        let second = format!("{base}&page=p2");
+        let bucket = |amount: &str, more: bool| {
+            format!(
+                r#"{{"data": [{{"starting_at": "2026-10-01T00:00:00Z", "ending_at": "2026-10-02T00:00:00Z", "results": [{{"amount": "{amount}"}}]}}], "has_more": {more}, "next_page": "p2"}}"#
+            )
+        };
+        let transport = MockTransport::new(vec![
+            (base.as_str(), HttpResponse::new(200, bucket("1000", true))),
+            (
+                second.as_str(),
+                HttpResponse::new(200, bucket("250", false)),
+            ),
+        ]);
+        let now = "2026-10-15T12:00:00Z".parse()?;
+
+        let cost =
+            AnthropicConsoleClient::fetch_month_cost_via(&transport, "sk-ant-admin", now).await?;
+
+        assert!((cost - 12.5).abs() < 1e-9);
+        let requests = transport.requests.lock().map_err(|e| e.to_string())?;
+        assert_eq!(requests.len(), 2);
+        assert!(
+            requests[0]
+                .headers
+                .contains(&("x-api-key".to_string(), "sk-ant-admin".to_string()))
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn prepaid_credits_surface_console_errors() {
+        let url = format!(
+            "{}/api/organizations/org-1/prepaid/credits",
+            base_url(Endpoint::AnthropicConsole)
+        );
+        let transport = MockTransport::new(vec![(url.as_str(), HttpResponse::new(403, "denied"))]);
+
+        let result =
+            AnthropicConsoleClient::fetch_prepaid_credits_via(&transport, "sessionKey=x", "org-1")
+                .await;
+
+        assert!(result.is_err_and(|e| e.is_auth_error()));
+    }
+}
@@ -0,0 +1,5 @@
+pub mod client;
+pub mod types;
+
+pub use client::AnthropicConsoleClient;
+pub use types::{ConsoleOrganization, CostReport, PrepaidCredits};
@@ -0,0 +1,108 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Prepaid API credit balance shown on console.anthropic.com.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PrepaidCredits {
+    /// Remaining balance in cents.
+    pub amount: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub currency: Option<String>,
+}
+
+impl PrepaidCredits {
+    /// Remaining balance in dollars.
+    #[must_use]
+    #[expect(clippy::cast_precision_loss)]
+    pub fn balance(&self) -> f64 {
+        self.amount as f64 / 100.0
+    }
+
+    /// Returns `true` once the prepaid balance is used up.
+    #[must_use]
+    pub fn is_limited(&self) -> bool {
+        self.amount <= 0
+    }
+}
+
+/// An organization the Console session belongs to.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ConsoleOrganization {
+    pub uuid: String,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// One page of the Admin API `/v1/organizations/cost_report`.
+#[derive(Debug, Deserialize)]
+pub struct CostReport {
+    pub data: Vec<CostBucket>,
+    #[serde(default)]
+    pub has_more: bool,
+    #[serde(default)]
+    pub next_page: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CostBucket {
+    pub starting_at: DateTime<Utc>,
+    pub ending_at: DateTime<Utc>,
+    #[serde(default)]
+    pub results: Vec<CostResult>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CostResult {
+    /// Cost in cents, as a decimal string (e.g. `"123.4500"`).
+    pub amount: String,
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+impl CostReport {
+    /// Total cost on this page in dollars; unparseable amounts count as zero.
+    #[must_use]
+    pub fn total(&self) -> f64 {
+        self.data
+            .iter()
+            .flat_map(|bucket| &bucket.results)
+            .filter_map(|result| result.amount.trim().parse::<f64>().ok())
+            .sum::<f64>()
+            / 100.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn prepaid_credits_are_in_cents() -> TestResult {
+        let credits: PrepaidCredits =
+            serde_json::from_str(r#"{"amount": 1250, "currency": "USD"}"#)?;
+        assert!((credits.balance() - 12.5).abs() < f64::EPSILON);
+        assert!(!credits.is_limited());
+        let empty: PrepaidCredits = serde_json::from_str(r#"{"amount": 0}"#)?;
+        assert!(empty.is_limited());
+        Ok(())
+    }
+
+    #[test]
+    fn cost_report_sums_decimal_cent_amounts() -> TestResult {
+        let json = r#"{
+            "data": [
+                {"starting_at": "2026-10-01T00:00:00Z", "ending_at": "2026-10-02T00:00:00Z",
+                 "results": [{"amount": "1234.50", "currency": "USD"}, {"amount": "65.50", "currency": "USD"}]},
+                {"starting_at": "2026-10-02T00:00:00Z", "ending_at": "2026-10-03T00:00:00Z", "results": []}
+            ],
+            "has_more": false,
+            "next_page": null
+        }"#;
+        let report: CostReport = serde_json::from_str(json)?;
+        assert!((report.total() - 13.0).abs() < 1e-9);
+        assert!(!report.has_more);
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use crate::browser::Profile;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use thiserror::Error;
+
+static INSTALLED: OnceLock<Installed> = OnceLock::new();
+
+struct Installed {
+    path: PathBuf,
+    subcommand: String,
+}
+
+#[derive(Debug, Error)]
+pub enum AuditError {
+    #[error("could not determine home directory for the audit log")]
+    HomeDirNotFound,
+
+    #[error("failed to access audit log: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to parse audit log: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// One read of a browser cookie database.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub at: DateTime<Utc>,
+    pub browser: String,
+    pub profile: String,
+    pub domain: String,
+    /// The seher subcommand that triggered the read (`run` for the default command).
+    pub subcommand: String,
+}
+
+/// `$SEHER_AUDIT_PATH`, else `~/.seher/audit.log`.
+///
+/// # Errors
+///
+/// Returns an error if the home directory cannot be determined.
+pub fn default_path() -> Result<PathBuf, AuditError> {
+    if let Some(path) = std::env::var_os("SEHER_AUDIT_PATH").filter(|p| !p.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+    Ok(dirs::home_dir()
+        .ok_or(AuditError::HomeDirNotFound)?
+        .join(".seher")
+        .join("audit.log"))
+}
+
+/// Enable audit logging for this process, attributing reads to `subcommand`.
+///
+/// Returns `false` if logging was already enabled.
+pub fn install(path: PathBuf, subcommand: impl Into<String>) -> bool {
+    INSTALLED
+        .set(Installed {
+            path,
+            subcommand: subcommand.into(),
+        })
+        .is_ok()
+}
+
+/// Record a read of `profile`'s cookie database for `domain` into the installed log.
+///
+/// This is best-effort: it does nothing when logging is not enabled, and failures never
+/// interrupt the read being logged.
+pub fn record(profile: &Profile, domain: &str) {
+    let Some(installed) = INSTALLED.get() else {
+        return;
+    };
+    let entry = AuditEntry {
+        at: Utc::now(),
+        browser: profile.browser_type.name().to_string(),
+        profile: profile.name.clone(),
+        domain: domain.to_string(),
+        subcommand: installed.subcommand.clone(),
+    };
+    if let Err(e) = append(&installed.path, &entry) {
+        crate::warning::warn(format_args!("failed to write audit log: {e}"));
+    }
+}
+
+/// Append `entry` as one JSON line, creating the log (mode 0600) if needed.
+///
+/// # Errors
+///
+/// Returns an error if the log cannot be created or written.
+pub fn append(path: &Path, entry: &AuditEntry) -> Result<(), AuditError> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut line = serde_json::to_vec(entry)?;
+    line.push(b'\n');
+    options.open(path)?.write_all(&line)?;
+    Ok(())
+}
+
+/// Every entry in the log at `path`, oldest first. A missing log has no entries.
+///
+/// # Errors
+///
+/// Returns an error if the log exists but cannot be read or holds a malformed line.
+pub fn read(path: &Path) -> Result<Vec<AuditEntry>, AuditError> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+    let mut entries = Vec::new();
+    for line in std::io::BufReader::new(file).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            entries.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    fn entry(domain: &str) -> AuditEntry {
+        AuditEntry {
+            at: Utc::now(),
+            browser: "Chrome".to_string(),
+            profile: "Default".to_string(),
+            domain: domain.to_string(),
+            subcommand: "status".to_string(),
+        }
+    }
+
+    #[test]
+    fn appends_and_reads_entries_in_order() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("nested").join("audit.log");
+        assert!(read(&path)?.is_empty());
+
+        append(&path, &entry("claude.ai"))?;
+        append(&path, &entry("github.com"))?;
+
+        let entries = read(&path)?;
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].domain, "claude.ai");
+        assert_eq!(entries[1].domain, "github.com");
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn log_is_private() -> TestResult {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("audit.log");
+        append(&path, &entry("claude.ai"))?;
+        assert_eq!(
+            std::fs::metadata(&path)?.permissions().mode() & 0o777,
+            0o600
+        );
+        Ok(())
+    }
+}
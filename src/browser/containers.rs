@@ -0,0 +1,103 @@
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Deserialize)]
+struct ContainersFile {
+    #[serde(default)]
+    identities: Vec<Identity>,
+}
+
+#[derive(Deserialize)]
+struct Identity {
+    #[serde(rename = "userContextId")]
+    user_context_id: u32,
+    #[serde(default)]
+    public: bool,
+    name: Option<String>,
+    /// Set instead of `name` on the built-in containers, e.g. `userContextWork.label`.
+    #[serde(rename = "l10nID")]
+    l10n_id: Option<String>,
+}
+
+impl Identity {
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref().or_else(|| {
+            self.l10n_id
+                .as_deref()?
+                .strip_prefix("userContext")?
+                .strip_suffix(".label")
+        })
+    }
+}
+
+/// The `userContextId` of the Multi-Account Container called `name` (case-insensitive)
+/// in the Firefox profile at `profile_dir`, from its `containers.json`. Built-in
+/// containers are named as Firefox shows them in English (`Personal`, `Work`, `Banking`,
+/// `Shopping`).
+#[must_use]
+pub fn container_id(profile_dir: &Path, name: &str) -> Option<u32> {
+    let content = std::fs::read_to_string(profile_dir.join("containers.json")).ok()?;
+    let file: ContainersFile = serde_json::from_str(&content).ok()?;
+    file.identities
+        .iter()
+        .filter(|identity| identity.public)
+        .find(|identity| {
+            identity
+                .name()
+                .is_some_and(|n| n.eq_ignore_ascii_case(name))
+        })
+        .map(|identity| identity.user_context_id)
+}
+
+/// The `userContextId` in a cookie's `originAttributes` suffix (`^userContextId=2&...`);
+/// `0` (no container) when absent.
+#[must_use]
+pub fn user_context_id(origin_attributes: &str) -> u32 {
+    origin_attributes
+        .trim_start_matches('^')
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("userContextId="))
+        .and_then(|id| id.parse().ok())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn resolves_custom_and_built_in_container_names() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(
+            dir.path().join("containers.json"),
+            r#"{"version":5,"identities":[
+                {"userContextId":1,"public":true,"l10nID":"userContextPersonal.label"},
+                {"userContextId":2,"public":true,"l10nID":"userContextWork.label"},
+                {"userContextId":4294967295,"public":false,"name":"userContextIdInternal.thumbnail"},
+                {"userContextId":6,"public":true,"name":"Claude"}
+            ]}"#,
+        )?;
+
+        assert_eq!(container_id(dir.path(), "work"), Some(2));
+        assert_eq!(container_id(dir.path(), "Claude"), Some(6));
+        assert_eq!(
+            container_id(dir.path(), "userContextIdInternal.thumbnail"),
+            None
+        );
+        assert_eq!(container_id(dir.path(), "Shopping"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn reads_the_container_from_origin_attributes() {
+        assert_eq!(user_context_id(""), 0);
+        assert_eq!(user_context_id("^userContextId=6"), 6);
+        assert_eq!(
+            user_context_id("^userContextId=2&partitionKey=%28https%2Cclaude.ai%29"),
+            2
+        );
+        assert_eq!(user_context_id("^partitionKey=%28https%2Cclaude.ai%29"), 0);
+    }
+}
@@ -0,0 +1,184 @@
+use super::types::Cookie;
+use std::collections::HashMap;
+
+/// Build the `Cookie` header a browser would send on a request to `path` on `host`.
+///
+/// Cookie readers match domains loosely, so only cookies that apply to the request are
+/// kept: host-only cookies for exactly `host`, domain cookies for `host` or a parent,
+/// paths that prefix `path`, and nothing expired (RFC 6265, section 5.4). Cookie
+/// databases hold one row per (name, domain, path), so a site can have both a host-only
+/// cookie and a broader `.domain` duplicate. Only one value per name is sent, picked in
+/// browser precedence order: host-only over domain cookies, then the longer path, then
+/// the newer cookie. `__Host-` and `__Secure-` cookies that break their prefix rules are
+/// dropped, as are values a header cannot carry. The remaining cookies are ordered
+/// longest path first, then oldest first.
+///
+/// `cookies` is expected newest first, as the cookie readers return them.
+#[must_use]
+pub fn cookie_header(cookies: &[Cookie], host: &str, path: &str) -> String {
+    let mut best: HashMap<&str, (usize, &Cookie)> = HashMap::new();
+    for (age, cookie) in cookies.iter().enumerate() {
+        if !applies_to(cookie, host, path) || !is_sendable(cookie) {
+            continue;
+        }
+        match best.get(cookie.name.as_str()) {
+            Some((_, current)) if precedence(current) >= precedence(cookie) => {}
+            _ => {
+                best.insert(&cookie.name, (age, cookie));
+            }
+        }
+    }
+    let mut chosen: Vec<(usize, &Cookie)> = best.into_values().collect();
+    // A larger index is an older cookie.
+    chosen.sort_by(|(a_age, a), (b_age, b)| b.path.len().cmp(&a.path.len()).then(b_age.cmp(a_age)));
+    chosen
+        .iter()
+        .map(|(_, c)| format!("{}={}", c.name, c.value))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// `true` if a browser would send `cookie` on a request to `path` on `host`.
+fn applies_to(cookie: &Cookie, host: &str, path: &str) -> bool {
+    domain_matches(cookie, host) && path_matches(&cookie.path, path) && !cookie.is_expired()
+}
+
+/// Host-only cookies match their host exactly; domain cookies also match subdomains.
+fn domain_matches(cookie: &Cookie, host: &str) -> bool {
+    match cookie.domain.strip_prefix('.') {
+        Some(domain) => {
+            host.eq_ignore_ascii_case(domain)
+                || host.len().checked_sub(domain.len() + 1).is_some_and(|dot| {
+                    host.as_bytes()[dot] == b'.' && host[dot + 1..].eq_ignore_ascii_case(domain)
+                })
+        }
+        None => host.eq_ignore_ascii_case(&cookie.domain),
+    }
+}
+
+/// RFC 6265, section 5.1.4: the cookie path is the request path or one of its
+/// directories.
+fn path_matches(cookie_path: &str, path: &str) -> bool {
+    let cookie_path = if cookie_path.is_empty() {
+        "/"
+    } else {
+        cookie_path
+    };
+    path.strip_prefix(cookie_path)
+        .is_some_and(|rest| rest.is_empty() || cookie_path.ends_with('/') || rest.starts_with('/'))
+}
+
+fn is_host_only(cookie: &Cookie) -> bool {
+    !cookie.domain.starts_with('.')
+}
+
+/// Ranks duplicates of one name; ties keep the earlier (newer) cookie.
+fn precedence(cookie: &Cookie) -> (bool, usize) {
+    (is_host_only(cookie), cookie.path.len())
+}
+
+fn is_sendable(cookie: &Cookie) -> bool {
+    if cookie.value.bytes().any(|b| b < 0x20 || b == 0x7f) {
+        return false;
+    }
+    if cookie.name.starts_with("__Host-") {
+        return cookie.is_secure && is_host_only(cookie) && cookie.path == "/";
+    }
+    if cookie.name.starts_with("__Secure-") {
+        return cookie.is_secure;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cookie(name: &str, value: &str, domain: &str, path: &str) -> Cookie {
+        Cookie {
+            name: name.to_string(),
+            value: value.to_string(),
+            domain: domain.to_string(),
+            path: path.to_string(),
+            expires_utc: 0,
+            is_secure: true,
+            is_httponly: true,
+            same_site: 0,
+        }
+    }
+
+    #[test]
+    fn host_only_cookies_win_over_domain_duplicates() {
+        // Newest first, as read from the database.
+        let cookies = [
+            cookie("user_session", "broad", ".github.com", "/"),
+            cookie("_octo", "octo", ".github.com", "/"),
+            cookie("user_session", "host", "github.com", "/"),
+            cookie("logged_in", "yes", ".github.com", "/"),
+        ];
+        assert_eq!(
+            cookie_header(&cookies, "github.com", "/"),
+            "logged_in=yes; user_session=host; _octo=octo"
+        );
+    }
+
+    #[test]
+    fn orders_longer_paths_first_and_drops_invalid_prefixed_cookies() {
+        let mut insecure = cookie("__Secure-next", "x", "github.com", "/");
+        insecure.is_secure = false;
+        let cookies = [
+            cookie("__Host-user_session_same_site", "host", "github.com", "/"),
+            cookie("__Host-bad", "domain", ".github.com", "/"),
+            insecure,
+            cookie("tz", "UTC", "github.com", "/"),
+            cookie("saml", "s", "github.com", "/orgs/acme"),
+            cookie("ctl", "bad\nvalue", "github.com", "/"),
+        ];
+        assert_eq!(
+            cookie_header(&cookies, "github.com", "/orgs/acme/settings"),
+            "saml=s; tz=UTC; __Host-user_session_same_site=host"
+        );
+    }
+
+    #[test]
+    fn sibling_subdomain_cookies_are_not_sent() {
+        // The readers match `%github.com`, so sibling hosts come along.
+        let cookies = [
+            cookie("user_session", "gist", "gist.github.com", "/"),
+            cookie("user_session", "api", "api.github.com", "/"),
+            cookie("_gh_sess", "other", "notgithub.com", "/"),
+            cookie("user_session", "broad", ".github.com", "/"),
+            cookie("logged_in", "yes", ".github.com", "/"),
+        ];
+        assert_eq!(
+            cookie_header(&cookies, "github.com", "/github-copilot/chat"),
+            "logged_in=yes; user_session=broad"
+        );
+        assert_eq!(
+            cookie_header(&cookies, "gist.github.com", "/"),
+            "logged_in=yes; user_session=gist"
+        );
+    }
+
+    #[test]
+    fn path_scoped_and_expired_cookies_are_left_out() {
+        let mut expired = cookie("old", "x", "github.com", "/");
+        // 2001-09-09, in microseconds since 1601-01-01.
+        expired.expires_utc = 12_644_473_600_000_000;
+        let cookies = [
+            cookie("saml", "s", "github.com", "/orgs/acme"),
+            cookie("prefix", "p", "github.com", "/orgs/ac"),
+            cookie("api", "a", "github.com", "/github-copilot/"),
+            expired,
+            cookie("tz", "UTC", "github.com", "/"),
+        ];
+        assert_eq!(
+            cookie_header(&cookies, "github.com", "/github-copilot/chat"),
+            "api=a; tz=UTC"
+        );
+        assert_eq!(
+            cookie_header(&cookies, "github.com", "/orgs/acme"),
+            "saml=s; tz=UTC"
+        );
+    }
+}
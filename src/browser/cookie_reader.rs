@@ -1,4 +1,4 @@
-use super::types::{Cookie, Profile};
+use super::types::{Cookie, CookieEngine, LazyCookie, Profile};
 use crate::crypto;
 use rusqlite::Connection;
 use std::path::Path;
@@ -17,10 +17,28 @@ pub enum CookieReaderError {
 
     #[error("No cookies found for domain: {0}")]
     NoCookiesFound(String),
+
+    #[error("Firefox container not found: {0}")]
+    ContainerNotFound(String),
 }
 
 pub type Result<T> = std::result::Result<T, CookieReaderError>;
 
+/// A cookie that was skipped because its value could not be decrypted.
+#[derive(Debug)]
+pub struct CookieWarning {
+    pub name: String,
+    pub error: crypto::CryptoError,
+}
+
+/// Cookies read from a profile, along with the ones that had to be skipped.
+#[derive(Debug, Default)]
+pub struct DecryptedCookies {
+    pub cookies: Vec<Cookie>,
+    pub warnings: Vec<CookieWarning>,
+}
+
+#[cfg(feature = "safari")]
 /// Clamp a finite `f64` to a safe subrange of `i64`, then convert via string.
 ///
 /// Safari cookie expiry values are seconds since 2001-01-01, on the order of
@@ -36,57 +54,205 @@ fn f64_to_i64_saturating(v: f64) -> i64 {
     clamped.to_string().parse().unwrap_or(0)
 }
 
+#[cfg(any(feature = "firefox", feature = "safari"))]
+fn into_lazy(cookies: Vec<Cookie>) -> Vec<LazyCookie> {
+    cookies.into_iter().map(LazyCookie::from).collect()
+}
+
+/// Copy a cookie database to a temporary file that is removed when dropped.
+fn copy_database(path: &Path) -> Result<tempfile::NamedTempFile> {
+    let copy = tempfile::Builder::new()
+        .prefix("cookies_")
+        .suffix(".db")
+        .tempfile()?;
+    std::fs::copy(path, copy.path())?;
+    Ok(copy)
+}
+
+/// The cookie query for a Chromium `cookies` table, adapted to its columns.
+///
+/// Older Chromium versions and forks name the flags `secure`/`httponly`, store
+/// `firstpartyonly` instead of `samesite` (or neither), and predate `encrypted_value`;
+/// missing columns are read as their defaults instead of failing the whole profile.
+fn chromium_query(conn: &Connection) -> Result<String> {
+    let mut stmt = conn.prepare("SELECT name FROM pragma_table_info('cookies')")?;
+    let columns = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<std::collections::HashSet<_>>>()?;
+    let pick = |candidates: &[&'static str], default: &'static str| {
+        candidates
+            .iter()
+            .copied()
+            .find(|column| columns.contains(*column))
+            .unwrap_or(default)
+    };
+    let encrypted_value = pick(&["encrypted_value"], "X''");
+    let value = pick(&["value"], "''");
+    let is_secure = pick(&["is_secure", "secure"], "0");
+    let is_httponly = pick(&["is_httponly", "httponly"], "0");
+    // Chromium's CookieSameSite: -1 unspecified, 2 strict (what `firstpartyonly` meant).
+    let same_site = if columns.contains("samesite") {
+        "samesite"
+    } else if columns.contains("firstpartyonly") {
+        "CASE WHEN firstpartyonly THEN 2 ELSE -1 END"
+    } else {
+        "-1"
+    };
+    Ok(format!(
+        "SELECT name, {encrypted_value}, {value}, host_key, path, expires_utc, {is_secure}, \
+         {is_httponly}, {same_site}
+         FROM cookies
+         WHERE host_key LIKE ?1 OR host_key LIKE ?2
+         ORDER BY creation_utc DESC"
+    ))
+}
+
 pub struct CookieReader;
 
 impl CookieReader {
+    /// Read and decrypt `domain`'s cookies. Cookies that fail to decrypt are left out and
+    /// reported in [`DecryptedCookies::warnings`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cookies file is not found or cannot be read, or if no cookie
+    /// could be decrypted.
+    pub fn read_cookies(profile: &Profile, domain: &str) -> Result<DecryptedCookies> {
+        let decrypted = Self::decrypt_cookies(Self::read_cookies_lazy(profile, domain)?);
+        if decrypted.cookies.is_empty() {
+            return Err(CookieReaderError::NoCookiesFound(domain.to_string()));
+        }
+        Ok(decrypted)
+    }
+
+    /// Like [`CookieReader::read_cookies`], but leaves encrypted values encrypted until
+    /// [`LazyCookie::value`] is called.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the cookies file is not found or cannot be read.
+    pub fn read_cookies_lazy(profile: &Profile, domain: &str) -> Result<Vec<LazyCookie>> {
+        crate::audit::record(profile, domain);
+        #[cfg(feature = "cli")]
+        let mut span = {
+            let mut span = crate::telemetry::Span::start("cookies.read");
+            span.set("browser", profile.browser_type.name());
+            span.set("profile", profile.name.as_str());
+            span.set("domain", domain);
+            span
+        };
+        let result = Self::read_profile_lazy(profile, domain);
+        #[cfg(feature = "cli")]
+        {
+            match &result {
+                Ok(cookies) => span.set("cookies", cookies.len()),
+                Err(e) => span.fail(e),
+            }
+            span.end();
+        }
+        result
+    }
+
+    fn read_profile_lazy(profile: &Profile, domain: &str) -> Result<Vec<LazyCookie>> {
+        #[cfg(feature = "firefox")]
+        if let Some(name) = &profile.container
+            && profile.browser_type.engine() == CookieEngine::Firefox
+        {
+            let id = super::containers::container_id(&profile.path, name)
+                .ok_or_else(|| CookieReaderError::ContainerNotFound(name.clone()))?;
+            let copy = copy_database(&profile.cookies_path())?;
+            return Self::read_firefox_cookies(copy.path(), domain, Some(id)).map(into_lazy);
+        }
+        Self::read_lazy_from_path(
+            &profile.cookies_path(),
+            profile.browser_type.engine(),
+            domain,
+        )
+    }
+
+    /// Read and decrypt `domain`'s cookies from a cookie database at `path`, e.g. a fixture,
+    /// a backup copy or a profile outside the usual browser directories.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the cookies file is not found, cannot be read, or decryption fails.
-    pub fn read_cookies(profile: &Profile, domain: &str) -> Result<Vec<Cookie>> {
-        let cookies_path = profile.cookies_path();
+    /// Returns an error if the file is not found or cannot be read, or if no cookie could be
+    /// decrypted.
+    pub fn read_from_path(
+        path: &Path,
+        engine: CookieEngine,
+        domain: &str,
+    ) -> Result<DecryptedCookies> {
+        let decrypted = Self::decrypt_cookies(Self::read_lazy_from_path(path, engine, domain)?);
+        if decrypted.cookies.is_empty() {
+            return Err(CookieReaderError::NoCookiesFound(domain.to_string()));
+        }
+        Ok(decrypted)
+    }
 
-        if !cookies_path.exists() {
+    /// Like [`CookieReader::read_from_path`], but leaves encrypted values encrypted until
+    /// [`LazyCookie::value`] is called.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file is not found or cannot be read.
+    pub fn read_lazy_from_path(
+        path: &Path,
+        engine: CookieEngine,
+        domain: &str,
+    ) -> Result<Vec<LazyCookie>> {
+        if !path.exists() {
             return Err(CookieReaderError::NoCookiesFound(format!(
                 "Cookies file not found: {}",
-                cookies_path.display()
+                path.display()
             )));
         }
 
-        let temp_dir = std::env::temp_dir();
-        let temp_cookies = temp_dir.join(format!("cookies_{}.db", std::process::id()));
-        std::fs::copy(&cookies_path, &temp_cookies)?;
-
-        let result = if profile.browser_type.is_chromium_based() {
-            Self::read_chromium_cookies(&temp_cookies, domain, profile)
-        } else if profile.browser_type == super::types::BrowserType::Firefox {
-            Self::read_firefox_cookies(&temp_cookies, domain)
-        } else if profile.browser_type == super::types::BrowserType::Safari {
-            Self::read_safari_cookies(&cookies_path, domain)
-        } else {
-            Err(CookieReaderError::NoCookiesFound(format!(
-                "Unsupported browser type: {:?}",
-                profile.browser_type
-            )))
-        };
-
-        let _ = std::fs::remove_file(&temp_cookies);
+        match engine {
+            CookieEngine::Chromium => {
+                // Copy the database first: the browser keeps it locked while running.
+                let copy = copy_database(path)?;
+                Self::read_chromium_cookies(copy.path(), domain)
+            }
+            #[cfg(feature = "firefox")]
+            CookieEngine::Firefox => {
+                let copy = copy_database(path)?;
+                Self::read_firefox_cookies(copy.path(), domain, None).map(into_lazy)
+            }
+            #[cfg(feature = "safari")]
+            CookieEngine::Safari => Self::read_safari_cookies(path, domain).map(into_lazy),
+            #[cfg(not(all(feature = "firefox", feature = "safari")))]
+            engine => Err(CookieReaderError::NoCookiesFound(format!(
+                "Unsupported cookie database: {engine:?}"
+            ))),
+        }
+    }
 
-        result
+    /// Decrypt every cookie, collecting a warning for each one that fails to decrypt.
+    #[must_use]
+    pub fn decrypt_cookies(cookies: Vec<LazyCookie>) -> DecryptedCookies {
+        #[cfg(feature = "cli")]
+        let mut span = crate::telemetry::Span::start("cookies.decrypt");
+        let mut decrypted = DecryptedCookies::default();
+        for cookie in cookies {
+            let name = cookie.name.clone();
+            match cookie.decrypt() {
+                Ok(cookie) => decrypted.cookies.push(cookie),
+                Err(error) => decrypted.warnings.push(CookieWarning { name, error }),
+            }
+        }
+        #[cfg(feature = "cli")]
+        {
+            span.set("cookies", decrypted.cookies.len());
+            span.set("failures", decrypted.warnings.len());
+            span.end();
+        }
+        decrypted
     }
 
-    fn read_chromium_cookies(
-        db_path: &Path,
-        domain: &str,
-        _profile: &Profile,
-    ) -> Result<Vec<Cookie>> {
+    fn read_chromium_cookies(db_path: &Path, domain: &str) -> Result<Vec<LazyCookie>> {
         let conn = Connection::open(db_path)?;
 
-        let mut stmt = conn.prepare(
-            "SELECT name, encrypted_value, host_key, path, expires_utc, is_secure, is_httponly, samesite
-             FROM cookies
-             WHERE host_key LIKE ?1 OR host_key LIKE ?2
-             ORDER BY creation_utc DESC",
-        )?;
+        let mut stmt = conn.prepare(&chromium_query(&conn)?)?;
 
         let domain_pattern = format!("%{domain}");
         let dot_domain_pattern = format!("%.{domain}");
@@ -96,16 +262,18 @@ impl CookieReader {
             |row| {
                 let name: String = row.get(0)?;
                 let encrypted_value: Vec<u8> = row.get(1)?;
-                let host_key: String = row.get(2)?;
-                let path: String = row.get(3)?;
-                let expires_utc: i64 = row.get(4)?;
-                let is_secure: bool = row.get(5)?;
-                let is_httponly: bool = row.get(6)?;
-                let same_site: i32 = row.get(7)?;
+                let value: String = row.get(2)?;
+                let host_key: String = row.get(3)?;
+                let path: String = row.get(4)?;
+                let expires_utc: i64 = row.get(5)?;
+                let is_secure: bool = row.get(6)?;
+                let is_httponly: bool = row.get(7)?;
+                let same_site: i32 = row.get(8)?;
 
                 Ok((
                     name,
                     encrypted_value,
+                    value,
                     host_key,
                     path,
                     expires_utc,
@@ -122,6 +290,7 @@ impl CookieReader {
             let (
                 name,
                 encrypted_value,
+                value,
                 host_key,
                 path,
                 expires_utc,
@@ -130,23 +299,32 @@ impl CookieReader {
                 same_site,
             ) = cookie_result?;
 
-            match crypto::decrypt_cookie_value(&encrypted_value) {
-                Ok(value) => {
-                    cookies.push(Cookie {
-                        name,
-                        value,
-                        domain: host_key,
-                        path,
-                        expires_utc,
-                        is_secure,
-                        is_httponly,
-                        same_site,
-                    });
-                }
-                Err(e) => {
-                    eprintln!("  [warn] Failed to decrypt cookie '{name}': {e}");
-                }
+            // Profiles from before cookie encryption, and cookies written by some forks,
+            // keep the value in plaintext.
+            if encrypted_value.is_empty() && !value.is_empty() {
+                cookies.push(LazyCookie::from(Cookie {
+                    name,
+                    value,
+                    domain: host_key,
+                    path,
+                    expires_utc,
+                    is_secure,
+                    is_httponly,
+                    same_site,
+                }));
+                continue;
             }
+
+            cookies.push(LazyCookie::encrypted(
+                name,
+                encrypted_value,
+                host_key,
+                path,
+                expires_utc,
+                is_secure,
+                is_httponly,
+                same_site,
+            ));
         }
 
         if cookies.is_empty() {
@@ -156,15 +334,28 @@ impl CookieReader {
         Ok(cookies)
     }
 
-    fn read_firefox_cookies(db_path: &Path, domain: &str) -> Result<Vec<Cookie>> {
+    /// Read `domain`'s cookies from a Firefox database; with `container`, only those of
+    /// that `userContextId`.
+    #[cfg(feature = "firefox")]
+    fn read_firefox_cookies(
+        db_path: &Path,
+        domain: &str,
+        container: Option<u32>,
+    ) -> Result<Vec<Cookie>> {
         let conn = Connection::open(db_path)?;
 
-        let mut stmt = conn.prepare(
-            "SELECT name, value, host, path, expiry, isSecure, isHttpOnly, sameSite
+        let origin_attributes = if container.is_some() {
+            "originAttributes"
+        } else {
+            "''"
+        };
+        let mut stmt = conn.prepare(&format!(
+            "SELECT name, value, host, path, expiry, isSecure, isHttpOnly, sameSite,
+                 {origin_attributes}
              FROM moz_cookies
              WHERE host LIKE ?1 OR host LIKE ?2
-             ORDER BY creationTime DESC",
-        )?;
+             ORDER BY creationTime DESC"
+        ))?;
 
         let domain_pattern = format!("%{domain}");
         let dot_domain_pattern = format!("%.{domain}");
@@ -180,6 +371,7 @@ impl CookieReader {
                 let is_secure: i32 = row.get(5)?;
                 let is_httponly: i32 = row.get(6)?;
                 let same_site: i32 = row.get(7)?;
+                let origin_attributes: String = row.get(8)?;
 
                 Ok((
                     name,
@@ -190,6 +382,7 @@ impl CookieReader {
                     is_secure,
                     is_httponly,
                     same_site,
+                    origin_attributes,
                 ))
             },
         )?;
@@ -197,8 +390,23 @@ impl CookieReader {
         let mut cookies = Vec::new();
 
         for cookie_result in cookie_iter {
-            let (name, value, host, path, expiry, is_secure, is_httponly, same_site) =
-                cookie_result?;
+            let (
+                name,
+                value,
+                host,
+                path,
+                expiry,
+                is_secure,
+                is_httponly,
+                same_site,
+                origin_attributes,
+            ) = cookie_result?;
+
+            if container
+                .is_some_and(|id| super::containers::user_context_id(&origin_attributes) != id)
+            {
+                continue;
+            }
 
             let cookie = Cookie {
                 name,
@@ -221,6 +429,7 @@ impl CookieReader {
         Ok(cookies)
     }
 
+    #[cfg(feature = "safari")]
     fn read_safari_cookies(cookies_path: &Path, domain: &str) -> Result<Vec<Cookie>> {
         let data = std::fs::read(cookies_path)?;
 
@@ -262,6 +471,7 @@ impl CookieReader {
         Ok(cookies)
     }
 
+    #[cfg(feature = "safari")]
     fn parse_safari_page(data: &[u8], offset: usize, domain: &str) -> Vec<Cookie> {
         let mut pos = offset + 4;
 
@@ -295,6 +505,7 @@ impl CookieReader {
         cookies
     }
 
+    #[cfg(feature = "safari")]
     fn parse_safari_cookie(
         data: &[u8],
         offset: usize,
@@ -382,6 +593,7 @@ impl CookieReader {
         }))
     }
 
+    #[cfg(feature = "safari")]
     fn read_safari_string(data: &[u8], offset: usize) -> Result<String> {
         let mut pos = offset;
         let mut bytes = Vec::new();
@@ -395,3 +607,120 @@ impl CookieReader {
             .map_err(|_| CookieReaderError::NoCookiesFound("Invalid UTF-8 in cookie".to_string()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = std::result::Result<(), Box<dyn std::error::Error>>;
+
+    #[cfg(target_os = "linux")]
+    fn encrypted(name: &str, payload: &[u8]) -> LazyCookie {
+        LazyCookie::encrypted(
+            name.to_string(),
+            payload.to_vec(),
+            ".claude.ai".to_string(),
+            "/".to_string(),
+            0,
+            true,
+            true,
+            0,
+        )
+    }
+
+    #[cfg(feature = "firefox")]
+    #[test]
+    fn read_from_path_reads_a_fixture_database() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let db_path = dir.path().join("cookies.sqlite");
+        let conn = Connection::open(&db_path)?;
+        conn.execute_batch(
+            "CREATE TABLE moz_cookies (name TEXT, value TEXT, host TEXT, path TEXT, expiry INTEGER,
+                 isSecure INTEGER, isHttpOnly INTEGER, sameSite INTEGER, creationTime INTEGER);
+             INSERT INTO moz_cookies VALUES ('sessionKey', 'sk-ant', '.claude.ai', '/', 0, 1, 1, 0, 1);
+             INSERT INTO moz_cookies VALUES ('other', 'x', 'example.com', '/', 0, 0, 0, 0, 2);",
+        )?;
+        drop(conn);
+
+        let decrypted = CookieReader::read_from_path(&db_path, CookieEngine::Firefox, "claude.ai")?;
+        assert_eq!(decrypted.cookies.len(), 1);
+        assert_eq!(decrypted.cookies[0].name, "sessionKey");
+        assert_eq!(decrypted.cookies[0].value, "sk-ant");
+        assert!(decrypted.warnings.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn reads_a_legacy_chromium_schema() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let db_path = dir.path().join("Cookies");
+        let conn = Connection::open(&db_path)?;
+        conn.execute_batch(
+            "CREATE TABLE cookies (creation_utc INTEGER, host_key TEXT, name TEXT, value TEXT,
+                 path TEXT, expires_utc INTEGER, secure INTEGER, httponly INTEGER,
+                 last_access_utc INTEGER);
+             INSERT INTO cookies VALUES (1, '.claude.ai', 'sessionKey', 'sk-ant', '/', 0, 1, 1, 0);
+             INSERT INTO cookies VALUES (2, 'example.com', 'other', 'x', '/', 0, 0, 0, 0);",
+        )?;
+        drop(conn);
+
+        let decrypted =
+            CookieReader::read_from_path(&db_path, CookieEngine::Chromium, "claude.ai")?;
+        assert_eq!(decrypted.cookies.len(), 1);
+        let cookie = &decrypted.cookies[0];
+        assert_eq!(cookie.name, "sessionKey");
+        assert_eq!(cookie.value, "sk-ant");
+        assert!(cookie.is_secure && cookie.is_httponly);
+        assert_eq!(cookie.same_site, -1);
+        Ok(())
+    }
+
+    #[cfg(feature = "firefox")]
+    #[test]
+    fn container_profiles_read_only_that_containers_cookies() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let conn = Connection::open(dir.path().join("cookies.sqlite"))?;
+        conn.execute_batch(
+            "CREATE TABLE moz_cookies (name TEXT, value TEXT, host TEXT, path TEXT, expiry INTEGER,
+                 isSecure INTEGER, isHttpOnly INTEGER, sameSite INTEGER, creationTime INTEGER,
+                 originAttributes TEXT);
+             INSERT INTO moz_cookies VALUES ('sessionKey', 'default', '.claude.ai', '/', 0, 1, 1, 0, 1, '');
+             INSERT INTO moz_cookies VALUES ('sessionKey', 'work', '.claude.ai', '/', 0, 1, 1, 0, 2, '^userContextId=2');",
+        )?;
+        drop(conn);
+        std::fs::write(
+            dir.path().join("containers.json"),
+            r#"{"identities":[{"userContextId":2,"public":true,"l10nID":"userContextWork.label"}]}"#,
+        )?;
+        let mut profile = Profile::new(
+            "default-release".to_string(),
+            dir.path().to_path_buf(),
+            crate::browser::BrowserType::Firefox,
+        );
+
+        profile.container = Some("Work".to_string());
+        let cookies = CookieReader::read_cookies(&profile, "claude.ai")?.cookies;
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(cookies[0].value, "work");
+
+        profile.container = Some("Banking".to_string());
+        assert!(matches!(
+            CookieReader::read_cookies(&profile, "claude.ai"),
+            Err(CookieReaderError::ContainerNotFound(_))
+        ));
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn decrypt_cookies_reports_failures_as_warnings() {
+        let decrypted = CookieReader::decrypt_cookies(vec![
+            encrypted("sessionKey", b"sk-ant"),
+            encrypted("broken", &[0xff, 0xfe, 0xfd]),
+        ]);
+        assert_eq!(decrypted.cookies.len(), 1);
+        assert_eq!(decrypted.cookies[0].name, "sessionKey");
+        assert_eq!(decrypted.warnings.len(), 1);
+        assert_eq!(decrypted.warnings[0].name, "broken");
+    }
+}
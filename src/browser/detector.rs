@@ -3,17 +3,68 @@ use std::path::{Path, PathBuf};
 
 pub struct BrowserDetector {
     home_dir: PathBuf,
+    user_data_dir: Option<(BrowserType, PathBuf)>,
+    container: Option<String>,
 }
 
 impl BrowserDetector {
     #[must_use]
     pub fn new() -> Self {
         let home_dir = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-        Self { home_dir }
+        Self {
+            home_dir,
+            user_data_dir: None,
+            container: None,
+        }
+    }
+
+    /// Read Firefox profiles' cookies from the Multi-Account Container called `name`
+    /// only. Other browsers are unaffected.
+    #[must_use]
+    pub fn with_container(mut self, name: impl Into<String>) -> Self {
+        self.container = Some(name.into());
+        self
+    }
+
+    /// The Firefox container set by [`Self::with_container`].
+    #[must_use]
+    pub fn container(&self) -> Option<&str> {
+        self.container.as_deref()
+    }
+
+    /// A detector that only sees `dir`: a Chromium user-data directory or profile
+    /// directory, or a Firefox profile folder (portable installs, Selenium profiles, test
+    /// fixtures). Without `browser`, a directory holding `cookies.sqlite` or
+    /// `profiles.ini` is read as Firefox and anything else as Chromium.
+    #[must_use]
+    pub fn for_user_data_dir(dir: PathBuf, browser: Option<BrowserType>) -> Self {
+        let browser = browser.unwrap_or_else(|| {
+            if dir.join("cookies.sqlite").exists() || dir.join("profiles.ini").exists() {
+                BrowserType::Firefox
+            } else {
+                BrowserType::Chromium
+            }
+        });
+        Self {
+            user_data_dir: Some((browser, dir)),
+            ..Self::new()
+        }
+    }
+
+    /// The browser and directory this detector is pinned to, if any.
+    #[must_use]
+    pub fn user_data_dir(&self) -> Option<(BrowserType, &Path)> {
+        self.user_data_dir
+            .as_ref()
+            .map(|(browser, dir)| (*browser, dir.as_path()))
     }
 
     #[must_use]
     pub fn detect_browsers(&self) -> Vec<BrowserType> {
+        if let Some((browser, dir)) = self.user_data_dir() {
+            return if dir.exists() { vec![browser] } else { vec![] };
+        }
+
         let mut browsers = Vec::new();
 
         for browser_type in [
@@ -43,6 +94,10 @@ impl BrowserDetector {
 
     #[must_use]
     pub fn get_browser_base_path(&self, browser_type: BrowserType) -> Option<PathBuf> {
+        if let Some((browser, dir)) = self.user_data_dir() {
+            return (browser == browser_type).then(|| dir.to_path_buf());
+        }
+
         #[cfg(target_os = "macos")]
         {
             let path = match browser_type {
@@ -92,7 +147,14 @@ impl BrowserDetector {
                 BrowserType::Comet => config_dir.join("comet"),
                 BrowserType::Dia => config_dir.join("dia"),
                 BrowserType::Atlas => config_dir.join("atlas"),
-                BrowserType::Firefox => self.home_dir.join(".mozilla/firefox"),
+                BrowserType::Firefox => {
+                    let roots = self.firefox_roots();
+                    return roots
+                        .iter()
+                        .find(|root| root.exists())
+                        .or(roots.first())
+                        .cloned();
+                }
                 BrowserType::Safari => return None,
             };
             Some(path)
@@ -130,8 +192,31 @@ impl BrowserDetector {
             _ => return Vec::new(),
         };
 
+        // A pinned directory may be a single profile rather than a container of them.
+        if self.user_data_dir.is_some() && browser_type != BrowserType::Safari {
+            let profile = Profile::new(
+                base_path.file_name().map_or_else(
+                    || "Default".to_string(),
+                    |n| n.to_string_lossy().to_string(),
+                ),
+                base_path.clone(),
+                browser_type,
+            );
+            if profile.cookies_path().exists() {
+                return self.in_container(vec![profile]);
+            }
+        }
+
         if browser_type == BrowserType::Firefox {
-            return Self::list_firefox_profiles(&base_path);
+            if self.user_data_dir.is_some() {
+                return self.in_container(Self::list_firefox_profiles(&base_path));
+            }
+            return self.in_container(
+                self.firefox_roots()
+                    .iter()
+                    .flat_map(|root| Self::list_firefox_profiles(root))
+                    .collect(),
+            );
         }
 
         if browser_type == BrowserType::Safari {
@@ -142,28 +227,64 @@ impl BrowserDetector {
             )];
         }
 
+        Self::list_chromium_profiles(&base_path, browser_type)
+    }
+
+    /// Directories holding Firefox's `profiles.ini`, native install first. On Linux the
+    /// Snap (Ubuntu's default Firefox) and Flatpak builds keep theirs in their sandboxes.
+    fn firefox_roots(&self) -> Vec<PathBuf> {
+        #[cfg(target_os = "linux")]
+        {
+            vec![
+                self.home_dir.join(".mozilla/firefox"),
+                self.home_dir.join("snap/firefox/common/.mozilla/firefox"),
+                self.home_dir
+                    .join(".var/app/org.mozilla.firefox/.mozilla/firefox"),
+            ]
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            self.get_browser_base_path(BrowserType::Firefox)
+                .into_iter()
+                .collect()
+        }
+    }
+
+    fn in_container(&self, profiles: Vec<Profile>) -> Vec<Profile> {
+        profiles
+            .into_iter()
+            .map(|mut profile| {
+                if profile.browser_type == BrowserType::Firefox {
+                    profile.container.clone_from(&self.container);
+                }
+                profile
+            })
+            .collect()
+    }
+
+    /// Every profile directory under a Chromium user-data directory, `Default` first.
+    ///
+    /// Any subdirectory with a `Cookies` database counts, so profiles named by enterprise
+    /// policies or forks (`Work Profile`, GUIDs) are found alongside `Profile N`. The
+    /// internal `System Profile` and `Guest Profile` are skipped.
+    fn list_chromium_profiles(base_path: &Path, browser_type: BrowserType) -> Vec<Profile> {
         let mut profiles = Vec::new();
 
-        if let Ok(entries) = std::fs::read_dir(&base_path) {
+        if let Ok(entries) = std::fs::read_dir(base_path) {
             for entry in entries.flatten() {
                 let path = entry.path();
                 if !path.is_dir() {
                     continue;
                 }
 
-                let file_name = entry.file_name();
-                let name = file_name.to_string_lossy();
+                let name = entry.file_name().to_string_lossy().to_string();
+                if name == "System Profile" || name == "Guest Profile" {
+                    continue;
+                }
 
-                if name == "Default" || name.starts_with("Profile ") {
-                    let cookies_path = path.join("Cookies");
-                    if cookies_path.exists() {
-                        let profile_name = if name == "Default" {
-                            "Default".to_string()
-                        } else {
-                            name.to_string()
-                        };
-                        profiles.push(Profile::new(profile_name, path, browser_type));
-                    }
+                if path.join("Cookies").exists() {
+                    profiles.push(Profile::new(name, path, browser_type));
                 }
             }
         }
@@ -257,3 +378,104 @@ impl Default for BrowserDetector {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn lists_any_chromium_profile_with_cookies() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        for name in [
+            "Profile 1",
+            "Default",
+            "Work Profile",
+            "3f2c9a1e-guid",
+            "System Profile",
+        ] {
+            std::fs::create_dir(dir.path().join(name))?;
+            std::fs::write(dir.path().join(name).join("Cookies"), "")?;
+        }
+        std::fs::create_dir(dir.path().join("Crashpad"))?;
+
+        let names: Vec<String> =
+            BrowserDetector::list_chromium_profiles(dir.path(), BrowserType::Chrome)
+                .into_iter()
+                .map(|p| p.name)
+                .collect();
+        assert_eq!(
+            names,
+            ["Default", "3f2c9a1e-guid", "Profile 1", "Work Profile"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn user_data_dir_pins_detection_to_one_directory() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let user_data = dir.path().join("User Data");
+        std::fs::create_dir_all(user_data.join("Default"))?;
+        std::fs::write(user_data.join("Default").join("Cookies"), "")?;
+        let firefox = dir.path().join("abcd.selenium");
+        std::fs::create_dir_all(&firefox)?;
+        std::fs::write(firefox.join("cookies.sqlite"), "")?;
+
+        let chromium = BrowserDetector::for_user_data_dir(user_data.clone(), None);
+        assert_eq!(chromium.detect_browsers(), [BrowserType::Chromium]);
+        let profiles = chromium.list_profiles(BrowserType::Chromium);
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].path, user_data.join("Default"));
+        assert!(chromium.list_profiles(BrowserType::Chrome).is_empty());
+
+        let single = BrowserDetector::for_user_data_dir(user_data.join("Default"), None);
+        assert_eq!(
+            single.list_profiles(BrowserType::Chromium)[0].name,
+            "Default"
+        );
+
+        let firefox = BrowserDetector::for_user_data_dir(firefox, None);
+        assert_eq!(firefox.detect_browsers(), [BrowserType::Firefox]);
+        assert_eq!(
+            firefox.list_profiles(BrowserType::Firefox)[0].name,
+            "abcd.selenium"
+        );
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn finds_snap_and_flatpak_firefox_profiles() -> TestResult {
+        let home = tempfile::tempdir()?;
+        for (root, profile) in [
+            ("snap/firefox/common/.mozilla/firefox", "snap.default"),
+            (
+                ".var/app/org.mozilla.firefox/.mozilla/firefox",
+                "flatpak.default",
+            ),
+        ] {
+            let root = home.path().join(root);
+            std::fs::create_dir_all(root.join(profile))?;
+            std::fs::write(root.join(profile).join("cookies.sqlite"), "")?;
+            std::fs::write(
+                root.join("profiles.ini"),
+                format!("[Profile0]\nName={profile}\nIsRelative=1\nPath={profile}\n"),
+            )?;
+        }
+        let detector = BrowserDetector {
+            home_dir: home.path().to_path_buf(),
+            user_data_dir: None,
+            container: None,
+        };
+
+        assert!(detector.detect_browsers().contains(&BrowserType::Firefox));
+        let names: Vec<String> = detector
+            .list_profiles(BrowserType::Firefox)
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, ["snap.default", "flatpak.default"]);
+        Ok(())
+    }
+}
@@ -1,7 +1,11 @@
+#[cfg(feature = "firefox")]
+pub mod containers;
+pub mod cookie_header;
 pub mod cookie_reader;
 pub mod detector;
 pub mod types;
 
-pub use cookie_reader::CookieReader;
+pub use cookie_header::cookie_header;
+pub use cookie_reader::{CookieReader, CookieWarning, DecryptedCookies};
 pub use detector::BrowserDetector;
-pub use types::{BrowserType, Cookie, Profile};
+pub use types::{BrowserType, Cookie, CookieEngine, LazyCookie, Profile};
@@ -1,6 +1,11 @@
+use crate::crypto;
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use std::sync::OnceLock;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "cli", derive(clap::ValueEnum))]
+#[serde(rename_all = "lowercase")]
 pub enum BrowserType {
     Chrome,
     Edge,
@@ -14,6 +19,17 @@ pub enum BrowserType {
     Safari,
 }
 
+/// On-disk cookie database format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CookieEngine {
+    /// `Cookies` database with encrypted values, used by every Chromium-based browser.
+    Chromium,
+    /// `cookies.sqlite` database with plaintext values.
+    Firefox,
+    /// `Cookies.binarycookies` file.
+    Safari,
+}
+
 impl BrowserType {
     #[must_use]
     pub fn name(&self) -> &str {
@@ -45,6 +61,21 @@ impl BrowserType {
                 | BrowserType::Atlas
         )
     }
+
+    #[must_use]
+    pub fn engine(&self) -> CookieEngine {
+        match self {
+            BrowserType::Firefox => CookieEngine::Firefox,
+            BrowserType::Safari => CookieEngine::Safari,
+            _ => CookieEngine::Chromium,
+        }
+    }
+}
+
+impl std::fmt::Display for BrowserType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
 }
 
 impl std::str::FromStr for BrowserType {
@@ -66,11 +97,14 @@ impl std::str::FromStr for BrowserType {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Profile {
     pub name: String,
     pub path: PathBuf,
     pub browser_type: BrowserType,
+    /// Firefox Multi-Account Container whose cookies to read; all cookies when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<String>,
 }
 
 impl Profile {
@@ -80,6 +114,7 @@ impl Profile {
             name,
             path,
             browser_type,
+            container: None,
         }
     }
 
@@ -100,7 +135,7 @@ impl Profile {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Cookie {
     pub name: String,
     pub value: String,
@@ -115,16 +150,210 @@ pub struct Cookie {
 impl Cookie {
     #[must_use]
     pub fn is_expired(&self) -> bool {
-        use std::time::{SystemTime, UNIX_EPOCH};
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs()
-            .cast_signed();
-
-        // Chrome uses microseconds since Windows epoch (1601-01-01)
-        // Convert to Unix timestamp
-        let unix_timestamp = (self.expires_utc / 1_000_000) - 11_644_473_600;
-        unix_timestamp > 0 && unix_timestamp < now
+        is_expired(self.expires_utc)
+    }
+}
+
+/// `true` if `expires_utc` (microseconds since 1601-01-01, as Chrome stores it) has passed.
+/// Session cookies (0) never expire.
+fn is_expired(expires_utc: i64) -> bool {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        .cast_signed();
+
+    // Chrome uses microseconds since Windows epoch (1601-01-01)
+    // Convert to Unix timestamp
+    let unix_timestamp = (expires_utc / 1_000_000) - 11_644_473_600;
+    unix_timestamp > 0 && unix_timestamp < now
+}
+
+/// A cookie whose value is decrypted only when it is first read.
+///
+/// Returned by [`CookieReader::read_cookies_lazy`](super::CookieReader::read_cookies_lazy),
+/// so callers that only look at names or need a couple of values skip the keychain and AES
+/// work for the rest.
+#[derive(Debug, Clone)]
+pub struct LazyCookie {
+    pub name: String,
+    pub domain: String,
+    pub path: String,
+    pub expires_utc: i64,
+    pub is_secure: bool,
+    pub is_httponly: bool,
+    pub same_site: i32,
+    payload: Payload,
+    decrypted: OnceLock<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Payload {
+    Plain(String),
+    Encrypted(Vec<u8>),
+}
+
+impl LazyCookie {
+    /// A cookie stored encrypted, as Chromium-based browsers do.
+    #[expect(
+        clippy::too_many_arguments,
+        reason = "mirrors the columns of the cookie database"
+    )]
+    pub(crate) fn encrypted(
+        name: String,
+        encrypted_value: Vec<u8>,
+        domain: String,
+        path: String,
+        expires_utc: i64,
+        is_secure: bool,
+        is_httponly: bool,
+        same_site: i32,
+    ) -> Self {
+        Self {
+            name,
+            domain,
+            path,
+            expires_utc,
+            is_secure,
+            is_httponly,
+            same_site,
+            payload: Payload::Encrypted(encrypted_value),
+            decrypted: OnceLock::new(),
+        }
+    }
+
+    /// The cookie's value, decrypting it on first use.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is encrypted and cannot be decrypted.
+    pub fn value(&self) -> crypto::Result<&str> {
+        match &self.payload {
+            Payload::Plain(value) => Ok(value),
+            Payload::Encrypted(bytes) => {
+                if let Some(value) = self.decrypted.get() {
+                    return Ok(value);
+                }
+                let value = crypto::decrypt_cookie_value(bytes)?;
+                Ok(self.decrypted.get_or_init(|| value))
+            }
+        }
+    }
+
+    /// `true` if reading the value requires decryption that has not happened yet.
+    #[must_use]
+    pub fn needs_decryption(&self) -> bool {
+        matches!(self.payload, Payload::Encrypted(_)) && self.decrypted.get().is_none()
+    }
+
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        is_expired(self.expires_utc)
+    }
+
+    /// Decrypt the value and return the cookie as a plain [`Cookie`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value is encrypted and cannot be decrypted.
+    pub fn decrypt(self) -> crypto::Result<Cookie> {
+        let value = self.value()?.to_string();
+        Ok(Cookie {
+            name: self.name,
+            value,
+            domain: self.domain,
+            path: self.path,
+            expires_utc: self.expires_utc,
+            is_secure: self.is_secure,
+            is_httponly: self.is_httponly,
+            same_site: self.same_site,
+        })
+    }
+}
+
+impl From<Cookie> for LazyCookie {
+    fn from(cookie: Cookie) -> Self {
+        Self {
+            name: cookie.name,
+            domain: cookie.domain,
+            path: cookie.path,
+            expires_utc: cookie.expires_utc,
+            is_secure: cookie.is_secure,
+            is_httponly: cookie.is_httponly,
+            same_site: cookie.same_site,
+            payload: Payload::Plain(cookie.value),
+            decrypted: OnceLock::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn plain_lazy_cookie_reads_without_decryption() -> TestResult {
+        let cookie = LazyCookie::from(Cookie {
+            name: "sessionKey".to_string(),
+            value: "sk-ant".to_string(),
+            domain: ".claude.ai".to_string(),
+            path: "/".to_string(),
+            expires_utc: 0,
+            is_secure: true,
+            is_httponly: true,
+            same_site: 0,
+        });
+        assert!(!cookie.needs_decryption());
+        assert_eq!(cookie.value()?, "sk-ant");
+        assert!(!cookie.is_expired());
+        assert_eq!(cookie.decrypt()?.value, "sk-ant");
+        Ok(())
+    }
+
+    #[test]
+    fn encrypted_lazy_cookie_defers_decryption() -> TestResult {
+        let cookie = LazyCookie::encrypted(
+            "sessionKey".to_string(),
+            vec![],
+            ".claude.ai".to_string(),
+            "/".to_string(),
+            0,
+            true,
+            true,
+            0,
+        );
+        assert!(cookie.needs_decryption());
+        // An empty payload decrypts to an empty value without touching the keychain.
+        assert_eq!(cookie.value()?, "");
+        assert!(!cookie.needs_decryption());
+        Ok(())
+    }
+
+    #[test]
+    fn browser_and_cookie_round_trip_through_json() -> TestResult {
+        assert_eq!(serde_json::to_string(&BrowserType::Chrome)?, r#""chrome""#);
+        assert_eq!(BrowserType::Firefox.to_string(), "Firefox");
+        let profile: Profile = serde_json::from_str(
+            r#"{"name": "Work", "path": "/tmp/Work", "browser_type": "edge"}"#,
+        )?;
+        assert_eq!(profile.browser_type, BrowserType::Edge);
+
+        let cookie = Cookie {
+            name: "sessionKey".to_string(),
+            value: "sk-ant".to_string(),
+            domain: ".claude.ai".to_string(),
+            path: "/".to_string(),
+            expires_utc: 0,
+            is_secure: true,
+            is_httponly: true,
+            same_site: 0,
+        };
+        let json = serde_json::to_string(&cookie)?;
+        let back: Cookie = serde_json::from_str(&json)?;
+        assert_eq!(back.value, "sk-ant");
+        Ok(())
     }
 }
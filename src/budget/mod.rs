@@ -0,0 +1,82 @@
+//! Monthly spend budgets for API-billed providers, whose "limit" is a dollar amount the
+//! user sets rather than a window the provider enforces.
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+
+/// Month-to-date API spend against a monthly budget, both in dollars.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonthlySpend {
+    pub spent: f64,
+    pub limit: f64,
+    /// Start of the next calendar month (UTC), when the spend starts over.
+    pub resets_at: Option<DateTime<Utc>>,
+}
+
+impl MonthlySpend {
+    /// Spend for the calendar month containing `now`.
+    #[must_use]
+    pub fn new(spent: f64, limit: f64, now: DateTime<Utc>) -> Self {
+        let next_month = if now.month() == 12 {
+            NaiveDate::from_ymd_opt(now.year() + 1, 1, 1)
+        } else {
+            NaiveDate::from_ymd_opt(now.year(), now.month() + 1, 1)
+        };
+        Self {
+            spent,
+            limit,
+            resets_at: next_month
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|t| t.and_utc()),
+        }
+    }
+
+    #[must_use]
+    pub fn is_limited(&self) -> bool {
+        self.spent >= self.limit
+    }
+
+    /// Spend as a percentage of the budget; a zero budget counts as used up.
+    #[must_use]
+    pub fn utilization(&self) -> f64 {
+        if self.limit > 0.0 {
+            self.spent / self.limit * 100.0
+        } else {
+            100.0
+        }
+    }
+
+    #[must_use]
+    pub fn remaining(&self) -> f64 {
+        (self.limit - self.spent).max(0.0)
+    }
+}
+
+/// Midnight UTC on the first day of `now`'s month, where the cost report starts.
+#[must_use]
+pub fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    NaiveDate::from_ymd_opt(now.year(), now.month(), 1)
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map_or(now, |t| t.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn monthly_spend_resets_at_the_next_month() -> TestResult {
+        let now = "2026-12-15T08:00:00Z".parse::<DateTime<Utc>>()?;
+        let spend = MonthlySpend::new(75.0, 50.0, now);
+        assert!(spend.is_limited());
+        assert!((spend.utilization() - 150.0).abs() < f64::EPSILON);
+        assert!(spend.remaining().abs() < f64::EPSILON);
+        assert_eq!(spend.resets_at, Some("2027-01-01T00:00:00Z".parse()?));
+        assert_eq!(
+            month_start(now),
+            "2026-12-01T00:00:00Z".parse::<DateTime<Utc>>()?
+        );
+        Ok(())
+    }
+}
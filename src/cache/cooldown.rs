@@ -0,0 +1,101 @@
+use chrono::{DateTime, Utc};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+static INSTALLED: OnceLock<Cooldowns> = OnceLock::new();
+
+/// When each provider last ran an agent, shared by every seher process, for per-agent
+/// `cooldown`s.
+///
+/// Each provider's last run is kept in `~/.cache/seher/cooldown/<provider>`, written when
+/// an agent starts and again when it exits.
+#[derive(Debug, Clone)]
+pub struct Cooldowns {
+    dir: PathBuf,
+}
+
+impl Cooldowns {
+    #[must_use]
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Install these cooldowns process-wide. Returns `false` if some were already installed.
+    pub fn install(self) -> bool {
+        INSTALLED.set(self).is_ok()
+    }
+
+    /// The installed cooldowns, if any.
+    #[must_use]
+    pub fn current() -> Option<&'static Self> {
+        INSTALLED.get()
+    }
+
+    /// Record that `provider` ran an agent at `at`. Failures are ignored, as a missed
+    /// record only shortens a cooldown.
+    pub fn record(&self, provider: &str, at: DateTime<Utc>) {
+        if std::fs::create_dir_all(&self.dir).is_ok() {
+            let _ = std::fs::write(self.path(provider), at.to_rfc3339());
+        }
+    }
+
+    /// When `provider` may run again if it has to rest `cooldown` after its last run, or
+    /// `None` if it may run at `now`.
+    #[must_use]
+    pub fn until(
+        &self,
+        provider: &str,
+        cooldown: Duration,
+        now: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let last = std::fs::read_to_string(self.path(provider))
+            .ok()?
+            .trim()
+            .parse::<DateTime<Utc>>()
+            .ok()?;
+        let until = last + chrono::Duration::from_std(cooldown).ok()?;
+        (until > now).then_some(until)
+    }
+
+    fn path(&self, provider: &str) -> PathBuf {
+        let name: String = provider
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn providers_rest_for_the_cooldown_after_a_run() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let cooldowns = Cooldowns::new(dir.path().join("cooldown"));
+        let now = Utc::now();
+        let ten_minutes = Duration::from_mins(10);
+
+        assert_eq!(cooldowns.until("claude (work)", ten_minutes, now), None);
+        cooldowns.record("claude (work)", now);
+        assert_eq!(
+            cooldowns.until("claude (work)", ten_minutes, now),
+            Some(now + chrono::Duration::minutes(10))
+        );
+        let later = now + chrono::Duration::minutes(11);
+        assert_eq!(cooldowns.until("claude (work)", ten_minutes, later), None);
+        // Other providers and accounts are unaffected.
+        assert_eq!(cooldowns.until("claude", ten_minutes, now), None);
+        Ok(())
+    }
+}
@@ -0,0 +1,164 @@
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+mod cooldown;
+mod session;
+mod throttle;
+
+pub use cooldown::Cooldowns;
+pub use session::SessionCache;
+pub use throttle::{FetchThrottle, SharedSlot, throttle};
+
+static INSTALLED: OnceLock<UsageCache> = OnceLock::new();
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Answer usage queries from the last cached snapshots without touching the network.
+pub fn set_offline(enabled: bool) {
+    OFFLINE.store(enabled, Ordering::Relaxed);
+}
+
+#[must_use]
+pub fn offline() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// On-disk cache of the last successful usage response per provider account.
+///
+/// Entries live under `~/.cache/seher/` (or `$XDG_CACHE_HOME/seher/`) and are served
+/// while younger than the TTL, so frequent status queries don't hit provider APIs.
+#[derive(Debug, Clone)]
+pub struct UsageCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    fetched_at: DateTime<Utc>,
+    value: T,
+}
+
+impl UsageCache {
+    #[must_use]
+    pub fn new(dir: PathBuf, ttl: Duration) -> Self {
+        Self { dir, ttl }
+    }
+
+    /// `$XDG_CACHE_HOME/seher`, falling back to `~/.cache/seher`.
+    #[must_use]
+    pub fn default_dir() -> Option<PathBuf> {
+        if let Some(dir) = std::env::var_os("XDG_CACHE_HOME").filter(|d| !d.is_empty()) {
+            return Some(PathBuf::from(dir).join("seher"));
+        }
+        dirs::home_dir().map(|home| home.join(".cache").join("seher"))
+    }
+
+    /// Install this cache process-wide. Returns `false` if one was already installed.
+    pub fn install(self) -> bool {
+        INSTALLED.set(self).is_ok()
+    }
+
+    /// The installed cache, if caching is enabled.
+    #[must_use]
+    pub fn current() -> Option<&'static Self> {
+        INSTALLED.get()
+    }
+
+    /// Return the cached value for `key` if it is younger than the TTL.
+    #[must_use]
+    pub fn get<T: DeserializeOwned>(&self, key: &str) -> Option<T> {
+        let content = std::fs::read_to_string(self.path(key)).ok()?;
+        let entry: Entry<T> = serde_json::from_str(&content).ok()?;
+        let age = (Utc::now() - entry.fetched_at).to_std().ok()?;
+        (age < self.ttl).then_some(entry.value)
+    }
+
+    /// The last value stored for `key` and when it was fetched, however old it is.
+    #[must_use]
+    pub fn last_snapshot<T: DeserializeOwned>(&self, key: &str) -> Option<(T, DateTime<Utc>)> {
+        let content = std::fs::read_to_string(self.path(key)).ok()?;
+        let entry: Entry<T> = serde_json::from_str(&content).ok()?;
+        Some((entry.value, entry.fetched_at))
+    }
+
+    /// Store `value` for `key`. Failures are ignored: the cache is best-effort.
+    pub fn put<T: Serialize>(&self, key: &str, value: &T) {
+        let entry = Entry {
+            fetched_at: Utc::now(),
+            value,
+        };
+        if let Ok(json) = serde_json::to_string(&entry) {
+            let _ = std::fs::create_dir_all(&self.dir)
+                .and_then(|()| write_atomic(&self.path(key), json.as_bytes()));
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+}
+
+pub(crate) fn write_atomic(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let mut tmp = tempfile::NamedTempFile::new_in(dir)?;
+    std::io::Write::write_all(&mut tmp, bytes)?;
+    tmp.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+/// Build a cache key for `provider` that distinguishes accounts without storing secrets.
+#[must_use]
+pub fn cache_key(provider: &str, account: &str) -> String {
+    // FNV-1a: stable across runs and platforms, unlike `DefaultHasher`.
+    let hash = account.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |h, b| {
+        (h ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{provider}-{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn serves_fresh_entries() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let cache = UsageCache::new(dir.path().to_path_buf(), Duration::from_mins(1));
+
+        cache.put("claude-abc", &vec![1, 2, 3]);
+
+        assert_eq!(cache.get::<Vec<i32>>("claude-abc"), Some(vec![1, 2, 3]));
+        assert_eq!(cache.get::<Vec<i32>>("claude-other"), None);
+        Ok(())
+    }
+
+    #[test]
+    fn expired_entries_are_ignored() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let cache = UsageCache::new(dir.path().to_path_buf(), Duration::ZERO);
+
+        cache.put("claude-abc", &1);
+
+        assert_eq!(cache.get::<i32>("claude-abc"), None);
+        let (value, fetched_at) = cache
+            .last_snapshot::<i32>("claude-abc")
+            .ok_or("expected a snapshot")?;
+        assert_eq!(value, 1);
+        assert!(fetched_at <= Utc::now());
+        Ok(())
+    }
+
+    #[test]
+    fn cache_key_is_stable_and_account_specific() {
+        assert_eq!(cache_key("claude", "a"), cache_key("claude", "a"));
+        assert_ne!(cache_key("claude", "a"), cache_key("claude", "b"));
+        assert!(cache_key("copilot", "a").starts_with("copilot-"));
+    }
+}
@@ -0,0 +1,202 @@
+use super::{cache_key, write_atomic};
+use crate::browser::Cookie;
+use crate::error::SeherError;
+use crate::keyring::{self, Keyring};
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+static INSTALLED: OnceLock<SessionCache> = OnceLock::new();
+
+const NONCE_LEN: usize = 12;
+
+/// Encrypted on-disk cache of session cookies recently extracted from browser profiles.
+///
+/// Entries live under `~/.cache/seher/sessions/`, one AES-256-GCM encrypted file per
+/// domain, keyed by the browser and profile they were read from. The key is generated on
+/// first use and kept in the OS keyring, so a cache hit touches neither the Keychain nor
+/// the browser's cookie database.
+pub struct SessionCache {
+    dir: PathBuf,
+    ttl: Duration,
+    cipher: Aes256Gcm,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    fetched_at: DateTime<Utc>,
+    cookies: Vec<Cookie>,
+}
+
+impl SessionCache {
+    /// A cache in `dir` encrypted with `key`.
+    #[must_use]
+    pub fn new(dir: PathBuf, ttl: Duration, key: &[u8; 32]) -> Self {
+        Self {
+            dir,
+            ttl,
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key)),
+        }
+    }
+
+    /// A cache in `dir` encrypted with the key stored in the keyring, generating and
+    /// storing a new key on first use.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the keyring cannot be read or written, or holds a malformed key.
+    pub fn open(dir: PathBuf, ttl: Duration) -> Result<Self, SeherError> {
+        let stored = Keyring::get(keyring::SESSION_CACHE_ACCOUNT)
+            .map_err(|e| SeherError::Other(Box::new(e)))?;
+        let key = if let Some(encoded) = stored {
+            STANDARD
+                .decode(encoded.trim())
+                .ok()
+                .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                .ok_or_else(|| SeherError::from("malformed session cache key in keyring"))?
+        } else {
+            let key: [u8; 32] = Aes256Gcm::generate_key(OsRng).into();
+            Keyring::set(keyring::SESSION_CACHE_ACCOUNT, &STANDARD.encode(key))
+                .map_err(|e| SeherError::Other(Box::new(e)))?;
+            key
+        };
+        Ok(Self::new(dir, ttl, &key))
+    }
+
+    /// Install this cache process-wide. Returns `false` if one was already installed.
+    pub fn install(self) -> bool {
+        INSTALLED.set(self).is_ok()
+    }
+
+    /// The installed cache, if session caching is enabled.
+    #[must_use]
+    pub fn current() -> Option<&'static Self> {
+        INSTALLED.get()
+    }
+
+    /// The cookies cached for `domain` from `scope` if they are younger than the TTL.
+    #[must_use]
+    pub fn get(&self, domain: &str, scope: &str) -> Option<Vec<Cookie>> {
+        let entry = self.read(domain).remove(scope)?;
+        let age = (Utc::now() - entry.fetched_at).to_std().ok()?;
+        (age < self.ttl).then_some(entry.cookies)
+    }
+
+    /// Store the cookies read for `domain` from `scope`. Failures are ignored: the cache is
+    /// best-effort.
+    pub fn put(&self, domain: &str, scope: &str, cookies: &[Cookie]) {
+        let mut entries = self.read(domain);
+        entries.insert(
+            scope.to_string(),
+            Entry {
+                fetched_at: Utc::now(),
+                cookies: cookies.to_vec(),
+            },
+        );
+        let Ok(json) = serde_json::to_vec(&entries) else {
+            return;
+        };
+        let nonce = Aes256Gcm::generate_nonce(OsRng);
+        let Ok(ciphertext) = self.cipher.encrypt(&nonce, json.as_slice()) else {
+            return;
+        };
+        let mut bytes = nonce.to_vec();
+        bytes.extend_from_slice(&ciphertext);
+        let path = self.path(domain);
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir).and_then(|()| write_atomic(&path, &bytes));
+        }
+    }
+
+    /// Drop every cached session for `domain`, e.g. after the provider rejected one.
+    pub fn invalidate(&self, domain: &str) {
+        let _ = std::fs::remove_file(self.path(domain));
+    }
+
+    /// All entries for `domain`; unreadable or undecryptable files count as empty.
+    fn read(&self, domain: &str) -> BTreeMap<String, Entry> {
+        std::fs::read(self.path(domain))
+            .ok()
+            .filter(|bytes| bytes.len() > NONCE_LEN)
+            .and_then(|bytes| {
+                let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+                self.cipher
+                    .decrypt(Nonce::from_slice(nonce), ciphertext)
+                    .ok()
+            })
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn path(&self, domain: &str) -> PathBuf {
+        self.dir
+            .join("sessions")
+            .join(format!("{}.bin", cache_key("session", domain)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    fn cookie(value: &str) -> Cookie {
+        Cookie {
+            name: "sessionKey".to_string(),
+            value: value.to_string(),
+            domain: ".claude.ai".to_string(),
+            path: "/".to_string(),
+            expires_utc: 0,
+            is_secure: true,
+            is_httponly: true,
+            same_site: 0,
+        }
+    }
+
+    #[test]
+    fn round_trips_encrypted_sessions_per_scope() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let cache = SessionCache::new(dir.path().to_path_buf(), Duration::from_mins(1), &[7; 32]);
+
+        cache.put("claude.ai", "chrome/Default", &[cookie("sk-ant-secret")]);
+        cache.put("claude.ai", "firefox/default", &[cookie("sk-ant-other")]);
+
+        let cached = cache.get("claude.ai", "chrome/Default").unwrap_or_default();
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].value, "sk-ant-secret");
+        assert!(cache.get("claude.ai", "edge/Default").is_none());
+        assert!(cache.get("github.com", "chrome/Default").is_none());
+
+        let raw = std::fs::read(cache.path("claude.ai"))?;
+        assert!(!String::from_utf8_lossy(&raw).contains("sk-ant-secret"));
+
+        cache.invalidate("claude.ai");
+        assert!(cache.get("claude.ai", "firefox/default").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn ignores_entries_it_cannot_decrypt_or_that_expired() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().to_path_buf();
+        SessionCache::new(path.clone(), Duration::from_mins(1), &[1; 32]).put(
+            "claude.ai",
+            "",
+            &[cookie("sk-ant")],
+        );
+
+        let other_key = SessionCache::new(path.clone(), Duration::from_mins(1), &[2; 32]);
+        assert!(other_key.get("claude.ai", "").is_none());
+        let expired = SessionCache::new(path, Duration::ZERO, &[1; 32]);
+        assert!(expired.get("claude.ai", "").is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,197 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::io::{Read, Seek, Write};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+static INSTALLED: OnceLock<FetchThrottle> = OnceLock::new();
+
+/// Minimum spacing between usage fetches per provider, shared by every seher process.
+///
+/// Each provider's next free slot is kept in `~/.cache/seher/throttle/<provider>`. A
+/// fetch reserves the slot under a file lock and sleeps until it comes, so concurrent
+/// invocations (prompt segments, `seher watch`, manual runs) queue up instead of hitting
+/// the provider at once.
+#[derive(Debug, Clone)]
+pub struct FetchThrottle {
+    dir: PathBuf,
+    intervals: HashMap<String, Duration>,
+}
+
+impl FetchThrottle {
+    #[must_use]
+    pub fn new(dir: PathBuf, intervals: HashMap<String, Duration>) -> Self {
+        Self { dir, intervals }
+    }
+
+    /// Install this throttle process-wide. Returns `false` if one was already installed.
+    pub fn install(self) -> bool {
+        INSTALLED.set(self).is_ok()
+    }
+
+    /// The installed throttle, if one is configured.
+    #[must_use]
+    pub fn current() -> Option<&'static Self> {
+        INSTALLED.get()
+    }
+
+    /// Reserve the next fetch slot for `provider` at or after `now` and return how long
+    /// to wait for it. Providers without an interval, and failures to read or write the
+    /// slot file, never wait.
+    #[must_use]
+    pub fn reserve(&self, provider: &str, now: DateTime<Utc>) -> Duration {
+        let Some(&interval) = self.intervals.get(provider).filter(|i| !i.is_zero()) else {
+            return Duration::ZERO;
+        };
+        self.reserve_slot(provider, now, interval)
+            .and_then(|slot| (slot - now).to_std().ok())
+            .unwrap_or_default()
+    }
+
+    fn reserve_slot(
+        &self,
+        provider: &str,
+        now: DateTime<Utc>,
+        interval: Duration,
+    ) -> Option<DateTime<Utc>> {
+        std::fs::create_dir_all(&self.dir).ok()?;
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(self.dir.join(provider))
+            .ok()?;
+        let _lock = FileLock::acquire(&file);
+        let mut content = String::new();
+        (&file).read_to_string(&mut content).ok()?;
+        let slot = content
+            .trim()
+            .parse::<DateTime<Utc>>()
+            .ok()
+            .map_or(now, |last| now.max(last + interval));
+        file.set_len(0).ok()?;
+        (&file).rewind().ok()?;
+        (&file).write_all(slot.to_rfc3339().as_bytes()).ok()?;
+        Some(slot)
+    }
+}
+
+/// Wait for `provider`'s next fetch slot when a throttle is installed.
+pub async fn throttle(provider: &str) {
+    wait_for_slot(FetchThrottle::current(), provider).await;
+}
+
+async fn wait_for_slot(throttle: Option<&FetchThrottle>, provider: &str) {
+    if let Some(throttle) = throttle {
+        let wait = throttle.reserve(provider, Utc::now());
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// One fetch slot shared by the requests that make up a single fetch (e.g. Claude's
+/// usage, organization and account lookups for one status): the first request that
+/// reaches the network waits for `provider`'s next slot and the others reuse it.
+pub struct SharedSlot<'a> {
+    provider: &'a str,
+    reserved: tokio::sync::OnceCell<()>,
+}
+
+impl<'a> SharedSlot<'a> {
+    #[must_use]
+    pub fn new(provider: &'a str) -> Self {
+        Self {
+            provider,
+            reserved: tokio::sync::OnceCell::new(),
+        }
+    }
+
+    /// Wait for the slot, reserving it with the installed throttle on the first call.
+    pub async fn wait(&self) {
+        self.wait_on(FetchThrottle::current()).await;
+    }
+
+    async fn wait_on(&self, throttle: Option<&FetchThrottle>) {
+        self.reserved
+            .get_or_init(|| wait_for_slot(throttle, self.provider))
+            .await;
+    }
+}
+
+/// Exclusive advisory lock on a file, released on drop. A no-op where `flock` is missing.
+struct FileLock<'a> {
+    #[cfg_attr(not(unix), expect(dead_code, reason = "only unix takes the lock"))]
+    file: &'a std::fs::File,
+}
+
+impl<'a> FileLock<'a> {
+    fn acquire(file: &'a std::fs::File) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::io::AsRawFd;
+            // SAFETY: the descriptor belongs to `file`, which outlives the lock.
+            unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) };
+        }
+        Self { file }
+    }
+}
+
+impl Drop for FileLock<'_> {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::io::AsRawFd;
+            // SAFETY: as in `acquire`.
+            unsafe { libc::flock(self.file.as_raw_fd(), libc::LOCK_UN) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn reservations_are_spaced_by_the_interval() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let throttle = FetchThrottle::new(
+            dir.path().to_path_buf(),
+            HashMap::from([("claude".to_string(), Duration::from_secs(30))]),
+        );
+        let now = Utc::now();
+
+        assert_eq!(throttle.reserve("claude", now), Duration::ZERO);
+        assert_eq!(throttle.reserve("claude", now), Duration::from_secs(30));
+        assert_eq!(throttle.reserve("claude", now), Duration::from_mins(1));
+        // Once the reserved slots have passed, fetches go straight through.
+        let later = now + chrono::Duration::minutes(5);
+        assert_eq!(throttle.reserve("claude", later), Duration::ZERO);
+        // Providers without an interval are not throttled.
+        assert_eq!(throttle.reserve("copilot", now), Duration::ZERO);
+        assert_eq!(throttle.reserve("copilot", now), Duration::ZERO);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn a_shared_slot_reserves_once_for_all_its_requests() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let throttle = FetchThrottle::new(
+            dir.path().to_path_buf(),
+            HashMap::from([("claude".to_string(), Duration::from_mins(1))]),
+        );
+        // Usage, organization and account lookups of one status.
+        let slot = SharedSlot::new("claude");
+        for _ in 0..3 {
+            slot.wait_on(Some(&throttle)).await;
+        }
+        // Only one slot was taken, so the next fetch waits a single interval.
+        let wait = throttle.reserve("claude", Utc::now());
+        assert!(wait > Duration::from_secs(50) && wait <= Duration::from_mins(1));
+        Ok(())
+    }
+}
@@ -1,5 +1,14 @@
 use super::error::{ClaudeApiError, Result};
-use super::types::UsageResponse;
+use super::types::{Account, Organization, UsageResponse};
+#[cfg(feature = "browser")]
+use crate::http::poll;
+use crate::http::{
+    BrowserHeaders, Endpoint, HttpRequest, HttpTransport, ReqwestTransport, base_url,
+};
+#[cfg(feature = "browser")]
+use futures_util::Stream;
+#[cfg(feature = "browser")]
+use std::time::Duration;
 
 #[cfg(feature = "browser")]
 use crate::Cookie;
@@ -50,68 +59,291 @@ fn extract_uuid(s: &str) -> Option<String> {
     None
 }
 
-pub struct ClaudeClient;
+/// Returns `true` if an error response is a Cloudflare challenge/interstitial page
+/// rather than an API error from claude.ai.
+fn is_cloudflare_challenge(status: u16, body: &str) -> bool {
+    matches!(status, 403 | 429 | 503)
+        && [
+            "Just a moment...",
+            "challenge-platform",
+            "cf-chl-",
+            "cf_chl_",
+        ]
+        .iter()
+        .any(|marker| body.contains(marker))
+}
 
-const USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) \
-    AppleWebKit/537.36 (KHTML, like Gecko) Chrome/133.0.0.0 Safari/537.36";
+fn truncate_at_char_boundary(s: &str, max: usize) -> &str {
+    let mut end = max.min(s.len());
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+pub struct ClaudeClient;
 
 impl ClaudeClient {
-    /// Fetch usage using a pre-built cookie header string and org ID.
-    ///
-    /// # Errors
-    ///
-    /// Returns an error if the API request fails or the response cannot be parsed.
-    pub async fn fetch_usage_with_header(
+    /// Send a GET request to a claude.ai API endpoint with browser-like headers.
+    async fn get_json<T: serde::de::DeserializeOwned>(
+        transport: &dyn HttpTransport,
+        url: &str,
         cookie_header: &str,
-        org_id: &str,
-    ) -> Result<UsageResponse> {
-        let url = format!("https://claude.ai/api/organizations/{org_id}/usage");
-
-        let client = reqwest::Client::builder().user_agent(USER_AGENT).build()?;
-
-        let response = client
-            .get(&url)
+    ) -> Result<T> {
+        let origin = base_url(Endpoint::Claude);
+        let browser = BrowserHeaders::current();
+        let mut request = HttpRequest::get(url)
             .header("Cookie", cookie_header)
+            .header("User-Agent", browser.user_agent())
             .header("Accept", "application/json")
             .header("Accept-Language", "en-US,en;q=0.9")
-            .header("Referer", "https://claude.ai/")
-            .header("Origin", "https://claude.ai")
+            .header("Referer", format!("{origin}/"))
+            .header("Origin", origin)
             .header("DNT", "1")
-            .header("sec-ch-ua-platform", "\"macOS\"")
             .header("sec-fetch-dest", "empty")
             .header("sec-fetch-mode", "cors")
-            .header("sec-fetch-site", "same-origin")
-            .send()
-            .await?;
+            .header("sec-fetch-site", "same-origin");
+        if let Some(platform) = browser.sec_ch_ua_platform() {
+            request = request.header("sec-ch-ua-platform", platform);
+        }
+        if crate::http::impersonate() {
+            if let Some(sec_ch_ua) = browser.sec_ch_ua() {
+                request = request
+                    .header("sec-ch-ua", sec_ch_ua)
+                    .header("sec-ch-ua-mobile", "?0");
+            }
+            request = request
+                .header("priority", "u=1, i")
+                .header("anthropic-client-platform", "web_claude_ai");
+        }
 
-        let status = response.status();
-        if !status.is_success() {
-            let body = response.text().await.unwrap_or_default();
+        let response = transport.send(request).await?;
+
+        if !response.is_success() {
+            let challenged = response.header("cf-mitigated") == Some("challenge");
+            if challenged || is_cloudflare_challenge(response.status, &response.body) {
+                return Err(ClaudeApiError::CloudflareChallenge {
+                    status: response.status,
+                });
+            }
             // Truncate Cloudflare HTML for readability
-            let body = if body.len() > 200 {
-                format!("{}...", &body[..200])
+            let body = if response.body.len() > 200 {
+                format!("{}...", truncate_at_char_boundary(&response.body, 200))
             } else {
-                body
+                response.body
             };
             return Err(ClaudeApiError::ApiError {
-                status: status.as_u16(),
+                status: response.status,
                 body,
             });
         }
 
-        let usage: UsageResponse = response.json().await?;
-        Ok(usage)
+        Ok(response.json()?)
     }
 
+    /// Fetch usage using a pre-built cookie header string and org ID.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the org ID cookie is missing, the API request fails, or the response
-    /// cannot be parsed.
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    pub async fn fetch_usage_with_header(
+        cookie_header: &str,
+        org_id: &str,
+    ) -> Result<UsageResponse> {
+        Self::fetch_usage_via(&ReqwestTransport, cookie_header, org_id).await
+    }
+
+    /// [`Self::fetch_usage_with_header`] over a caller-supplied transport.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    pub async fn fetch_usage_via(
+        transport: &dyn HttpTransport,
+        cookie_header: &str,
+        org_id: &str,
+    ) -> Result<UsageResponse> {
+        let url = format!(
+            "{}/api/organizations/{org_id}/usage",
+            base_url(Endpoint::Claude)
+        );
+        Self::get_json(transport, &url, cookie_header).await
+    }
+
+    /// List the organizations the session belongs to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    pub async fn fetch_organizations(cookie_header: &str) -> Result<Vec<Organization>> {
+        Self::fetch_organizations_via(&ReqwestTransport, cookie_header).await
+    }
+
+    /// [`Self::fetch_organizations`] over a caller-supplied transport.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    pub async fn fetch_organizations_via(
+        transport: &dyn HttpTransport,
+        cookie_header: &str,
+    ) -> Result<Vec<Organization>> {
+        let url = format!("{}/api/organizations", base_url(Endpoint::Claude));
+        Self::get_json(transport, &url, cookie_header).await
+    }
+
+    /// Fetch metadata (plan, capabilities) for a single organization.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    pub async fn fetch_organization_with_header(
+        cookie_header: &str,
+        org_id: &str,
+    ) -> Result<Organization> {
+        Self::fetch_organization_via(&ReqwestTransport, cookie_header, org_id).await
+    }
+
+    /// [`Self::fetch_organization_with_header`] over a caller-supplied transport.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    pub async fn fetch_organization_via(
+        transport: &dyn HttpTransport,
+        cookie_header: &str,
+        org_id: &str,
+    ) -> Result<Organization> {
+        let url = format!("{}/api/organizations/{org_id}", base_url(Endpoint::Claude));
+        Self::get_json(transport, &url, cookie_header).await
+    }
+
+    /// Fetch the signed-in user (email, names) behind the session.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    pub async fn fetch_account_with_header(cookie_header: &str) -> Result<Account> {
+        Self::fetch_account_via(&ReqwestTransport, cookie_header).await
+    }
+
+    /// [`Self::fetch_account_with_header`] over a caller-supplied transport.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    pub async fn fetch_account_via(
+        transport: &dyn HttpTransport,
+        cookie_header: &str,
+    ) -> Result<Account> {
+        let url = format!("{}/api/account", base_url(Endpoint::Claude));
+        Self::get_json(transport, &url, cookie_header).await
+    }
+
+    /// Resolve an org ID from `/api/organizations`, used when `lastActiveOrg` is unavailable.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the session has no organizations.
+    pub async fn fetch_default_org_id(cookie_header: &str) -> Result<String> {
+        let orgs = Self::fetch_organizations(cookie_header).await?;
+        Organization::select_default(&orgs)
+            .map(|org| org.uuid.clone())
+            .ok_or(ClaudeApiError::OrganizationNotFound)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if no organization can be resolved, the API request fails, or the
+    /// response cannot be parsed.
     #[cfg(feature = "browser")]
     pub async fn fetch_usage(cookies: &[Cookie]) -> Result<UsageResponse> {
-        let org_id = Self::find_org_id(cookies)?;
+        Self::fetch_usage_with_cookies_via(&ReqwestTransport, cookies).await
+    }
+
+    /// [`Self::fetch_usage`] over a caller-supplied transport.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no organization can be resolved, the API request fails, or the
+    /// response cannot be parsed.
+    #[cfg(feature = "browser")]
+    pub async fn fetch_usage_with_cookies_via(
+        transport: &dyn HttpTransport,
+        cookies: &[Cookie],
+    ) -> Result<UsageResponse> {
+        let cookie_header = Self::build_cookie_header(cookies);
+        let org_id = Self::resolve_org_id_via(transport, cookies, &cookie_header).await?;
+        Self::fetch_usage_via(transport, &cookie_header, &org_id).await
+    }
+
+    /// Fetch usage right away and then once every `interval`, for dashboards that want to
+    /// follow updates. The organization is resolved on the first successful poll and reused.
+    #[cfg(feature = "browser")]
+    pub fn usage_stream(
+        cookies: &[Cookie],
+        interval: Duration,
+    ) -> impl Stream<Item = Result<UsageResponse>> + use<> {
+        let cookie_header = Self::build_cookie_header(cookies);
+        let org_id = Self::find_org_id(cookies).ok();
+        poll(interval, org_id, move |org_id| {
+            let cookie_header = cookie_header.clone();
+            async move {
+                let org_id = match org_id {
+                    Some(id) => id,
+                    None => match Self::fetch_default_org_id(&cookie_header).await {
+                        Ok(id) => id,
+                        Err(e) => return (Err(e), None),
+                    },
+                };
+                let usage = Self::fetch_usage_with_header(&cookie_header, &org_id).await;
+                (usage, Some(org_id))
+            }
+        })
+    }
+
+    /// Fetch the signed-in user behind the session.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the API request fails or the response cannot be parsed.
+    #[cfg(feature = "browser")]
+    pub async fn fetch_account(cookies: &[Cookie]) -> Result<Account> {
+        Self::fetch_account_with_header(&Self::build_cookie_header(cookies)).await
+    }
+
+    /// Fetch metadata for the session's active organization.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no organization can be resolved, the API request fails, or the
+    /// response cannot be parsed.
+    #[cfg(feature = "browser")]
+    pub async fn fetch_organization(cookies: &[Cookie]) -> Result<Organization> {
         let cookie_header = Self::build_cookie_header(cookies);
-        Self::fetch_usage_with_header(&cookie_header, &org_id).await
+        let org_id = Self::resolve_org_id(cookies, &cookie_header).await?;
+        Self::fetch_organization_with_header(&cookie_header, &org_id).await
+    }
+
+    #[cfg(feature = "browser")]
+    async fn resolve_org_id(cookies: &[Cookie], cookie_header: &str) -> Result<String> {
+        Self::resolve_org_id_via(&ReqwestTransport, cookies, cookie_header).await
+    }
+
+    #[cfg(feature = "browser")]
+    async fn resolve_org_id_via(
+        transport: &dyn HttpTransport,
+        cookies: &[Cookie],
+        cookie_header: &str,
+    ) -> Result<String> {
+        // Fresh logins often lack `lastActiveOrg`; fall back to the organization list.
+        if let Ok(id) = Self::find_org_id(cookies) {
+            return Ok(id);
+        }
+        let orgs = Self::fetch_organizations_via(transport, cookie_header).await?;
+        Organization::select_default(&orgs)
+            .map(|org| org.uuid.clone())
+            .ok_or(ClaudeApiError::OrganizationNotFound)
     }
 
     #[cfg(feature = "browser")]
@@ -135,10 +367,88 @@ impl ClaudeClient {
 
     #[cfg(feature = "browser")]
     fn build_cookie_header(cookies: &[Cookie]) -> String {
-        cookies
-            .iter()
-            .map(|c| format!("{}={}", c.name, c.value))
-            .collect::<Vec<_>>()
-            .join("; ")
+        crate::browser::cookie_header(cookies, "claude.ai", "/api/")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::HttpResponse;
+    use crate::http::transport::mock::MockTransport;
+
+    type TestResult = std::result::Result<(), Box<dyn std::error::Error>>;
+
+    const ORG_ID: &str = "00000000-0000-0000-0000-000000000000";
+
+    fn usage_url() -> String {
+        format!(
+            "{}/api/organizations/{ORG_ID}/usage",
+            base_url(Endpoint::Claude)
+        )
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn fetch_usage_via_parses_limited_response() -> TestResult {
+        let body = r#"{
+            "five_hour": {"utilization": 100.0, "resets_at": "2026-01-01T05:00:00Z"},
+            "seven_day": {"utilization": 40.0, "resets_at": "2026-01-07T00:00:00Z"}
+        }"#;
+        let url = usage_url();
+        let transport = MockTransport::new(vec![(url.as_str(), HttpResponse::new(200, body))]);
+
+        let usage = ClaudeClient::fetch_usage_via(&transport, "sessionKey=abc", ORG_ID).await?;
+
+        assert!(usage.is_limited());
+        let requests = transport.requests.lock().map_err(|e| e.to_string())?;
+        assert!(
+            requests[0]
+                .headers
+                .contains(&("Cookie".to_string(), "sessionKey=abc".to_string()))
+        );
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn fetch_usage_via_reports_cloudflare_challenge() {
+        let mut response = HttpResponse::new(403, "<html>blocked</html>");
+        response
+            .headers
+            .push(("cf-mitigated".to_string(), "challenge".to_string()));
+        let url = usage_url();
+        let transport = MockTransport::new(vec![(url.as_str(), response)]);
+
+        let result = ClaudeClient::fetch_usage_via(&transport, "", ORG_ID).await;
+
+        assert!(matches!(
+            result,
+            Err(ClaudeApiError::CloudflareChallenge { status: 403 })
+        ));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn fetch_account_via_parses_identity() -> TestResult {
+        let body = r#"{"uuid": "u", "email_address": "me@example.com", "full_name": "Me"}"#;
+        let url = format!("{}/api/account", base_url(Endpoint::Claude));
+        let transport = MockTransport::new(vec![(url.as_str(), HttpResponse::new(200, body))]);
+
+        let account = ClaudeClient::fetch_account_via(&transport, "sessionKey=abc").await?;
+
+        assert_eq!(account.identity(), Some("me@example.com"));
+        Ok(())
+    }
+
+    #[test]
+    fn detects_cloudflare_interstitial() {
+        let body = "<!DOCTYPE html><html><head><title>Just a moment...</title>\
+            <script src=\"/cdn-cgi/challenge-platform/h/b/orchestrate/chl_page/v1\"></script>";
+        assert!(is_cloudflare_challenge(403, body));
+    }
+
+    #[test]
+    fn api_errors_are_not_cloudflare_challenges() {
+        let body = r#"{"type":"error","error":{"type":"permission_error","message":"denied"}}"#;
+        assert!(!is_cloudflare_challenge(403, body));
+        assert!(!is_cloudflare_challenge(200, "Just a moment..."));
     }
 }
@@ -5,6 +5,9 @@ pub enum ClaudeApiError {
     #[error("HTTP request error: {0}")]
     RequestError(#[from] reqwest::Error),
 
+    #[error("HTTP transport error: {0}")]
+    Transport(#[from] crate::http::TransportError),
+
     #[error("JSON parse error: {0}")]
     ParseError(#[from] serde_json::Error),
 
@@ -12,6 +15,15 @@ pub enum ClaudeApiError {
     #[error("Cookie not found: {0}")]
     CookieNotFound(String),
 
+    #[error("No organization found for this session")]
+    OrganizationNotFound,
+
+    #[error(
+        "Cloudflare challenge blocked the request (status {status}); open https://claude.ai in \
+         your browser to refresh the cf_clearance cookie, or enable `impersonate` in settings"
+    )]
+    CloudflareChallenge { status: u16 },
+
     #[error("API error (status {status}): {body}")]
     ApiError { status: u16, body: String },
 }
@@ -4,4 +4,4 @@ pub mod types;
 
 pub use client::ClaudeClient;
 pub use error::ClaudeApiError;
-pub use types::{UsageResponse, UsageWindow};
+pub use types::{Account, ExtraUsage, Organization, UsageResponse, UsageWindow};
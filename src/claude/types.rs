@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Deserialize, Serialize)]
 pub struct UsageWindow {
@@ -23,7 +24,68 @@ pub struct UsageResponse {
     pub seven_day_opus: Option<UsageWindow>,
     pub seven_day_cowork: Option<UsageWindow>,
     pub iguana_necktie: Option<UsageWindow>,
-    pub extra_usage: Option<UsageWindow>,
+    pub extra_usage: Option<ExtraUsage>,
+    /// Windows not known to this version (e.g. new model-specific limits), keyed by name.
+    #[serde(flatten, deserialize_with = "deserialize_additional_windows")]
+    pub additional_windows: BTreeMap<String, UsageWindow>,
+}
+
+/// Pay-as-you-go overage credits that keep the account usable past its plan windows.
+///
+/// Credit amounts are reported by claude.ai in cents.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct ExtraUsage {
+    #[serde(default)]
+    pub is_enabled: bool,
+    #[serde(default)]
+    pub monthly_limit: Option<f64>,
+    #[serde(default)]
+    pub used_credits: Option<f64>,
+    #[serde(default)]
+    pub utilization: Option<f64>,
+}
+
+impl ExtraUsage {
+    /// Credits left before the monthly overage cap, or `None` when no cap is reported.
+    #[must_use]
+    pub fn remaining_credits(&self) -> Option<f64> {
+        self.monthly_limit
+            .map(|limit| (limit - self.used_credits.unwrap_or(0.0)).max(0.0))
+    }
+
+    /// Returns `true` if overage is enabled and not yet exhausted.
+    #[must_use]
+    pub fn has_balance(&self) -> bool {
+        self.is_enabled
+            && self.utilization.unwrap_or(0.0) < 100.0
+            && self
+                .remaining_credits()
+                .is_none_or(|remaining| remaining > 0.0)
+    }
+}
+
+/// Keep only unknown fields that look like usage windows, so unrelated fields added
+/// to the response don't break parsing or show up as empty windows.
+fn deserialize_additional_windows<'de, D>(
+    deserializer: D,
+) -> Result<BTreeMap<String, UsageWindow>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let raw: BTreeMap<String, serde_json::Value> = Deserialize::deserialize(deserializer)?;
+    Ok(raw
+        .into_iter()
+        .filter(|(_, value)| {
+            value
+                .as_object()
+                .is_some_and(|obj| obj.contains_key("utilization") || obj.contains_key("resets_at"))
+        })
+        .filter_map(|(name, value)| {
+            serde_json::from_value::<UsageWindow>(value)
+                .ok()
+                .map(|w| (name, w))
+        })
+        .collect())
 }
 
 impl UsageResponse {
@@ -38,13 +100,28 @@ impl UsageResponse {
             ("seven_day_opus", self.seven_day_opus.as_ref()),
             ("seven_day_cowork", self.seven_day_cowork.as_ref()),
             ("iguana_necktie", self.iguana_necktie.as_ref()),
-            ("extra_usage", self.extra_usage.as_ref()),
         ]
         .into_iter()
         .filter_map(|(name, w)| w.map(|w| (name, w)))
+        .chain(
+            self.additional_windows
+                .iter()
+                .map(|(name, w)| (name.as_str(), w)),
+        )
         .collect()
     }
 
+    /// Returns `true` if a window is exhausted and no extra-usage balance can cover it.
+    #[must_use]
+    pub fn is_limited(&self) -> bool {
+        let has_limited_window = self.all_windows().iter().any(|(_, w)| w.is_limited());
+        has_limited_window
+            && !self
+                .extra_usage
+                .as_ref()
+                .is_some_and(ExtraUsage::has_balance)
+    }
+
     #[must_use]
     pub fn next_reset_time(&self) -> Option<chrono::DateTime<Utc>> {
         self.all_windows()
@@ -54,3 +131,224 @@ impl UsageResponse {
             .max()
     }
 }
+
+/// The signed-in claude.ai user, from `/api/account`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Account {
+    pub uuid: String,
+    #[serde(default)]
+    pub email_address: Option<String>,
+    #[serde(default)]
+    pub full_name: Option<String>,
+    #[serde(default)]
+    pub display_name: Option<String>,
+}
+
+impl Account {
+    /// The email address, or the display name when the email is missing, to tell accounts
+    /// apart.
+    #[must_use]
+    pub fn identity(&self) -> Option<&str> {
+        [&self.email_address, &self.display_name, &self.full_name]
+            .into_iter()
+            .find_map(|field| field.as_deref().filter(|s| !s.is_empty()))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Organization {
+    pub uuid: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub capabilities: Vec<String>,
+    #[serde(default)]
+    pub rate_limit_tier: Option<String>,
+    #[serde(default)]
+    pub billing_type: Option<String>,
+    /// Set for Team/Enterprise organizations (e.g. `"team"`).
+    #[serde(default)]
+    pub raven_type: Option<String>,
+}
+
+impl Organization {
+    /// Pick the organization to query when no `lastActiveOrg` cookie is present.
+    ///
+    /// Prefers the first organization with the `chat` capability (API-only
+    /// organizations have no claude.ai usage), falling back to the first entry.
+    #[must_use]
+    pub fn select_default(orgs: &[Organization]) -> Option<&Organization> {
+        orgs.iter()
+            .find(|org| org.capabilities.iter().any(|c| c == "chat"))
+            .or_else(|| orgs.first())
+    }
+
+    fn has_capability(&self, capability: &str) -> bool {
+        self.capabilities.iter().any(|c| c == capability)
+    }
+
+    /// Human-readable subscription tier (e.g. `"Pro"`, `"Max 20x"`, `"Team"`).
+    ///
+    /// Returns `None` for organizations without claude.ai chat access.
+    #[must_use]
+    pub fn plan_name(&self) -> Option<String> {
+        if let Some(raven) = self.raven_type.as_deref().filter(|r| !r.is_empty()) {
+            let mut chars = raven.chars();
+            return chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect());
+        }
+        if self.has_capability("claude_max") {
+            let multiplier = self
+                .rate_limit_tier
+                .as_deref()
+                .and_then(|tier| ["20x", "5x"].into_iter().find(|m| tier.contains(m)));
+            return Some(multiplier.map_or_else(|| "Max".to_string(), |m| format!("Max {m}")));
+        }
+        if self.has_capability("claude_pro") {
+            return Some("Pro".to_string());
+        }
+        self.has_capability("chat").then(|| "Free".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn usage_response_captures_unknown_windows() -> TestResult {
+        let json = r#"{
+            "five_hour": {"utilization": 12.0, "resets_at": "2025-06-15T12:00:00Z"},
+            "seven_day": null,
+            "seven_day_haiku": {"utilization": 100.0, "resets_at": "2025-06-20T00:00:00Z"},
+            "some_flag": true,
+            "unrelated": {"enabled": false}
+        }"#;
+        let usage: UsageResponse = serde_json::from_str(json)?;
+
+        assert_eq!(usage.additional_windows.len(), 1);
+        let names: Vec<&str> = usage.all_windows().iter().map(|(n, _)| *n).collect();
+        assert_eq!(names, vec!["five_hour", "seven_day_haiku"]);
+        assert_eq!(
+            usage.next_reset_time(),
+            Some("2025-06-20T00:00:00Z".parse::<DateTime<Utc>>()?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn usage_response_round_trips_unknown_windows() -> TestResult {
+        let json = r#"{"seven_day_haiku": {"utilization": 40.0, "resets_at": null}}"#;
+        let usage: UsageResponse = serde_json::from_str(json)?;
+
+        let value = serde_json::to_value(&usage)?;
+        assert_eq!(value["seven_day_haiku"]["utilization"], 40.0);
+        Ok(())
+    }
+
+    #[test]
+    fn limited_window_is_not_blocking_when_extra_usage_has_balance() -> TestResult {
+        let json = r#"{
+            "five_hour": {"utilization": 100.0, "resets_at": "2025-06-15T12:00:00Z"},
+            "extra_usage": {"is_enabled": true, "monthly_limit": 5000, "used_credits": 1250.0, "utilization": 25.0}
+        }"#;
+        let usage: UsageResponse = serde_json::from_str(json)?;
+
+        assert!(!usage.is_limited());
+        let extra = usage.extra_usage.as_ref().ok_or("expected extra_usage")?;
+        assert_eq!(extra.remaining_credits(), Some(3750.0));
+        Ok(())
+    }
+
+    #[test]
+    fn limited_window_blocks_when_extra_usage_is_exhausted_or_disabled() -> TestResult {
+        let exhausted = r#"{
+            "five_hour": {"utilization": 100.0, "resets_at": null},
+            "extra_usage": {"is_enabled": true, "monthly_limit": 5000, "used_credits": 5000.0, "utilization": 100.0}
+        }"#;
+        let disabled = r#"{
+            "five_hour": {"utilization": 100.0, "resets_at": null},
+            "extra_usage": {"is_enabled": false, "monthly_limit": null, "used_credits": null, "utilization": null}
+        }"#;
+
+        assert!(serde_json::from_str::<UsageResponse>(exhausted)?.is_limited());
+        assert!(serde_json::from_str::<UsageResponse>(disabled)?.is_limited());
+        Ok(())
+    }
+
+    #[test]
+    fn select_default_prefers_chat_capable_organization() -> TestResult {
+        let json = r#"[
+            {"uuid": "api-org", "name": "API", "capabilities": ["api"]},
+            {"uuid": "chat-org", "name": "Personal", "capabilities": ["chat", "claude_pro"]}
+        ]"#;
+        let orgs: Vec<Organization> = serde_json::from_str(json)?;
+
+        let selected = Organization::select_default(&orgs).ok_or("expected Some")?;
+        assert_eq!(selected.uuid, "chat-org");
+        Ok(())
+    }
+
+    #[test]
+    fn select_default_falls_back_to_first_organization() -> TestResult {
+        let json = r#"[{"uuid": "only-org"}, {"uuid": "second-org"}]"#;
+        let orgs: Vec<Organization> = serde_json::from_str(json)?;
+
+        let selected = Organization::select_default(&orgs).ok_or("expected Some")?;
+        assert_eq!(selected.uuid, "only-org");
+        Ok(())
+    }
+
+    #[test]
+    fn plan_name_reports_max_tier_multiplier() -> TestResult {
+        let json = r#"{
+            "uuid": "org",
+            "capabilities": ["chat", "claude_max"],
+            "rate_limit_tier": "default_claude_max_20x"
+        }"#;
+        let org: Organization = serde_json::from_str(json)?;
+
+        assert_eq!(org.plan_name().as_deref(), Some("Max 20x"));
+        Ok(())
+    }
+
+    #[test]
+    fn plan_name_distinguishes_pro_team_and_free() -> TestResult {
+        let pro: Organization =
+            serde_json::from_str(r#"{"uuid": "a", "capabilities": ["chat", "claude_pro"]}"#)?;
+        let team: Organization = serde_json::from_str(
+            r#"{"uuid": "b", "capabilities": ["chat"], "raven_type": "team"}"#,
+        )?;
+        let free: Organization =
+            serde_json::from_str(r#"{"uuid": "c", "capabilities": ["chat"]}"#)?;
+        let api: Organization = serde_json::from_str(r#"{"uuid": "d", "capabilities": ["api"]}"#)?;
+
+        assert_eq!(pro.plan_name().as_deref(), Some("Pro"));
+        assert_eq!(team.plan_name().as_deref(), Some("Team"));
+        assert_eq!(free.plan_name().as_deref(), Some("Free"));
+        assert_eq!(api.plan_name(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn account_identity_prefers_email() -> TestResult {
+        let account: Account = serde_json::from_str(
+            r#"{"uuid": "a", "email_address": "me@example.com", "display_name": "Me"}"#,
+        )?;
+        assert_eq!(account.identity(), Some("me@example.com"));
+        let account: Account =
+            serde_json::from_str(r#"{"uuid": "a", "email_address": "", "display_name": "Me"}"#)?;
+        assert_eq!(account.identity(), Some("Me"));
+        let account: Account = serde_json::from_str(r#"{"uuid": "a"}"#)?;
+        assert_eq!(account.identity(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn select_default_returns_none_for_empty_list() {
+        assert!(Organization::select_default(&[]).is_none());
+    }
+}
@@ -1,15 +1,40 @@
 use chrono::{DateTime, Local, Utc};
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use futures_util::stream::{self, Stream, StreamExt};
 use seher::{
-    Agent, AgentConfig, AgentLimit, AgentStatus, BrowserDetector, BrowserType, CodexClient,
-    CookieReader, Settings,
+    Agent, AgentConfig, AgentLimit, AgentStatus, BrowserDetector, BrowserType, ClaudeClient,
+    CodexClient, CookieReader, SeherError, Settings,
+    agent::{OutputCapture, Stop},
+    alert::{self, AlertRule, AlertTracker},
+    audit,
+    cache::{Cooldowns, FetchThrottle, SessionCache, UsageCache},
+    config::{AgentRetry, BudgetAction, ProgressConfig, PromptInput, RetryOn, ScheduledRun},
+    copilot::GithubDeviceFlow,
+    events::{self, Event, LimitTracker},
+    history,
+    hooks::{HookEvent, HookPayload, HooksConfig},
+    keyring::{self, Keyring},
+    notify::{NotificationConfig, Notifier},
+    output::{
+        AuditOutput, HistoryOutput, QueueOutput, StatusOutput, Summary, SummaryOutput, WaitEvent,
+        WaitEventKind,
+    },
+    pending::PendingWait,
+    queue::{Queue, QueueItem},
+    session,
+    telemetry::TelemetryConfig,
+    template::{
+        self,
+        front_matter::{self, FrontMatter},
+    },
+    wait::keys::KeyInput,
 };
 use std::cmp::Reverse;
+use std::collections::HashSet;
 use std::future::Future;
 use std::path::PathBuf;
 use std::pin::Pin;
-use std::str::FromStr;
-use zzsleep::sleep_until;
+use std::process::ExitCode;
 
 #[derive(Parser)]
 #[command(
@@ -24,12 +49,26 @@ use zzsleep::sleep_until;
 pub struct Args {
     /// Browser to use (chrome, edge, brave, firefox, safari, etc.)
     #[arg(long, short)]
-    pub browser: Option<String>,
+    pub browser: Option<BrowserType>,
 
     /// Browser profile name (e.g. "Profile 1", "default-release")
     #[arg(long)]
     pub profile: Option<String>,
 
+    /// Firefox Multi-Account Container to read cookies from (e.g. "Work")
+    #[arg(long, value_name = "NAME")]
+    pub container: Option<String>,
+
+    /// Use the first browser profile found with a valid session rather than the first in
+    /// profile order
+    #[arg(long)]
+    pub first_valid_session: bool,
+
+    /// Read cookies only from this Chromium user-data directory or Firefox profile folder
+    /// (portable browsers, Selenium profiles); overrides settings
+    #[arg(long, value_name = "DIR")]
+    pub user_data_dir: Option<PathBuf>,
+
     /// Filter agents by command name
     #[arg(long)]
     pub command: Option<String>,
@@ -46,12 +85,16 @@ pub struct Args {
     #[arg(long, short)]
     pub model: Option<String>,
 
+    /// Run only the agent with this name (or command or provider), still checking its limit and waiting for its reset
+    #[arg(long, value_name = "NAME")]
+    pub agent: Option<String>,
+
     /// Suppress informational output (usage, sleep progress, etc.)
-    #[arg(long, short)]
+    #[arg(long, short, global = true)]
     pub quiet: bool,
 
     /// Output provider usage as JSON and exit
-    #[arg(long, short = 'j')]
+    #[arg(long, short = 'j', global = true)]
     pub json: bool,
 
     /// Path to settings file
@@ -65,6 +108,239 @@ pub struct Args {
     /// Open the web-based config editor and exit when the server stops
     #[arg(long)]
     pub gui_config: bool,
+
+    /// Proxy URL for provider requests (http, https, socks5, socks5h); overrides settings
+    #[arg(long)]
+    pub proxy: Option<String>,
+
+    /// Timeout in seconds for each provider request; overrides settings (default 30)
+    #[arg(long, value_name = "SECS")]
+    pub timeout: Option<u64>,
+
+    /// Override a provider base URL as KEY=URL (keys: claude, github, `github_api`, `anthropic_console`, `anthropic_api`, `openai_api`)
+    #[arg(long = "base-url", value_name = "KEY=URL")]
+    pub base_urls: Vec<String>,
+
+    /// Serve cached usage younger than SECS instead of querying providers; overrides settings
+    #[arg(long, value_name = "SECS")]
+    pub cache_ttl: Option<u64>,
+
+    /// Answer from the last cached usage instead of querying providers (no network)
+    #[arg(long)]
+    pub offline: bool,
+
+    /// Write each raw provider response (status, headers, body) to FILE, cookies redacted
+    #[arg(long, value_name = "FILE")]
+    pub dump_response: Option<PathBuf>,
+
+    /// Send full browser headers to claude.ai to reduce Cloudflare challenges
+    #[arg(long)]
+    pub impersonate: bool,
+
+    /// Ring the terminal bell when a reset wait finishes and when the agent exits
+    #[arg(long)]
+    pub bell: bool,
+
+    /// Re-check usage this often while waiting, to start early if the limit clears (0 disables)
+    #[arg(long, value_name = "DURATION", value_parser = history::parse_duration)]
+    pub recheck_interval: Option<chrono::Duration>,
+
+    /// Print a plain countdown line every few minutes instead of drawing a progress bar
+    #[arg(long)]
+    pub plain_progress: bool,
+
+    /// With --json, print a JSON progress event this often while waiting for a reset (default 10s)
+    #[arg(long, value_name = "DURATION", value_parser = history::parse_duration)]
+    pub progress_interval: Option<chrono::Duration>,
+
+    /// Send a notification this long before a waited-for reset completes (e.g. 10m)
+    #[arg(long, value_name = "DURATION", value_parser = history::parse_duration)]
+    pub notify_before: Option<chrono::Duration>,
+
+    /// Use a prompt template from settings; `{args}`, `{cwd}`, `{git_branch}` and `{stdin}` are expanded
+    #[arg(long, short = 't', value_name = "NAME")]
+    pub template: Option<String>,
+
+    /// Replace seher with the agent process once the limit check passes (Unix only)
+    #[arg(long)]
+    pub exec_replace: bool,
+
+    /// Check limits and print which agent would run, with which arguments and how long it would wait, without launching or sleeping
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Kill the agent if it runs longer than this (e.g. 2h)
+    #[arg(long, value_name = "DURATION", value_parser = history::parse_duration)]
+    pub exec_timeout: Option<chrono::Duration>,
+
+    /// Re-run the agent, waiting through resets, until it succeeds or --max-iterations is reached
+    #[arg(long)]
+    pub until_success: bool,
+
+    /// Give up after this many runs in --until-success mode
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_ITERATIONS, value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_iterations: u32,
+
+    /// Count a run as successful only if its output contains this text (case-insensitive, repeatable; implies --until-success)
+    #[arg(long, value_name = "TEXT")]
+    pub success_pattern: Vec<String>,
+
+    /// Exit code that counts as success (repeatable, default 0; implies --until-success)
+    #[arg(long, value_name = "CODE")]
+    pub success_exit_code: Vec<i32>,
+
+    /// Stop the agent when usage crosses this ceiling, written like an alert rule (repeatable)
+    #[arg(long, value_name = "RULE")]
+    pub budget: Vec<String>,
+
+    /// Only warn when a budget ceiling is crossed instead of stopping the agent
+    #[arg(long)]
+    pub budget_warn: bool,
+
+    #[command(subcommand)]
+    pub subcommand: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Log in to a provider and store the token in the OS keyring
+    Login {
+        /// Provider to log in to
+        provider: LoginProvider,
+        /// For claude: store a sessionKey cookie copied from a browser session (claude.ai's
+        /// email sign-in needs a browser challenge, so seher cannot sign in by itself)
+        #[arg(long)]
+        session_key: bool,
+    },
+    /// Manage provider API keys kept in the OS keyring instead of settings.json
+    Secrets {
+        #[command(subcommand)]
+        action: SecretsAction,
+    },
+    /// Show recorded usage samples
+    History {
+        /// How far back to look (e.g. 30m, 12h, 7d, 2w)
+        #[arg(long, default_value = "7d")]
+        since: String,
+        /// Only show samples for this provider
+        #[arg(long)]
+        provider: Option<String>,
+        /// Draw a utilization sparkline per window instead of listing samples
+        #[arg(long)]
+        graph: bool,
+    },
+    /// Show when seher read browser cookie databases
+    Audit {
+        /// How far back to look (e.g. 30m, 12h, 7d, 2w)
+        #[arg(long, default_value = "7d")]
+        since: String,
+        /// Only show reads for this domain (e.g. claude.ai)
+        #[arg(long)]
+        domain: Option<String>,
+    },
+    /// Print recent lifecycle events as JSON lines: limit changes, waits, runs and errors
+    Events {
+        /// How far back to look (e.g. 30m, 12h, 7d, 2w)
+        #[arg(long, default_value = "1h")]
+        since: String,
+        /// Keep printing new events as they happen, until interrupted
+        #[arg(short, long)]
+        follow: bool,
+    },
+    /// Fetch and print one provider's usage, reading only that provider's cookies
+    Usage {
+        /// Provider to query (e.g. claude, copilot, codex)
+        #[arg(long)]
+        provider: String,
+    },
+    /// Show every provider's usage windows in one table, marking the most constrained
+    Summary,
+    /// Print each configured agent's usage (the same document as `--json` with `--json`)
+    Status,
+    /// Wait until an agent is available, without launching it (honors --agent and --model)
+    Wait,
+    /// Run the prompts in the settings' `schedule` at their cron times, until interrupted
+    Daemon,
+    /// Continue a reset wait interrupted by a restart
+    Resume,
+    /// Poll usage periodically and fire the configured alert rules
+    Watch {
+        /// Time between polls (e.g. 30m, 5m)
+        #[arg(long, default_value = "5m")]
+        interval: String,
+    },
+    /// Run the prompts in a file one after another, waiting through resets
+    Run {
+        /// File with one prompt per line, or one prompt per fenced block
+        #[arg(long, value_name = "FILE")]
+        from: PathBuf,
+        /// Keep going after a prompt fails instead of stopping
+        #[arg(long)]
+        continue_on_error: bool,
+    },
+    /// Queue prompts and run them one after another, waiting through resets
+    Queue {
+        #[command(subcommand)]
+        action: QueueAction,
+    },
+    /// Replace this binary with the latest GitHub release, after checking its SHA-256
+    Upgrade {
+        /// Only report whether a newer release exists
+        #[arg(long)]
+        check: bool,
+    },
+    /// Fetch the latest release for the update hint (run in the background by seher)
+    #[command(hide = true)]
+    UpdateCheck,
+}
+
+#[derive(Subcommand)]
+pub enum QueueAction {
+    /// Add a prompt to the queue (read from stdin when omitted)
+    Add {
+        /// Prompt for the agent
+        prompt: Option<String>,
+        /// Only run agents whose name, command or provider matches this name
+        #[arg(long)]
+        agent: Option<String>,
+        /// Model level for this item
+        #[arg(long, short)]
+        model: Option<String>,
+        /// Extra arguments for the agent (after `--`)
+        #[arg(last = true)]
+        args: Vec<String>,
+    },
+    /// Show queued items and the results of finished ones
+    List,
+    /// Run queued items in order until the queue is drained
+    Run {
+        /// Run up to N items at once, each on a different provider
+        #[arg(long, short = 'p', value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        parallel: u32,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum SecretsAction {
+    /// Store a provider's API key (read from stdin) in the OS keyring
+    Set {
+        /// Provider whose key to store (e.g. openrouter, zai, anthropic)
+        provider: String,
+        /// Secret to store instead of the provider's default, e.g. `OPENAI_ADMIN_KEY`
+        #[arg(long)]
+        name: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum LoginProvider {
+    /// GitHub (Copilot quota checks) via the OAuth device-code flow
+    Github,
+    /// claude.ai, with `--session-key`: a sessionKey cookie copied from a browser on any
+    /// machine
+    Claude,
+    /// platform.openai.com, with an API key (rate-limit headroom checks)
+    Openai,
 }
 
 /// Normalized result of executing a child agent process.
@@ -75,9 +351,15 @@ enum ChildExitKind {
     /// Process exited with a non-zero status (or unknown code).
     Failure { code: Option<i32> },
     /// Process was terminated by a signal (Unix only).
-    SignalTerminated,
+    SignalTerminated { signal: Option<i32> },
     /// Process could not be spawned (IO error before execution).
     SpawnError,
+    /// The agent reported a provider rate limit in its output.
+    RateLimited,
+    /// The agent ran past `--exec-timeout` and was killed.
+    TimedOut,
+    /// The agent was stopped for crossing a `budget` ceiling.
+    BudgetExceeded,
 }
 
 impl From<std::io::Result<std::process::ExitStatus>> for ChildExitKind {
@@ -85,7 +367,9 @@ impl From<std::io::Result<std::process::ExitStatus>> for ChildExitKind {
         match result {
             Err(_) => ChildExitKind::SpawnError,
             Ok(status) if status.success() => ChildExitKind::Success,
-            Ok(status) if status.code().is_none() => ChildExitKind::SignalTerminated,
+            Ok(status) if status.code().is_none() => ChildExitKind::SignalTerminated {
+                signal: exit_signal(status),
+            },
             Ok(status) => ChildExitKind::Failure {
                 code: status.code(),
             },
@@ -93,6 +377,62 @@ impl From<std::io::Result<std::process::ExitStatus>> for ChildExitKind {
     }
 }
 
+impl From<std::io::Result<Stop>> for ChildExitKind {
+    fn from(result: std::io::Result<Stop>) -> Self {
+        match result {
+            Ok(Stop::Exited(status)) => Ok(status).into(),
+            Ok(Stop::TimedOut) => ChildExitKind::TimedOut,
+            Ok(Stop::Stopped(_)) => ChildExitKind::BudgetExceeded,
+            Err(e) => Err::<std::process::ExitStatus, _>(e).into(),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn exit_signal(status: std::process::ExitStatus) -> Option<i32> {
+    std::os::unix::process::ExitStatusExt::signal(&status)
+}
+
+#[cfg(not(unix))]
+fn exit_signal(_status: std::process::ExitStatus) -> Option<i32> {
+    None
+}
+
+/// The exit code seher reports for an agent run, mirroring the agent's own where there
+/// is one and following shell conventions otherwise (`128 + signal`, 124 for a timeout,
+/// 127 when the agent could not be started).
+fn exit_code(exit: Option<&ChildExitKind>) -> ExitCode {
+    match exit {
+        Some(ChildExitKind::Success) => ExitCode::SUCCESS,
+        Some(ChildExitKind::Failure { code: Some(code) }) => {
+            u8::try_from(*code).map_or(ExitCode::FAILURE, ExitCode::from)
+        }
+        Some(ChildExitKind::SignalTerminated {
+            signal: Some(signal),
+        }) => u8::try_from(128 + signal).map_or(ExitCode::FAILURE, ExitCode::from),
+        Some(ChildExitKind::TimedOut) => ExitCode::from(124),
+        Some(ChildExitKind::SpawnError) => ExitCode::from(127),
+        Some(
+            ChildExitKind::Failure { code: None }
+            | ChildExitKind::SignalTerminated { signal: None }
+            | ChildExitKind::RateLimited
+            | ChildExitKind::BudgetExceeded,
+        )
+        | None => ExitCode::FAILURE,
+    }
+}
+
+/// Print the error, if any, and turn the result into an exit code.
+fn report<E: std::fmt::Display>(result: Result<(), E>) -> ExitCode {
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
 /// Tri-state representing how a user-supplied prompt has been resolved from stdin.
 #[derive(Debug)]
 enum PromptState {
@@ -104,6 +444,98 @@ enum PromptState {
     Empty,
 }
 
+/// Default `--max-iterations` cap for `--until-success`.
+const DEFAULT_MAX_ITERATIONS: u32 = 10;
+
+/// Default `budget_poll_secs`.
+const DEFAULT_BUDGET_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_mins(5);
+
+/// Built-in look of the wait progress bar; `progress.template` and `progress.chars`
+/// override it.
+const DEFAULT_PROGRESS_TEMPLATE: &str = "⠿ [{bar:40.cyan/blue}] {msg}";
+const DEFAULT_PROGRESS_CHARS: &str = "█░";
+
+/// Default `progress.refresh_hz`.
+const DEFAULT_PROGRESS_HZ: u8 = 20;
+
+/// Default `progress.plain_interval_mins`.
+const DEFAULT_PLAIN_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_mins(5);
+
+/// Default cadence of `--json` progress events while waiting for a reset.
+const DEFAULT_PROGRESS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Default interval for re-polling usage while waiting for a reset.
+const DEFAULT_RECHECK_INTERVAL: std::time::Duration = std::time::Duration::from_mins(15);
+
+/// How `sleep_until_reset` behaves while waiting for a limit to reset.
+struct WaitOptions {
+    quiet: bool,
+    /// Lead time for the pre-reset reminder, if requested.
+    notify_before: Option<chrono::Duration>,
+    notifier: Notifier,
+    /// Ring the terminal bell once the wait is over.
+    bell: bool,
+    /// How often to re-poll usage during a wait; `None` disables early starts.
+    recheck_interval: Option<std::time::Duration>,
+    /// Set once the pre-reset reminder has been sent, so re-waits don't repeat it.
+    reminded: std::cell::Cell<bool>,
+    /// Under `--json`, print [`WaitEvent`]s this often instead of drawing a progress bar.
+    progress_events: Option<std::time::Duration>,
+    /// How the progress bar looks, or whether plain lines replace it.
+    progress: ProgressConfig,
+    /// `on_limited` and `on_reset` hooks run around the wait.
+    hooks: HooksConfig,
+}
+
+impl WaitOptions {
+    fn new(settings: &Settings, args: &Args) -> Self {
+        // An explicit reminder request falls back to a desktop notification.
+        let notifications = settings
+            .notifications
+            .clone()
+            .filter(NotificationConfig::has_channel)
+            .unwrap_or(NotificationConfig {
+                desktop: args.notify_before.is_some(),
+                ..NotificationConfig::default()
+            });
+        Self {
+            quiet: args.quiet,
+            notify_before: args.notify_before,
+            notifier: Notifier::new(notifications),
+            bell: args.bell || settings.bell,
+            recheck_interval: Some(
+                args.recheck_interval
+                    .and_then(|d| d.to_std().ok())
+                    .or_else(|| {
+                        settings
+                            .recheck_interval_secs
+                            .map(std::time::Duration::from_secs)
+                    })
+                    .unwrap_or(DEFAULT_RECHECK_INTERVAL),
+            )
+            .filter(|d| !d.is_zero()),
+            reminded: std::cell::Cell::new(false),
+            progress_events: args.json.then(|| {
+                args.progress_interval
+                    .and_then(|d| d.to_std().ok())
+                    .or_else(|| {
+                        settings
+                            .progress_interval_secs
+                            .map(std::time::Duration::from_secs)
+                    })
+                    .unwrap_or(DEFAULT_PROGRESS_INTERVAL)
+                    .max(seher::wait::TICK)
+            }),
+            progress: {
+                let mut progress = settings.progress.clone().unwrap_or_default();
+                progress.plain |= args.plain_progress;
+                progress
+            },
+            hooks: settings.hooks.clone().unwrap_or_default(),
+        }
+    }
+}
+
 /// Preserved invocation state that can be reused across auto-rerun attempts.
 struct InvocationInput {
     /// Raw trailing args as received from the CLI, before agent-specific mapping.
@@ -112,6 +544,12 @@ struct InvocationInput {
     pub cached_prompt: Option<String>,
     /// Prompt resolved from stdin before the first execution attempt (tri-state).
     pub stdin_prompt: PromptState,
+    /// Relaunch with the agent's `resume_args` instead of the prompt.
+    pub resume_session: bool,
+    /// Set when the last run's output matched a `--success-pattern`.
+    pub success_output: bool,
+    /// Id of the queue item being run, if any.
+    pub queue_item: Option<u64>,
 }
 
 /// Return `true` if an auto-rerun should be triggered.
@@ -141,55 +579,308 @@ fn candidate_indices_in_priority_order(
     indices.into_iter().map(|(i, _)| i).collect()
 }
 
-pub async fn run(args: Args) {
+pub async fn run(args: Args) -> ExitCode {
+    seher::warning::set_handler(|message| eprintln!("Warning: {message}"));
+    match &args.subcommand {
+        Some(Command::Login {
+            provider,
+            session_key,
+        }) => return report(login(provider, *session_key).await),
+        Some(Command::Secrets {
+            action: SecretsAction::Set { provider, name },
+        }) => return report(set_secret(provider, name.as_deref())),
+        Some(Command::History {
+            since,
+            provider,
+            graph,
+        }) => {
+            return report(print_history(since, provider.as_deref(), *graph, args.json));
+        }
+        Some(Command::Audit { since, domain }) => {
+            return report(print_audit(since, domain.as_deref(), args.json));
+        }
+        Some(Command::Events { since, follow }) => {
+            return report(print_events(since, *follow).await);
+        }
+        Some(Command::Queue { action }) if !matches!(action, QueueAction::Run { .. }) => {
+            return report(manage_queue(action, args.json));
+        }
+        Some(Command::Upgrade { check }) => return report(upgrade(*check).await),
+        Some(Command::UpdateCheck) => return report(refresh_update_check().await),
+        Some(
+            Command::Usage { .. }
+            | Command::Summary
+            | Command::Status
+            | Command::Wait
+            | Command::Daemon
+            | Command::Watch { .. }
+            | Command::Resume
+            | Command::Run { .. }
+            | Command::Queue { .. },
+        )
+        | None => {}
+    }
+
     let settings = match Settings::load(args.config.as_deref()) {
         Ok(s) => s,
         Err(e) => {
             eprintln!("Failed to load settings: {e}");
-            return;
+            return ExitCode::FAILURE;
         }
     };
 
+    if let Err(e) = install_runtime_config(&settings, &args) {
+        eprintln!("{e}");
+        return ExitCode::FAILURE;
+    }
+    check_for_update(&settings, &args);
+
+    if let Some(Command::Usage { provider }) = &args.subcommand {
+        return report(print_provider_usage(&settings, provider, &args).await);
+    }
+
+    if let Some(name) = &args.template
+        && !settings.templates.contains_key(name)
+    {
+        eprintln!("Unknown template '{name}'");
+        return ExitCode::FAILURE;
+    }
+
+    if let Err(e) = budget_rules(&settings, &args) {
+        eprintln!("Invalid budget: {e}");
+        return ExitCode::FAILURE;
+    }
+
     if args.priority {
         print_priority(&settings);
-        return;
+        return ExitCode::SUCCESS;
     }
 
     if args.gui_config {
-        if let Err(e) = seher::web::serve(settings, args.config).await {
-            eprintln!("Config editor error: {e}");
-        }
-        return;
+        return report(
+            seher::web::serve(settings, args.config)
+                .await
+                .map_err(|e| format!("Config editor error: {e}")),
+        );
     }
 
-    let detector = BrowserDetector::new();
+    let detector = browser_detector(&settings, &args);
     let browsers = detector.detect_browsers();
 
-    if browsers.is_empty() {
+    // Without a browser, agents can still run on stored sessions and CLI credentials.
+    if browsers.is_empty() && !args.quiet {
         eprintln!("No browsers found");
-        return;
     }
 
     let agents = build_agents(&settings, &detector, &browsers, &args).await;
 
     if agents.is_empty() {
         eprintln!("No agents with valid cookies found");
-        return;
+        return ExitCode::FAILURE;
     }
 
     let agents = filter_agents(agents, args.command.as_deref(), args.provider.as_deref());
 
     if agents.is_empty() {
         eprintln!("No agents match the specified filters");
-        return;
+        return ExitCode::FAILURE;
     }
 
-    if args.json {
+    if args.json && matches!(args.subcommand, None | Some(Command::Status)) {
         print_json_status(&agents).await;
+        return ExitCode::SUCCESS;
+    }
+
+    dispatch(&settings, &agents, &args).await
+}
+
+/// Run the subcommand that needs agents, or the default limit-aware launch.
+async fn dispatch(settings: &Settings, agents: &[Agent], args: &Args) -> ExitCode {
+    match &args.subcommand {
+        Some(Command::Resume) => resume_pending_wait(settings, agents, args).await,
+        Some(Command::Run {
+            from,
+            continue_on_error,
+        }) => run_task_file(settings, agents, from, *continue_on_error, args).await,
+        Some(Command::Queue {
+            action: QueueAction::Run { parallel },
+        }) if *parallel > 1 => run_queue_parallel(settings, agents, *parallel as usize, args).await,
+        Some(Command::Queue { .. }) => run_queue(settings, agents, args).await,
+        Some(Command::Summary) => {
+            print_summary(agents, args.json).await;
+            ExitCode::SUCCESS
+        }
+        Some(Command::Status) => {
+            print_status(agents).await;
+            ExitCode::SUCCESS
+        }
+        Some(Command::Wait) => wait_for_available(settings, agents, args).await,
+        Some(Command::Daemon) => run_daemon(settings, agents, args).await,
+        Some(Command::Watch { interval }) => {
+            report(watch(settings, agents, interval, args.quiet).await)
+        }
+        Some(
+            Command::Login { .. }
+            | Command::Secrets { .. }
+            | Command::History { .. }
+            | Command::Audit { .. }
+            | Command::Events { .. }
+            | Command::Usage { .. }
+            | Command::Upgrade { .. }
+            | Command::UpdateCheck,
+        )
+        | None => run_with_limit_check(settings, agents, args).await,
+    }
+}
+
+/// Install the process-wide HTTP, cache and history configuration from settings and flags.
+fn install_runtime_config(settings: &Settings, args: &Args) -> Result<(), String> {
+    // The proxy and timeout only apply to a shared client that has not been built yet,
+    // so they go in before anything else.
+    if let Some(proxy) = args.proxy.as_deref().or(settings.proxy.as_deref()) {
+        let applied =
+            seher::http::set_proxy(proxy).map_err(|e| format!("Invalid proxy {proxy}: {e}"))?;
+        if !applied {
+            seher::warning::warn(format_args!(
+                "proxy {proxy} ignored: the shared HTTP client was already configured"
+            ));
+        }
+    }
+    if let Some(secs) = args.timeout.or(settings.timeout_secs)
+        && !seher::http::set_timeout(std::time::Duration::from_secs(secs))
+    {
+        seher::warning::warn(format_args!(
+            "timeout of {secs}s ignored: the shared HTTP client was already configured"
+        ));
+    }
+    if let Some(retry) = settings.retry.clone() {
+        retry.install();
+    }
+    seher::http::set_impersonate(args.impersonate || settings.impersonate);
+    let mut base_urls = settings.base_urls.clone().unwrap_or_default();
+    for spec in &args.base_urls {
+        base_urls
+            .set_from_spec(spec)
+            .map_err(|e| format!("Invalid --base-url: {e}"))?;
+    }
+    base_urls.install();
+    if let Some(headers) = settings.browser_headers.clone() {
+        headers.install();
+    }
+    if settings.history.unwrap_or(true)
+        && let Ok(path) = history::HistoryStore::default_path()
+    {
+        history::install(path);
+    }
+    if settings.audit.unwrap_or(true)
+        && let Ok(path) = audit::default_path()
+    {
+        audit::install(path, subcommand_name(args.subcommand.as_ref()));
+    }
+    if settings.events.unwrap_or(true)
+        && let Ok(path) = events::default_path()
+    {
+        events::install(path);
+    }
+    // Always keep the last snapshot on disk so `--offline` and the automatic fallback
+    // have something to answer from; a zero TTL never serves it while online.
+    if let Some(dir) = UsageCache::default_dir() {
+        // A dump needs real responses, so never answer from the cache while writing one.
+        let ttl = if args.dump_response.is_some() {
+            0
+        } else {
+            args.cache_ttl.or(settings.cache_ttl_secs).unwrap_or(0)
+        };
+        UsageCache::new(dir, std::time::Duration::from_secs(ttl)).install();
+    }
+    seher::cache::set_offline(args.offline);
+    if let Some(path) = &args.dump_response {
+        seher::http::dump::install(path)
+            .map_err(|e| format!("Cannot write response dump {}: {e}", path.display()))?;
+    }
+    if !settings.min_fetch_interval_secs.is_empty()
+        && let Some(dir) = UsageCache::default_dir()
+    {
+        let intervals = settings
+            .min_fetch_interval_secs
+            .iter()
+            .map(|(provider, &secs)| (provider.clone(), std::time::Duration::from_secs(secs)))
+            .collect();
+        FetchThrottle::new(dir.join("throttle"), intervals).install();
+    }
+    if settings.agents.iter().any(|agent| agent.cooldown.is_some())
+        && let Some(dir) = UsageCache::default_dir()
+    {
+        Cooldowns::new(dir.join("cooldown")).install();
+    }
+    if let Some(ttl) = settings.session_cache_ttl_secs.filter(|&t| t > 0)
+        && let Some(dir) = UsageCache::default_dir()
+    {
+        match SessionCache::open(dir, std::time::Duration::from_secs(ttl)) {
+            Ok(cache) => {
+                cache.install();
+            }
+            Err(e) if !args.quiet => eprintln!("Session cache disabled: {e}"),
+            Err(_) => {}
+        }
+    }
+    if let Some(telemetry) = settings
+        .telemetry
+        .clone()
+        .or_else(TelemetryConfig::from_env)
+    {
+        seher::telemetry::install(telemetry);
+    }
+    Ok(())
+}
+
+/// Print a hint when the last daily check found a newer release, and start the next check
+/// in a detached `seher update-check` process when one is due, so this run never waits
+/// on the network and short commands don't cut the check off; its result shows on a
+/// later run.
+fn check_for_update(settings: &Settings, args: &Args) {
+    if args.quiet || args.json || args.offline || !settings.update_check.unwrap_or(true) {
+        return;
+    }
+    let Some(dir) = UsageCache::default_dir() else {
         return;
+    };
+    let check = seher::upgrade::UpdateCheck::new(dir.join("update-check.json"));
+    if let Some(hint) = check.hint() {
+        eprintln!("{hint}");
+    }
+    if !check.claim(Utc::now()) {
+        return;
+    }
+    let spawned = std::env::current_exe().and_then(|exe| {
+        std::process::Command::new(exe)
+            .arg("update-check")
+            .stdin(std::process::Stdio::null())
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+    });
+    if let Ok(mut child) = spawned {
+        // Reap the child if it finishes while this run is still going.
+        std::thread::spawn(move || child.wait());
     }
+}
+
+/// `seher update-check`: fetch the latest release for the update hint.
+async fn refresh_update_check() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = UsageCache::default_dir().ok_or("no cache directory")?;
+    seher::upgrade::UpdateCheck::new(dir.join("update-check.json"))
+        .refresh()
+        .await?;
+    Ok(())
+}
 
-    run_with_limit_check(&settings, agents, &args).await;
+/// `true` if the agent's name, command or provider is `name`, as chosen by `--agent` or
+/// prompt front-matter.
+fn matches_name(agent: &Agent, name: &str) -> bool {
+    agent.config.name.as_deref() == Some(name)
+        || agent.command() == name
+        || agent.config.resolve_provider() == Some(name)
 }
 
 fn filter_agents(
@@ -212,152 +903,772 @@ async fn build_agents(
     browsers: &[BrowserType],
     args: &Args,
 ) -> Vec<Agent> {
+    let first = args.first_valid_session || settings.first_valid_session.unwrap_or(false);
     let mut agents: Vec<Agent> = Vec::new();
     for config in &settings.agents {
-        let domain = config.resolve_domain();
-        let cookies = match domain {
-            Some(d) => {
-                if let Some(c) = get_cookies_for_domain(
-                    detector,
-                    browsers,
-                    args.browser.as_ref(),
-                    args.profile.as_ref(),
-                    d,
-                )
-                .await
-                {
-                    c
-                } else {
-                    if !args.quiet {
-                        eprintln!("No cookies found for {} (domain: {d})", config.command);
-                    }
-                    continue;
-                }
+        if config.accounts.is_empty() {
+            let browser = args.browser;
+            let profile = args.profile.as_ref();
+            if let Some(cookies) = agent_cookies(
+                config, detector, browsers, browser, profile, first, args.quiet,
+            )
+            .await
+            {
+                agents.push(Agent::new(config.clone(), cookies).with_fallback_profiles(
+                    fallback_profiles(detector, browsers, browser, profile),
+                ));
             }
-            None => vec![],
-        };
-        agents.push(Agent::new(config.clone(), cookies));
+            continue;
+        }
+        for account in &config.accounts {
+            let browser = account.browser.or(args.browser);
+            let profile = account.profile.as_ref().or(args.profile.as_ref());
+            let account_config = config.for_account(account);
+            if let Some(cookies) = agent_cookies(
+                &account_config,
+                detector,
+                browsers,
+                browser,
+                profile,
+                first,
+                args.quiet,
+            )
+            .await
+            {
+                agents.push(
+                    Agent::new(account_config, cookies)
+                        .with_account(&account.name)
+                        .with_fallback_profiles(fallback_profiles(
+                            detector, browsers, browser, profile,
+                        )),
+                );
+            }
+        }
     }
     agents
 }
 
-async fn print_json_status(agents: &[Agent]) {
-    let mut statuses: Vec<AgentStatus> = Vec::new();
-    for agent in agents {
-        match agent.fetch_status().await {
-            Ok(status) => statuses.push(status),
-            Err(e) => eprintln!("Failed to fetch status for {}: {e}", agent.command()),
-        }
-    }
-    match serde_json::to_string_pretty(&statuses) {
-        Ok(json) => println!("{json}"),
-        Err(e) => eprintln!("Failed to serialize status: {e}"),
+/// The detector for `--user-data-dir` (or `user_data_dir`), else for the installed browsers,
+/// reading Firefox cookies from `--container` when given.
+fn browser_detector(settings: &Settings, args: &Args) -> BrowserDetector {
+    let detector = match args
+        .user_data_dir
+        .as_ref()
+        .or(settings.user_data_dir.as_ref())
+    {
+        Some(dir) => BrowserDetector::for_user_data_dir(dir.clone(), args.browser),
+        None => BrowserDetector::new(),
+    };
+    match &args.container {
+        Some(container) => detector.with_container(container),
+        None => detector,
     }
 }
 
-#[derive(Debug, PartialEq)]
-enum ScanOutcome {
-    Available {
-        index: usize,
-    },
-    AllLimited {
-        limited: Vec<(usize, Option<DateTime<Utc>>)>,
-    },
+/// Profiles an agent falls through to when the provider rejects the session it started
+/// with: the same candidates its cookies were picked from.
+fn fallback_profiles(
+    detector: &BrowserDetector,
+    browsers: &[BrowserType],
+    browser: Option<BrowserType>,
+    profile: Option<&String>,
+) -> Vec<seher::Profile> {
+    session::candidate_profiles(detector, browsers, browser, profile.map(String::as_str))
 }
 
-#[expect(clippy::needless_lifetimes)]
-async fn scan_candidates<'a, F>(
-    _agents: &'a [Agent],
-    candidates: Vec<usize>,
-    mut check_fn: F,
-) -> ScanOutcome
-where
-    F: FnMut(
-        usize,
+/// Cookies for `config`'s provider domain, or `None` (after explaining why) if the agent
+/// has to be skipped.
+async fn agent_cookies(
+    config: &AgentConfig,
+    detector: &BrowserDetector,
+    browsers: &[BrowserType],
+    browser: Option<BrowserType>,
+    profile: Option<&String>,
+    first_valid_session: bool,
+    quiet: bool,
+) -> Option<Vec<seher::Cookie>> {
+    let Some(domain) = config.resolve_domain() else {
+        return Some(vec![]);
+    };
+    let scope = format!(
+        "{}/{}{}",
+        browser.as_ref().map_or("*", BrowserType::name),
+        profile.map_or("*", String::as_str),
+        detector
+            .container()
+            .map_or_else(String::new, |container| format!("#{container}"))
+    );
+    let cache = SessionCache::current();
+    if let Some(cookies) = cache.and_then(|c| c.get(domain, &scope)) {
+        return Some(cookies);
+    }
+    if let Some(cookies) = get_cookies_for_domain(
+        detector,
+        browsers,
+        browser,
+        profile,
+        domain,
+        first_valid_session,
     )
-        -> Pin<Box<dyn Future<Output = Result<AgentLimit, Box<dyn std::error::Error>>> + 'a>>,
-{
-    let mut limited: Vec<(usize, Option<DateTime<Utc>>)> = Vec::new();
-    for idx in candidates {
-        match check_fn(idx).await {
-            Ok(AgentLimit::NotLimited) => return ScanOutcome::Available { index: idx },
-            Ok(AgentLimit::Limited { reset_time }) => limited.push((idx, reset_time)),
-            Err(_) => {}
+    .await
+    {
+        if let Some(cache) = cache {
+            cache.put(domain, &scope, &cookies);
+        }
+        Some(cookies)
+    } else if let Some(cookies) = session::stored_session(domain) {
+        Some(cookies)
+    } else if has_cookieless_credentials(config) {
+        Some(vec![])
+    } else {
+        if !quiet {
+            eprintln!("No cookies found for {} (domain: {domain})", config.command);
         }
+        None
     }
-    ScanOutcome::AllLimited { limited }
 }
 
-async fn run_with_limit_check(settings: &Settings, agents: Vec<Agent>, args: &Args) {
-    let model = args.model.as_deref();
-    let now = Local::now();
+/// Run the GitHub device-code flow and store the resulting token for Copilot checks.
+async fn login_github() -> Result<(), Box<dyn std::error::Error>> {
+    let client_id = std::env::var("SEHER_GITHUB_CLIENT_ID")
+        .unwrap_or_else(|_| GithubDeviceFlow::DEFAULT_CLIENT_ID.to_string());
+    let code = GithubDeviceFlow::request_code(&client_id).await?;
+
+    eprintln!(
+        "Open {} and enter code: {}",
+        code.verification_uri, code.user_code
+    );
+    if open::that(&code.verification_uri).is_err() {
+        eprintln!("(could not open a browser; visit the URL manually)");
+    }
 
-    let candidates = candidate_indices_in_priority_order(settings, &agents, model, &now);
+    let token = GithubDeviceFlow::poll_token(&client_id, &code).await?;
+    if Keyring::set(keyring::GITHUB_ACCOUNT, &token)? {
+        eprintln!("Logged in to GitHub; token stored in the OS keyring");
+    } else {
+        eprintln!(
+            "Logged in to GitHub; token stored in {}",
+            Keyring::fallback_path()?.display()
+        );
+    }
+    Ok(())
+}
 
-    if candidates.is_empty() {
-        if let Some(model_key) = model {
-            eprintln!("No agents found with model '{model_key}'");
-        } else {
-            eprintln!("No available agents");
-        }
-        return;
+/// `seher login`: store credentials for `provider`.
+async fn login(
+    provider: &LoginProvider,
+    session_key: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match provider {
+        LoginProvider::Github => login_github().await,
+        LoginProvider::Claude => login_claude(session_key).await,
+        LoginProvider::Openai => login_openai().await,
     }
+}
 
-    let stdin_prompt = {
-        use std::io::{IsTerminal, Read};
-        if std::io::stdin().is_terminal() {
-            PromptState::Unresolved
-        } else {
-            let mut content = String::new();
-            if let Err(e) = std::io::stdin().read_to_string(&mut content)
-                && !args.quiet
+/// Ask for a claude.ai session key copied from a browser, check it against the API and
+/// store it for agents that find no browser session, e.g. on servers and in containers.
+///
+/// claude.ai's own sign-in needs a browser challenge, so the key has to come from a
+/// browser session on some machine; `--session-key` makes that explicit.
+async fn login_claude(session_key: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if !session_key {
+        return Err(
+            "claude.ai sign-in needs a browser; sign in on any machine, copy the \
+                    sessionKey cookie from the developer tools and run \
+                    `seher login claude --session-key`"
+                .into(),
+        );
+    }
+    eprintln!(
+        "Sign in to https://claude.ai in a browser on any machine, open the developer tools \
+         and copy the value of the sessionKey cookie."
+    );
+    let key = read_secret("sessionKey (input hidden): ")?;
+    let key = key.as_str();
+    if key.is_empty() {
+        return Err("no session key given".into());
+    }
+
+    let cookies = [session::session_key_cookie(key)];
+    let account = ClaudeClient::fetch_account(&cookies)
+        .await
+        .map_err(|e| format!("the session key was rejected: {e}"))?;
+    let who = account.identity().unwrap_or("claude.ai");
+    if Keyring::set(keyring::CLAUDE_ACCOUNT, key)? {
+        eprintln!("Logged in as {who}; session stored in the OS keyring");
+    } else {
+        eprintln!(
+            "Logged in as {who}; session stored in {}",
+            Keyring::fallback_path()?.display()
+        );
+    }
+    Ok(())
+}
+
+/// Prompt for a secret and read it without echoing it.
+fn read_secret(prompt: &str) -> std::io::Result<String> {
+    use std::io::{IsTerminal, Write};
+    eprint!("{prompt}");
+    std::io::stderr().flush()?;
+    let secret = seher::secrets::read_hidden()?;
+    if std::io::stdin().is_terminal() {
+        // The Enter key was not echoed either.
+        eprintln!();
+    }
+    Ok(secret)
+}
+
+/// Store an API key for the `openai` provider after checking it against the API.
+async fn login_openai() -> Result<(), Box<dyn std::error::Error>> {
+    eprintln!("Create an API key at https://platform.openai.com/api-keys and paste it here.");
+    let key = read_secret("API key (input hidden): ")?;
+    let key = key.as_str();
+    if key.is_empty() {
+        return Err("no API key given".into());
+    }
+
+    seher::openai::OpenAiClient::fetch_rate_limits(key)
+        .await
+        .map_err(|e| format!("the API key was rejected: {e}"))?;
+    report_secret_stored(
+        "OPENAI_API_KEY",
+        seher::secrets::set("OPENAI_API_KEY", key)?,
+    )
+}
+
+/// Store the API key of `provider` (or the secret `name`) read from stdin.
+fn set_secret(provider: &str, name: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    let name = match name {
+        Some(name) => name,
+        None => seher::secrets::secret_name(provider).ok_or_else(|| {
+            let known: Vec<&str> = seher::secrets::PROVIDER_SECRETS
+                .iter()
+                .map(|(p, _)| *p)
+                .collect();
+            format!(
+                "{provider} does not use an API key (known: {}); pass --name to store one anyway",
+                known.join(", ")
+            )
+        })?,
+    };
+    eprint!("{name}: ");
+    let mut value = String::new();
+    std::io::stdin().read_line(&mut value)?;
+    let value = value.trim();
+    if value.is_empty() {
+        return Err("no value given".into());
+    }
+    report_secret_stored(name, seher::secrets::set(name, value)?)
+}
+
+fn report_secret_stored(name: &str, in_keyring: bool) -> Result<(), Box<dyn std::error::Error>> {
+    if in_keyring {
+        eprintln!("{name} stored in the OS keyring");
+    } else {
+        eprintln!("{name} stored in {}", Keyring::fallback_path()?.display());
+    }
+    Ok(())
+}
+
+/// Install the latest release over this binary, or with `check` only report it.
+async fn upgrade(check: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let release = seher::upgrade::latest_release().await?;
+    if !release.is_newer() {
+        eprintln!(
+            "seher {} is up to date (latest: {})",
+            seher::upgrade::CURRENT_VERSION,
+            release.version()
+        );
+        return Ok(());
+    }
+    if check {
+        println!(
+            "seher {} is available (installed: {}): {}",
+            release.version(),
+            seher::upgrade::CURRENT_VERSION,
+            release.html_url
+        );
+        return Ok(());
+    }
+
+    eprintln!("Downloading seher {}...", release.version());
+    let exe = seher::upgrade::install(&release).await?;
+    eprintln!(
+        "Upgraded {} from {} to {}",
+        exe.display(),
+        seher::upgrade::CURRENT_VERSION,
+        release.version()
+    );
+    Ok(())
+}
+
+/// Returns `true` if the agent's provider can be queried without browser cookies.
+fn has_cookieless_credentials(config: &AgentConfig) -> bool {
+    match config.resolve_provider() {
+        Some("codex") => Agent::new(config.clone(), vec![])
+            .codex_credentials()
+            .is_ok(),
+        Some("copilot") => Agent::new(config.clone(), vec![]).github_token().is_ok(),
+        Some("anthropic") => Agent::new(config.clone(), vec![])
+            .anthropic_admin_key()
+            .is_some(),
+        _ => false,
+    }
+}
+
+async fn print_json_status(agents: &[Agent]) {
+    let mut statuses: Vec<AgentStatus> = Vec::new();
+    for (agent, result) in fetch_statuses(agents).await {
+        match result {
+            Ok(status) => statuses.push(status),
+            Err(e) => eprintln!("Failed to fetch status for {}: {e}", agent.command()),
+        }
+    }
+    match serde_json::to_string_pretty(&StatusOutput::new(&statuses)) {
+        Ok(json) => println!("{json}"),
+        Err(e) => eprintln!("Failed to serialize status: {e}"),
+    }
+}
+
+/// Print every agent's usage, one block per agent as in `seher usage`.
+async fn print_status(agents: &[Agent]) {
+    for (agent, result) in fetch_statuses(agents).await {
+        match result {
+            Ok(status) => print!("{}", format_usage(&status)),
+            Err(e) => eprintln!("Failed to fetch status for {}: {e}", agent.command()),
+        }
+    }
+}
+
+/// Print every agent's usage windows as one table, or as a [`SummaryOutput`] with `json`.
+async fn print_summary(agents: &[Agent], json: bool) {
+    let mut statuses: Vec<AgentStatus> = Vec::new();
+    for (agent, result) in fetch_statuses(agents).await {
+        match result {
+            Ok(status) => statuses.push(status),
+            Err(e) => eprintln!("Failed to fetch status for {}: {e}", agent.command()),
+        }
+    }
+    let summary = Summary::new(&statuses);
+    if !json {
+        print!("{}", format_summary(&summary));
+        return;
+    }
+    match serde_json::to_string_pretty(&SummaryOutput::new(&summary)) {
+        Ok(json) => println!("{json}"),
+        Err(e) => eprintln!("Failed to serialize summary: {e}"),
+    }
+}
+
+/// [`Summary`] as an aligned table, with the most constrained row marked `*`.
+fn format_summary(summary: &Summary) -> String {
+    use std::fmt::Write;
+    if summary.rows.is_empty() {
+        return "No usage to show\n".to_string();
+    }
+    let mut out = format!(
+        "  {:<12} {:<22} {:>6}  {:<23}  {}\n",
+        "PROVIDER", "WINDOW", "USED", "RESETS", "LIMITED"
+    );
+    for (i, row) in summary.rows.iter().enumerate() {
+        let marker = if summary.most_constrained == Some(i) {
+            '*'
+        } else {
+            ' '
+        };
+        let resets = row.resets_at.map_or_else(
+            || "-".to_string(),
+            |t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+        );
+        let _ = writeln!(
+            out,
+            "{marker} {:<12} {:<22} {:>5.1}%  {resets:<23}  {}",
+            row.provider,
+            row.window,
+            row.utilization,
+            if row.limited { "yes" } else { "no" }
+        );
+    }
+    out
+}
+
+/// Fetch and print `provider`'s usage without building the configured agents, so no other
+/// provider's cookies are read. A settings entry for the provider supplies its keys, if any.
+async fn print_provider_usage(
+    settings: &Settings,
+    provider: &str,
+    args: &Args,
+) -> Result<(), String> {
+    let config = settings
+        .agents
+        .iter()
+        .find(|a| a.resolve_provider() == Some(provider))
+        .cloned()
+        .unwrap_or_else(|| AgentConfig::for_provider(provider));
+    let detector = browser_detector(settings, args);
+    let browsers = detector.detect_browsers();
+    let first = args.first_valid_session || settings.first_valid_session.unwrap_or(false);
+    let cookies = agent_cookies(
+        &config,
+        &detector,
+        &browsers,
+        args.browser,
+        args.profile.as_ref(),
+        first,
+        args.quiet,
+    )
+    .await
+    .ok_or_else(|| format!("No session found for {provider}"))?;
+    let status = Agent::new(config, cookies)
+        .fetch_status()
+        .await
+        .map_err(|e| format!("Failed to fetch usage for {provider}: {e}"))?;
+    if args.json {
+        let json = serde_json::to_string_pretty(&StatusOutput::new(std::slice::from_ref(&status)))
+            .map_err(|e| format!("Failed to serialize status: {e}"))?;
+        println!("{json}");
+    } else {
+        print!("{}", format_usage(&status));
+    }
+    Ok(())
+}
+
+/// One line per usage window of `status`, under a heading with the provider and plan.
+fn format_usage(status: &AgentStatus) -> String {
+    use std::fmt::Write;
+    let provider = status.provider.as_deref().unwrap_or(&status.command);
+    let mut out = match &status.plan {
+        Some(plan) => format!("{provider} ({plan})"),
+        None => provider.to_string(),
+    };
+    if let Some(identity) = &status.identity {
+        let _ = write!(out, " as {identity}");
+    }
+    out.push('\n');
+    for entry in &status.usage {
+        let _ = write!(out, "  {}: {:.1}%", entry.entry_type, entry.utilization);
+        if entry.limited {
+            out.push_str(" (limited)");
+        }
+        if let Some(reset) = entry.resets_at {
+            let _ = write!(out, ", resets {}", reset.format("%Y-%m-%d %H:%M:%S UTC"));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Poll every agent's usage each `interval`, printing it and firing alert rules.
+async fn watch(
+    settings: &Settings,
+    agents: &[Agent],
+    interval: &str,
+    quiet: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let interval = history::parse_duration(interval)?.to_std()?;
+    let rules = settings
+        .alerts
+        .iter()
+        .map(|rule| rule.parse::<AlertRule>())
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut tracker = AlertTracker::new(
+        rules,
+        settings
+            .alert_hysteresis
+            .unwrap_or(alert::DEFAULT_HYSTERESIS),
+    );
+    let notifier = Notifier::new(settings.notifications.clone().unwrap_or_default());
+    let mut limits = LimitTracker::new();
+
+    loop {
+        let now = Local::now().format("%H:%M");
+        let mut failures = Vec::new();
+        let mut gauges = Vec::new();
+        for (agent, result) in fetch_statuses(agents).await {
+            let status = match result {
+                Ok(status) => status,
+                Err(e) => {
+                    eprintln!(
+                        "[{now}] Failed to fetch status for {}: {e}",
+                        agent.command()
+                    );
+                    failures.push(format!("{}: {e}", agent.label()));
+                    events::publish(&Event::error(Some(agent.label()), &e));
+                    continue;
+                }
+            };
+            if !quiet {
+                let who = status
+                    .identity
+                    .as_ref()
+                    .map_or_else(String::new, |identity| format!(" ({identity})"));
+                for entry in &status.usage {
+                    println!(
+                        "[{now}] {}{who} {}: {:.1}%",
+                        status.command, entry.entry_type, entry.utilization
+                    );
+                }
+            }
+            for entry in &status.usage {
+                if let Some(event) = limits.observe(
+                    &agent.label(),
+                    status.provider.as_deref(),
+                    &entry.entry_type,
+                    entry.limited,
+                    entry.resets_at,
+                ) {
+                    events::publish(&event);
+                }
+            }
+            for fired in
+                tracker.evaluate(&status.command, status.provider.as_deref(), &status.usage)
             {
-                eprintln!("Failed to read stdin: {e}");
+                println!("[{now}] ALERT: {fired}");
+                notifier.notify("seher alert", &fired.to_string()).await;
             }
-            match parse_stdin_content(&content) {
-                Some(s) => PromptState::Resolved(s),
-                None => PromptState::Empty,
+            if let Some(statsd) = &settings.statsd {
+                let provider = status.provider.as_deref().unwrap_or(&status.command);
+                for entry in &status.usage {
+                    gauges.extend(statsd.gauges(
+                        provider,
+                        status.identity.as_deref(),
+                        &entry.entry_type,
+                        entry.utilization,
+                        entry.limited,
+                    ));
+                }
             }
         }
-    };
+        if let Some(statsd) = &settings.statsd {
+            statsd.send(&gauges).await;
+        }
+        if let Some(heartbeat) = &settings.heartbeat {
+            heartbeat
+                .ping(failures.is_empty(), &failures.join("\n"))
+                .await;
+        }
+        seher::telemetry::flush().await;
+        tokio::time::sleep(interval).await;
+    }
+}
 
-    let mut input = InvocationInput {
-        raw_agent_args: args.extra.clone(),
-        cached_prompt: None,
-        stdin_prompt,
+#[derive(Debug, PartialEq)]
+enum ScanOutcome {
+    Available {
+        index: usize,
+    },
+    AllLimited {
+        limited: Vec<(usize, Option<DateTime<Utc>>)>,
+    },
+}
+
+/// How many usage requests seher has in flight at once.
+const MAX_CONCURRENT_CHECKS: usize = 8;
+
+/// Check `candidates` concurrently and return the first available one in priority order,
+/// or every limited candidate if none is available. Checks that fail are reported and
+/// skipped.
+#[expect(clippy::needless_lifetimes)]
+async fn scan_candidates<'a, F>(
+    agents: &'a [Agent],
+    candidates: Vec<usize>,
+    mut check_fn: F,
+) -> ScanOutcome
+where
+    F: FnMut(usize) -> Pin<Box<dyn Future<Output = Result<AgentLimit, SeherError>> + 'a>>,
+{
+    let mut checks = stream::iter(candidates)
+        .map(|idx| {
+            let check = check_fn(idx);
+            async move { (idx, check.await) }
+        })
+        .buffered(MAX_CONCURRENT_CHECKS);
+    let mut limited: Vec<(usize, Option<DateTime<Utc>>)> = Vec::new();
+    while let Some((idx, result)) = checks.next().await {
+        match result {
+            Ok(AgentLimit::NotLimited) => return ScanOutcome::Available { index: idx },
+            Ok(AgentLimit::Limited { reset_time }) => limited.push((idx, reset_time)),
+            Err(e) => {
+                eprintln!("Failed to check limit for {}: {e}", agents[idx].label());
+                events::publish(&Event::error(Some(agents[idx].label()), &e));
+            }
+        }
+    }
+    ScanOutcome::AllLimited { limited }
+}
+
+/// Fetch every agent's status concurrently, in the order of `agents`.
+async fn fetch_statuses(agents: &[Agent]) -> Vec<(&Agent, Result<AgentStatus, SeherError>)> {
+    stream::iter(agents)
+        .map(|agent| async move { (agent, Box::pin(agent.fetch_status()).await) })
+        .buffered(MAX_CONCURRENT_CHECKS)
+        .collect()
+        .await
+}
+
+async fn run_with_limit_check(settings: &Settings, agents: &[Agent], args: &Args) -> ExitCode {
+    let mut input = invocation_input(settings, args);
+    let front = match compose_prompt(&mut input, args.quiet, args.dry_run) {
+        Ok(front) => front,
+        Err(e) => {
+            eprintln!("{e}");
+            return ExitCode::FAILURE;
+        }
     };
+    let model = front.model.as_deref().or(args.model.as_deref());
+    let exit = launch_until(
+        settings,
+        agents,
+        args.agent.as_deref().or(front.agent.as_deref()),
+        &mut input,
+        model,
+        args,
+        None,
+    )
+    .await;
+    exit_code(exit.as_ref())
+}
 
-    let quiet = args.quiet;
-    let agents_slice = &agents[..];
-    let outcome = scan_candidates(agents_slice, candidates, |idx| {
-        if !quiet {
-            println!(
-                "Checking limit for {}...",
-                format_agent_identity(&agents_slice[idx].config)
+/// Run [`launch`] repeatedly in `--until-success` mode until [`run_succeeded`] holds or
+/// `--max-iterations` runs have been made; otherwise run it once.
+///
+/// `scanned` is a scan the caller already made, used by the first run instead of scanning
+/// again.
+///
+/// Returns the exit of the last run.
+async fn launch_until(
+    settings: &Settings,
+    agents: &[Agent],
+    only: Option<&str>,
+    input: &mut InvocationInput,
+    model: Option<&str>,
+    args: &Args,
+    mut scanned: Option<Scan>,
+) -> Option<ChildExitKind> {
+    if !loop_mode(args) {
+        return Box::pin(launch(settings, agents, only, input, model, args, scanned)).await;
+    }
+    let max = args.max_iterations;
+    let mut exit = None;
+    for iteration in 1..=max {
+        input.success_output = false;
+        exit = Box::pin(launch(
+            settings,
+            agents,
+            only,
+            input,
+            model,
+            args,
+            scanned.take(),
+        ))
+        .await;
+        let Some(kind) = &exit else {
+            return None;
+        };
+        if run_succeeded(kind, input.success_output, args) {
+            if !args.quiet && iteration > 1 {
+                println!("Success condition met after {iteration} runs");
+            }
+            return Some(ChildExitKind::Success);
+        }
+        if halts_batch(kind) {
+            return exit;
+        }
+        if !args.quiet {
+            eprintln!(
+                "Run {iteration}/{max} {}; success condition not met",
+                describe_exit(kind)
             );
         }
-        Box::pin(agents_slice[idx].check_limit())
-    })
-    .await;
+    }
+    eprintln!("Giving up after {max} runs without meeting the success condition");
+    match exit {
+        Some(ChildExitKind::Success) => Some(ChildExitKind::Failure { code: None }),
+        other => other,
+    }
+}
+
+/// `true` if `--until-success` or one of the flags implying it was given.
+fn loop_mode(args: &Args) -> bool {
+    !args.dry_run
+        && (args.until_success
+            || !args.success_pattern.is_empty()
+            || !args.success_exit_code.is_empty())
+}
+
+/// Whether a run meets the `--until-success` condition: its exit code is one of
+/// `--success-exit-code` (default 0) and, if `--success-pattern` was given, its output
+/// contained one of the patterns.
+fn run_succeeded(kind: &ChildExitKind, success_output: bool, args: &Args) -> bool {
+    let code = match kind {
+        ChildExitKind::Success => 0,
+        ChildExitKind::Failure { code: Some(code) } => *code,
+        _ => return false,
+    };
+    let code_ok = if args.success_exit_code.is_empty() {
+        code == 0
+    } else {
+        args.success_exit_code.contains(&code)
+    };
+    code_ok && (args.success_pattern.is_empty() || success_output)
+}
+
+/// Run the best available agent for `input`, waiting for a reset when every candidate is
+/// limited. `only` restricts candidates by command or provider name; `scanned` is used
+/// instead of scanning them when given.
+///
+/// Returns the final exit of the agent, or `None` if no agent was run.
+async fn launch(
+    settings: &Settings,
+    agents: &[Agent],
+    only: Option<&str>,
+    input: &mut InvocationInput,
+    model: Option<&str>,
+    args: &Args,
+    scanned: Option<Scan>,
+) -> Option<ChildExitKind> {
+    let quiet = args.quiet;
+    let bell = args.bell || settings.bell;
+    let (candidates, outcome) = match scanned {
+        Some(scan) => scan,
+        None => scan_in_priority_order(settings, agents, only, model, quiet).await?,
+    };
 
     match outcome {
         ScanOutcome::Available { index } => {
             if !quiet {
+                println!("Agent {} is available (not limited)", agents[index].label());
+            }
+            if args.dry_run {
                 println!(
-                    "Agent {} is available (not limited)",
-                    agents[index].command()
+                    "Would run {}: {}",
+                    format_agent(&agents[index]),
+                    planned_command(&agents[index], input, model)
                 );
+                return Some(ChildExitKind::Success);
+            }
+            let exit =
+                execute_with_failover(settings, agents, &candidates, index, input, model, args)
+                    .await;
+            if bell {
+                ring_bell();
             }
-            execute_with_auto_rerun(&agents, index, &mut input, model, quiet);
+            Some(exit)
         }
         ScanOutcome::AllLimited { limited } => {
             if !limited.is_empty() {
-                let earliest = limited
-                    .iter()
-                    .filter_map(|(i, rt)| rt.map(|t| (*i, t)))
-                    .min_by_key(|(_, t)| *t);
-
-                if let Some((idx, rt)) = earliest {
+                if let Some((idx, rt)) = earliest_reset(&limited) {
+                    if args.dry_run {
+                        println!(
+                            "Would wait until {} ({} seconds) for {}, then run: {}",
+                            rt.format("%Y-%m-%d %H:%M:%S UTC"),
+                            (rt - Utc::now()).num_seconds().max(0),
+                            format_agent(&agents[idx]),
+                            planned_command(&agents[idx], input, model)
+                        );
+                        return Some(ChildExitKind::Success);
+                    }
                     if !quiet {
                         println!(
                             "All agents limited. Waiting for {} ({} seconds)...",
@@ -365,113 +1676,918 @@ async fn run_with_limit_check(settings: &Settings, agents: Vec<Agent>, args: &Ar
                             (rt - Utc::now()).num_seconds()
                         );
                     }
-                    sleep_until_reset(rt, quiet).await;
-                    execute_with_auto_rerun(&agents, idx, &mut input, model, quiet);
-                    return;
+                    return wait_then_execute(settings, agents, idx, rt, input, model, args).await;
                 }
                 if !quiet {
                     println!("All agents limited, no reset time available");
                 }
-                return;
+                return None;
             }
             eprintln!("No available agents");
+            None
         }
     }
 }
 
-fn collect_candidate_profiles_with<GetProfile, ListProfiles>(
-    browsers: &[BrowserType],
-    browser_arg: Option<&str>,
-    profile_arg: Option<&str>,
-    mut get_profile: GetProfile,
-    mut list_profiles: ListProfiles,
-) -> Vec<seher::Profile>
-where
-    GetProfile: FnMut(BrowserType, &str) -> Option<seher::Profile>,
-    ListProfiles: FnMut(BrowserType) -> Vec<seher::Profile>,
-{
-    if let Some(browser_name) = browser_arg {
-        let Ok(browser_type) = BrowserType::from_str(browser_name) else {
-            return Vec::new();
-        };
+/// Check the candidates for `model` (narrowed to `only`) in priority order until one is
+/// available. Returns `None`, after saying why, when no agent is a candidate.
+/// The candidates in priority order and the outcome of scanning them.
+type Scan = (Vec<usize>, ScanOutcome);
 
-        if !browsers.contains(&browser_type) {
-            return Vec::new();
-        }
+async fn scan_in_priority_order(
+    settings: &Settings,
+    agents: &[Agent],
+    only: Option<&str>,
+    model: Option<&str>,
+    quiet: bool,
+) -> Option<Scan> {
+    let now = Local::now();
+    let mut candidates = candidate_indices_in_priority_order(settings, agents, model, &now);
+    if let Some(name) = only {
+        candidates.retain(|&i| matches_name(&agents[i], name));
+    }
 
-        return match profile_arg {
-            Some(profile_name) => get_profile(browser_type, profile_name)
-                .into_iter()
-                .collect(),
-            None => list_profiles(browser_type),
-        };
+    if candidates.is_empty() {
+        match (only, model) {
+            (Some(name), _) => eprintln!("No available agents match '{name}'"),
+            (None, Some(model_key)) => eprintln!("No agents found with model '{model_key}'"),
+            (None, None) => eprintln!("No available agents"),
+        }
+        return None;
     }
 
-    let mut profiles = Vec::new();
-    for browser in browsers {
-        if !browser.is_chromium_based() {
-            continue;
+    let outcome = scan_candidates(agents, candidates.clone(), |idx| {
+        if !quiet {
+            println!("Checking limit for {}...", format_agent(&agents[idx]));
         }
+        Box::pin(check_limit_after_cooldown(&agents[idx], quiet))
+    })
+    .await;
+    Some((candidates, outcome))
+}
 
-        match profile_arg {
-            Some(profile_name) => {
-                if let Some(profile) = get_profile(*browser, profile_name) {
-                    profiles.push(profile);
-                }
-            }
-            None => profiles.extend(list_profiles(*browser)),
+/// The limit of `agent`, which counts as limited until its `cooldown` since the last run
+/// on its provider slot has passed.
+async fn check_limit_after_cooldown(agent: &Agent, quiet: bool) -> Result<AgentLimit, SeherError> {
+    if let Some(until) = cooldown_until(agent, Utc::now()) {
+        if !quiet {
+            println!(
+                "{} is cooling down until {}",
+                format_agent(agent),
+                until.with_timezone(&Local).format("%H:%M")
+            );
         }
+        return Ok(AgentLimit::Limited {
+            reset_time: Some(until),
+        });
     }
-
-    profiles
+    agent.check_limit().await
 }
 
-fn collect_candidate_profiles(
-    detector: &BrowserDetector,
-    browsers: &[BrowserType],
-    browser_arg: Option<&String>,
-    profile_arg: Option<&String>,
-) -> Vec<seher::Profile> {
-    collect_candidate_profiles_with(
-        browsers,
-        browser_arg.map(String::as_str),
-        profile_arg.map(String::as_str),
-        |browser_type, profile_name| detector.get_profile(browser_type, Some(profile_name)),
-        |browser_type| detector.list_profiles(browser_type),
-    )
+/// When `agent` may run again after its `cooldown`, or `None` if it may run at `now`.
+fn cooldown_until(agent: &Agent, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let cooldown = agent.config.cooldown().ok().flatten()?;
+    Cooldowns::current()?.until(&provider_slot(agent), cooldown, now)
 }
 
-fn collect_cookie_candidates(
-    detector: &BrowserDetector,
-    browsers: &[BrowserType],
-    browser_arg: Option<&String>,
-    profile_arg: Option<&String>,
-    domain: &str,
-) -> Vec<Vec<seher::Cookie>> {
-    collect_candidate_profiles(detector, browsers, browser_arg, profile_arg)
-        .into_iter()
-        .filter_map(|profile| CookieReader::read_cookies(&profile, domain).ok())
-        .collect()
+/// The limited agent that resets first, with its reset time.
+fn earliest_reset(limited: &[(usize, Option<DateTime<Utc>>)]) -> Option<(usize, DateTime<Utc>)> {
+    limited
+        .iter()
+        .filter_map(|(i, rt)| rt.map(|t| (*i, t)))
+        .min_by_key(|(_, t)| *t)
 }
 
-fn has_valid_session_cookie(domain: &str, cookie: &seher::Cookie) -> bool {
-    has_session_cookie(domain, cookie) && !cookie.is_expired()
+/// `seher wait`: return once an agent is available, waiting through its reset like a
+/// launch would, but without running it.
+async fn wait_for_available(settings: &Settings, agents: &[Agent], args: &Args) -> ExitCode {
+    let quiet = args.quiet;
+    let Some((_, outcome)) = scan_in_priority_order(
+        settings,
+        agents,
+        args.agent.as_deref(),
+        args.model.as_deref(),
+        quiet,
+    )
+    .await
+    else {
+        return ExitCode::FAILURE;
+    };
+    let (idx, rt) = match outcome {
+        ScanOutcome::Available { index } => {
+            if !quiet {
+                println!("Agent {} is available (not limited)", agents[index].label());
+            }
+            return ExitCode::SUCCESS;
+        }
+        ScanOutcome::AllLimited { limited } => match earliest_reset(&limited) {
+            Some(earliest) => earliest,
+            None if limited.is_empty() => {
+                eprintln!("No available agents");
+                return ExitCode::FAILURE;
+            }
+            None => {
+                eprintln!("All agents limited, no reset time available");
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+    if !quiet {
+        println!(
+            "All agents limited. Waiting for {} ({} seconds)...",
+            rt.format("%Y-%m-%d %H:%M:%S UTC"),
+            (rt - Utc::now()).num_seconds()
+        );
+    }
+    let opts = WaitOptions::new(settings, args);
+    match wait_for_reset(&agents[idx], rt, &opts).await {
+        WaitOutcome::Ready => {
+            if !quiet {
+                println!("Agent {} is available", agents[idx].label());
+            }
+            ExitCode::SUCCESS
+        }
+        WaitOutcome::Aborted => ExitCode::FAILURE,
+    }
 }
 
-async fn select_cookie_candidate<F, Fut>(
+/// Wait for `agents[idx]` to reset, then run it.
+///
+/// The wait is saved to the pending-wait state file for its duration so that
+/// `seher resume` can pick it up if seher or the machine restarts.
+async fn wait_then_execute(
+    settings: &Settings,
+    agents: &[Agent],
+    idx: usize,
+    reset_time: DateTime<Utc>,
+    input: &mut InvocationInput,
+    model: Option<&str>,
+    args: &Args,
+) -> Option<ChildExitKind> {
+    let state_path = PendingWait::default_path();
+    if let Some(path) = &state_path {
+        let pending = PendingWait {
+            created_at: Utc::now(),
+            reset_time,
+            command: agents[idx].command().to_string(),
+            account: agents[idx].account.clone(),
+            provider: agents[idx]
+                .config
+                .resolve_provider()
+                .map(ToString::to_string),
+            model: model.map(ToString::to_string),
+            args: input.raw_agent_args.clone(),
+            prompt: match &input.stdin_prompt {
+                PromptState::Resolved(prompt) => Some(prompt.clone()),
+                PromptState::Unresolved | PromptState::Empty => input.cached_prompt.clone(),
+            },
+            cwd: agents[idx]
+                .working_dir
+                .clone()
+                .unwrap_or_else(|| std::env::current_dir().unwrap_or_default()),
+            queue_item: input.queue_item,
+        };
+        if let Err(e) = pending.save(path)
+            && !args.quiet
+        {
+            eprintln!("Warning: could not save pending wait: {e}");
+        }
+    }
+
+    let opts = WaitOptions::new(settings, args);
+    let outcome = wait_for_reset(&agents[idx], reset_time, &opts).await;
+    if let Some(path) = &state_path {
+        let _ = PendingWait::clear(path);
+    }
+    if outcome == WaitOutcome::Aborted {
+        return None;
+    }
+    hold_for_do_not_disturb(settings, &opts).await;
+    let exit = execute_with_auto_rerun(settings, agents, idx, input, model, args).await;
+    if opts.bell {
+        ring_bell();
+    }
+    Some(exit)
+}
+
+/// Hold a launch released by a reset wait until the `do_not_disturb` window is over.
+async fn hold_for_do_not_disturb(settings: &Settings, opts: &WaitOptions) {
+    let Some(end) = settings
+        .do_not_disturb
+        .as_ref()
+        .and_then(|rule| rule.window_end_after(&Local::now()))
+    else {
+        return;
+    };
+    if !opts.quiet {
+        println!(
+            "Do-not-disturb hours; holding the launch until {}",
+            end.format("%Y-%m-%d %H:%M")
+        );
+    }
+    sleep_until_reset(end.with_timezone(&Utc), opts).await;
+}
+
+/// Continue the wait saved by an interrupted run.
+async fn resume_pending_wait(settings: &Settings, agents: &[Agent], args: &Args) -> ExitCode {
+    let Some(path) = PendingWait::default_path() else {
+        eprintln!("Could not determine the pending-wait state path");
+        return ExitCode::FAILURE;
+    };
+    let pending = match PendingWait::load(&path) {
+        Ok(Some(pending)) => pending,
+        Ok(None) => {
+            eprintln!("No pending wait to resume");
+            return ExitCode::FAILURE;
+        }
+        Err(e) => {
+            eprintln!("Failed to read pending wait {}: {e}", path.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    let Some(idx) = agents.iter().position(|a| {
+        a.command() == pending.command
+            && a.config.resolve_provider() == pending.provider.as_deref()
+            && a.account == pending.account
+    }) else {
+        eprintln!(
+            "Agent {} from the pending wait is not available",
+            pending.command
+        );
+        return ExitCode::FAILURE;
+    };
+    if let Err(e) = std::env::set_current_dir(&pending.cwd) {
+        eprintln!("Cannot enter {}: {e}", pending.cwd.display());
+        return ExitCode::FAILURE;
+    }
+    if !args.quiet {
+        println!(
+            "Resuming wait for {} until {} (in {})",
+            pending.command,
+            pending.reset_time.format("%Y-%m-%d %H:%M:%S UTC"),
+            pending.cwd.display()
+        );
+    }
+    let mut input = InvocationInput {
+        raw_agent_args: pending.args.clone(),
+        cached_prompt: None,
+        resume_session: false,
+        success_output: false,
+        queue_item: pending.queue_item,
+        stdin_prompt: pending
+            .prompt
+            .clone()
+            .map_or(PromptState::Unresolved, PromptState::Resolved),
+    };
+    let exit = wait_then_execute(
+        settings,
+        agents,
+        idx,
+        pending.reset_time,
+        &mut input,
+        pending.model.as_deref(),
+        args,
+    )
+    .await;
+    if let Some(id) = pending.queue_item {
+        finish_queue_item(id, exit.as_ref(), args.quiet);
+    }
+    exit_code(exit.as_ref())
+}
+
+/// Record the exit of queue item `id` after `seher resume` ran it.
+fn finish_queue_item(id: u64, exit: Option<&ChildExitKind>, quiet: bool) {
+    let Some(path) = Queue::default_path() else {
+        return;
+    };
+    let success = exit == Some(&ChildExitKind::Success);
+    let outcome = exit.map_or_else(|| "not run".to_string(), describe_exit);
+    if !quiet {
+        println!("Queue item #{id}: {outcome}");
+    }
+    if let Err(e) = update_queue(&path, &mut |q| q.finish(id, success, outcome.clone())) {
+        eprintln!("Failed to update queue: {e}");
+    }
+}
+
+/// Add to or list the prompt queue.
+fn manage_queue(action: &QueueAction, json: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let path = Queue::default_path().ok_or("Could not determine the queue state path")?;
+    let mut queue = Queue::load(&path)?;
+    match action {
+        QueueAction::Add {
+            prompt,
+            agent,
+            model,
+            args,
+        } => {
+            let prompt = if let Some(prompt) = prompt {
+                prompt.clone()
+            } else if std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+                String::new()
+            } else {
+                use std::io::Read;
+                let mut content = String::new();
+                std::io::stdin().read_to_string(&mut content)?;
+                parse_stdin_content(&content).unwrap_or_default()
+            };
+            if prompt.is_empty() && args.is_empty() {
+                return Err("Nothing to queue: give a prompt or agent arguments".into());
+            }
+            let id = queue.push(
+                prompt,
+                args.clone(),
+                agent.clone(),
+                model.clone(),
+                std::env::current_dir()?,
+            );
+            queue.save(&path)?;
+            println!("Queued #{id}");
+        }
+        QueueAction::List => {
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&QueueOutput::new(&queue.items))?
+                );
+            } else {
+                print!("{}", format_queue(&queue));
+            }
+        }
+        QueueAction::Run { .. } => {}
+    }
+    Ok(())
+}
+
+fn format_queue(queue: &Queue) -> String {
+    use std::fmt::Write;
+    if queue.items.is_empty() {
+        return "Queue is empty\n".to_string();
+    }
+    let mut out = String::new();
+    for item in &queue.items {
+        let summary = if item.prompt.is_empty() {
+            item.args.join(" ")
+        } else {
+            item.prompt.lines().next().unwrap_or_default().to_string()
+        };
+        let _ = write!(out, "#{:<4} {:<8} {summary}", item.id, item.status);
+        if let Some(outcome) = &item.outcome {
+            let _ = write!(out, " ({outcome})");
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Run queued items in order against the best available agent until none are left.
+async fn run_queue(settings: &Settings, agents: &[Agent], args: &Args) -> ExitCode {
+    let Some(path) = Queue::default_path() else {
+        eprintln!("Could not determine the queue state path");
+        return ExitCode::FAILURE;
+    };
+    let update = |f: &mut dyn FnMut(&mut Queue)| update_queue(&path, f);
+    let (mut succeeded, mut failed) = (0, 0);
+    let mut queue = match update(&mut |q| {
+        q.requeue_interrupted();
+    }) {
+        Ok(queue) => queue,
+        Err(e) => {
+            eprintln!("Failed to read queue {}: {e}", path.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    while let Some(item) = queue.next_queued().cloned() {
+        if !args.quiet {
+            println!("Running queue item #{}", item.id);
+        }
+        if let Err(e) = update(&mut |q| q.start(item.id)) {
+            eprintln!("Failed to update queue: {e}");
+            return ExitCode::FAILURE;
+        }
+        let exit = run_queue_item(settings, agents, &item, args).await;
+        let success = exit == Some(ChildExitKind::Success);
+        let outcome = exit
+            .as_ref()
+            .map_or_else(|| "not run".to_string(), describe_exit);
+        if success {
+            succeeded += 1;
+        } else {
+            failed += 1;
+        }
+        if !args.quiet {
+            println!("Queue item #{}: {outcome}", item.id);
+        }
+        queue = match update(&mut |q| q.finish(item.id, success, outcome.clone())) {
+            Ok(queue) => queue,
+            Err(e) => {
+                eprintln!("Failed to update queue: {e}");
+                return ExitCode::FAILURE;
+            }
+        };
+        if let Some(kind) = exit.as_ref().filter(|kind| halts_batch(kind)) {
+            eprintln!("Queue run stopped; remaining items stay queued");
+            return exit_code(Some(kind));
+        }
+    }
+    queue_drained(succeeded, failed, args.quiet)
+}
+
+/// Report a drained queue; the run fails if any item did.
+fn queue_drained(succeeded: usize, failed: usize, quiet: bool) -> ExitCode {
+    if !quiet {
+        println!("Queue drained: {succeeded} succeeded, {failed} failed");
+    }
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Apply `f` to the queue at `path` and save it.
+///
+/// The queue is reloaded around every change so items added while it runs are picked up.
+fn update_queue(path: &std::path::Path, f: &mut dyn FnMut(&mut Queue)) -> std::io::Result<Queue> {
+    let mut queue = Queue::load(path)?;
+    f(&mut queue);
+    queue.save(path)?;
+    Ok(queue)
+}
+
+/// How long `queue run --parallel` waits for a running item before re-checking whether
+/// the items that could not start can start now.
+const PARALLEL_RECHECK_INTERVAL: std::time::Duration = std::time::Duration::from_mins(1);
+
+/// `queue run --parallel`: run up to `parallel` queued items at once.
+///
+/// Each running item holds the [provider slot](provider_slot) it runs on, so no two items
+/// share a provider, and an item only starts on a slot that is free and not limited.
+/// When nothing is running and every free candidate is limited, the next item waits for
+/// the earliest reset as a sequential `queue run` would.
+async fn run_queue_parallel(
+    settings: &Settings,
+    agents: &[Agent],
+    parallel: usize,
+    args: &Args,
+) -> ExitCode {
+    let Some(path) = Queue::default_path() else {
+        eprintln!("Could not determine the queue state path");
+        return ExitCode::FAILURE;
+    };
+    // Items run side by side, so label every line of their output with the agent.
+    let mut settings = settings.clone();
+    settings.prefix_output = true;
+    let settings = &settings;
+    if let Err(e) = update_queue(&path, &mut |q| {
+        q.requeue_interrupted();
+    }) {
+        eprintln!("Failed to read queue {}: {e}", path.display());
+        return ExitCode::FAILURE;
+    }
+    let mut running = stream::FuturesUnordered::new();
+    let mut busy: HashSet<String> = HashSet::new();
+    let (mut succeeded, mut failed) = (0, 0);
+    let mut halted = None;
+    loop {
+        if halted.is_none() {
+            let queued: Vec<QueueItem> = match Queue::load(&path) {
+                Ok(queue) => queue.queued().cloned().collect(),
+                Err(e) => {
+                    eprintln!("Failed to read queue {}: {e}", path.display());
+                    return ExitCode::FAILURE;
+                }
+            };
+            for item in queued {
+                if running.len() >= parallel {
+                    break;
+                }
+                let Some(job) =
+                    plan_parallel_item(settings, agents, &item, &busy, running.is_empty(), args)
+                        .await
+                else {
+                    continue;
+                };
+                if let Err(e) = update_queue(&path, &mut |q| q.start(item.id)) {
+                    eprintln!("Failed to update queue: {e}");
+                    return ExitCode::FAILURE;
+                }
+                let slots: Vec<String> = job.iter().map(provider_slot).collect();
+                if !args.quiet {
+                    println!("Running queue item #{} on {}", item.id, slots.join(", "));
+                }
+                busy.extend(slots.iter().cloned());
+                running.push(run_parallel_item(settings, job, item, slots, args));
+            }
+        }
+        if running.is_empty() {
+            break;
+        }
+        let finished = if halted.is_some() {
+            running.next().await
+        } else {
+            tokio::select! {
+                finished = running.next() => finished,
+                () = tokio::time::sleep(PARALLEL_RECHECK_INTERVAL) => None,
+            }
+        };
+        let Some((item, slots, exit)) = finished else {
+            continue;
+        };
+        for slot in &slots {
+            busy.remove(slot);
+        }
+        let success = exit == Some(ChildExitKind::Success);
+        let outcome = exit
+            .as_ref()
+            .map_or_else(|| "not run".to_string(), describe_exit);
+        if success {
+            succeeded += 1;
+        } else {
+            failed += 1;
+        }
+        if !args.quiet {
+            println!("Queue item #{}: {outcome}", item.id);
+        }
+        if let Err(e) = update_queue(&path, &mut |q| q.finish(item.id, success, outcome.clone())) {
+            eprintln!("Failed to update queue: {e}");
+            return ExitCode::FAILURE;
+        }
+        if halted.is_none()
+            && let Some(kind) = exit.filter(halts_batch)
+        {
+            eprintln!("Queue run stopping after the running items; remaining items stay queued");
+            halted = Some(kind);
+        }
+    }
+    if let Some(kind) = &halted {
+        return exit_code(Some(kind));
+    }
+    queue_drained(succeeded, failed, args.quiet)
+}
+
+/// The usage an agent draws on: its provider (or command) and account. Parallel queue
+/// items never run on the same slot.
+fn provider_slot(agent: &Agent) -> String {
+    let provider = agent
+        .config
+        .resolve_provider()
+        .unwrap_or_else(|| agent.command());
+    match &agent.account {
+        Some(account) => format!("{provider} ({account})"),
+        None => provider.to_string(),
+    }
+}
+
+/// The agents `item` would run on next to the running items, set up to run in the item's
+/// directory, or `None` if it has to wait for a running item to finish.
+///
+/// The item gets the agents sharing the slot of its first available candidate whose slot
+/// is not `busy`. If every such candidate is limited it waits, unless nothing is running
+/// (`idle`), in which case it gets all of them and waits for the earliest reset itself.
+async fn plan_parallel_item(
+    settings: &Settings,
+    agents: &[Agent],
+    item: &QueueItem,
+    busy: &HashSet<String>,
+    idle: bool,
+    args: &Args,
+) -> Option<Vec<Agent>> {
+    let model = item.model.as_deref().or(args.model.as_deref());
+    let mut candidates =
+        candidate_indices_in_priority_order(settings, agents, model, &Local::now());
+    if let Some(name) = &item.agent {
+        candidates.retain(|&i| matches_name(&agents[i], name));
+    }
+    candidates.retain(|&i| !busy.contains(&provider_slot(&agents[i])));
+    let outcome = if candidates.is_empty() {
+        ScanOutcome::AllLimited { limited: vec![] }
+    } else {
+        scan_candidates(agents, candidates.clone(), |idx| {
+            Box::pin(check_limit_after_cooldown(&agents[idx], true))
+        })
+        .await
+    };
+    match outcome {
+        ScanOutcome::Available { index } => {
+            let slot = provider_slot(&agents[index]);
+            candidates.retain(|&i| provider_slot(&agents[i]) == slot);
+        }
+        ScanOutcome::AllLimited { .. } if idle => {}
+        ScanOutcome::AllLimited { .. } => return None,
+    }
+    Some(
+        candidates
+            .into_iter()
+            .map(|i| agents[i].clone().with_working_dir(item.cwd.clone()))
+            .collect(),
+    )
+}
+
+/// Run `item` on `agents` for [`run_queue_parallel`], handing back the item and the slots
+/// it held with the exit.
+async fn run_parallel_item(
+    settings: &Settings,
+    agents: Vec<Agent>,
+    item: QueueItem,
+    slots: Vec<String>,
+    args: &Args,
+) -> (QueueItem, Vec<String>, Option<ChildExitKind>) {
+    let mut input = queue_item_input(&item);
+    let model = item.model.as_deref().or(args.model.as_deref());
+    let exit = launch_until(
+        settings,
+        &agents,
+        item.agent.as_deref(),
+        &mut input,
+        model,
+        args,
+        None,
+    )
+    .await;
+    (item, slots, exit)
+}
+
+/// Run each prompt in `path` in order with the trailing args, stopping at the first
+/// failure unless `continue_on_error` is set.
+async fn run_task_file(
+    settings: &Settings,
+    agents: &[Agent],
+    path: &std::path::Path,
+    continue_on_error: bool,
+    args: &Args,
+) -> ExitCode {
+    let prompts = match std::fs::read_to_string(path) {
+        Ok(text) => seher::queue::parse_task_list(&text),
+        Err(e) => {
+            eprintln!("Failed to read {}: {e}", path.display());
+            return ExitCode::FAILURE;
+        }
+    };
+    let total = prompts.len();
+    let mut failed = 0;
+    for (n, prompt) in prompts.into_iter().enumerate() {
+        if !args.quiet {
+            println!("Task {}/{total}", n + 1);
+        }
+        let mut input = InvocationInput {
+            raw_agent_args: args.extra.clone(),
+            cached_prompt: None,
+            resume_session: false,
+            success_output: false,
+            queue_item: None,
+            stdin_prompt: PromptState::Resolved(prompt),
+        };
+        let exit = launch_until(
+            settings,
+            agents,
+            None,
+            &mut input,
+            args.model.as_deref(),
+            args,
+            None,
+        )
+        .await;
+        if exit == Some(ChildExitKind::Success) {
+            continue;
+        }
+        failed += 1;
+        if !args.quiet {
+            let outcome = exit
+                .as_ref()
+                .map_or_else(|| "not run".to_string(), describe_exit);
+            eprintln!("Task {}/{total}: {outcome}", n + 1);
+        }
+        if let Some(kind) = exit.as_ref().filter(|kind| halts_batch(kind)) {
+            return exit_code(Some(kind));
+        }
+        if !continue_on_error {
+            eprintln!("Stopping; pass --continue-on-error to run the remaining tasks");
+            return ExitCode::FAILURE;
+        }
+    }
+    if !args.quiet {
+        println!(
+            "Ran {total} tasks: {} succeeded, {failed} failed",
+            total - failed
+        );
+    }
+    if failed == 0 {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// `seher daemon`: run each `schedule` entry whenever its cron expression fires.
+///
+/// Runs that would have fired while an earlier one was still going are skipped rather
+/// than run late.
+async fn run_daemon(settings: &Settings, agents: &[Agent], args: &Args) -> ExitCode {
+    // Settings::load has already validated every expression.
+    let runs: Vec<_> = settings
+        .schedule
+        .iter()
+        .filter_map(|run| run.cron_schedule().ok().map(|cron| (run, cron)))
+        .collect();
+    if runs.is_empty() {
+        eprintln!("No scheduled runs in settings");
+        return ExitCode::FAILURE;
+    }
+    #[cfg(unix)]
+    if let Ok(socket) = events::default_socket_path() {
+        tokio::spawn(async move {
+            if let Err(e) = events::serve(&socket).await {
+                eprintln!(
+                    "Warning: event socket {} unavailable: {e}",
+                    socket.display()
+                );
+            }
+        });
+    }
+    let mut after = Local::now();
+    loop {
+        let Some(next) = runs.iter().filter_map(|(_, c)| c.next_after(&after)).min() else {
+            eprintln!("None of the scheduled runs will fire again");
+            return ExitCode::FAILURE;
+        };
+        if !args.quiet {
+            println!("Next scheduled run at {}", next.format("%Y-%m-%d %H:%M"));
+        }
+        seher::wait::sleep_until(next.with_timezone(&Utc), |_| {}).await;
+        for (run, _) in runs.iter().filter(|(_, c)| c.matches(&next)) {
+            let outcome = run_scheduled(settings, agents, run, args).await;
+            if let Err(failure) = &outcome {
+                events::publish(&Event::error(run.agent.clone(), failure));
+            }
+            if let Some(heartbeat) = &settings.heartbeat {
+                match outcome {
+                    Ok(()) => heartbeat.ping(true, "").await,
+                    Err(failure) => heartbeat.ping(false, &failure).await,
+                }
+            }
+        }
+        seher::telemetry::flush().await;
+        after = Local::now().max(next);
+    }
+}
+
+/// Run one scheduled prompt. Unless the entry asks to wait, it is skipped when every
+/// candidate agent is limited.
+///
+/// Returns why the run failed, if it did; a skipped run is not a failure.
+async fn run_scheduled(
+    settings: &Settings,
+    agents: &[Agent],
+    run: &ScheduledRun,
+    args: &Args,
+) -> Result<(), String> {
+    let label = run.label();
+    let model = run.model.as_deref().or(args.model.as_deref());
+    if !args.quiet {
+        println!("Scheduled run {label}");
+    }
+    // Unless it waits, the run needs an available agent now; the launch reuses this scan.
+    let scanned = if run.wait {
+        None
+    } else {
+        match scan_in_priority_order(settings, agents, run.agent.as_deref(), model, args.quiet)
+            .await
+        {
+            Some(scan @ (_, ScanOutcome::Available { .. })) => Some(scan),
+            Some((_, ScanOutcome::AllLimited { .. })) => {
+                println!("Skipping scheduled run {label}: every candidate agent is limited");
+                return Ok(());
+            }
+            None => return Err(format!("scheduled run {label}: no candidate agent")),
+        }
+    };
+    let mut input = InvocationInput {
+        raw_agent_args: run.args.clone(),
+        cached_prompt: None,
+        resume_session: false,
+        success_output: false,
+        queue_item: None,
+        stdin_prompt: PromptState::Resolved(run.prompt.clone()),
+    };
+    let exit = launch_until(
+        settings,
+        agents,
+        run.agent.as_deref(),
+        &mut input,
+        model,
+        args,
+        scanned,
+    )
+    .await;
+    let outcome = exit
+        .as_ref()
+        .map_or_else(|| "not run".to_string(), describe_exit);
+    if !args.quiet {
+        println!("Scheduled run {label} {outcome}");
+    }
+    match exit {
+        Some(ChildExitKind::Success) => Ok(()),
+        _ => Err(format!("scheduled run {label} {outcome}")),
+    }
+}
+
+async fn run_queue_item(
+    settings: &Settings,
+    agents: &[Agent],
+    item: &QueueItem,
+    args: &Args,
+) -> Option<ChildExitKind> {
+    if let Err(e) = std::env::set_current_dir(&item.cwd) {
+        eprintln!("Cannot enter {}: {e}", item.cwd.display());
+        return None;
+    }
+    let mut input = queue_item_input(item);
+    let model = item.model.as_deref().or(args.model.as_deref());
+    launch_until(
+        settings,
+        agents,
+        item.agent.as_deref(),
+        &mut input,
+        model,
+        args,
+        None,
+    )
+    .await
+}
+
+/// The invocation input for a queue item's prompt and arguments.
+fn queue_item_input(item: &QueueItem) -> InvocationInput {
+    InvocationInput {
+        raw_agent_args: item.args.clone(),
+        cached_prompt: None,
+        resume_session: false,
+        success_output: false,
+        queue_item: Some(item.id),
+        // Never fall back to the editor for unattended items.
+        stdin_prompt: parse_stdin_content(&item.prompt)
+            .map_or(PromptState::Empty, PromptState::Resolved),
+    }
+}
+
+/// How many cookie databases are read at once.
+const MAX_CONCURRENT_PROFILE_READS: usize = 8;
+
+/// Read `domain`'s cookies from every candidate profile, several at a time on blocking
+/// threads since opening and decrypting the databases blocks.
+///
+/// Cookies come out in profile order, or as soon as each read finishes with
+/// `first_finished`. Profiles that cannot be read or have no live session cookie are
+/// skipped before any value is decrypted.
+fn read_cookie_candidates(
+    profiles: Vec<seher::Profile>,
+    domain: &str,
+    first_finished: bool,
+) -> Pin<Box<dyn Stream<Item = Vec<seher::Cookie>>>> {
+    let domain = domain.to_string();
+    let reads = stream::iter(profiles).map(move |profile| {
+        let domain = domain.clone();
+        async move {
+            tokio::task::spawn_blocking(move || {
+                let cookies = CookieReader::read_cookies_lazy(&profile, &domain).ok()?;
+                if !session::has_lazy_session(&cookies, session::session_cookie_names(&domain)) {
+                    return None;
+                }
+                let decrypted = CookieReader::decrypt_cookies(cookies);
+                for warning in &decrypted.warnings {
+                    eprintln!(
+                        "  [warn] Failed to decrypt cookie '{}': {}",
+                        warning.name, warning.error
+                    );
+                }
+                Some(decrypted.cookies).filter(|cookies| !cookies.is_empty())
+            })
+            .await
+            .ok()
+            .flatten()
+        }
+    });
+    let reads: Pin<Box<dyn Stream<Item = Option<Vec<seher::Cookie>>>>> = if first_finished {
+        Box::pin(reads.buffer_unordered(MAX_CONCURRENT_PROFILE_READS))
+    } else {
+        Box::pin(reads.buffered(MAX_CONCURRENT_PROFILE_READS))
+    };
+    Box::pin(reads.filter_map(std::future::ready))
+}
+
+/// The first candidate with a valid session; no further candidates are read once found.
+async fn select_cookie_candidate<F, Fut>(
     domain: &str,
-    candidates: Vec<Vec<seher::Cookie>>,
+    candidates: impl Stream<Item = Vec<seher::Cookie>>,
     mut codex_validator: F,
 ) -> Option<Vec<seher::Cookie>>
 where
     F: FnMut(Vec<seher::Cookie>) -> Fut,
     Fut: Future<Output = (Vec<seher::Cookie>, bool)>,
 {
-    for cookies in candidates {
-        if !cookies
-            .iter()
-            .any(|cookie| has_valid_session_cookie(domain, cookie))
-        {
+    let mut candidates = std::pin::pin!(candidates);
+    while let Some(cookies) = candidates.next().await {
+        if !session::has_session(&cookies, session::session_cookie_names(domain)) {
             continue;
         }
 
@@ -492,12 +2608,18 @@ where
 async fn get_cookies_for_domain(
     detector: &BrowserDetector,
     browsers: &[BrowserType],
-    browser_arg: Option<&String>,
+    browser_arg: Option<BrowserType>,
     profile_arg: Option<&String>,
     domain: &str,
+    first_valid_session: bool,
 ) -> Option<Vec<seher::Cookie>> {
-    let candidates =
-        collect_cookie_candidates(detector, browsers, browser_arg, profile_arg, domain);
+    let profiles = session::candidate_profiles(
+        detector,
+        browsers,
+        browser_arg,
+        profile_arg.map(String::as_str),
+    );
+    let candidates = read_cookie_candidates(profiles, domain, first_valid_session);
 
     select_cookie_candidate(domain, candidates, |cookies| async move {
         let is_valid = CodexClient::session_has_access_token(&cookies)
@@ -508,175 +2630,1252 @@ async fn get_cookies_for_domain(
     .await
 }
 
-fn has_session_cookie(domain: &str, cookie: &seher::Cookie) -> bool {
-    match domain {
-        "claude.ai" => cookie.name == "sessionKey",
-        "chatgpt.com" => cookie.name.starts_with("__Secure-next-auth.session-token"),
-        "github.com" => {
-            cookie.name == "user_session" || cookie.name == "__Host-user_session_same_site"
-        }
-        _ => false,
-    }
-}
-
 fn parse_stdin_content(content: &str) -> Option<String> {
     let trimmed = content.trim();
     (!trimmed.is_empty()).then(|| trimmed.to_string())
 }
 
-fn prompt_from_editor() -> std::result::Result<String, Box<dyn std::error::Error>> {
-    let tmp = tempfile::NamedTempFile::new()?;
-    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vim".to_string());
-    std::process::Command::new(&editor)
-        .arg(tmp.path())
-        .status()?;
-    Ok(std::fs::read_to_string(tmp.path())?.trim().to_string())
+/// Read a piped prompt and apply `--template`, producing the input for the first run.
+fn invocation_input(settings: &Settings, args: &Args) -> InvocationInput {
+    let stdin_prompt = {
+        use std::io::{IsTerminal, Read};
+        if std::io::stdin().is_terminal() {
+            PromptState::Unresolved
+        } else {
+            let mut content = String::new();
+            if let Err(e) = std::io::stdin().read_to_string(&mut content)
+                && !args.quiet
+            {
+                eprintln!("Failed to read stdin: {e}");
+            }
+            match parse_stdin_content(&content) {
+                Some(s) => PromptState::Resolved(s),
+                None => PromptState::Empty,
+            }
+        }
+    };
+
+    match args
+        .template
+        .as_ref()
+        .and_then(|t| settings.templates.get(t))
+    {
+        Some(template) => apply_template(template, &args.extra, stdin_prompt),
+        None => InvocationInput {
+            raw_agent_args: args.extra.clone(),
+            cached_prompt: None,
+            resume_session: false,
+            success_output: false,
+            queue_item: None,
+            stdin_prompt,
+        },
+    }
+}
+
+/// `true` if the prompt still has to be composed in `$EDITOR`.
+fn wants_editor(input: &InvocationInput, quiet: bool) -> bool {
+    matches!(input.stdin_prompt, PromptState::Unresolved)
+        && input.raw_agent_args.is_empty()
+        && !quiet
+}
+
+/// Stands in for the prompt `$EDITOR` would supply in `--dry-run` output.
+const EDITOR_PROMPT_PLACEHOLDER: &str = "<prompt from editor>";
+
+/// Open the editor before any agent is chosen, so the prompt's front-matter can pick the
+/// agent, model and extra arguments. Returns empty front-matter when no editor is needed.
+///
+/// A dry run never opens the editor: the prompt is [`EDITOR_PROMPT_PLACEHOLDER`] instead.
+fn compose_prompt(
+    input: &mut InvocationInput,
+    quiet: bool,
+    dry_run: bool,
+) -> Result<FrontMatter, Box<dyn std::error::Error>> {
+    if !wants_editor(input, quiet) {
+        return Ok(FrontMatter::default());
+    }
+    if dry_run {
+        input.cached_prompt = Some(EDITOR_PROMPT_PLACEHOLDER.to_string());
+        return Ok(FrontMatter::default());
+    }
+    let text = prompt_from_editor().map_err(|e| format!("Editor error: {e}"))?;
+    let (front, body) = front_matter::parse(&text)?;
+    input.cached_prompt = Some(front.apply_files(body.trim()));
+    input.raw_agent_args.extend(front.args.iter().cloned());
+    Ok(front)
+}
+
+/// Expand `template` into the prompt, consuming the trailing args and piped prompt it
+/// references. A piped prompt the template doesn't reference is appended after it.
+fn apply_template(template: &str, extra: &[String], stdin_prompt: PromptState) -> InvocationInput {
+    let stdin = match stdin_prompt {
+        PromptState::Resolved(p) => Some(p),
+        PromptState::Unresolved | PromptState::Empty => None,
+    };
+    let ctx = template::TemplateContext::current(extra, stdin.as_deref());
+    let mut prompt = template::expand(template, &ctx);
+    if let Some(stdin) = stdin.filter(|_| !template::uses_stdin(template)) {
+        prompt = format!("{prompt}\n\n{stdin}");
+    }
+    InvocationInput {
+        raw_agent_args: if template::uses_args(template) {
+            vec![]
+        } else {
+            extra.to_vec()
+        },
+        cached_prompt: None,
+        resume_session: false,
+        success_output: false,
+        queue_item: None,
+        stdin_prompt: match parse_stdin_content(&prompt) {
+            Some(p) => PromptState::Resolved(p),
+            None => PromptState::Empty,
+        },
+    }
+}
+
+fn prompt_from_editor() -> std::result::Result<String, Box<dyn std::error::Error>> {
+    let tmp = tempfile::NamedTempFile::new()?;
+    let editor = std::env::var("EDITOR").unwrap_or_else(|_| "vim".to_string());
+    std::process::Command::new(&editor)
+        .arg(tmp.path())
+        .status()?;
+    Ok(std::fs::read_to_string(tmp.path())?.trim().to_string())
+}
+
+/// Relaunches allowed after an agent reports a rate limit in its output.
+const MAX_RATE_LIMIT_RECOVERIES: u32 = 3;
+
+/// Run `agents[first]`, then each next available candidate while runs keep failing
+/// (unless failover is disabled), and report what was attempted.
+///
+/// When an agent reports a rate limit and no other agent is available, wait for its
+/// reset and relaunch it.
+async fn execute_with_failover(
+    settings: &Settings,
+    agents: &[Agent],
+    candidates: &[usize],
+    first: usize,
+    input: &mut InvocationInput,
+    model: Option<&str>,
+    args: &Args,
+) -> ChildExitKind {
+    let quiet = args.quiet;
+    let mut attempts = vec![(
+        first,
+        execute_with_auto_rerun(settings, agents, first, input, model, args).await,
+    )];
+    let mut recoveries = 0;
+    while let Some(&(previous, ref last)) = attempts.last() {
+        if !should_fail_over(last) {
+            break;
+        }
+        let rate_limited = *last == ChildExitKind::RateLimited;
+        if settings.failover.unwrap_or(true) {
+            let tried: Vec<usize> = attempts.iter().map(|(i, _)| *i).collect();
+            let remaining = untried_candidates(candidates, &tried);
+            let outcome = scan_candidates(agents, remaining, |idx| {
+                Box::pin(check_limit_after_cooldown(&agents[idx], quiet))
+            })
+            .await;
+            if let ScanOutcome::Available { index: next } = outcome {
+                if !quiet {
+                    eprintln!(
+                        "{} failed; falling back to {}",
+                        agents[previous].command(),
+                        agents[next].command()
+                    );
+                }
+                attempts.push((
+                    next,
+                    execute_with_auto_rerun(settings, agents, next, input, model, args).await,
+                ));
+                continue;
+            }
+        }
+        if !rate_limited || recoveries >= MAX_RATE_LIMIT_RECOVERIES {
+            break;
+        }
+        recoveries += 1;
+        let Ok(AgentLimit::Limited {
+            reset_time: Some(reset_time),
+        }) = agents[previous].check_limit().await
+        else {
+            break;
+        };
+        if !quiet {
+            eprintln!(
+                "{} hit its rate limit; waiting for the reset to relaunch it",
+                agents[previous].command()
+            );
+        }
+        let opts = WaitOptions::new(settings, args);
+        if wait_for_reset(&agents[previous], reset_time, &opts).await == WaitOutcome::Aborted {
+            break;
+        }
+        hold_for_do_not_disturb(settings, &opts).await;
+        input.resume_session = !agents[previous].config.resume_args.is_empty();
+        attempts.push((
+            previous,
+            execute_with_auto_rerun(settings, agents, previous, input, model, args).await,
+        ));
+        input.resume_session = false;
+    }
+    if attempts.len() > 1 && !quiet {
+        eprint!("{}", format_attempt_report(agents, &attempts));
+    }
+    attempts
+        .pop()
+        .map_or(ChildExitKind::SpawnError, |(_, exit)| exit)
+}
+
+/// Run the agent, re-running it per its `retry` policy, or once if it fails without one,
+/// and return the final exit.
+async fn execute_with_auto_rerun(
+    settings: &Settings,
+    agents: &[Agent],
+    idx: usize,
+    input: &mut InvocationInput,
+    model: Option<&str>,
+    args: &Args,
+) -> ChildExitKind {
+    let exit_kind = execute_agent(settings, agents, idx, input, model, args).await;
+    if let Some(retry) = &agents[idx].config.retry {
+        return Box::pin(retry_agent(
+            settings, agents, idx, input, model, args, retry, exit_kind,
+        ))
+        .await;
+    }
+    let provider_aware = agents[idx].config.resolve_provider().is_some();
+    if should_auto_rerun(&exit_kind, provider_aware) {
+        if !args.quiet {
+            eprintln!("Agent failed, retrying...");
+        }
+        return execute_agent(settings, agents, idx, input, model, args).await;
+    }
+    exit_kind
+}
+
+/// Retry `agents[idx]` per `retry` while its runs fail in a way the policy covers,
+/// sleeping the backoff and re-checking its limit before each attempt. A limited agent
+/// is waited for like a rate-limited run.
+#[expect(clippy::too_many_arguments)]
+async fn retry_agent(
+    settings: &Settings,
+    agents: &[Agent],
+    idx: usize,
+    input: &mut InvocationInput,
+    model: Option<&str>,
+    args: &Args,
+    retry: &AgentRetry,
+    mut exit_kind: ChildExitKind,
+) -> ChildExitKind {
+    let agent = &agents[idx];
+    // Settings::load has already validated the backoff.
+    let backoff = retry.backoff().unwrap_or_default();
+    for attempt in 1..=retry.max {
+        if !retry_applies(retry, &exit_kind) {
+            break;
+        }
+        if !args.quiet {
+            eprintln!(
+                "{} {}; retrying in {} ({attempt}/{})",
+                agent.command(),
+                describe_exit(&exit_kind),
+                retry.backoff.as_deref().unwrap_or("1m"),
+                retry.max
+            );
+        }
+        tokio::time::sleep(backoff).await;
+        match agent.check_limit().await {
+            Ok(AgentLimit::Limited {
+                reset_time: Some(reset_time),
+            }) => {
+                let opts = WaitOptions::new(settings, args);
+                if wait_for_reset(agent, reset_time, &opts).await == WaitOutcome::Aborted {
+                    break;
+                }
+                hold_for_do_not_disturb(settings, &opts).await;
+            }
+            Ok(AgentLimit::Limited { reset_time: None }) => {
+                eprintln!(
+                    "{} is limited with no reset time; not retrying",
+                    agent.command()
+                );
+                break;
+            }
+            // A failed check is no reason to give up on the retry.
+            Ok(AgentLimit::NotLimited) | Err(_) => {}
+        }
+        exit_kind = execute_agent(settings, agents, idx, input, model, args).await;
+    }
+    exit_kind
+}
+
+/// `true` if `retry` covers a run that ended with `exit_kind`.
+fn retry_applies(retry: &AgentRetry, exit_kind: &ChildExitKind) -> bool {
+    match exit_kind {
+        ChildExitKind::Failure { .. } | ChildExitKind::SpawnError => {
+            retry.on.contains(&RetryOn::Nonzero)
+        }
+        ChildExitKind::TimedOut => retry.on.contains(&RetryOn::Timeout),
+        ChildExitKind::Success
+        | ChildExitKind::SignalTerminated { .. }
+        | ChildExitKind::RateLimited
+        | ChildExitKind::BudgetExceeded => false,
+    }
+}
+
+/// Return `true` if the next available agent should be tried after this exit.
+///
+/// Signal terminations are left alone since they usually mean the user interrupted.
+fn should_fail_over(exit_kind: &ChildExitKind) -> bool {
+    matches!(
+        exit_kind,
+        ChildExitKind::Failure { .. }
+            | ChildExitKind::SpawnError
+            | ChildExitKind::RateLimited
+            | ChildExitKind::TimedOut
+            | ChildExitKind::BudgetExceeded
+    )
+}
+
+/// `true` if a batch of runs (`--until-success`, `run --from`, `queue run`) should stop
+/// after this exit instead of moving on.
+fn halts_batch(exit_kind: &ChildExitKind) -> bool {
+    matches!(
+        exit_kind,
+        ChildExitKind::SignalTerminated { .. } | ChildExitKind::BudgetExceeded
+    )
+}
+
+/// Candidates not yet tried, in their original priority order.
+fn untried_candidates(candidates: &[usize], tried: &[usize]) -> Vec<usize> {
+    candidates
+        .iter()
+        .copied()
+        .filter(|i| !tried.contains(i))
+        .collect()
+}
+
+fn format_attempt_report(agents: &[Agent], attempts: &[(usize, ChildExitKind)]) -> String {
+    use std::fmt::Write;
+    let mut report = String::from("Attempted agents:\n");
+    for (n, (idx, kind)) in attempts.iter().enumerate() {
+        let _ = writeln!(
+            report,
+            "  {}. {}: {}",
+            n + 1,
+            format_agent(&agents[*idx]),
+            describe_exit(kind)
+        );
+    }
+    report
+}
+
+fn describe_exit(kind: &ChildExitKind) -> String {
+    match kind {
+        ChildExitKind::Success => "succeeded".to_string(),
+        ChildExitKind::Failure { code: Some(code) } => format!("exited with code {code}"),
+        ChildExitKind::Failure { code: None } => "failed".to_string(),
+        ChildExitKind::SignalTerminated {
+            signal: Some(signal),
+        } => {
+            format!("terminated by signal {signal}")
+        }
+        ChildExitKind::SignalTerminated { signal: None } => "terminated by signal".to_string(),
+        ChildExitKind::SpawnError => "failed to start".to_string(),
+        ChildExitKind::RateLimited => "hit a rate limit".to_string(),
+        ChildExitKind::TimedOut => "timed out".to_string(),
+        ChildExitKind::BudgetExceeded => "stopped at the budget ceiling".to_string(),
+    }
+}
+
+async fn execute_agent(
+    settings: &Settings,
+    agents: &[Agent],
+    selected_index: usize,
+    input: &mut InvocationInput,
+    model: Option<&str>,
+    args: &Args,
+) -> ChildExitKind {
+    let quiet = args.quiet;
+    let selected_agent = &agents[selected_index];
+    if input.resume_session {
+        return resume_agent(settings, selected_agent, input, model, args).await;
+    }
+    let mut final_args = selected_agent.mapped_args(&input.raw_agent_args);
+
+    let mut prompt = match &input.stdin_prompt {
+        PromptState::Resolved(p) => Some(p.clone()),
+        PromptState::Unresolved | PromptState::Empty => None,
+    };
+
+    if wants_editor(input, quiet) && input.cached_prompt.is_none() {
+        match prompt_from_editor() {
+            Ok(prompt) => input.cached_prompt = Some(prompt),
+            Err(e) => {
+                eprintln!("Editor error: {e}");
+                // SpawnError prevents auto-rerun, which is correct -- the agent was never started.
+                return ChildExitKind::SpawnError;
+            }
+        }
+    }
+    if prompt.is_none() {
+        prompt = input.cached_prompt.clone().filter(|p| !p.is_empty());
+    }
+
+    let stdin = match selected_agent.config.input {
+        PromptInput::Stdin => prompt,
+        PromptInput::Arg => {
+            final_args.extend(prompt);
+            None
+        }
+    };
+
+    let resolved = selected_agent.resolved_args(model);
+    spawn_agent(
+        settings,
+        selected_agent,
+        &resolved,
+        &final_args,
+        stdin.as_deref(),
+        input,
+        args,
+    )
+    .await
+}
+
+/// Relaunch the agent with its `resume_args` to continue the interrupted session.
+///
+/// The prompt and trailing arguments are not passed again, since they would start a
+/// new turn in the resumed conversation.
+async fn resume_agent(
+    settings: &Settings,
+    agent: &Agent,
+    input: &mut InvocationInput,
+    model: Option<&str>,
+    args: &Args,
+) -> ChildExitKind {
+    if !args.quiet {
+        println!("Resuming the interrupted {} session", agent.command());
+    }
+    let resolved = agent.resolved_args(model);
+    spawn_agent(
+        settings,
+        agent,
+        &resolved,
+        &agent.config.resume_args,
+        None,
+        input,
+        args,
+    )
+    .await
+}
+
+/// Start the agent with its final arguments and wait for it to exit.
+async fn spawn_agent(
+    settings: &Settings,
+    agent: &Agent,
+    resolved: &[String],
+    final_args: &[String],
+    stdin: Option<&str>,
+    input: &mut InvocationInput,
+    args: &Args,
+) -> ChildExitKind {
+    let quiet = args.quiet;
+    let timeout = args.exec_timeout.and_then(|d| d.to_std().ok());
+    if !quiet {
+        println!("Executing: {}", command_line(agent, resolved, final_args));
+        if stdin.is_some() {
+            println!("(prompt passed on stdin)");
+        }
+    }
+
+    let hooks = settings.hooks.clone().unwrap_or_default();
+    let notifier = Notifier::new(settings.notifications.clone().unwrap_or_default());
+    let mut pre_exec = hook_payload(HookEvent::PreExec, agent);
+    pre_exec.command_line = Some(command_line(agent, resolved, final_args));
+    announce(&hooks, &notifier, &pre_exec).await;
+
+    let cooldowns = Cooldowns::current();
+    if let Some(cooldowns) = cooldowns {
+        cooldowns.record(&provider_slot(agent), Utc::now());
+    }
+    let capture = output_capture(settings, agent, &args.success_pattern);
+    if args.exec_replace {
+        let supervised = timeout.is_some()
+            || loop_mode(args)
+            || budget_rules(settings, args).is_ok_and(|rules| !rules.is_empty());
+        match exec_replace_blocker(stdin.is_some(), capture.is_some(), supervised) {
+            None => return replace_process(agent, resolved, final_args),
+            Some(reason) if !quiet => eprintln!("--exec-replace ignored: {reason}"),
+            Some(_) => {}
+        }
+    }
+    let budget = watch_budget(settings, agent, args);
+    let exit_kind = if let Some(capture) = capture {
+        match agent
+            .execute_captured(resolved, final_args, stdin, timeout, budget, &capture)
+            .await
+        {
+            Ok((stop, matches)) => {
+                input.success_output = matches.success;
+                if matches.rate_limited {
+                    ChildExitKind::RateLimited
+                } else {
+                    Ok(stop).into()
+                }
+            }
+            Err(e) => Err::<Stop, _>(e).into(),
+        }
+    } else {
+        agent
+            .execute(resolved, final_args, stdin, timeout, budget)
+            .await
+            .into()
+    };
+    if let Some(cooldowns) = cooldowns {
+        cooldowns.record(&provider_slot(agent), Utc::now());
+    }
+    if exit_kind == ChildExitKind::TimedOut && !quiet {
+        eprintln!("{} exceeded --exec-timeout and was killed", agent.command());
+    }
+    let mut post_exec = hook_payload(HookEvent::PostExec, agent);
+    post_exec.exit_code = match exit_kind {
+        ChildExitKind::Success => Some(0),
+        ChildExitKind::Failure { code } => code,
+        _ => None,
+    };
+    post_exec.outcome = Some(describe_exit(&exit_kind));
+    announce(&hooks, &notifier, &post_exec).await;
+    exit_kind
+}
+
+/// Run the event's hook and post the event to the Slack and Discord notifiers.
+async fn announce(hooks: &HooksConfig, notifier: &Notifier, payload: &HookPayload) {
+    events::publish(&Event::from(payload));
+    hooks.run(payload).await;
+    notifier.notify_event(payload).await;
+}
+
+/// The limited usage window of `agent` that resets last, which is what holds it back.
+async fn limited_window(agent: &Agent) -> Option<String> {
+    let status = agent.fetch_status().await.ok()?;
+    status
+        .usage
+        .into_iter()
+        .filter(|entry| entry.limited)
+        .max_by_key(|entry| entry.resets_at)
+        .map(|entry| entry.entry_type)
+}
+
+/// The hook payload for `event` on `agent`, without event-specific fields.
+fn hook_payload(event: HookEvent, agent: &Agent) -> HookPayload {
+    let mut payload = HookPayload::new(event, agent.label(), agent.command().to_string());
+    payload.provider = agent.config.resolve_provider().map(ToString::to_string);
+    payload
+}
+
+/// `command` followed by its arguments, as shown before running an agent.
+fn command_line(agent: &Agent, resolved: &[String], final_args: &[String]) -> String {
+    std::iter::once(agent.command())
+        .chain(resolved.iter().chain(final_args).map(String::as_str))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The command line [`execute_agent`] would run for `agent`, for `--dry-run`.
+///
+/// Only a prompt that is already known (piped, from a previous attempt, or the
+/// [`EDITOR_PROMPT_PLACEHOLDER`]) is included; the editor is never opened.
+fn planned_command(agent: &Agent, input: &InvocationInput, model: Option<&str>) -> String {
+    let resolved = agent.resolved_args(model);
+    if input.resume_session {
+        return command_line(agent, &resolved, &agent.config.resume_args);
+    }
+    let mut final_args = agent.mapped_args(&input.raw_agent_args);
+    let prompt = match &input.stdin_prompt {
+        PromptState::Resolved(p) => Some(p.clone()),
+        PromptState::Unresolved | PromptState::Empty => None,
+    }
+    .or_else(|| input.cached_prompt.clone().filter(|p| !p.is_empty()));
+    match (agent.config.input, prompt) {
+        (PromptInput::Stdin, Some(_)) => format!(
+            "{} (prompt passed on stdin)",
+            command_line(agent, &resolved, &final_args)
+        ),
+        (PromptInput::Arg, prompt) => {
+            final_args.extend(prompt);
+            command_line(agent, &resolved, &final_args)
+        }
+        (PromptInput::Stdin, None) => command_line(agent, &resolved, &final_args),
+    }
+}
+
+/// The `budget` ceilings from settings and `--budget`.
+fn budget_rules(settings: &Settings, args: &Args) -> Result<Vec<AlertRule>, String> {
+    settings
+        .budget
+        .iter()
+        .chain(&args.budget)
+        .map(|rule| rule.parse())
+        .collect()
+}
+
+/// Poll `agent`'s usage while it runs and resolve to the crossed rule once a budget
+/// ceiling is crossed.
+///
+/// In warn mode crossings are only printed and this never resolves; it also never
+/// resolves when no budget is set or the agent has no provider to poll.
+async fn watch_budget(settings: &Settings, agent: &Agent, args: &Args) -> String {
+    let rules = budget_rules(settings, args).unwrap_or_default();
+    if rules.is_empty() || agent.config.resolve_provider().is_none() {
+        return std::future::pending().await;
+    }
+    let warn_only = args.budget_warn || settings.budget_action == BudgetAction::Warn;
+    let interval = settings
+        .budget_poll_secs
+        .map_or(DEFAULT_BUDGET_POLL_INTERVAL, |secs| {
+            std::time::Duration::from_secs(secs.max(1))
+        });
+    let mut tracker = AlertTracker::new(
+        rules,
+        settings
+            .alert_hysteresis
+            .unwrap_or(alert::DEFAULT_HYSTERESIS),
+    );
+    loop {
+        tokio::time::sleep(interval).await;
+        let Ok(status) = Box::pin(agent.fetch_status()).await else {
+            continue;
+        };
+        for fired in tracker.evaluate(&status.command, status.provider.as_deref(), &status.usage) {
+            if !warn_only {
+                eprintln!(
+                    "Budget ceiling crossed: {fired}; stopping {}",
+                    agent.command()
+                );
+                return fired.to_string();
+            }
+            eprintln!("Budget warning: {fired}");
+        }
+    }
+}
+
+/// Why the agent can't replace seher's process, or `None` if it can.
+fn exec_replace_blocker(
+    stdin_prompt: bool,
+    captured: bool,
+    supervised: bool,
+) -> Option<&'static str> {
+    if !cfg!(unix) {
+        Some("only supported on Unix")
+    } else if stdin_prompt {
+        Some("the prompt is passed on stdin")
+    } else if captured {
+        Some("agent output is captured")
+    } else if supervised {
+        Some("seher has to keep running for --exec-timeout, --until-success or a budget")
+    } else {
+        None
+    }
+}
+
+/// Exec the agent in place of seher. Returns only if that fails.
+#[cfg(unix)]
+fn replace_process(agent: &Agent, resolved: &[String], extra: &[String]) -> ChildExitKind {
+    agent.exec(resolved, extra).into()
+}
+
+#[cfg(not(unix))]
+fn replace_process(_agent: &Agent, _resolved: &[String], _extra: &[String]) -> ChildExitKind {
+    ChildExitKind::SpawnError
+}
+
+/// How to capture the agent's output, or `None` to let it use the terminal directly.
+///
+/// Output is captured only when it has to be prefixed, logged or scanned for rate limits
+/// or success patterns, since piping it hides the terminal from interactive agents.
+fn output_capture(
+    settings: &Settings,
+    agent: &Agent,
+    success_patterns: &[String],
+) -> Option<OutputCapture> {
+    let detect = agent.config.detect_rate_limit;
+    if !detect
+        && !settings.prefix_output
+        && settings.output_log.is_none()
+        && success_patterns.is_empty()
+    {
+        return None;
+    }
+    Some(OutputCapture {
+        prefix: settings
+            .prefix_output
+            .then(|| format!("[{}] ", agent.command())),
+        log: settings.output_log.clone(),
+        patterns: if detect {
+            agent.rate_limit_patterns()
+        } else {
+            vec![]
+        },
+        success_patterns: success_patterns.to_vec(),
+    })
+}
+
+fn format_agent_identity(config: &AgentConfig) -> String {
+    let provider = config.resolve_provider().unwrap_or("(none)");
+    format!("command={} provider={}", config.command, provider)
+}
+
+/// [`format_agent_identity`] plus the account, when the agent runs as one, and the GitHub
+/// user of a Copilot session.
+fn format_agent(agent: &Agent) -> String {
+    use std::fmt::Write;
+    let identity = format_agent_identity(&agent.config);
+    let mut out = match &agent.account {
+        Some(account) => format!("{identity} account={account}"),
+        None => identity,
+    };
+    if let Some(login) = agent.github_login() {
+        let _ = write!(out, " user={login}");
+    }
+    out
+}
+
+fn format_priority_entry<W: std::io::Write>(
+    writer: &mut W,
+    rank: usize,
+    config: &AgentConfig,
+    priority: i32,
+) {
+    let env_display = match &config.env {
+        None => String::new(),
+        Some(env) => {
+            let mut keys: Vec<&str> = env.keys().map(String::as_str).collect();
+            keys.sort_unstable();
+            keys.join(", ")
+        }
+    };
+    writeln!(
+        writer,
+        "  {}. [priority={:3}] {} env={{{}}}",
+        rank,
+        priority,
+        format_agent_identity(config),
+        env_display
+    )
+    .ok();
+}
+
+fn write_model_section<W: std::io::Write>(
+    writer: &mut W,
+    settings: &Settings,
+    model: Option<&str>,
+    now: &DateTime<Local>,
+) {
+    let mut candidates: Vec<_> = settings
+        .agents
+        .iter()
+        .enumerate()
+        .filter(|(_, config)| model.is_none_or(|key| config.has_model(key)))
+        .map(|(i, config)| (i, config, settings.priority_for_at(config, model, now)))
+        .collect();
+    candidates.sort_by_key(|(i, _, priority)| (Reverse(*priority), *i));
+
+    writeln!(writer, "Model: {}", model.unwrap_or("(none)")).ok();
+    for (rank, (_, config, priority)) in candidates.iter().enumerate() {
+        format_priority_entry(writer, rank + 1, config, *priority);
+    }
+    writeln!(writer).ok();
+}
+
+fn write_priority<W: std::io::Write>(writer: &mut W, settings: &Settings) {
+    use std::collections::BTreeSet;
+
+    let now = Local::now();
+
+    let model_keys: BTreeSet<String> = settings
+        .agents
+        .iter()
+        .filter_map(|config| config.models.as_ref())
+        .flat_map(|m| m.keys().cloned())
+        .collect();
+
+    for key in &model_keys {
+        write_model_section(writer, settings, Some(key.as_str()), &now);
+    }
+    write_model_section(writer, settings, None, &now);
+}
+
+fn print_history(
+    since: &str,
+    provider: Option<&str>,
+    graph: bool,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let lookback = history::parse_duration(since)?;
+    let store = history::HistoryStore::open_default()?;
+    let now = Utc::now();
+    let samples = store.samples_since(now - lookback, provider)?;
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&HistoryOutput::new(&samples))?
+        );
+    } else if graph {
+        write_history_graph(&mut std::io::stdout(), &samples, now - lookback, now);
+    } else {
+        write_history(&mut std::io::stdout(), &samples);
+    }
+    Ok(())
+}
+
+fn print_audit(
+    since: &str,
+    domain: Option<&str>,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let cutoff = Utc::now() - history::parse_duration(since)?;
+    let entries: Vec<audit::AuditEntry> = audit::read(&audit::default_path()?)?
+        .into_iter()
+        .filter(|e| e.at >= cutoff && domain.is_none_or(|d| e.domain == d))
+        .collect();
+    if json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&AuditOutput::new(&entries))?
+        );
+    } else {
+        write_audit(&mut std::io::stdout(), &entries);
+    }
+    Ok(())
+}
+
+async fn print_events(since: &str, follow: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let cutoff = Utc::now() - history::parse_duration(since)?;
+    let path = events::default_path()?;
+    for event in events::read(&path)?
+        .into_iter()
+        .filter(|e| e.timestamp >= cutoff)
+    {
+        println!("{}", serde_json::to_string(&event)?);
+    }
+    if follow {
+        events::follow(&path, |event| {
+            if let Ok(line) = serde_json::to_string(&event) {
+                println!("{line}");
+            }
+        })
+        .await?;
+    }
+    Ok(())
+}
+
+fn write_audit<W: std::io::Write>(out: &mut W, entries: &[audit::AuditEntry]) {
+    if entries.is_empty() {
+        let _ = writeln!(out, "No cookie database reads recorded in this period.");
+        return;
+    }
+    let _ = writeln!(
+        out,
+        "{:<19}  {:<10}  {:<10}  {:<20}  DOMAIN",
+        "TIME", "COMMAND", "BROWSER", "PROFILE"
+    );
+    for entry in entries {
+        let _ = writeln!(
+            out,
+            "{:<19}  {:<10}  {:<10}  {:<20}  {}",
+            entry.at.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S"),
+            entry.subcommand,
+            entry.browser,
+            entry.profile,
+            entry.domain,
+        );
+    }
+}
+
+/// The name a subcommand is logged under in the audit log.
+fn subcommand_name(command: Option<&Command>) -> &'static str {
+    match command {
+        None => "run",
+        Some(Command::Login { .. }) => "login",
+        Some(Command::Secrets { .. }) => "secrets",
+        Some(Command::History { .. }) => "history",
+        Some(Command::Audit { .. }) => "audit",
+        Some(Command::Events { .. }) => "events",
+        Some(Command::Usage { .. }) => "usage",
+        Some(Command::Summary) => "summary",
+        Some(Command::Status) => "status",
+        Some(Command::Wait) => "wait",
+        Some(Command::Daemon) => "daemon",
+        Some(Command::Resume) => "resume",
+        Some(Command::Watch { .. }) => "watch",
+        Some(Command::Run { .. }) => "run-file",
+        Some(Command::Queue { .. }) => "queue",
+        Some(Command::Upgrade { .. }) => "upgrade",
+        Some(Command::UpdateCheck) => "update-check",
+    }
+}
+
+fn write_history<W: std::io::Write>(out: &mut W, samples: &[history::Sample]) {
+    if samples.is_empty() {
+        let _ = writeln!(out, "No usage samples recorded in this period.");
+        return;
+    }
+    let _ = writeln!(
+        out,
+        "{:<16}  {:<12}  {:<28}  {:>7}  RESETS",
+        "TIME", "PROVIDER", "WINDOW", "USED"
+    );
+    for sample in samples {
+        let resets = sample.resets_at.map_or_else(
+            || "-".to_string(),
+            |t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string(),
+        );
+        let _ = writeln!(
+            out,
+            "{:<16}  {:<12}  {:<28}  {:>6.1}%  {}{}",
+            sample
+                .recorded_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M"),
+            sample.provider,
+            sample.window,
+            sample.utilization,
+            resets,
+            if sample.limited { "  (limited)" } else { "" },
+        );
+    }
+    write_history_pace(out, samples, Utc::now());
+}
+
+/// Number of columns in each `seher history --graph` sparkline.
+const GRAPH_WIDTH: usize = 48;
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Render utilization buckets (0-100%) as a sparkline; empty buckets are blank.
+fn sparkline(buckets: &[Option<f64>]) -> String {
+    buckets
+        .iter()
+        .map(|bucket| match bucket {
+            None => ' ',
+            Some(utilization) => {
+                #[expect(
+                    clippy::cast_possible_truncation,
+                    clippy::cast_sign_loss,
+                    reason = "clamped to the level range"
+                )]
+                let level = (utilization.clamp(0.0, 100.0) / 100.0 * 7.0).round() as usize;
+                SPARK_LEVELS[level]
+            }
+        })
+        .collect()
+}
+
+/// Bucket each window's samples over `[from, to]` and draw one sparkline per window,
+/// using the peak utilization in each bucket.
+fn write_history_graph<W: std::io::Write>(
+    out: &mut W,
+    samples: &[history::Sample],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) {
+    if samples.is_empty() {
+        let _ = writeln!(out, "No usage samples recorded in this period.");
+        return;
+    }
+    let mut windows: Vec<(&str, &str)> = samples
+        .iter()
+        .map(|s| (s.provider.as_str(), s.window.as_str()))
+        .collect();
+    windows.sort_unstable();
+    windows.dedup();
+
+    let span = (to - from).num_seconds().max(1);
+    for (provider, window) in windows {
+        let mut buckets = vec![None::<f64>; GRAPH_WIDTH];
+        let mut latest = 0.0;
+        for sample in samples
+            .iter()
+            .filter(|s| s.provider == provider && s.window == window)
+        {
+            let offset = (sample.recorded_at - from).num_seconds().clamp(0, span - 1);
+            let index = usize::try_from(offset).unwrap_or(0) * GRAPH_WIDTH
+                / usize::try_from(span).unwrap_or(1);
+            let bucket = &mut buckets[index];
+            *bucket = Some(bucket.map_or(sample.utilization, |v| v.max(sample.utilization)));
+            latest = sample.utilization;
+        }
+        let label = format!("{provider} {window}");
+        let _ = writeln!(out, "{label:<40} |{}| {latest:>5.1}%", sparkline(&buckets));
+    }
+    let _ = writeln!(
+        out,
+        "{:<40}  {} .. {}",
+        "",
+        from.with_timezone(&Local).format("%m-%d %H:%M"),
+        to.with_timezone(&Local).format("%m-%d %H:%M"),
+    );
+}
+
+/// Append the current pace and time-to-limit estimate of each window.
+fn write_history_pace<W: std::io::Write>(
+    out: &mut W,
+    samples: &[history::Sample],
+    now: DateTime<Utc>,
+) {
+    let mut windows: Vec<(&str, &str, &str)> = samples
+        .iter()
+        .map(|s| (s.provider.as_str(), s.account.as_str(), s.window.as_str()))
+        .collect();
+    windows.sort_unstable();
+    windows.dedup();
+    let mut header_written = false;
+    for (provider, account, window) in windows {
+        let series: Vec<history::Sample> = samples
+            .iter()
+            .filter(|s| s.provider == provider && s.account == account && s.window == window)
+            .cloned()
+            .collect();
+        let Some(rate) = history::burn_rate(&series, now) else {
+            continue;
+        };
+        if !header_written {
+            let _ = writeln!(out);
+            header_written = true;
+        }
+        let latest = series.last().map_or(0.0, |s| s.utilization);
+        let estimate = rate.eta.map_or_else(
+            || "not on track to hit the limit before reset".to_string(),
+            |eta| {
+                format!(
+                    "limit reached in {} at the current pace",
+                    history::format_eta(eta, now)
+                )
+            },
+        );
+        let _ = writeln!(
+            out,
+            "{provider} {window}: {latest:.1}% ({:+.1}%/h), {estimate}",
+            rate.per_hour
+        );
+    }
+}
+
+fn print_priority(settings: &Settings) {
+    write_priority(&mut std::io::stdout(), settings);
+}
+
+/// Extra wait when a limit is still reported after its reset time without a new one.
+const REVERIFY_GRACE: chrono::TimeDelta = chrono::TimeDelta::minutes(1);
+
+/// Grace waits allowed before launching anyway, so a stuck reset cannot block forever.
+const MAX_GRACE_WAITS: u32 = 5;
+
+/// How a reset wait ended.
+#[derive(Debug, PartialEq)]
+enum WaitOutcome {
+    /// Launch the agent.
+    Ready,
+    /// The user quit during the wait.
+    Aborted,
 }
 
-fn execute_with_auto_rerun(
-    agents: &[Agent],
-    idx: usize,
-    input: &mut InvocationInput,
-    model: Option<&str>,
-    quiet: bool,
-) {
-    let exit_kind = execute_agent(agents, idx, input, model, quiet);
-    let provider_aware = agents[idx].config.resolve_provider().is_some();
-    if should_auto_rerun(&exit_kind, provider_aware) {
-        if !quiet {
-            eprintln!("Agent failed, retrying...");
+/// Keys accepted while waiting for a reset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum WaitKey {
+    /// Stop waiting and launch the agent now.
+    Skip,
+    /// Re-check usage immediately.
+    Recheck,
+    /// Quit without launching the agent.
+    Abort,
+    /// Print the agent's current usage windows.
+    Details,
+}
+
+impl WaitKey {
+    fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_lowercase() {
+            's' => Some(Self::Skip),
+            'r' => Some(Self::Recheck),
+            'q' => Some(Self::Abort),
+            'd' => Some(Self::Details),
+            _ => None,
         }
-        execute_agent(agents, idx, input, model, quiet);
     }
 }
 
-fn execute_agent(
-    agents: &[Agent],
-    selected_index: usize,
-    input: &mut InvocationInput,
-    model: Option<&str>,
-    quiet: bool,
-) -> ChildExitKind {
-    let selected_agent = &agents[selected_index];
-    let mut final_args = selected_agent.mapped_args(&input.raw_agent_args);
-
-    if let PromptState::Resolved(p) = &input.stdin_prompt {
-        final_args.push(p.clone());
+/// How long each key poll blocks before checking again.
+const KEY_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(200);
+
+async fn wait_for_reset(
+    agent: &Agent,
+    reset_time: DateTime<Utc>,
+    opts: &WaitOptions,
+) -> WaitOutcome {
+    let mut limited = hook_payload(HookEvent::OnLimited, agent);
+    limited.reset_time = Some(reset_time);
+    if opts.hooks.on_limited.is_some() || opts.notifier.wants_events() {
+        limited.window = limited_window(agent).await;
+    }
+    announce(&opts.hooks, &opts.notifier, &limited).await;
+    let keys = KeyInput::enable();
+    if keys.is_some() && !opts.quiet {
+        println!("Keys: [s] start now  [r] re-check  [d] details  [q] quit");
+    }
+    let outcome = wait_until_unlimited(agent, reset_time, opts, keys.as_ref()).await;
+    // Restore the terminal before the agent takes it over.
+    drop(keys);
+    if outcome == WaitOutcome::Ready {
+        if opts.bell {
+            ring_bell();
+        }
+        let reset = hook_payload(HookEvent::OnReset, agent);
+        announce(&opts.hooks, &opts.notifier, &reset).await;
     }
+    outcome
+}
 
-    if matches!(input.stdin_prompt, PromptState::Unresolved)
-        && input.raw_agent_args.is_empty()
-        && !quiet
-    {
-        if input.cached_prompt.is_none() {
-            match prompt_from_editor() {
-                Ok(prompt) => input.cached_prompt = Some(prompt),
-                Err(e) => {
-                    eprintln!("Editor error: {e}");
-                    // SpawnError prevents auto-rerun, which is correct -- the agent was never started.
-                    return ChildExitKind::SpawnError;
+/// Sleep until `reset_time`, then re-check the agent's usage and keep waiting while it is
+/// still limited (clock skew, rolling windows) instead of trusting the predicted reset.
+async fn wait_until_unlimited(
+    agent: &Agent,
+    reset_time: DateTime<Utc>,
+    opts: &WaitOptions,
+    keys: Option<&KeyInput>,
+) -> WaitOutcome {
+    let mut reset_time = reset_time;
+    let mut grace_waits = 0;
+    loop {
+        let limit = tokio::select! {
+            () = sleep_until_reset(reset_time, opts) => agent.check_limit().await,
+            () = poll_until_unlimited(agent, opts.recheck_interval) => {
+                if !opts.quiet {
+                    println!("\n{} is no longer limited; starting early", agent.command());
+                }
+                return WaitOutcome::Ready;
+            }
+            key = next_wait_key(keys) => match key {
+                WaitKey::Skip => return WaitOutcome::Ready,
+                WaitKey::Abort => return WaitOutcome::Aborted,
+                WaitKey::Details => {
+                    print_wait_details(agent).await;
+                    continue;
+                }
+                WaitKey::Recheck => {
+                    println!("\nRe-checking usage for {}...", agent.command());
+                    agent.check_limit().await
+                }
+            },
+        };
+        let limit = match limit {
+            Ok(limit) => limit,
+            Err(e) => {
+                if !opts.quiet {
+                    eprintln!("Could not re-check usage after the wait: {e}");
                 }
+                return WaitOutcome::Ready;
+            }
+        };
+        let now = Utc::now();
+        let Some(next) = next_reset_after_wait(&limit, now) else {
+            return WaitOutcome::Ready;
+        };
+        if next == now + REVERIFY_GRACE {
+            grace_waits += 1;
+            if grace_waits > MAX_GRACE_WAITS {
+                return WaitOutcome::Ready;
             }
         }
-        if let Some(p) = input.cached_prompt.as_deref()
-            && !p.is_empty()
-        {
-            final_args.push(p.to_string());
+        if !opts.quiet {
+            println!(
+                "{} is still limited; waiting until {}",
+                agent.command(),
+                next.format("%Y-%m-%d %H:%M:%S UTC")
+            );
         }
+        reset_time = next;
     }
-
-    let resolved = selected_agent.resolved_args(model);
-    if !quiet {
-        println!(
-            "Executing: {} {}",
-            selected_agent.command(),
-            resolved
-                .iter()
-                .chain(final_args.iter())
-                .map(|s: &String| s.as_str())
-                .collect::<Vec<_>>()
-                .join(" ")
-        );
-    }
-
-    selected_agent.execute(&resolved, &final_args).into()
-}
-
-fn format_agent_identity(config: &AgentConfig) -> String {
-    let provider = config.resolve_provider().unwrap_or("(none)");
-    format!("command={} provider={}", config.command, provider)
 }
 
-fn format_priority_entry<W: std::io::Write>(
-    writer: &mut W,
-    rank: usize,
-    config: &AgentConfig,
-    priority: i32,
-) {
-    let env_display = match &config.env {
-        None => String::new(),
-        Some(env) => {
-            let mut keys: Vec<&str> = env.keys().map(String::as_str).collect();
-            keys.sort_unstable();
-            keys.join(", ")
-        }
+/// Return the next recognized key, or never when key input is unavailable.
+async fn next_wait_key(keys: Option<&KeyInput>) -> WaitKey {
+    let Some(keys) = keys else {
+        return std::future::pending().await;
     };
-    writeln!(
-        writer,
-        "  {}. [priority={:3}] {} env={{{}}}",
-        rank,
-        priority,
-        format_agent_identity(config),
-        env_display
-    )
-    .ok();
+    loop {
+        if let Some(key) = keys
+            .next_key(KEY_POLL_INTERVAL)
+            .await
+            .and_then(WaitKey::from_char)
+        {
+            return key;
+        }
+    }
 }
 
-fn write_model_section<W: std::io::Write>(
-    writer: &mut W,
-    settings: &Settings,
-    model: Option<&str>,
-    now: &DateTime<Local>,
-) {
-    let mut candidates: Vec<_> = settings
-        .agents
-        .iter()
-        .enumerate()
-        .filter(|(_, config)| model.is_none_or(|key| config.has_model(key)))
-        .map(|(i, config)| (i, config, settings.priority_for_at(config, model, now)))
-        .collect();
-    candidates.sort_by_key(|(i, _, priority)| (Reverse(*priority), *i));
-
-    writeln!(writer, "Model: {}", model.unwrap_or("(none)")).ok();
-    for (rank, (_, config, priority)) in candidates.iter().enumerate() {
-        format_priority_entry(writer, rank + 1, config, *priority);
+async fn print_wait_details(agent: &Agent) {
+    println!();
+    match agent.fetch_status().await {
+        Ok(status) => {
+            for entry in &status.usage {
+                let resets = entry.resets_at.map_or_else(String::new, |t| {
+                    format!(
+                        ", resets {}",
+                        t.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+                    )
+                });
+                println!(
+                    "  {} {}: {:.1}%{resets}{}",
+                    status.command,
+                    entry.entry_type,
+                    entry.utilization,
+                    if entry.limited { " (limited)" } else { "" }
+                );
+            }
+        }
+        Err(e) => eprintln!("  Failed to fetch status for {}: {e}", agent.command()),
     }
-    writeln!(writer).ok();
 }
 
-fn write_priority<W: std::io::Write>(writer: &mut W, settings: &Settings) {
-    use std::collections::BTreeSet;
-
-    let now = Local::now();
-
-    let model_keys: BTreeSet<String> = settings
-        .agents
-        .iter()
-        .filter_map(|config| config.models.as_ref())
-        .flat_map(|m| m.keys().cloned())
-        .collect();
-
-    for key in &model_keys {
-        write_model_section(writer, settings, Some(key.as_str()), &now);
+/// Re-check usage every `interval` and return once the agent is no longer limited.
+///
+/// Never returns when `interval` is `None`. Failed checks are ignored so a flaky
+/// network cannot cut a wait short.
+async fn poll_until_unlimited(agent: &Agent, interval: Option<std::time::Duration>) {
+    let Some(interval) = interval else {
+        return std::future::pending().await;
+    };
+    loop {
+        tokio::time::sleep(interval).await;
+        if matches!(agent.check_limit().await, Ok(AgentLimit::NotLimited)) {
+            return;
+        }
     }
-    write_model_section(writer, settings, None, &now);
 }
 
-fn print_priority(settings: &Settings) {
-    write_priority(&mut std::io::stdout(), settings);
+/// When to wake up next after a wait, or `None` if the agent is no longer limited.
+fn next_reset_after_wait(limit: &AgentLimit, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    match limit {
+        AgentLimit::NotLimited => None,
+        AgentLimit::Limited { reset_time } => Some(
+            reset_time
+                .filter(|t| *t > now)
+                .unwrap_or(now + REVERIFY_GRACE),
+        ),
+    }
 }
 
-async fn sleep_until_reset(reset_time: DateTime<Utc>, quiet: bool) {
+async fn sleep_until_reset(reset_time: DateTime<Utc>, opts: &WaitOptions) {
+    if let Some(interval) = opts.progress_events {
+        sleep_with_progress_events(reset_time, interval, opts).await;
+        return;
+    }
+    let quiet = opts.quiet;
     let now = Utc::now();
     if reset_time <= now {
         if !quiet {
@@ -694,8 +3893,157 @@ async fn sleep_until_reset(reset_time: DateTime<Utc>, quiet: bool) {
         );
     }
 
-    let local_reset_time = reset_time.with_timezone(&Local);
-    sleep_until(local_reset_time, quiet).await;
+    let plain = !quiet && opts.progress.plain;
+    let progress = (!quiet && !plain).then(|| new_wait_progress_bar(&opts.progress));
+    let plain_interval = opts
+        .progress
+        .plain_interval_mins
+        .map_or(DEFAULT_PLAIN_PROGRESS_INTERVAL, |m| {
+            std::time::Duration::from_secs(m.saturating_mul(60))
+        });
+    let mut last_line: Option<std::time::Instant> = None;
+    tokio::select! {
+        () = seher::wait::sleep_until(reset_time, |p| {
+            if let Some(bar) = &progress {
+                update_wait_progress_bar(bar, p);
+            } else if plain && last_line.is_none_or(|t| t.elapsed() >= plain_interval) {
+                last_line = Some(std::time::Instant::now());
+                println!("Waiting: {}", format_wait_message(p, &Local::now()));
+            }
+        }) => {}
+        () = remind_before_reset(reset_time, opts) => {}
+    }
+    if let Some(bar) = progress {
+        bar.finish();
+    }
+}
+
+/// Like [`sleep_until_reset`], but reports progress as JSON lines on stdout: a `waiting`
+/// event right away and every `interval`, then a `finished` event.
+async fn sleep_with_progress_events(
+    reset_time: DateTime<Utc>,
+    interval: std::time::Duration,
+    opts: &WaitOptions,
+) {
+    let mut last: Option<std::time::Instant> = None;
+    let mut total = chrono::TimeDelta::zero();
+    tokio::select! {
+        () = seher::wait::sleep_until(reset_time, |p| {
+            total = p.total;
+            if last.is_none_or(|t| t.elapsed() >= interval) {
+                last = Some(std::time::Instant::now());
+                print_wait_event(WaitEventKind::Waiting, p);
+            }
+        }) => {}
+        () = remind_before_reset(reset_time, opts) => {}
+    }
+    print_wait_event(
+        WaitEventKind::Finished,
+        &seher::wait::WaitProgress {
+            deadline: reset_time,
+            total,
+            remaining: chrono::TimeDelta::zero(),
+        },
+    );
+}
+
+fn print_wait_event(kind: WaitEventKind, progress: &seher::wait::WaitProgress) {
+    use std::io::Write;
+    if let Ok(json) = serde_json::to_string(&WaitEvent::new(kind, progress)) {
+        let mut stdout = std::io::stdout();
+        let _ = writeln!(stdout, "{json}");
+        let _ = stdout.flush();
+    }
+}
+
+fn new_wait_progress_bar(config: &ProgressConfig) -> indicatif::ProgressBar {
+    let hz = config.refresh_hz.unwrap_or(DEFAULT_PROGRESS_HZ).max(1);
+    let bar = indicatif::ProgressBar::with_draw_target(
+        Some(1),
+        indicatif::ProgressDrawTarget::stderr_with_hz(hz),
+    );
+    if let Some(style) = wait_progress_style(config) {
+        bar.set_style(style);
+    }
+    bar
+}
+
+/// The configured bar style. An invalid template falls back to the built-in one, and bar
+/// characters that indicatif would reject (fewer than two, or of unequal width) to the
+/// built-in characters.
+fn wait_progress_style(config: &ProgressConfig) -> Option<indicatif::ProgressStyle> {
+    let style = config
+        .template
+        .as_deref()
+        .and_then(|t| indicatif::ProgressStyle::with_template(t).ok())
+        .or_else(|| indicatif::ProgressStyle::with_template(DEFAULT_PROGRESS_TEMPLATE).ok())?;
+    let chars = config
+        .chars
+        .as_deref()
+        .filter(|c| valid_progress_chars(c))
+        .unwrap_or(DEFAULT_PROGRESS_CHARS);
+    Some(style.progress_chars(chars))
+}
+
+/// `true` if indicatif accepts `chars` as bar characters: at least two, all equally wide.
+fn valid_progress_chars(chars: &str) -> bool {
+    let mut widths = chars
+        .chars()
+        .map(|c| unicode_width::UnicodeWidthChar::width(c).unwrap_or(0));
+    let Some(first) = widths.next() else {
+        return false;
+    };
+    chars.chars().count() >= 2 && widths.all(|w| w == first)
+}
+
+fn update_wait_progress_bar(bar: &indicatif::ProgressBar, progress: &seher::wait::WaitProgress) {
+    let total = progress.total.num_seconds().max(1).cast_unsigned();
+    let elapsed = progress.elapsed().num_seconds().max(0).cast_unsigned();
+    bar.set_length(total);
+    bar.set_position(elapsed.min(total));
+    bar.set_message(format_wait_message(progress, &Local::now()));
+}
+
+/// `HH:MM:SS | ETA <time>` with the ETA date shown only when it is not today.
+fn format_wait_message(progress: &seher::wait::WaitProgress, now: &DateTime<Local>) -> String {
+    let secs = (progress.remaining.num_milliseconds().max(0) + 999) / 1000;
+    let end = progress.deadline.with_timezone(&Local);
+    let eta = if end.date_naive() == now.date_naive() {
+        end.format("%H:%M:%S")
+    } else {
+        end.format("%m-%d %H:%M:%S")
+    };
+    format!(
+        "{:02}:{:02}:{:02} | ETA {eta}",
+        secs / 3600,
+        (secs % 3600) / 60,
+        secs % 60
+    )
+}
+
+/// Ring the terminal bell on stderr so it is heard even when stdout is redirected.
+fn ring_bell() {
+    use std::io::Write;
+    let mut stderr = std::io::stderr();
+    let _ = stderr.write_all(b"\x07");
+    let _ = stderr.flush();
+}
+
+/// Notify once `notify_before` ahead of `reset_time`, then wait forever.
+async fn remind_before_reset(reset_time: DateTime<Utc>, opts: &WaitOptions) {
+    if let Some(lead) = opts.notify_before.filter(|_| !opts.reminded.get()) {
+        if let Ok(wait) = (reset_time - lead - Utc::now()).to_std() {
+            tokio::time::sleep(wait).await;
+        }
+        let message = format!(
+            "Usage resets in {} (at {})",
+            history::format_eta(reset_time, Utc::now()),
+            reset_time.with_timezone(&Local).format("%H:%M")
+        );
+        opts.notifier.notify("seher", &message).await;
+        opts.reminded.set(true);
+    }
+    std::future::pending::<()>().await;
 }
 
 #[cfg(test)]
@@ -705,6 +4053,7 @@ async fn sleep_until_reset(reset_time: DateTime<Utc>, quiet: bool) {
 )]
 mod tests {
     use super::*;
+    use clap::CommandFactory;
     use seher::{AgentConfig, PriorityRule, config::ProviderConfig};
     use std::cell::RefCell;
     use std::collections::HashMap;
@@ -722,10 +4071,6 @@ mod tests {
             .unwrap_or_else(|| Local.from_local_datetime(&naive).latest().unwrap())
     }
 
-    fn sample_cookie(name: &str) -> seher::Cookie {
-        sample_cookie_with_value(name, "value", i64::MAX)
-    }
-
     fn sample_cookie_with_value(name: &str, value: &str, expires_utc: i64) -> seher::Cookie {
         seher::Cookie {
             name: name.to_string(),
@@ -739,14 +4084,6 @@ mod tests {
         }
     }
 
-    fn sample_profile(name: &str, browser_type: BrowserType) -> seher::Profile {
-        seher::Profile::new(
-            name.to_string(),
-            PathBuf::from(format!("/tmp/{}/{}", browser_type.name(), name)),
-            browser_type,
-        )
-    }
-
     fn make_sample_agent(
         command: &str,
         provider: Option<ProviderConfig>,
@@ -755,6 +4092,7 @@ mod tests {
         Agent::new(
             AgentConfig {
                 command: command.to_string(),
+                name: None,
                 args: vec![],
                 models,
                 arg_maps: HashMap::new(),
@@ -765,6 +4103,13 @@ mod tests {
                 pre_command: vec![],
                 active: None,
                 inactive: None,
+                detect_rate_limit: false,
+                rate_limit_patterns: vec![],
+                input: PromptInput::Arg,
+                resume_args: vec![],
+                accounts: vec![],
+                retry: None,
+                cooldown: None,
             },
             vec![],
         )
@@ -829,6 +4174,93 @@ mod tests {
         assert_eq!(result[0].command(), "opencode");
     }
 
+    #[test]
+    fn matches_name_checks_command_or_provider() {
+        let opencode = sample_agent(
+            "opencode",
+            Some(ProviderConfig::Explicit("copilot".to_string())),
+        );
+        assert!(matches_name(&opencode, "copilot"));
+        assert!(matches_name(&opencode, "opencode"));
+        assert!(!matches_name(&opencode, "claude"));
+        assert!(matches_name(&sample_agent("claude", None), "claude"));
+
+        let mut named = sample_agent("claude", None);
+        named.config.name = Some("claude-work".to_string());
+        assert!(matches_name(&named, "claude-work"));
+        assert!(matches_name(&named, "claude"));
+        assert!(!matches_name(&opencode, "claude-work"));
+    }
+
+    #[test]
+    fn compose_prompt_skips_editor_when_prompt_is_known() -> TestResult {
+        let mut input = InvocationInput {
+            raw_agent_args: vec!["fix bugs".to_string()],
+            cached_prompt: None,
+            resume_session: false,
+            success_output: false,
+            queue_item: None,
+            stdin_prompt: PromptState::Unresolved,
+        };
+        assert_eq!(
+            compose_prompt(&mut input, false, false)?,
+            FrontMatter::default()
+        );
+        assert!(input.cached_prompt.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn dry_run_shows_a_placeholder_instead_of_opening_the_editor() -> TestResult {
+        let agent = sample_agent("claude", None);
+        let mut input = InvocationInput {
+            raw_agent_args: vec![],
+            cached_prompt: None,
+            resume_session: false,
+            success_output: false,
+            queue_item: None,
+            stdin_prompt: PromptState::Unresolved,
+        };
+        assert_eq!(
+            compose_prompt(&mut input, false, true)?,
+            FrontMatter::default()
+        );
+        assert_eq!(
+            planned_command(&agent, &input, None),
+            "claude <prompt from editor>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn planned_command_includes_mapped_args_and_prompt() {
+        let mut agent = sample_agent("claude", None);
+        agent.config.arg_maps.insert(
+            "--yolo".to_string(),
+            vec!["--dangerously-skip-permissions".to_string()],
+        );
+        let mut input = InvocationInput {
+            raw_agent_args: vec!["--yolo".to_string()],
+            cached_prompt: Some("fix bugs".to_string()),
+            resume_session: false,
+            success_output: false,
+            queue_item: None,
+            stdin_prompt: PromptState::Unresolved,
+        };
+        assert_eq!(
+            planned_command(&agent, &input, Some("opus")),
+            "claude --model opus --dangerously-skip-permissions fix bugs"
+        );
+        agent.config.input = PromptInput::Stdin;
+        assert_eq!(
+            planned_command(&agent, &input, None),
+            "claude --dangerously-skip-permissions (prompt passed on stdin)"
+        );
+        agent.config.resume_args = vec!["--continue".to_string()];
+        input.resume_session = true;
+        assert_eq!(planned_command(&agent, &input, None), "claude --continue");
+    }
+
     #[test]
     fn filter_agents_no_match_returns_empty() {
         let agents = vec![sample_agent("claude", None), sample_agent("codex", None)];
@@ -845,6 +4277,104 @@ mod tests {
         assert!(!should_auto_rerun(&ChildExitKind::Success, true));
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn exec_replace_needs_a_plain_terminal_run() {
+        assert_eq!(exec_replace_blocker(false, false, false), None);
+        assert!(exec_replace_blocker(true, false, false).is_some());
+        assert!(exec_replace_blocker(false, true, false).is_some());
+        assert!(exec_replace_blocker(false, false, true).is_some());
+    }
+
+    #[test]
+    fn exit_code_mirrors_the_agent() {
+        assert_eq!(exit_code(Some(&ChildExitKind::Success)), ExitCode::SUCCESS);
+        assert_eq!(
+            exit_code(Some(&ChildExitKind::Failure { code: Some(3) })),
+            ExitCode::from(3)
+        );
+        assert_eq!(
+            exit_code(Some(&ChildExitKind::SignalTerminated { signal: Some(2) })),
+            ExitCode::from(130)
+        );
+        assert_eq!(
+            exit_code(Some(&ChildExitKind::TimedOut)),
+            ExitCode::from(124)
+        );
+        assert_eq!(exit_code(None), ExitCode::FAILURE);
+    }
+
+    #[test]
+    fn timeout_stop_maps_to_timed_out() {
+        assert_eq!(
+            ChildExitKind::from(Ok(Stop::TimedOut)),
+            ChildExitKind::TimedOut
+        );
+        assert!(should_fail_over(&ChildExitKind::TimedOut));
+        assert!(!should_auto_rerun(&ChildExitKind::TimedOut, true));
+    }
+
+    #[test]
+    fn budget_stop_maps_to_budget_exceeded_and_halts_batches() {
+        let stop = Ok(Stop::Stopped("seven_day > 90%".to_string()));
+        assert_eq!(ChildExitKind::from(stop), ChildExitKind::BudgetExceeded);
+        assert!(halts_batch(&ChildExitKind::BudgetExceeded));
+        assert!(!halts_batch(&ChildExitKind::Failure { code: Some(1) }));
+        assert_eq!(
+            exit_code(Some(&ChildExitKind::BudgetExceeded)),
+            ExitCode::FAILURE
+        );
+    }
+
+    #[test]
+    fn budget_rules_combine_settings_and_flags() -> TestResult {
+        let mut settings = Settings::default();
+        settings.budget = vec!["seven_day > 90%".to_string()];
+        let args = Args::try_parse_from(["seher", "--budget", "claude five_hour > 95", "fix"])?;
+        let rules = budget_rules(&settings, &args)?;
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[1].provider.as_deref(), Some("claude"));
+
+        let args = Args::try_parse_from(["seher", "--budget", "seven_day", "fix"])?;
+        assert!(budget_rules(&Settings::default(), &args).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn dry_run_never_loops() -> TestResult {
+        for flags in [
+            &["--until-success"][..],
+            &["--success-pattern", "DONE"],
+            &["--success-exit-code", "3"],
+        ] {
+            let args = Args::try_parse_from(["seher"].iter().chain(flags).chain(&["fix"]))?;
+            assert!(loop_mode(&args), "{flags:?}");
+            let args =
+                Args::try_parse_from(["seher", "--dry-run"].iter().chain(flags).chain(&["fix"]))?;
+            assert!(!loop_mode(&args), "--dry-run {flags:?}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn retry_covers_only_the_configured_failures() {
+        let retry = AgentRetry {
+            max: 2,
+            backoff: None,
+            on: vec![RetryOn::Timeout],
+        };
+        assert!(retry_applies(&retry, &ChildExitKind::TimedOut));
+        assert!(!retry_applies(
+            &retry,
+            &ChildExitKind::Failure { code: Some(1) }
+        ));
+        assert!(!retry_applies(&retry, &ChildExitKind::Success));
+        assert!(!retry_applies(
+            &retry,
+            &ChildExitKind::SignalTerminated { signal: Some(2) }
+        ));
+    }
+
     #[test]
     fn should_auto_rerun_returns_false_for_spawn_error() {
         assert!(!should_auto_rerun(&ChildExitKind::SpawnError, true));
@@ -852,7 +4382,10 @@ mod tests {
 
     #[test]
     fn should_auto_rerun_returns_false_for_signal_terminated() {
-        assert!(!should_auto_rerun(&ChildExitKind::SignalTerminated, true));
+        assert!(!should_auto_rerun(
+            &ChildExitKind::SignalTerminated { signal: None },
+            true
+        ));
     }
 
     #[test]
@@ -874,57 +4407,9 @@ mod tests {
     #[test]
     fn should_auto_rerun_returns_true_for_provider_aware_failure_with_no_exit_code() {
         assert!(should_auto_rerun(
-            &ChildExitKind::Failure { code: None },
-            true,
-        ));
-    }
-
-    #[test]
-    fn has_session_cookie_recognizes_codex_session_cookie_fragments() {
-        assert!(has_session_cookie(
-            "chatgpt.com",
-            &sample_cookie("__Secure-next-auth.session-token.0"),
-        ));
-        assert!(has_session_cookie(
-            "chatgpt.com",
-            &sample_cookie("__Secure-next-auth.session-token"),
-        ));
-        assert!(!has_session_cookie(
-            "chatgpt.com",
-            &sample_cookie("cf_clearance"),
-        ));
-    }
-
-    #[test]
-    fn collect_candidate_profiles_filters_named_profiles_without_browser_arg() {
-        let chrome_default = sample_profile("Default", BrowserType::Chrome);
-        let chrome_profile = sample_profile("Profile 17", BrowserType::Chrome);
-        let edge_profile = sample_profile("Profile 17", BrowserType::Edge);
-        let firefox_profile = sample_profile("Profile 17", BrowserType::Firefox);
-
-        let profiles = collect_candidate_profiles_with(
-            &[BrowserType::Chrome, BrowserType::Edge, BrowserType::Firefox],
-            None,
-            Some("Profile 17"),
-            |browser_type, profile_name| match (browser_type, profile_name) {
-                (BrowserType::Chrome, "Profile 17") => Some(chrome_profile.clone()),
-                (BrowserType::Edge, "Profile 17") => Some(edge_profile.clone()),
-                (BrowserType::Firefox, "Profile 17") => Some(firefox_profile.clone()),
-                _ => None,
-            },
-            |browser_type| match browser_type {
-                BrowserType::Chrome => vec![chrome_default.clone(), chrome_profile.clone()],
-                BrowserType::Edge => vec![edge_profile.clone()],
-                BrowserType::Firefox => vec![firefox_profile.clone()],
-                _ => vec![],
-            },
-        );
-
-        assert_eq!(profiles.len(), 2);
-        assert_eq!(profiles[0].browser_type, BrowserType::Chrome);
-        assert_eq!(profiles[0].name, "Profile 17");
-        assert_eq!(profiles[1].browser_type, BrowserType::Edge);
-        assert_eq!(profiles[1].name, "Profile 17");
+            &ChildExitKind::Failure { code: None },
+            true,
+        ));
     }
 
     #[tokio::test(flavor = "current_thread")]
@@ -942,7 +4427,7 @@ mod tests {
 
         let selected = select_cookie_candidate(
             "chatgpt.com",
-            vec![invalid, valid.clone()],
+            stream::iter(vec![invalid, valid.clone()]),
             |cookies| async move {
                 let is_valid = cookies.iter().any(|cookie| cookie.value == "valid");
                 (cookies, is_valid)
@@ -955,6 +4440,29 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn select_cookie_candidate_stops_reading_after_a_valid_session() -> TestResult {
+        let valid = vec![sample_cookie_with_value("sessionKey", "first", i64::MAX)];
+        let read = Rc::new(std::cell::Cell::new(0));
+        let read_c = read.clone();
+        let candidates =
+            stream::iter(vec![valid.clone(), valid.clone(), valid]).inspect(move |_| {
+                read_c.set(read_c.get() + 1);
+            });
+
+        let selected =
+            select_cookie_candidate(
+                "claude.ai",
+                candidates,
+                |cookies| async move { (cookies, true) },
+            )
+            .await;
+
+        assert_eq!(selected.ok_or("expected Some")?[0].value, "first");
+        assert_eq!(read.get(), 1);
+        Ok(())
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn select_cookie_candidate_ignores_expired_session_cookies() -> TestResult {
         let expired = vec![sample_cookie_with_value(
@@ -970,7 +4478,7 @@ mod tests {
 
         let selected = select_cookie_candidate(
             "chatgpt.com",
-            vec![expired, valid.clone()],
+            stream::iter(vec![expired, valid.clone()]),
             |cookies| async move { (cookies, true) },
         )
         .await;
@@ -1011,6 +4519,7 @@ mod tests {
         s.agents = vec![
             AgentConfig {
                 command: "claude".to_string(),
+                name: None,
                 args: vec![],
                 models: Some(claude_models),
                 arg_maps: HashMap::new(),
@@ -1025,9 +4534,17 @@ mod tests {
                 pre_command: vec![],
                 active: None,
                 inactive: None,
+                detect_rate_limit: false,
+                rate_limit_patterns: vec![],
+                input: PromptInput::Arg,
+                resume_args: vec![],
+                accounts: vec![],
+                retry: None,
+                cooldown: None,
             },
             AgentConfig {
                 command: "codex".to_string(),
+                name: None,
                 args: vec![],
                 models: None,
                 arg_maps: HashMap::new(),
@@ -1038,6 +4555,13 @@ mod tests {
                 pre_command: vec![],
                 active: None,
                 inactive: None,
+                detect_rate_limit: false,
+                rate_limit_patterns: vec![],
+                input: PromptInput::Arg,
+                resume_args: vec![],
+                accounts: vec![],
+                retry: None,
+                cooldown: None,
             },
         ];
         s
@@ -1199,7 +4723,7 @@ mod tests {
         assert!(status.signal().is_some());
         assert_eq!(
             ChildExitKind::from(Ok(status)),
-            ChildExitKind::SignalTerminated
+            ChildExitKind::SignalTerminated { signal: Some(9) }
         );
         Ok(())
     }
@@ -1246,6 +4770,37 @@ mod tests {
         );
     }
 
+    #[test]
+    fn format_agent_includes_account() {
+        let agent = sample_agent("claude", None).with_account("work");
+        assert_eq!(
+            format_agent(&agent),
+            "command=claude provider=claude account=work"
+        );
+        assert_eq!(agent.label(), "claude (work)");
+        assert_eq!(sample_agent("claude", None).label(), "claude");
+    }
+
+    #[test]
+    fn format_agent_includes_github_user_of_copilot_session() {
+        let cookie = seher::Cookie {
+            name: "dotcom_user".to_string(),
+            value: "octocat".to_string(),
+            domain: ".github.com".to_string(),
+            path: "/".to_string(),
+            expires_utc: 0,
+            is_secure: true,
+            is_httponly: false,
+            same_site: 0,
+        };
+        let agent = Agent::new(sample_agent("copilot", None).config, vec![cookie]);
+        assert_eq!(
+            format_agent(&agent),
+            "command=copilot provider=copilot user=octocat"
+        );
+        assert_eq!(agent.label(), "copilot (octocat)");
+    }
+
     #[test]
     fn format_agent_identity_returns_command_and_explicit_provider() {
         assert_eq!(
@@ -1523,7 +5078,7 @@ mod tests {
     // -----------------------------------------------------------------------
 
     #[tokio::test(flavor = "current_thread")]
-    async fn scan_stops_after_first_available() -> TestResult {
+    async fn scan_returns_first_available_in_priority_order() -> TestResult {
         // Given: codex has highest priority and is not limited
         let settings = sample_settings_with_priority(vec![PriorityRule {
             command: "codex".to_string(),
@@ -1549,7 +5104,7 @@ mod tests {
             checked_clone.borrow_mut().push(idx);
             let is_available = idx == 1;
             Box::pin(async move {
-                Ok::<_, Box<dyn std::error::Error>>(if is_available {
+                Ok::<_, SeherError>(if is_available {
                     AgentLimit::NotLimited
                 } else {
                     AgentLimit::Limited { reset_time: None }
@@ -1558,13 +5113,40 @@ mod tests {
         })
         .await;
 
-        // Then: returns Available at index 1, only that agent was checked
+        // Then: returns Available at index 1; checks were started in priority order
         assert_eq!(result, ScanOutcome::Available { index: 1 });
-        assert_eq!(*checked.borrow(), vec![1]);
+        assert_eq!(checked.borrow().first(), Some(&1));
 
         Ok(())
     }
 
+    #[tokio::test(flavor = "current_thread")]
+    async fn scan_runs_checks_concurrently_and_keeps_priority() {
+        // Given: the highest-priority check is slow, the next one answers immediately
+        let agents = vec![sample_agent("claude", None), sample_agent("codex", None)];
+        let in_flight = Rc::new(std::cell::Cell::new(0));
+        let peak = Rc::new(std::cell::Cell::new(0));
+
+        // When: scanning both
+        let (in_flight_c, peak_c) = (in_flight.clone(), peak.clone());
+        let result = scan_candidates(&agents, vec![0, 1], move |idx| {
+            let (in_flight, peak) = (in_flight_c.clone(), peak_c.clone());
+            Box::pin(async move {
+                in_flight.set(in_flight.get() + 1);
+                peak.set(peak.get().max(in_flight.get()));
+                let delay = if idx == 0 { 50 } else { 0 };
+                tokio::time::sleep(std::time::Duration::from_millis(delay)).await;
+                in_flight.set(in_flight.get() - 1);
+                Ok::<_, SeherError>(AgentLimit::NotLimited)
+            })
+        })
+        .await;
+
+        // Then: both checks overlapped, and the higher-priority agent still wins
+        assert_eq!(peak.get(), 2);
+        assert_eq!(result, ScanOutcome::Available { index: 0 });
+    }
+
     #[tokio::test(flavor = "current_thread")]
     async fn scan_collects_reset_times_when_all_limited() -> TestResult {
         // Given: two agents, both limited with different reset times
@@ -1577,9 +5159,7 @@ mod tests {
             candidate_indices_in_priority_order(&settings, &agents, None, &Local::now());
         let result = scan_candidates(&agents, candidates, |idx| {
             let rt = if idx == 1 { Some(reset_time) } else { None };
-            Box::pin(async move {
-                Ok::<_, Box<dyn std::error::Error>>(AgentLimit::Limited { reset_time: rt })
-            })
+            Box::pin(async move { Ok::<_, SeherError>(AgentLimit::Limited { reset_time: rt }) })
         })
         .await;
 
@@ -1623,7 +5203,7 @@ mod tests {
             checked_clone.borrow_mut().push(idx);
             Box::pin(async move {
                 match idx {
-                    1 => Err::<AgentLimit, Box<dyn std::error::Error>>("network error".into()),
+                    1 => Err::<AgentLimit, SeherError>("network error".into()),
                     0 => Ok(AgentLimit::NotLimited),
                     _ => Ok(AgentLimit::Limited { reset_time: None }),
                 }
@@ -1655,9 +5235,7 @@ mod tests {
         let candidates =
             candidate_indices_in_priority_order(&settings, &agents, None, &Local::now());
         let result = scan_candidates(&agents, candidates, |_| {
-            Box::pin(async {
-                Err::<AgentLimit, Box<dyn std::error::Error>>("network error".into())
-            })
+            Box::pin(async { Err::<AgentLimit, SeherError>("network error".into()) })
         })
         .await;
 
@@ -1666,4 +5244,623 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn login_subcommand_parses_provider() -> TestResult {
+        let args = Args::try_parse_from(["seher", "login", "github"])?;
+        assert!(matches!(
+            args.subcommand,
+            Some(Command::Login {
+                provider: LoginProvider::Github,
+                session_key: false,
+            })
+        ));
+        let args = Args::try_parse_from(["seher", "login", "claude", "--session-key"])?;
+        assert!(matches!(
+            args.subcommand,
+            Some(Command::Login {
+                provider: LoginProvider::Claude,
+                session_key: true,
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn wait_subcommand_keeps_global_agent_selection() -> TestResult {
+        let args = Args::try_parse_from(["seher", "--agent", "codex", "-q", "wait"])?;
+        assert!(matches!(args.subcommand, Some(Command::Wait)));
+        assert_eq!(args.agent.as_deref(), Some("codex"));
+        assert!(args.quiet);
+        Ok(())
+    }
+
+    #[test]
+    fn earliest_reset_skips_agents_without_a_reset_time() {
+        let now = Utc::now();
+        let later = now + chrono::Duration::hours(2);
+        let sooner = now + chrono::Duration::hours(1);
+        assert_eq!(
+            earliest_reset(&[(0, Some(later)), (1, None), (2, Some(sooner))]),
+            Some((2, sooner))
+        );
+        assert_eq!(earliest_reset(&[(0, None)]), None);
+    }
+
+    #[test]
+    fn history_subcommand_defaults_to_seven_days() -> TestResult {
+        let args = Args::try_parse_from(["seher", "history", "--provider", "claude"])?;
+        match args.subcommand {
+            Some(Command::History {
+                since,
+                provider,
+                graph: false,
+            }) => {
+                assert_eq!(since, "7d");
+                assert_eq!(provider.as_deref(), Some("claude"));
+            }
+            _ => return Err("expected history subcommand".into()),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn write_history_lists_samples() -> TestResult {
+        let samples = vec![history::Sample {
+            recorded_at: Utc::now(),
+            provider: "claude".to_string(),
+            account: "acct".to_string(),
+            window: "five_hour".to_string(),
+            utilization: 100.0,
+            limited: true,
+            resets_at: None,
+        }];
+        let mut output = Vec::new();
+        write_history(&mut output, &samples);
+        let output = String::from_utf8(output)?;
+        assert!(output.contains("five_hour"));
+        assert!(output.contains("100.0%"));
+        assert!(output.contains("(limited)"));
+        Ok(())
+    }
+
+    #[test]
+    fn write_audit_lists_reads() -> TestResult {
+        let entries = vec![audit::AuditEntry {
+            at: Utc::now(),
+            browser: "Chrome".to_string(),
+            profile: "Work".to_string(),
+            domain: "claude.ai".to_string(),
+            subcommand: subcommand_name(Some(&Command::Summary)).to_string(),
+        }];
+        let mut output = Vec::new();
+        write_audit(&mut output, &entries);
+        let output = String::from_utf8(output)?;
+        assert!(output.contains("summary"));
+        assert!(output.contains("Work"));
+        assert!(output.contains("claude.ai"));
+
+        let mut output = Vec::new();
+        write_audit(&mut output, &[]);
+        assert!(String::from_utf8(output)?.contains("No cookie database reads"));
+        Ok(())
+    }
+
+    #[test]
+    fn write_history_pace_reports_time_to_limit() -> TestResult {
+        let now = Utc::now();
+        let samples: Vec<history::Sample> = [(60, 40.0), (30, 45.0), (0, 50.0)]
+            .into_iter()
+            .map(|(ago, utilization)| history::Sample {
+                recorded_at: now - chrono::Duration::minutes(ago),
+                provider: "claude".to_string(),
+                account: "acct".to_string(),
+                window: "seven_day".to_string(),
+                utilization,
+                limited: false,
+                resets_at: None,
+            })
+            .collect();
+        let mut output = Vec::new();
+        write_history_pace(&mut output, &samples, now);
+        let output = String::from_utf8(output)?;
+        assert!(output.contains("claude seven_day: 50.0% (+10.0%/h)"));
+        assert!(output.contains("limit reached in ~5h 0m at the current pace"));
+        Ok(())
+    }
+
+    #[test]
+    fn sparkline_maps_utilization_to_levels() {
+        assert_eq!(
+            sparkline(&[Some(0.0), Some(50.0), None, Some(100.0), Some(150.0)]),
+            "▁▅ ██"
+        );
+    }
+
+    #[test]
+    fn write_history_graph_draws_one_line_per_window() -> TestResult {
+        let to = Utc::now();
+        let from = to - chrono::Duration::hours(48);
+        let sample = |hours_ago, window: &str, utilization| history::Sample {
+            recorded_at: to - chrono::Duration::hours(hours_ago),
+            provider: "claude".to_string(),
+            account: "acct".to_string(),
+            window: window.to_string(),
+            utilization,
+            limited: false,
+            resets_at: None,
+        };
+        let samples = vec![
+            sample(48, "seven_day", 0.0),
+            sample(0, "seven_day", 100.0),
+            sample(1, "five_hour", 30.0),
+        ];
+        let mut output = Vec::new();
+        write_history_graph(&mut output, &samples, from, to);
+        let output = String::from_utf8(output)?;
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("claude five_hour"));
+        assert!(lines[1].contains("|▁"));
+        assert!(lines[1].ends_with("█| 100.0%"));
+        Ok(())
+    }
+
+    #[test]
+    fn watch_subcommand_parses_interval() -> TestResult {
+        let args = Args::try_parse_from(["seher", "watch", "--interval", "1m"])?;
+        assert!(matches!(
+            args.subcommand,
+            Some(Command::Watch { ref interval }) if interval == "1m"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn events_subcommand_defaults_to_the_last_hour() -> TestResult {
+        let args = Args::try_parse_from(["seher", "events", "-f"])?;
+        assert!(matches!(
+            args.subcommand,
+            Some(Command::Events { ref since, follow: true }) if since == "1h"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn queue_run_parses_parallelism() -> TestResult {
+        let args = Args::try_parse_from(["seher", "queue", "run", "-p", "3"])?;
+        assert!(matches!(
+            args.subcommand,
+            Some(Command::Queue {
+                action: QueueAction::Run { parallel: 3 }
+            })
+        ));
+        assert!(Args::try_parse_from(["seher", "queue", "run", "--parallel", "0"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn output_flags_are_accepted_after_subcommands() -> TestResult {
+        Args::command().debug_assert();
+        for argv in [
+            &["seher", "queue", "list", "--json"][..],
+            &["seher", "--json", "queue", "list"],
+            &["seher", "queue", "run", "-j", "-q"],
+        ] {
+            let args = Args::try_parse_from(argv)?;
+            assert!(args.json, "{argv:?}");
+        }
+        assert!(Args::try_parse_from(["seher", "status", "--quiet"])?.quiet);
+        Ok(())
+    }
+
+    #[test]
+    fn provider_slots_separate_providers_and_accounts() {
+        let opencode = sample_agent(
+            "opencode",
+            Some(ProviderConfig::Explicit("copilot".to_string())),
+        );
+        assert_eq!(provider_slot(&opencode), "copilot");
+        assert_eq!(provider_slot(&sample_agent("aider", None)), "aider");
+        let work = sample_agent("claude", None).with_account("work");
+        assert_eq!(provider_slot(&work), "claude (work)");
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn parallel_items_skip_busy_slots() -> TestResult {
+        let agents = vec![sample_agent("aider", None), sample_agent("goose", None)];
+        let mut queue = Queue::default();
+        let id = queue.push(
+            "fix bugs".to_string(),
+            vec![],
+            None,
+            None,
+            PathBuf::from("/tmp/project"),
+        );
+        let item = queue.queued().find(|i| i.id == id).ok_or("item missing")?;
+        let args = Args::try_parse_from(["seher", "queue", "run", "-p", "2"])?;
+        let settings = Settings::default();
+
+        let busy = HashSet::from(["aider".to_string()]);
+        let job = plan_parallel_item(&settings, &agents, item, &busy, false, &args)
+            .await
+            .ok_or("expected a job")?;
+        assert_eq!(job.len(), 1);
+        assert_eq!(job[0].command(), "goose");
+        assert_eq!(job[0].working_dir, Some(PathBuf::from("/tmp/project")));
+
+        let busy = HashSet::from(["aider".to_string(), "goose".to_string()]);
+        assert!(
+            plan_parallel_item(&settings, &agents, item, &busy, false, &args)
+                .await
+                .is_none()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn queue_add_parses_prompt_and_agent_args() -> TestResult {
+        let args = Args::try_parse_from([
+            "seher", "queue", "add", "fix bugs", "--agent", "claude", "--", "--chrome",
+        ])?;
+        let Some(Command::Queue {
+            action:
+                QueueAction::Add {
+                    prompt,
+                    agent,
+                    model,
+                    args,
+                },
+        }) = args.subcommand
+        else {
+            return Err("expected queue add".into());
+        };
+        assert_eq!(prompt.as_deref(), Some("fix bugs"));
+        assert_eq!(agent.as_deref(), Some("claude"));
+        assert_eq!(model, None);
+        assert_eq!(args, vec!["--chrome"]);
+        Ok(())
+    }
+
+    #[test]
+    fn run_subcommand_parses_task_file() -> TestResult {
+        let args =
+            Args::try_parse_from(["seher", "run", "--from", "tasks.txt", "--continue-on-error"])?;
+        assert!(matches!(
+            args.subcommand,
+            Some(Command::Run { ref from, continue_on_error: true }) if from == &PathBuf::from("tasks.txt")
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn until_success_flags_parse() -> TestResult {
+        let args = Args::try_parse_from(["seher", "fix", "bugs"])?;
+        assert!(!loop_mode(&args));
+        assert_eq!(args.max_iterations, DEFAULT_MAX_ITERATIONS);
+
+        let args = Args::try_parse_from([
+            "seher",
+            "--success-pattern",
+            "all tests passed",
+            "--max-iterations",
+            "3",
+            "fix",
+        ])?;
+        assert!(loop_mode(&args));
+        assert_eq!(args.max_iterations, 3);
+        assert!(Args::try_parse_from(["seher", "--max-iterations", "0", "fix"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn run_succeeded_checks_exit_code_and_output() -> TestResult {
+        let args = Args::try_parse_from(["seher", "--until-success", "fix"])?;
+        assert!(run_succeeded(&ChildExitKind::Success, false, &args));
+        assert!(!run_succeeded(
+            &ChildExitKind::Failure { code: Some(1) },
+            false,
+            &args
+        ));
+        assert!(!run_succeeded(&ChildExitKind::RateLimited, false, &args));
+
+        let args = Args::try_parse_from([
+            "seher",
+            "--success-exit-code",
+            "2",
+            "--success-pattern",
+            "done",
+            "fix",
+        ])?;
+        assert!(!run_succeeded(&ChildExitKind::Success, true, &args));
+        assert!(!run_succeeded(
+            &ChildExitKind::Failure { code: Some(2) },
+            false,
+            &args
+        ));
+        assert!(run_succeeded(
+            &ChildExitKind::Failure { code: Some(2) },
+            true,
+            &args
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn format_summary_marks_most_constrained_row() {
+        let summary = Summary {
+            rows: vec![
+                seher::output::SummaryRow {
+                    provider: "claude".to_string(),
+                    window: "five_hour".to_string(),
+                    utilization: 42.0,
+                    resets_at: None,
+                    limited: false,
+                },
+                seher::output::SummaryRow {
+                    provider: "copilot".to_string(),
+                    window: "premium_interactions".to_string(),
+                    utilization: 100.0,
+                    resets_at: None,
+                    limited: true,
+                },
+            ],
+            most_constrained: Some(1),
+        };
+        let out = format_summary(&summary);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("  PROVIDER"));
+        assert!(lines[1].starts_with("  claude"));
+        assert!(lines[1].contains(" 42.0%"));
+        assert!(lines[2].starts_with("* copilot"));
+        assert!(lines[2].ends_with("yes"));
+        assert_eq!(format_summary(&Summary::default()), "No usage to show\n");
+    }
+
+    #[test]
+    fn format_usage_lists_each_window() -> TestResult {
+        let status = AgentStatus {
+            command: "claude".to_string(),
+            provider: Some("claude".to_string()),
+            plan: Some("Max 5x".to_string()),
+            identity: Some("me@example.com".to_string()),
+            usage: vec![seher::UsageEntry {
+                entry_type: "five_hour".to_string(),
+                limited: true,
+                utilization: 100.0,
+                resets_at: Some("2026-01-02T03:04:05Z".parse()?),
+                remaining: None,
+                limit: None,
+                limit_eta: None,
+            }],
+        };
+        assert_eq!(
+            format_usage(&status),
+            "claude (Max 5x) as me@example.com\n  five_hour: 100.0% (limited), resets 2026-01-02 03:04:05 UTC\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn format_queue_shows_status_and_outcome() {
+        let mut queue = Queue::default();
+        assert_eq!(format_queue(&queue), "Queue is empty\n");
+        queue.push(
+            "fix bugs\nin the parser".to_string(),
+            vec![],
+            None,
+            None,
+            PathBuf::from("/tmp"),
+        );
+        queue.push(
+            String::new(),
+            vec!["--resume".to_string()],
+            None,
+            None,
+            PathBuf::from("/tmp"),
+        );
+        queue.finish(1, true, "succeeded".to_string());
+        let out = format_queue(&queue);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines[0], "#1    done     fix bugs (succeeded)");
+        assert_eq!(lines[1], "#2    queued   --resume");
+    }
+
+    #[test]
+    fn notify_before_parses_duration() -> TestResult {
+        let args = Args::try_parse_from(["seher", "--notify-before", "10m", "fix"])?;
+        assert_eq!(args.notify_before, Some(chrono::Duration::minutes(10)));
+        assert!(Args::try_parse_from(["seher", "--notify-before", "soon"]).is_err());
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn sleep_until_reset_returns_when_reset_has_passed() {
+        let opts = WaitOptions {
+            quiet: true,
+            notify_before: Some(chrono::Duration::minutes(10)),
+            notifier: Notifier::default(),
+            bell: false,
+            recheck_interval: None,
+            reminded: std::cell::Cell::new(false),
+            progress_events: None,
+            progress: ProgressConfig::default(),
+            hooks: HooksConfig::default(),
+        };
+        sleep_until_reset(Utc::now() - chrono::Duration::seconds(1), &opts).await;
+    }
+
+    #[test]
+    fn next_reset_after_wait_follows_new_reset_or_grace() {
+        let now = Utc::now();
+        assert_eq!(next_reset_after_wait(&AgentLimit::NotLimited, now), None);
+
+        let later = now + chrono::Duration::hours(1);
+        let limit = AgentLimit::Limited {
+            reset_time: Some(later),
+        };
+        assert_eq!(next_reset_after_wait(&limit, now), Some(later));
+
+        let stale = AgentLimit::Limited {
+            reset_time: Some(now - chrono::Duration::seconds(5)),
+        };
+        assert_eq!(
+            next_reset_after_wait(&stale, now),
+            Some(now + REVERIFY_GRACE)
+        );
+        let unknown = AgentLimit::Limited { reset_time: None };
+        assert_eq!(
+            next_reset_after_wait(&unknown, now),
+            Some(now + REVERIFY_GRACE)
+        );
+    }
+
+    #[test]
+    fn recheck_interval_defaults_and_can_be_disabled() -> TestResult {
+        let settings = Settings::default();
+        let args = Args::try_parse_from(["seher", "fix"])?;
+        assert_eq!(
+            WaitOptions::new(&settings, &args).recheck_interval,
+            Some(DEFAULT_RECHECK_INTERVAL)
+        );
+        let args = Args::try_parse_from(["seher", "--recheck-interval", "0m", "fix"])?;
+        assert_eq!(WaitOptions::new(&settings, &args).recheck_interval, None);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_progress_chars_fall_back_to_the_built_in_bar() {
+        assert!(valid_progress_chars("#-"));
+        assert!(valid_progress_chars("█▓░"));
+        assert!(!valid_progress_chars("#"));
+        assert!(!valid_progress_chars("🟩-"));
+        let config = ProgressConfig {
+            template: Some("{bar".to_string()),
+            chars: Some("🟩-".to_string()),
+            ..ProgressConfig::default()
+        };
+        assert!(wait_progress_style(&config).is_some());
+    }
+
+    #[test]
+    fn plain_progress_flag_switches_off_the_bar() -> TestResult {
+        let settings = Settings::default();
+        let args = Args::try_parse_from(["seher", "--plain-progress", "fix"])?;
+        assert!(WaitOptions::new(&settings, &args).progress.plain);
+        Ok(())
+    }
+
+    #[test]
+    fn format_wait_message_rounds_up_remaining_seconds() {
+        let now = Local::now();
+        let deadline = now.with_timezone(&Utc) + chrono::Duration::milliseconds(3_661_500);
+        let progress = seher::wait::WaitProgress {
+            deadline,
+            total: chrono::Duration::hours(2),
+            remaining: chrono::Duration::milliseconds(3_661_500),
+        };
+        let message = format_wait_message(&progress, &now);
+        assert!(message.starts_with("01:01:02 | ETA "), "{message}");
+    }
+
+    #[test]
+    fn wait_key_maps_controls_case_insensitively() {
+        assert_eq!(WaitKey::from_char('s'), Some(WaitKey::Skip));
+        assert_eq!(WaitKey::from_char('R'), Some(WaitKey::Recheck));
+        assert_eq!(WaitKey::from_char('q'), Some(WaitKey::Abort));
+        assert_eq!(WaitKey::from_char('d'), Some(WaitKey::Details));
+        assert_eq!(WaitKey::from_char('x'), None);
+    }
+
+    #[test]
+    fn resume_subcommand_parses() -> TestResult {
+        let args = Args::try_parse_from(["seher", "resume"])?;
+        assert!(matches!(args.subcommand, Some(Command::Resume)));
+        Ok(())
+    }
+
+    #[test]
+    fn fails_over_only_on_failure_or_spawn_error() {
+        assert!(should_fail_over(&ChildExitKind::Failure { code: Some(1) }));
+        assert!(should_fail_over(&ChildExitKind::SpawnError));
+        assert!(should_fail_over(&ChildExitKind::RateLimited));
+        assert!(!should_fail_over(&ChildExitKind::Success));
+        assert!(!should_fail_over(&ChildExitKind::SignalTerminated {
+            signal: Some(2)
+        }));
+    }
+
+    #[test]
+    fn untried_candidates_keeps_priority_order() {
+        assert_eq!(untried_candidates(&[3, 1, 2, 0], &[1, 0]), vec![3, 2]);
+    }
+
+    #[test]
+    fn format_attempt_report_lists_each_attempt() {
+        let agents = vec![sample_agent("claude", None), sample_agent("codex", None)];
+        let report = format_attempt_report(
+            &agents,
+            &[
+                (0, ChildExitKind::Failure { code: Some(2) }),
+                (1, ChildExitKind::Success),
+            ],
+        );
+        assert!(report.contains("1. command=claude"));
+        assert!(report.contains("exited with code 2"));
+        assert!(report.contains("2. command=codex"));
+        assert!(report.contains("succeeded"));
+    }
+
+    #[test]
+    fn apply_template_consumes_referenced_args() {
+        let extra = vec!["src/lib.rs".to_string()];
+        let input = apply_template("Fix {args}", &extra, PromptState::Unresolved);
+        assert!(input.raw_agent_args.is_empty());
+        assert!(
+            matches!(input.stdin_prompt, PromptState::Resolved(ref p) if p == "Fix src/lib.rs")
+        );
+
+        let input = apply_template(
+            "Review this",
+            &extra,
+            PromptState::Resolved("diff".to_string()),
+        );
+        assert_eq!(input.raw_agent_args, extra);
+        assert!(
+            matches!(input.stdin_prompt, PromptState::Resolved(ref p) if p == "Review this\n\ndiff")
+        );
+    }
+
+    #[test]
+    fn output_capture_only_when_needed() {
+        let mut settings = Settings::default();
+        let agent = sample_agent("claude", None);
+        assert!(output_capture(&settings, &agent, &[]).is_none());
+        let success = ["tests passed".to_string()];
+        let capture = output_capture(&settings, &agent, &success);
+        assert!(capture.is_some_and(|c| c.success_patterns == success && c.prefix.is_none()));
+
+        settings.prefix_output = true;
+        settings.output_log = Some(PathBuf::from("/tmp/seher.log"));
+        let capture = output_capture(&settings, &agent, &[]);
+        assert_eq!(
+            capture.as_ref().and_then(|c| c.prefix.as_deref()),
+            Some("[claude] ")
+        );
+        assert!(capture.is_some_and(|c| c.patterns.is_empty()));
+
+        let mut agent = sample_agent("claude", None);
+        agent.config.detect_rate_limit = true;
+        let capture = output_capture(&Settings::default(), &agent, &[]);
+        assert!(capture.is_some_and(|c| c.prefix.is_none() && !c.patterns.is_empty()));
+    }
+
+    #[test]
+    fn prompt_words_are_not_subcommands() -> TestResult {
+        let args = Args::try_parse_from(["seher", "fix", "the", "bug"])?;
+        assert!(args.subcommand.is_none());
+        assert_eq!(args.extra, vec!["fix", "the", "bug"]);
+        Ok(())
+    }
 }
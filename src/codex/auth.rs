@@ -0,0 +1,152 @@
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum CodexAuthError {
+    #[error("could not determine home directory for Codex auth.json")]
+    HomeDirNotFound,
+
+    #[error("Codex auth file not found: {0}")]
+    AuthFileNotFound(String),
+
+    #[error("failed to read Codex auth file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to parse Codex auth file: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("Codex auth.json contains neither ChatGPT tokens nor an OpenAI API key")]
+    MissingCredentials,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuthFile {
+    #[serde(rename = "OPENAI_API_KEY")]
+    openai_api_key: Option<String>,
+    tokens: Option<AuthTokens>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuthTokens {
+    access_token: Option<String>,
+    account_id: Option<String>,
+}
+
+/// Credentials written by `codex login`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CodexCredentials {
+    /// Signed in with a `ChatGPT` account; usage is subject to plan rate limits.
+    ChatGpt {
+        access_token: String,
+        account_id: Option<String>,
+    },
+    /// Signed in with an `OpenAI` API key; billed per token, no plan windows.
+    ApiKey(String),
+}
+
+pub struct CodexAuth;
+
+impl CodexAuth {
+    /// Resolves `$CODEX_HOME/auth.json`, defaulting to `~/.codex/auth.json`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `CODEX_HOME` is unset and the current user's home
+    /// directory cannot be resolved.
+    pub fn default_path() -> Result<PathBuf, CodexAuthError> {
+        if let Some(codex_home) = std::env::var_os("CODEX_HOME") {
+            return Ok(PathBuf::from(codex_home).join("auth.json"));
+        }
+        let home = dirs::home_dir().ok_or(CodexAuthError::HomeDirNotFound)?;
+        Ok(home.join(".codex/auth.json"))
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error when the auth file is missing, unreadable, malformed,
+    /// or does not contain any credentials.
+    pub fn read_credentials() -> Result<CodexCredentials, CodexAuthError> {
+        let path = Self::default_path()?;
+        Self::read_credentials_from(&path)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error when the auth file is missing, unreadable, malformed,
+    /// or does not contain any credentials.
+    pub fn read_credentials_from(path: &Path) -> Result<CodexCredentials, CodexAuthError> {
+        if !path.exists() {
+            return Err(CodexAuthError::AuthFileNotFound(path.display().to_string()));
+        }
+
+        let content = std::fs::read_to_string(path)?;
+        let auth: AuthFile = serde_json::from_str(&content)?;
+
+        if let Some(tokens) = auth.tokens
+            && let Some(access_token) = tokens.access_token.filter(|t| !t.is_empty())
+        {
+            return Ok(CodexCredentials::ChatGpt {
+                access_token,
+                account_id: tokens.account_id,
+            });
+        }
+
+        auth.openai_api_key
+            .filter(|key| !key.is_empty())
+            .map(CodexCredentials::ApiKey)
+            .ok_or(CodexAuthError::MissingCredentials)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn reads_chatgpt_tokens() -> TestResult {
+        let tmp = tempfile::NamedTempFile::new()?;
+        std::fs::write(
+            tmp.path(),
+            r#"{
+                "OPENAI_API_KEY": null,
+                "tokens": {"id_token": "id", "access_token": "at", "refresh_token": "rt", "account_id": "acct"},
+                "last_refresh": "2025-06-15T12:00:00Z"
+            }"#,
+        )?;
+
+        let creds = CodexAuth::read_credentials_from(tmp.path())?;
+        assert_eq!(
+            creds,
+            CodexCredentials::ChatGpt {
+                access_token: "at".to_string(),
+                account_id: Some("acct".to_string()),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reads_api_key_when_tokens_are_absent() -> TestResult {
+        let tmp = tempfile::NamedTempFile::new()?;
+        std::fs::write(tmp.path(), r#"{"OPENAI_API_KEY": "sk-test"}"#)?;
+
+        let creds = CodexAuth::read_credentials_from(tmp.path())?;
+        assert_eq!(creds, CodexCredentials::ApiKey("sk-test".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_auth_file_without_credentials() -> TestResult {
+        let tmp = tempfile::NamedTempFile::new()?;
+        std::fs::write(tmp.path(), r#"{"OPENAI_API_KEY": null, "tokens": null}"#)?;
+
+        let err = CodexAuth::read_credentials_from(tmp.path())
+            .err()
+            .ok_or("expected missing credentials error")?;
+        assert!(matches!(err, CodexAuthError::MissingCredentials));
+        Ok(())
+    }
+}
@@ -1,10 +1,9 @@
 use super::types::CodexUsageResponse;
 use crate::Cookie;
+use crate::error::SeherError;
+use crate::http::BrowserHeaders;
 use serde::Deserialize;
-use std::time::Duration;
 
-const USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) \
-    AppleWebKit/537.36 (KHTML, like Gecko) Chrome/145.0.0.0 Safari/537.36";
 const SESSION_URL: &str = "https://chatgpt.com/api/auth/session";
 const USAGE_URL: &str = "https://chatgpt.com/backend-api/wham/usage";
 const USAGE_REFERER: &str = "https://chatgpt.com/codex/settings/usage";
@@ -25,15 +24,17 @@ impl CodexClient {
     /// parsed.
     pub async fn fetch_usage(
         cookies: &[Cookie],
-    ) -> Result<CodexUsageResponse, Box<dyn std::error::Error>> {
-        let cookie_header = Self::build_cookie_header(cookies);
+    ) -> Result<CodexUsageResponse, Box<dyn std::error::Error + Send + Sync>> {
         let client = Self::build_client()?;
 
-        let access_token = Self::fetch_access_token(&client, &cookie_header).await?;
+        let access_token =
+            Self::fetch_access_token(&client, &Self::build_cookie_header(cookies, SESSION_URL))
+                .await?;
 
         let response = client
             .get(USAGE_URL)
-            .header("Cookie", &cookie_header)
+            .header("User-Agent", BrowserHeaders::current().user_agent())
+            .header("Cookie", Self::build_cookie_header(cookies, USAGE_URL))
             .header("Accept", "application/json")
             .header("Authorization", format!("Bearer {access_token}"))
             .header("Referer", USAGE_REFERER)
@@ -44,7 +45,46 @@ impl CodexClient {
         if !status.is_success() {
             let body = response.text().await.unwrap_or_default();
             let body = Self::truncate_body(&body);
-            return Err(format!("Codex usage API error: {status} - {body}").into());
+            return Err(Box::new(SeherError::Status {
+                service: "Codex usage API",
+                status: status.as_u16(),
+                body,
+            }));
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Fetch usage with a `ChatGPT` access token, such as the one stored by `codex login`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the usage API request fails or the response cannot be parsed.
+    pub async fn fetch_usage_with_token(
+        access_token: &str,
+        account_id: Option<&str>,
+    ) -> Result<CodexUsageResponse, Box<dyn std::error::Error + Send + Sync>> {
+        let client = Self::build_client()?;
+
+        let mut request = client
+            .get(USAGE_URL)
+            .header("User-Agent", BrowserHeaders::current().user_agent())
+            .header("Accept", "application/json")
+            .header("Authorization", format!("Bearer {access_token}"));
+        if let Some(account_id) = account_id {
+            request = request.header("ChatGPT-Account-Id", account_id);
+        }
+        let response = request.send().await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            let body = Self::truncate_body(&body);
+            return Err(Box::new(SeherError::Status {
+                service: "Codex usage API",
+                status: status.as_u16(),
+                body,
+            }));
         }
 
         Ok(response.json().await?)
@@ -55,8 +95,8 @@ impl CodexClient {
     /// Returns an error if the session API request fails or the response cannot be parsed.
     pub async fn session_has_access_token(
         cookies: &[Cookie],
-    ) -> Result<bool, Box<dyn std::error::Error>> {
-        let cookie_header = Self::build_cookie_header(cookies);
+    ) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        let cookie_header = Self::build_cookie_header(cookies, SESSION_URL);
         let client = Self::build_client()?;
         let session = Self::fetch_session(&client, &cookie_header).await?;
 
@@ -64,16 +104,13 @@ impl CodexClient {
     }
 
     fn build_client() -> Result<reqwest::Client, reqwest::Error> {
-        reqwest::Client::builder()
-            .timeout(Duration::from_secs(30))
-            .user_agent(USER_AGENT)
-            .build()
+        crate::http::shared_client()
     }
 
     async fn fetch_access_token(
         client: &reqwest::Client,
         cookie_header: &str,
-    ) -> Result<String, Box<dyn std::error::Error>> {
+    ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
         let session = Self::fetch_session(client, cookie_header).await?;
 
         Self::extract_access_token(session).map_err(|detail| {
@@ -84,9 +121,10 @@ impl CodexClient {
     async fn fetch_session(
         client: &reqwest::Client,
         cookie_header: &str,
-    ) -> Result<SessionResponse, Box<dyn std::error::Error>> {
+    ) -> Result<SessionResponse, Box<dyn std::error::Error + Send + Sync>> {
         let response = client
             .get(SESSION_URL)
+            .header("User-Agent", BrowserHeaders::current().user_agent())
             .header("Cookie", cookie_header)
             .header("Accept", "application/json")
             .send()
@@ -96,7 +134,11 @@ impl CodexClient {
         if !status.is_success() {
             let body = response.text().await.unwrap_or_default();
             let body = Self::truncate_body(&body);
-            return Err(format!("Codex session API error: {status} - {body}").into());
+            return Err(Box::new(SeherError::Status {
+                service: "Codex session API",
+                status: status.as_u16(),
+                body,
+            }));
         }
 
         Ok(response.json().await?)
@@ -111,13 +153,10 @@ impl CodexClient {
         }
     }
 
-    fn build_cookie_header(cookies: &[Cookie]) -> String {
-        cookies
-            .iter()
-            .filter(|c| !c.value.bytes().any(|b| b < 0x20 || b == 0x7f))
-            .map(|c| format!("{}={}", c.name, c.value))
-            .collect::<Vec<_>>()
-            .join("; ")
+    /// The cookies chatgpt.com would get with a request to `url`.
+    fn build_cookie_header(cookies: &[Cookie], url: &str) -> String {
+        let path = url.strip_prefix("https://chatgpt.com").unwrap_or("/");
+        crate::browser::cookie_header(cookies, "chatgpt.com", path)
     }
 
     fn truncate_body(body: &str) -> String {
@@ -1,5 +1,7 @@
+pub mod auth;
 pub mod client;
 pub mod types;
 
+pub use auth::{CodexAuth, CodexCredentials};
 pub use client::CodexClient;
 pub use types::{CodexRateLimit, CodexUsageResponse, CodexWindow};
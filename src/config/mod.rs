@@ -1,9 +1,23 @@
-use chrono::{DateTime, Local};
+use crate::browser::BrowserType;
+#[cfg(feature = "cli")]
+use crate::cron::CronSchedule;
+use crate::error::SeherError;
+use crate::heartbeat::HeartbeatConfig;
+#[cfg(feature = "cli")]
+use crate::hooks::HooksConfig;
+use crate::http::{BaseUrls, BrowserHeaders, RetryPolicy};
+#[cfg(feature = "cli")]
+use crate::notify::NotificationConfig;
+#[cfg(feature = "cli")]
+use crate::statsd::StatsdConfig;
+#[cfg(feature = "cli")]
+use crate::telemetry::TelemetryConfig;
+use chrono::{DateTime, Local, Timelike};
 use jsonc_parser::cst::{
     CstArray, CstContainerNode, CstInputValue, CstLeafNode, CstNode, CstObject, CstRootNode,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -11,6 +25,125 @@ pub struct Settings {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub priority: Vec<PriorityRule>,
     pub agents: Vec<AgentConfig>,
+    /// Retry policy for provider usage requests; defaults apply when omitted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+    /// Use the first browser profile found with a valid session instead of the first in
+    /// profile order (disabled when unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_valid_session: Option<bool>,
+    /// Read cookies only from this Chromium user-data directory or Firefox profile folder
+    /// instead of the detected browsers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_data_dir: Option<PathBuf>,
+    /// Send full browser headers to cookie-based endpoints to reduce Cloudflare challenges.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub impersonate: bool,
+    /// Proxy URL for provider requests (`http://`, `https://`, `socks5://`, `socks5h://`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Timeout in seconds for each provider request (default 30).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Base URL overrides for provider hosts (mock servers, gateways).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_urls: Option<BaseUrls>,
+    /// User-Agent and client hints sent to cookie-based endpoints (Chrome on the host OS
+    /// when unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub browser_headers: Option<BrowserHeaders>,
+    /// Serve cached Claude/Copilot usage younger than this many seconds (disabled when unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_ttl_secs: Option<u64>,
+    /// Reuse session cookies extracted from browsers for this many seconds, encrypted
+    /// under `~/.cache/seher/sessions/` (disabled when unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_cache_ttl_secs: Option<u64>,
+    /// Minimum seconds between usage fetches per provider, e.g. `{"claude": 60}`, shared
+    /// by every running seher process.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub min_fetch_interval_secs: HashMap<String, u64>,
+    /// Record usage samples for `seher history` (enabled when unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<bool>,
+    /// Log every browser cookie database read to `~/.seher/audit.log` (enabled when unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit: Option<bool>,
+    /// Log lifecycle events to `~/.seher/events.log` for `seher events` (enabled when unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub events: Option<bool>,
+    /// Check GitHub once a day for a newer release and print a hint (enabled when unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub update_check: Option<bool>,
+    /// Alert rules for `seher watch`, e.g. `"five_hour > 80%"` or `"copilot premium > 95%"`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub alerts: Vec<String>,
+    /// Points utilization must drop below a threshold before its alert can fire again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alert_hysteresis: Option<f64>,
+    /// Channels used for alerts and reminders.
+    #[cfg(feature = "cli")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notifications: Option<NotificationConfig>,
+    /// Shell commands run on lifecycle events (`on_limited`, `on_reset`, `pre_exec`,
+    /// `post_exec`), each given a JSON payload on stdin.
+    #[cfg(feature = "cli")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<HooksConfig>,
+    /// URL pinged after each `seher watch` poll and `seher daemon` run, so a monitor
+    /// that stops pinging can raise an alert.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub heartbeat: Option<HeartbeatConfig>,
+    /// OTLP/HTTP collector for usage gauges and spans around cookie reads, decryption and
+    /// provider requests (`OTEL_EXPORTER_OTLP_ENDPOINT` when unset).
+    #[cfg(feature = "cli")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub telemetry: Option<TelemetryConfig>,
+    /// StatsD/DogStatsD agent sent utilization and limited gauges after each `seher watch`
+    /// poll.
+    #[cfg(feature = "cli")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub statsd: Option<StatsdConfig>,
+    /// Ring the terminal bell when a reset wait finishes and when the agent exits.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub bell: bool,
+    /// Seconds between usage re-checks while waiting for a reset (default 900, 0 disables).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recheck_interval_secs: Option<u64>,
+    /// Seconds between `--json` progress events while waiting for a reset (default 10).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress_interval_secs: Option<u64>,
+    /// Appearance of the progress bar drawn while waiting for a reset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<ProgressConfig>,
+    /// Try the next available agent when the selected one fails (enabled when unset).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failover: Option<bool>,
+    /// Prefix each line of agent output with `[<command>] `.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub prefix_output: bool,
+    /// Append a copy of all agent output to this file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_log: Option<PathBuf>,
+    /// Named prompt templates for `--template`.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub templates: HashMap<String, String>,
+    /// Utilization ceilings checked while an agent runs, e.g. `"seven_day > 90%"`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub budget: Vec<String>,
+    /// What to do when a `budget` ceiling is crossed.
+    #[serde(default, skip_serializing_if = "BudgetAction::is_stop")]
+    pub budget_action: BudgetAction,
+    /// Seconds between usage polls while a `budget` is set (default 300).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_poll_secs: Option<u64>,
+    /// Prompts `seher daemon` runs on a cron schedule.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schedule: Vec<ScheduledRun>,
+    /// Hours during which seher holds back launching an agent after a reset wait, e.g.
+    /// `{"hours": ["23-31"]}` for 23:00 to 07:00.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub do_not_disturb: Option<ScheduleRule>,
     #[serde(skip)]
     original_text: Option<String>,
 }
@@ -89,6 +222,9 @@ where
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct AgentConfig {
     pub command: String,
+    /// Unique name used to pick this agent with `--agent`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub args: Vec<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -114,6 +250,246 @@ pub struct AgentConfig {
     pub active: Option<ScheduleRule>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub inactive: Option<ScheduleRule>,
+    /// Watch the agent's output for rate-limit messages and wait for the reset when one
+    /// appears. The agent's stdout/stderr are piped through seher while this is on.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub detect_rate_limit: bool,
+    /// Extra case-insensitive substrings that mark a rate limit in the agent's output.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rate_limit_patterns: Vec<String>,
+    /// How the prompt is handed to the agent.
+    #[serde(default, skip_serializing_if = "PromptInput::is_arg")]
+    pub input: PromptInput,
+    /// Arguments that continue the previous session (e.g. `["--continue"]`), used instead
+    /// of the prompt when relaunching after a rate limit interrupted a run.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resume_args: Vec<String>,
+    /// Accounts to rotate through; each is checked and run as a separate candidate, so a
+    /// limited account is skipped in favor of the next one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub accounts: Vec<AgentAccount>,
+    /// Re-run the agent after a failed run, e.g. a network blip or a flaky tool.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<AgentRetry>,
+    /// Minimum rest between runs on the agent's provider and account, e.g. `"10m"`, even
+    /// while it is not limited.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cooldown: Option<String>,
+}
+
+/// When and how often a failed agent run is retried (`retry` on an agent).
+///
+/// The agent's limit is checked again before each retry, so a run that failed because
+/// the provider ran out waits for the reset instead of failing again.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct AgentRetry {
+    /// Retries after the first run.
+    pub max: u32,
+    /// Delay before each retry, e.g. `"2m"` (default `"1m"`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backoff: Option<String>,
+    /// Failures that are retried (default all of them).
+    #[serde(default = "RetryOn::all", skip_serializing_if = "RetryOn::is_all")]
+    pub on: Vec<RetryOn>,
+}
+
+/// A kind of failed run that [`AgentRetry`] retries.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RetryOn {
+    /// The agent exited with a non-zero status.
+    Nonzero,
+    /// The agent ran past `--exec-timeout` and was killed.
+    Timeout,
+}
+
+impl RetryOn {
+    fn all() -> Vec<Self> {
+        vec![Self::Nonzero, Self::Timeout]
+    }
+
+    fn is_all(on: &[Self]) -> bool {
+        Self::all().iter().all(|kind| on.contains(kind))
+    }
+}
+
+impl AgentRetry {
+    /// The delay before each retry.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `backoff` is not a duration like `30m` or `2h`.
+    pub fn backoff(&self) -> Result<std::time::Duration, String> {
+        let Some(backoff) = &self.backoff else {
+            return Ok(std::time::Duration::from_mins(1));
+        };
+        crate::history::parse_duration(backoff)?
+            .to_std()
+            .map_err(|_| format!("negative backoff {backoff:?}"))
+    }
+}
+
+/// One account of an agent, identified by the browser profile holding its session.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct AgentAccount {
+    /// Label shown in messages, e.g. `"work"`.
+    pub name: String,
+    /// Browser to read the account's cookies from (defaults to `--browser`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub browser: Option<BrowserType>,
+    /// Browser profile signed in to the account (defaults to `--profile`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Environment added to the agent's `env` when running as this account, e.g.
+    /// `CLAUDE_CONFIG_DIR` pointing at the account's login.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+}
+
+impl AgentConfig {
+    /// A bare agent whose provider is `provider`, for querying its usage without a settings
+    /// entry.
+    #[must_use]
+    pub fn for_provider(provider: &str) -> Self {
+        Self {
+            command: provider.to_string(),
+            name: None,
+            args: vec![],
+            models: None,
+            arg_maps: HashMap::new(),
+            env: None,
+            provider: Some(ProviderConfig::Explicit(provider.to_string())),
+            openrouter_management_key: None,
+            glm_api_key: None,
+            pre_command: vec![],
+            active: None,
+            inactive: None,
+            detect_rate_limit: false,
+            rate_limit_patterns: vec![],
+            input: PromptInput::Arg,
+            resume_args: vec![],
+            accounts: vec![],
+            retry: None,
+            cooldown: None,
+        }
+    }
+
+    /// This agent as run under `account`: the account's `env` is layered over the agent's.
+    #[must_use]
+    pub fn for_account(&self, account: &AgentAccount) -> Self {
+        let mut config = self.clone();
+        if !account.env.is_empty() {
+            config
+                .env
+                .get_or_insert_with(HashMap::new)
+                .extend(account.env.clone());
+        }
+        config.accounts = vec![];
+        config
+    }
+}
+
+/// How seher passes the prompt to an agent.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptInput {
+    /// Append the prompt as the last command-line argument.
+    #[default]
+    Arg,
+    /// Write the prompt to the agent's stdin and close it.
+    Stdin,
+}
+
+impl PromptInput {
+    #[expect(
+        clippy::trivially_copy_pass_by_ref,
+        reason = "serde skip_serializing_if passes a reference"
+    )]
+    fn is_arg(&self) -> bool {
+        *self == Self::Arg
+    }
+}
+
+/// What seher does when a running agent crosses a `budget` ceiling.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetAction {
+    /// Stop the agent (SIGTERM, then kill after a grace period).
+    #[default]
+    Stop,
+    /// Print a warning and let the agent keep running.
+    Warn,
+}
+
+impl BudgetAction {
+    #[expect(
+        clippy::trivially_copy_pass_by_ref,
+        reason = "serde skip_serializing_if passes a reference"
+    )]
+    fn is_stop(&self) -> bool {
+        *self == Self::Stop
+    }
+}
+
+/// How the wait for a reset is displayed; unset fields keep the built-in bar.
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ProgressConfig {
+    /// indicatif template, e.g. `"[{bar:40}] {msg}"`; `{msg}` is the countdown and ETA.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+    /// Characters for the filled, in-progress and empty parts of `{bar}` (at least two).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chars: Option<String>,
+    /// Redraws per second (default 20).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_hz: Option<u8>,
+    /// Print a plain countdown line now and then instead of drawing a bar, for dumb
+    /// terminals and CI logs.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub plain: bool,
+    /// Minutes between plain countdown lines (default 5).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plain_interval_mins: Option<u64>,
+}
+
+/// A prompt that `seher daemon` runs whenever its cron expression fires.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct ScheduledRun {
+    /// Shown in the daemon's messages; defaults to the cron expression.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Five-field cron expression in local time, e.g. `"0 2 * * *"` for 02:00 every day.
+    pub cron: String,
+    pub prompt: String,
+    /// Only run agents whose name, command or provider matches this name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Extra arguments for the agent.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Wait for a reset when every candidate agent is limited, instead of skipping this run.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub wait: bool,
+}
+
+impl ScheduledRun {
+    #[cfg(feature = "cli")]
+    /// The parsed `cron` expression.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `cron` is not a valid expression.
+    pub fn cron_schedule(&self) -> Result<CronSchedule, String> {
+        self.cron.parse()
+    }
+
+    #[must_use]
+    pub fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.cron)
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, PartialEq, Eq)]
@@ -153,9 +529,30 @@ impl ScheduleRule {
         schedule_matches_at(self.weekdays.as_deref(), self.hours.as_deref(), now)
     }
 
-    fn validate(&self, label: &str) -> Result<(), Box<dyn std::error::Error>> {
+    /// When the window containing `now` ends: the first whole hour after `now` that the
+    /// rule does not match. `None` if `now` is outside the window, or if the window never
+    /// ends (e.g. it covers every hour).
+    #[must_use]
+    pub fn window_end_after(&self, now: &DateTime<Local>) -> Option<DateTime<Local>> {
+        if !self.matches_at(now) {
+            return None;
+        }
+        let mut t = now.with_minute(0)?.with_second(0)?.with_nanosecond(0)?;
+        // A week covers every weekday and hour combination.
+        for _ in 0..7 * 24 {
+            t += chrono::Duration::hours(1);
+            if !self.matches_at(&t) {
+                return Some(t);
+            }
+        }
+        None
+    }
+
+    fn validate(&self, label: &str) -> Result<(), String> {
         if self.weekdays.is_none() && self.hours.is_none() {
-            return Err(format!("{label}: must specify at least one of weekdays or hours").into());
+            return Err(format!(
+                "{label}: must specify at least one of weekdays or hours"
+            ));
         }
         validate_schedule_rule(self.weekdays.as_deref(), self.hours.as_deref(), label)
     }
@@ -188,11 +585,28 @@ fn provider_to_domain(provider: &str) -> Option<&str> {
         "claude" => Some("claude.ai"),
         "codex" => Some("chatgpt.com"),
         "copilot" => Some("github.com"),
+        "anthropic" => Some("console.anthropic.com"),
         _ => None,
     }
 }
 
 impl AgentConfig {
+    /// The rest between runs from `cooldown`, if set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `cooldown` is not a duration like `10m` or `1h`.
+    pub fn cooldown(&self) -> Result<Option<std::time::Duration>, String> {
+        self.cooldown
+            .as_deref()
+            .map(|cooldown| {
+                crate::history::parse_duration(cooldown)?
+                    .to_std()
+                    .map_err(|_| format!("negative cooldown {cooldown:?}"))
+            })
+            .transpose()
+    }
+
     #[must_use]
     pub fn resolve_provider(&self) -> Option<&str> {
         resolve_provider(&self.command, self.provider.as_ref())
@@ -267,6 +681,7 @@ impl Default for Settings {
             priority: vec![],
             agents: vec![AgentConfig {
                 command: "claude".to_string(),
+                name: None,
                 args: vec![],
                 models: None,
                 arg_maps: HashMap::new(),
@@ -277,7 +692,53 @@ impl Default for Settings {
                 pre_command: vec![],
                 active: None,
                 inactive: None,
+                detect_rate_limit: false,
+                rate_limit_patterns: vec![],
+                input: PromptInput::Arg,
+                resume_args: vec![],
+                accounts: vec![],
+                retry: None,
+                cooldown: None,
             }],
+            retry: None,
+            impersonate: false,
+            proxy: None,
+            timeout_secs: None,
+            base_urls: None,
+            browser_headers: None,
+            cache_ttl_secs: None,
+            session_cache_ttl_secs: None,
+            min_fetch_interval_secs: HashMap::new(),
+            history: None,
+            audit: None,
+            events: None,
+            update_check: None,
+            alerts: vec![],
+            alert_hysteresis: None,
+            #[cfg(feature = "cli")]
+            notifications: None,
+            #[cfg(feature = "cli")]
+            hooks: None,
+            heartbeat: None,
+            #[cfg(feature = "cli")]
+            telemetry: None,
+            #[cfg(feature = "cli")]
+            statsd: None,
+            bell: false,
+            recheck_interval_secs: None,
+            progress_interval_secs: None,
+            progress: None,
+            failover: None,
+            prefix_output: false,
+            output_log: None,
+            templates: HashMap::new(),
+            budget: vec![],
+            budget_action: BudgetAction::Stop,
+            budget_poll_secs: None,
+            schedule: vec![],
+            do_not_disturb: None,
+            first_valid_session: None,
+            user_data_dir: None,
             original_text: None,
         }
     }
@@ -539,11 +1000,11 @@ fn validate_schedule_rule(
     weekdays: Option<&[String]>,
     hours: Option<&[String]>,
     label: &str,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), String> {
     // Hours use half-open [start, end) intervals, so start == end would be an empty range.
     if let Some(hour_ranges) = hours {
         if hour_ranges.is_empty() {
-            return Err(format!("hours array in {label} must not be empty").into());
+            return Err(format!("hours array in {label} must not be empty"));
         }
         for range_str in hour_ranges {
             let (start, end) = parse_schedule_range(range_str)
@@ -551,21 +1012,19 @@ fn validate_schedule_rule(
             if start >= end {
                 return Err(format!(
                     "invalid hours range in {label} {range_str:?}: start must be less than end"
-                )
-                .into());
+                ));
             }
             if end > 48 {
                 return Err(format!(
                     "invalid hours range in {label} {range_str:?}: end must not exceed 48"
-                )
-                .into());
+                ));
             }
         }
     }
     // Weekdays use inclusive [start, end] intervals, so start == end is a single day (valid).
     if let Some(wd_ranges) = weekdays {
         if wd_ranges.is_empty() {
-            return Err(format!("weekdays array in {label} must not be empty").into());
+            return Err(format!("weekdays array in {label} must not be empty"));
         }
         for range_str in wd_ranges {
             let (start, end) = parse_schedule_range(range_str)
@@ -573,14 +1032,12 @@ fn validate_schedule_rule(
             if start > end {
                 return Err(format!(
                     "invalid weekdays range in {label} {range_str:?}: start must not exceed end"
-                )
-                .into());
+                ));
             }
             if end > 6 {
                 return Err(format!(
                     "invalid weekdays range in {label} {range_str:?}: end must not exceed 6"
-                )
-                .into());
+                ));
             }
         }
     }
@@ -624,7 +1081,7 @@ impl Settings {
             .map_or(0, |rule| rule.priority)
     }
 
-    fn validate_priority_schedule(&self) -> Result<(), Box<dyn std::error::Error>> {
+    fn validate_priority_schedule(&self) -> Result<(), String> {
         for rule in &self.priority {
             validate_schedule_rule(
                 rule.weekdays.as_deref(),
@@ -635,14 +1092,32 @@ impl Settings {
         Ok(())
     }
 
-    fn validate_agent_schedules(&self) -> Result<(), Box<dyn std::error::Error>> {
+    fn validate_agent_names(&self) -> Result<(), String> {
+        let mut seen = HashSet::new();
+        for name in self.agents.iter().filter_map(|a| a.name.as_deref()) {
+            if !seen.insert(name) {
+                return Err(format!("agent name {name:?} is used more than once"));
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    fn validate_scheduled_runs(&self) -> Result<(), String> {
+        for run in &self.schedule {
+            run.cron_schedule()
+                .map_err(|e| format!("schedule {:?}: invalid cron: {e}", run.label()))?;
+        }
+        Ok(())
+    }
+
+    fn validate_agent_schedules(&self) -> Result<(), String> {
         for agent in &self.agents {
             if agent.active.is_some() && agent.inactive.is_some() {
                 return Err(format!(
                     "agent {:?}: cannot have both active and inactive schedules",
                     agent.command
-                )
-                .into());
+                ));
             }
             if let Some(active) = &agent.active {
                 active.validate(&format!("agent {:?} active schedule", agent.command))?;
@@ -650,6 +1125,14 @@ impl Settings {
             if let Some(inactive) = &agent.inactive {
                 inactive.validate(&format!("agent {:?} inactive schedule", agent.command))?;
             }
+            if let Some(retry) = &agent.retry {
+                retry
+                    .backoff()
+                    .map_err(|e| format!("agent {:?} retry backoff: {e}", agent.command))?;
+            }
+            agent
+                .cooldown()
+                .map_err(|e| format!("agent {:?} cooldown: {e}", agent.command))?;
         }
         Ok(())
     }
@@ -657,7 +1140,7 @@ impl Settings {
     /// # Errors
     ///
     /// Returns an error if the settings file cannot be read or parsed.
-    pub fn load(path: Option<&Path>) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn load(path: Option<&Path>) -> Result<Self, SeherError> {
         let path = match path {
             Some(p) => p.to_path_buf(),
             None => Self::settings_path()?,
@@ -674,13 +1157,28 @@ impl Settings {
         std::io::Read::read_to_string(&mut stripped, &mut json_str)?;
         let clean = strip_trailing_commas(&json_str);
         let mut settings: Settings = serde_json::from_str(&clean)?;
-        settings.validate_priority_schedule()?;
-        settings.validate_agent_schedules()?;
+        settings
+            .validate_priority_schedule()
+            .map_err(SeherError::Config)?;
+        settings
+            .validate_agent_schedules()
+            .map_err(SeherError::Config)?;
+        settings
+            .validate_agent_names()
+            .map_err(SeherError::Config)?;
+        #[cfg(feature = "cli")]
+        settings
+            .validate_scheduled_runs()
+            .map_err(SeherError::Config)?;
+        if let Some(rule) = &settings.do_not_disturb {
+            rule.validate("do_not_disturb")
+                .map_err(SeherError::Config)?;
+        }
         settings.original_text = Some(content);
         Ok(settings)
     }
 
-    fn save_with_cst(&self, original: &str) -> Result<String, Box<dyn std::error::Error>> {
+    fn save_with_cst(&self, original: &str) -> Result<String, SeherError> {
         let root = CstRootNode::parse(original, &jsonc_parser::ParseOptions::default())
             .map_err(|e| e.to_string())?;
         let root_obj = root.object_value_or_set();
@@ -698,7 +1196,7 @@ impl Settings {
     /// # Errors
     ///
     /// Returns an error if serialization or file writing fails.
-    pub fn save(&self, path: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    pub fn save(&self, path: Option<&Path>) -> Result<(), SeherError> {
         let path = match path {
             Some(p) => p.to_path_buf(),
             None => Self::settings_path()?,
@@ -757,7 +1255,7 @@ impl Settings {
         });
     }
 
-    fn settings_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    fn settings_path() -> Result<PathBuf, SeherError> {
         let home = dirs::home_dir().ok_or("HOME directory not found")?;
         let dir = home.join(".config").join("seher");
         let jsonc_path = dir.join("settings.jsonc");
@@ -931,6 +1429,137 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_load_rejects_duplicate_agent_names() -> TestResult {
+        let json = r#"{"agents": [
+            {"command": "claude", "name": "main"},
+            {"command": "codex", "name": "main"}
+        ]}"#;
+        let tmp = tempfile::NamedTempFile::new()?;
+        std::fs::write(tmp.path(), json)?;
+
+        let result = Settings::load(Some(tmp.path()));
+        assert!(matches!(result, Err(SeherError::Config(_))));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_load_rejects_invalid_cron() -> TestResult {
+        let tmp = tempfile::NamedTempFile::new()?;
+        std::fs::write(
+            tmp.path(),
+            r#"{"agents": [], "schedule": [{"cron": "0 2 * * *", "prompt": "refactor", "agent": "claude"}]}"#,
+        )?;
+        let settings = Settings::load(Some(tmp.path()))?;
+        assert_eq!(settings.schedule[0].label(), "0 2 * * *");
+        assert!(!settings.schedule[0].wait);
+
+        std::fs::write(
+            tmp.path(),
+            r#"{"agents": [], "schedule": [{"cron": "0 25 * * *", "prompt": "refactor"}]}"#,
+        )?;
+        let result = Settings::load(Some(tmp.path()));
+        assert!(matches!(result, Err(SeherError::Config(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_agent_retry_defaults_and_validation() -> TestResult {
+        let tmp = tempfile::NamedTempFile::new()?;
+        std::fs::write(
+            tmp.path(),
+            r#"{"agents": [{"command": "claude", "retry": {"max": 3}}]}"#,
+        )?;
+        let settings = Settings::load(Some(tmp.path()))?;
+        let retry = settings.agents[0].retry.as_ref().ok_or("retry missing")?;
+        assert_eq!(retry.max, 3);
+        assert_eq!(retry.on, [RetryOn::Nonzero, RetryOn::Timeout]);
+        assert_eq!(retry.backoff()?, std::time::Duration::from_mins(1));
+        assert_eq!(serde_json::to_value(retry)?, serde_json::json!({"max": 3}));
+
+        std::fs::write(
+            tmp.path(),
+            r#"{"agents": [{"command": "claude", "retry": {"max": 3, "backoff": "2x"}}]}"#,
+        )?;
+        let result = Settings::load(Some(tmp.path()));
+        assert!(matches!(result, Err(SeherError::Config(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_agent_cooldown_parses_and_validates() -> TestResult {
+        let tmp = tempfile::NamedTempFile::new()?;
+        std::fs::write(
+            tmp.path(),
+            r#"{"agents": [{"command": "claude", "cooldown": "10m"}, {"command": "codex"}]}"#,
+        )?;
+        let settings = Settings::load(Some(tmp.path()))?;
+        assert_eq!(
+            settings.agents[0].cooldown()?,
+            Some(std::time::Duration::from_mins(10))
+        );
+        assert_eq!(settings.agents[1].cooldown()?, None);
+
+        std::fs::write(
+            tmp.path(),
+            r#"{"agents": [{"command": "claude", "cooldown": "soon"}]}"#,
+        )?;
+        let result = Settings::load(Some(tmp.path()));
+        assert!(matches!(result, Err(SeherError::Config(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_input_mode_defaults_to_arg() -> TestResult {
+        let json = r#"{"agents": [{"command": "claude"}, {"command": "llm", "input": "stdin"}]}"#;
+        let settings: Settings = serde_json::from_str(json)?;
+
+        assert_eq!(settings.agents[0].input, PromptInput::Arg);
+        assert_eq!(settings.agents[1].input, PromptInput::Stdin);
+        let round_trip = serde_json::to_value(&settings.agents[0])?;
+        assert!(round_trip.get("input").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_for_account_layers_account_env() -> TestResult {
+        let json = r#"{"agents": [{
+            "command": "claude",
+            "env": {"A": "1", "B": "agent"},
+            "accounts": [
+                {"name": "work", "profile": "Profile 1", "env": {"B": "work"}},
+                {"name": "personal", "browser": "firefox"}
+            ]
+        }]}"#;
+        let settings: Settings = serde_json::from_str(json)?;
+        let agent = &settings.agents[0];
+        assert_eq!(agent.accounts[0].profile.as_deref(), Some("Profile 1"));
+        assert_eq!(agent.accounts[1].browser, Some(BrowserType::Firefox));
+
+        let work = agent.for_account(&agent.accounts[0]);
+        let env = work.env.ok_or("env missing")?;
+        assert_eq!(env.get("A").map(String::as_str), Some("1"));
+        assert_eq!(env.get("B").map(String::as_str), Some("work"));
+        assert!(work.accounts.is_empty());
+
+        let personal = agent.for_account(&agent.accounts[1]);
+        assert_eq!(personal.env, agent.env);
+        Ok(())
+    }
+
+    #[test]
+    fn test_budget_action_defaults_to_stop() -> TestResult {
+        let settings: Settings = serde_json::from_str(r#"{"agents": []}"#)?;
+        assert_eq!(settings.budget_action, BudgetAction::Stop);
+        let settings: Settings = serde_json::from_str(
+            r#"{"agents": [], "budget": ["seven_day > 90%"], "budget_action": "warn"}"#,
+        )?;
+        assert_eq!(settings.budget_action, BudgetAction::Warn);
+        assert_eq!(settings.budget, vec!["seven_day > 90%"]);
+        Ok(())
+    }
+
     #[test]
     fn test_provider_field_null() -> TestResult {
         let json = r#"{"agents": [{"command": "claude", "provider": null}]}"#;
@@ -1238,6 +1867,23 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_anthropic_provider_reads_console_cookies() -> TestResult {
+        // Given: agent with explicit "anthropic" provider
+        let json = r#"{"agents": [{"command": "my-batch-job", "provider": "anthropic"}]}"#;
+
+        // When: provider and domain resolved
+        let settings: Settings = serde_json::from_str(json)?;
+
+        // Then: cookies are read from the Anthropic Console
+        assert_eq!(settings.agents[0].resolve_provider(), Some("anthropic"));
+        assert_eq!(
+            settings.agents[0].resolve_domain(),
+            Some("console.anthropic.com")
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_parse_settings_with_pre_command() -> TestResult {
         let json =
@@ -1248,6 +1894,20 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_settings_with_partial_retry_policy() -> TestResult {
+        let json = r#"{"agents": [], "retry": {"max_attempts": 5}}"#;
+        let settings: Settings = serde_json::from_str(json)?;
+
+        let retry = settings.retry.ok_or("retry should be parsed")?;
+        assert_eq!(retry.max_attempts, 5);
+        assert_eq!(
+            retry.initial_backoff_ms,
+            RetryPolicy::default().initial_backoff_ms
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_pre_command_defaults_to_empty_when_absent() -> TestResult {
         let json = r#"{"agents": [{"command": "claude"}]}"#;
@@ -1416,6 +2076,7 @@ mod tests {
         let mut settings = Settings::load(Some(tmp.path()))?;
         settings.agents.push(AgentConfig {
             command: "codex".to_string(),
+            name: None,
             args: vec![],
             models: None,
             arg_maps: HashMap::new(),
@@ -1426,6 +2087,13 @@ mod tests {
             pre_command: vec![],
             active: None,
             inactive: None,
+            detect_rate_limit: false,
+            rate_limit_patterns: vec![],
+            input: PromptInput::Arg,
+            resume_args: vec![],
+            accounts: vec![],
+            retry: None,
+            cooldown: None,
         });
         settings.save(Some(tmp.path()))?;
 
@@ -1754,6 +2422,45 @@ mod tests {
         let settings = Settings {
             priority: vec![rule],
             agents: vec![],
+            retry: None,
+            impersonate: false,
+            proxy: None,
+            timeout_secs: None,
+            base_urls: None,
+            browser_headers: None,
+            cache_ttl_secs: None,
+            session_cache_ttl_secs: None,
+            min_fetch_interval_secs: HashMap::new(),
+            history: None,
+            audit: None,
+            events: None,
+            update_check: None,
+            alerts: vec![],
+            alert_hysteresis: None,
+            #[cfg(feature = "cli")]
+            notifications: None,
+            #[cfg(feature = "cli")]
+            hooks: None,
+            heartbeat: None,
+            #[cfg(feature = "cli")]
+            telemetry: None,
+            #[cfg(feature = "cli")]
+            statsd: None,
+            bell: false,
+            recheck_interval_secs: None,
+            progress_interval_secs: None,
+            progress: None,
+            failover: None,
+            prefix_output: false,
+            output_log: None,
+            templates: HashMap::new(),
+            budget: vec![],
+            budget_action: BudgetAction::Stop,
+            budget_poll_secs: None,
+            schedule: vec![],
+            do_not_disturb: None,
+            first_valid_session: None,
+            user_data_dir: None,
             original_text: None,
         };
 
@@ -1780,6 +2487,45 @@ mod tests {
         let settings = Settings {
             priority: vec![rule],
             agents: vec![],
+            retry: None,
+            impersonate: false,
+            proxy: None,
+            timeout_secs: None,
+            base_urls: None,
+            browser_headers: None,
+            cache_ttl_secs: None,
+            session_cache_ttl_secs: None,
+            min_fetch_interval_secs: HashMap::new(),
+            history: None,
+            audit: None,
+            events: None,
+            update_check: None,
+            alerts: vec![],
+            alert_hysteresis: None,
+            #[cfg(feature = "cli")]
+            notifications: None,
+            #[cfg(feature = "cli")]
+            hooks: None,
+            heartbeat: None,
+            #[cfg(feature = "cli")]
+            telemetry: None,
+            #[cfg(feature = "cli")]
+            statsd: None,
+            bell: false,
+            recheck_interval_secs: None,
+            progress_interval_secs: None,
+            progress: None,
+            failover: None,
+            prefix_output: false,
+            output_log: None,
+            templates: HashMap::new(),
+            budget: vec![],
+            budget_action: BudgetAction::Stop,
+            budget_poll_secs: None,
+            schedule: vec![],
+            do_not_disturb: None,
+            first_valid_session: None,
+            user_data_dir: None,
             original_text: None,
         };
 
@@ -1857,7 +2603,10 @@ mod tests {
 
         // When/Then: load returns an error
         let result = Settings::load(Some(tmp.path()));
-        assert!(result.is_err(), "expected load error for hours end > 48");
+        assert!(
+            matches!(result, Err(SeherError::Config(_))),
+            "expected settings error for hours end > 48"
+        );
         Ok(())
     }
 
@@ -2324,6 +3073,7 @@ mod tests {
     ) -> AgentConfig {
         AgentConfig {
             command: command.to_string(),
+            name: None,
             args: vec![],
             models: None,
             arg_maps: HashMap::new(),
@@ -2334,6 +3084,13 @@ mod tests {
             pre_command: vec![],
             active,
             inactive,
+            detect_rate_limit: false,
+            rate_limit_patterns: vec![],
+            input: PromptInput::Arg,
+            resume_args: vec![],
+            accounts: vec![],
+            retry: None,
+            cooldown: None,
         }
     }
 
@@ -2352,6 +3109,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_window_end_after_finds_the_end_of_overnight_hours() {
+        let rule = make_schedule_rule(None, Some(vec!["23-31"]));
+        let at_3am = make_local_dt(2024, 1, 9, 3);
+        assert_eq!(
+            rule.window_end_after(&at_3am),
+            Some(make_local_dt(2024, 1, 9, 7))
+        );
+        assert_eq!(rule.window_end_after(&make_local_dt(2024, 1, 9, 12)), None);
+        let always = make_schedule_rule(None, Some(vec!["0-24"]));
+        assert_eq!(always.window_end_after(&at_3am), None);
+    }
+
     #[test]
     fn test_is_active_at_no_schedule_always_active() {
         let agent = make_agent_config_with_schedule("claude", None, None);
@@ -0,0 +1,179 @@
+use crate::browser::{BrowserDetector, BrowserType, Cookie};
+use crate::error::SeherError;
+use crate::session;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::Pin;
+
+/// Future returned by [`CookieStore::cookies`].
+pub type CookieFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<Vec<Cookie>, SeherError>> + Send + 'a>>;
+
+/// A source of session cookies for a domain.
+///
+/// [`crate::Agent::from_store`] builds an agent from any store, so cookies can come from a
+/// browser profile, the environment, a file or a test fixture without touching the
+/// provider code.
+pub trait CookieStore: Send + Sync {
+    /// The cookies to send to `domain`.
+    fn cookies<'a>(&'a self, domain: &'a str) -> CookieFuture<'a>;
+}
+
+/// A fixed set of cookies, returned for every domain.
+impl CookieStore for Vec<Cookie> {
+    fn cookies<'a>(&'a self, _domain: &'a str) -> CookieFuture<'a> {
+        Box::pin(async move { Ok(self.clone()) })
+    }
+}
+
+/// Reads the first signed-in session from the detected browser profiles, like
+/// [`session::find_session`].
+#[derive(Debug, Clone, Default)]
+pub struct BrowserCookieStore {
+    browser: Option<BrowserType>,
+    profile: Option<String>,
+}
+
+impl BrowserCookieStore {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only look in this browser.
+    #[must_use]
+    pub fn browser(mut self, browser: BrowserType) -> Self {
+        self.browser = Some(browser);
+        self
+    }
+
+    /// Only look in the profile with this name.
+    #[must_use]
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+}
+
+impl CookieStore for BrowserCookieStore {
+    fn cookies<'a>(&'a self, domain: &'a str) -> CookieFuture<'a> {
+        let store = self.clone();
+        let domain = domain.to_string();
+        Box::pin(async move {
+            // Opening and decrypting cookie databases blocks.
+            tokio::task::spawn_blocking(move || {
+                let detector = BrowserDetector::new();
+                let profiles = session::candidate_profiles(
+                    &detector,
+                    &detector.detect_browsers(),
+                    store.browser,
+                    store.profile.as_deref(),
+                );
+                session::first_session(profiles, &domain, session::session_cookie_names(&domain))
+                    .map(|(_, cookies)| cookies)
+                    .ok_or(SeherError::SessionNotFound(domain))
+            })
+            .await
+            .map_err(|e| SeherError::Other(Box::new(e)))?
+        })
+    }
+}
+
+/// Reads a `Cookie` header value (`name=value; name2=value2`) from an environment variable.
+#[derive(Debug, Clone)]
+pub struct EnvCookieStore {
+    var: String,
+}
+
+impl EnvCookieStore {
+    #[must_use]
+    pub fn new(var: impl Into<String>) -> Self {
+        Self { var: var.into() }
+    }
+}
+
+impl CookieStore for EnvCookieStore {
+    fn cookies<'a>(&'a self, domain: &'a str) -> CookieFuture<'a> {
+        Box::pin(async move {
+            let header = std::env::var(&self.var)
+                .map_err(|_| SeherError::Message(format!("{} is not set", self.var)))?;
+            Ok(parse_cookie_header(&header, domain))
+        })
+    }
+}
+
+/// Reads a `Cookie` header value (`name=value; name2=value2`) from a file.
+#[derive(Debug, Clone)]
+pub struct FileCookieStore {
+    path: PathBuf,
+}
+
+impl FileCookieStore {
+    #[must_use]
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl CookieStore for FileCookieStore {
+    fn cookies<'a>(&'a self, domain: &'a str) -> CookieFuture<'a> {
+        Box::pin(async move {
+            let header = std::fs::read_to_string(&self.path)?;
+            Ok(parse_cookie_header(&header, domain))
+        })
+    }
+}
+
+/// Turn a `Cookie` header value into session cookies for `domain`.
+fn parse_cookie_header(header: &str, domain: &str) -> Vec<Cookie> {
+    header
+        .split(';')
+        .filter_map(|pair| {
+            let (name, value) = pair.trim().split_once('=')?;
+            let name = name.trim();
+            (!name.is_empty()).then(|| Cookie {
+                name: name.to_string(),
+                value: value.trim().to_string(),
+                domain: format!(".{domain}"),
+                path: "/".to_string(),
+                expires_utc: 0,
+                is_secure: true,
+                is_httponly: true,
+                same_site: 0,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn parse_cookie_header_splits_pairs() {
+        let cookies = parse_cookie_header(
+            "sessionKey=sk-ant; cf_clearance = abc=; ;junk\n",
+            "claude.ai",
+        );
+        let pairs: Vec<_> = cookies
+            .iter()
+            .map(|c| (c.name.as_str(), c.value.as_str()))
+            .collect();
+        assert_eq!(pairs, [("sessionKey", "sk-ant"), ("cf_clearance", "abc=")]);
+        assert_eq!(cookies[0].domain, ".claude.ai");
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn file_cookie_store_reads_header_file() -> TestResult {
+        let file = tempfile::NamedTempFile::new()?;
+        std::fs::write(file.path(), "sessionKey=sk-ant\n")?;
+        let cookies = FileCookieStore::new(file.path())
+            .cookies("claude.ai")
+            .await?;
+        assert_eq!(cookies.len(), 1);
+        assert_eq!(cookies[0].value, "sk-ant");
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const GITHUB_HOST: &str = "github.com";
+
+#[derive(Debug, Error)]
+pub enum GhAuthError {
+    #[error("gh hosts.yml not found: {0}")]
+    HostsFileNotFound(String),
+
+    #[error("failed to read gh hosts.yml: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("no GitHub token found (run `gh auth login` or set GH_TOKEN)")]
+    TokenNotFound,
+}
+
+/// Reads the GitHub token used by the `gh` CLI.
+pub struct GhAuth;
+
+impl GhAuth {
+    /// Resolve a GitHub token the same way `gh` does: `GH_TOKEN`/`GITHUB_TOKEN`,
+    /// then `gh auth token` (which covers keyring storage), then `hosts.yml`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if no token can be found in any of the sources.
+    pub fn token() -> Result<String, GhAuthError> {
+        for var in ["GH_TOKEN", "GITHUB_TOKEN"] {
+            if let Ok(token) = std::env::var(var)
+                && !token.trim().is_empty()
+            {
+                return Ok(token.trim().to_string());
+            }
+        }
+        if let Ok(token) = Self::token_from_cli() {
+            return Ok(token);
+        }
+        let path = Self::hosts_path().ok_or(GhAuthError::TokenNotFound)?;
+        Self::read_hosts_token(&path)
+    }
+
+    /// Run `gh auth token` for github.com.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `gh` is not installed, not logged in, or prints no token.
+    pub fn token_from_cli() -> Result<String, GhAuthError> {
+        let output = std::process::Command::new("gh")
+            .args(["auth", "token", "--hostname", GITHUB_HOST])
+            .output()?;
+        let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if output.status.success() && !token.is_empty() {
+            Ok(token)
+        } else {
+            Err(GhAuthError::TokenNotFound)
+        }
+    }
+
+    /// Location of `gh`'s `hosts.yml`, honoring `GH_CONFIG_DIR` and `XDG_CONFIG_HOME`.
+    #[must_use]
+    pub fn hosts_path() -> Option<PathBuf> {
+        if let Some(dir) = std::env::var_os("GH_CONFIG_DIR") {
+            return Some(PathBuf::from(dir).join("hosts.yml"));
+        }
+        if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME") {
+            return Some(PathBuf::from(dir).join("gh").join("hosts.yml"));
+        }
+        #[cfg(target_os = "windows")]
+        {
+            std::env::var_os("APPDATA")
+                .map(|dir| PathBuf::from(dir).join("GitHub CLI").join("hosts.yml"))
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            std::env::var_os("HOME").map(|home| {
+                PathBuf::from(home)
+                    .join(".config")
+                    .join("gh")
+                    .join("hosts.yml")
+            })
+        }
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the file is missing, unreadable, or has no github.com token.
+    pub fn read_hosts_token(path: &Path) -> Result<String, GhAuthError> {
+        if !path.exists() {
+            return Err(GhAuthError::HostsFileNotFound(path.display().to_string()));
+        }
+        let content = std::fs::read_to_string(path)?;
+        Self::parse_hosts_token(&content, GITHUB_HOST).ok_or(GhAuthError::TokenNotFound)
+    }
+
+    /// Extract the first `oauth_token` nested under `host:` in a `hosts.yml` document.
+    ///
+    /// Only the small subset of YAML that `gh` writes is understood.
+    fn parse_hosts_token(content: &str, host: &str) -> Option<String> {
+        let mut in_host = false;
+        for line in content.lines() {
+            if line.trim().is_empty() || line.trim_start().starts_with('#') {
+                continue;
+            }
+            let top_level = !line.starts_with(' ') && !line.starts_with('\t');
+            if top_level {
+                in_host = line.trim_end().trim_end_matches(':') == host;
+                continue;
+            }
+            if in_host && let Some(value) = line.trim().strip_prefix("oauth_token:") {
+                let token = value.trim().trim_matches(|c| c == '"' || c == '\'');
+                if !token.is_empty() {
+                    return Some(token.to_string());
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hosts_token_reads_github_com_entry() {
+        let content = "\
+ghe.example.com:
+    oauth_token: ghe_token
+github.com:
+    users:
+        octocat:
+            oauth_token: gho_user
+    git_protocol: https
+    user: octocat
+";
+        assert_eq!(
+            GhAuth::parse_hosts_token(content, "github.com").as_deref(),
+            Some("gho_user")
+        );
+    }
+
+    #[test]
+    fn parse_hosts_token_returns_none_without_token() {
+        let content = "github.com:\n    git_protocol: https\n    user: octocat\n";
+        assert_eq!(GhAuth::parse_hosts_token(content, "github.com"), None);
+    }
+}
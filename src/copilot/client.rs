@@ -1,15 +1,32 @@
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use super::types::{CopilotUserResponse, QuotaSnapshot, parse_reset_date};
+use crate::error::SeherError;
+use crate::http::retry::{is_rate_limited, wait_from_headers};
+use crate::http::{
+    BrowserHeaders, Endpoint, HttpRequest, HttpResponse, HttpTransport, ReqwestTransport, base_url,
+};
+
 #[cfg(feature = "browser")]
 use crate::Cookie;
+#[cfg(feature = "browser")]
+use crate::http::poll;
+#[cfg(feature = "browser")]
+use futures_util::Stream;
+#[cfg(feature = "browser")]
+use std::time::Duration;
 
 #[derive(Debug, Deserialize)]
 pub struct QuotaRemaining {
-    #[serde(rename = "chatPercentage")]
-    pub chat_percentage: f64,
-    #[serde(rename = "premiumInteractionsPercentage")]
-    pub premium_interactions_percentage: f64,
+    #[serde(rename = "chatPercentage", default)]
+    pub chat_percentage: Option<f64>,
+    /// Absent on the Free plan, which has no premium request allowance.
+    #[serde(rename = "premiumInteractionsPercentage", default)]
+    pub premium_interactions_percentage: Option<f64>,
+    /// Only reported on the Free plan.
+    #[serde(rename = "completionsPercentage", default)]
+    pub completions_percentage: Option<f64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -21,14 +38,53 @@ pub struct Quotas {
 
 #[derive(Debug, Deserialize)]
 pub struct CopilotQuotaResponse {
-    pub quotas: Quotas,
+    /// Absent for organization-managed (Business/Enterprise) seats, whose limits
+    /// are only exposed through the entitlement API.
+    pub quotas: Option<Quotas>,
+}
+
+impl CopilotQuotaResponse {
+    /// Convert the chat page payload, returning `None` for organization-managed seats.
+    #[must_use]
+    pub fn into_quota(self) -> Option<CopilotQuota> {
+        let quotas = self.quotas?;
+        let remaining = quotas.remaining;
+        let utilization = |percentage: Option<f64>| 100.0 - percentage.unwrap_or(100.0);
+        let is_free = remaining.premium_interactions_percentage.is_none();
+        Some(CopilotQuota {
+            chat_utilization: utilization(remaining.chat_percentage),
+            premium_utilization: utilization(remaining.premium_interactions_percentage),
+            reset_time: parse_reset_date(&quotas.reset_date),
+            plan: is_free.then(|| "Free".to_string()),
+            login: None,
+            chat_remaining: None,
+            chat_entitlement: None,
+            premium_remaining: None,
+            premium_entitlement: None,
+        })
+    }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CopilotQuota {
     pub chat_utilization: f64,
     pub premium_utilization: f64,
     pub reset_time: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan: Option<String>,
+    /// GitHub user the quota belongs to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub login: Option<String>,
+    /// Chat requests left this period; `None` when unlimited or not reported.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_remaining: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chat_entitlement: Option<f64>,
+    /// Premium requests left this period; `None` when unlimited or not reported.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub premium_remaining: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub premium_entitlement: Option<f64>,
 }
 
 impl CopilotQuota {
@@ -43,8 +99,36 @@ impl CopilotQuota {
     }
 }
 
-const USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) \
-    AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36";
+impl From<&CopilotUserResponse> for CopilotQuota {
+    fn from(user: &CopilotUserResponse) -> Self {
+        let utilization = |quota_id: &str| {
+            user.snapshot(quota_id).map_or_else(
+                || user.limited_utilization(quota_id).unwrap_or(0.0),
+                QuotaSnapshot::utilization,
+            )
+        };
+        let counts = |quota_id: &str| match user.snapshot(quota_id) {
+            Some(s) if s.unlimited => (None, None),
+            Some(s) => (s.remaining, s.entitlement),
+            None => user.limited_counts(quota_id),
+        };
+        let (chat_remaining, chat_entitlement) = counts("chat");
+        let (premium_remaining, premium_entitlement) = counts("premium_interactions");
+        Self {
+            chat_utilization: utilization("chat"),
+            premium_utilization: utilization("premium_interactions"),
+            reset_time: user.reset_time(),
+            plan: user.plan_name(),
+            login: user.login.clone(),
+            chat_remaining,
+            chat_entitlement,
+            premium_remaining,
+            premium_entitlement,
+        }
+    }
+}
+
+const ENTITLEMENT_PATH: &str = "/copilot_internal/user";
 
 pub struct CopilotClient;
 
@@ -53,67 +137,330 @@ impl CopilotClient {
     ///
     /// # Errors
     ///
+    /// Returns an error if the GitHub Copilot API request fails, the response cannot be parsed,
+    /// or the seat is managed by an organization (use [`Self::fetch_quota_with_token`] instead).
+    pub async fn fetch_quota_with_header(cookie_header: &str) -> Result<CopilotQuota, SeherError> {
+        Self::fetch_quota_via(&ReqwestTransport, cookie_header).await
+    }
+
+    /// [`Self::fetch_quota_with_header`] over a caller-supplied transport.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the GitHub Copilot API request fails, the response cannot be parsed,
+    /// or the seat is managed by an organization.
+    pub async fn fetch_quota_via(
+        transport: &dyn HttpTransport,
+        cookie_header: &str,
+    ) -> Result<CopilotQuota, SeherError> {
+        Self::fetch_chat_quota_via(transport, cookie_header)
+            .await?
+            .ok_or_else(|| {
+                "Copilot Business/Enterprise quotas are not exposed on github.com; \
+                 use a GitHub token (gh auth login) instead"
+                    .into()
+            })
+    }
+
+    /// Fetch the individual-plan quota from the github.com chat page.
+    ///
+    /// Returns `Ok(None)` when the seat is assigned by an organization, since the chat
+    /// page does not report Business/Enterprise quotas.
+    ///
+    /// # Errors
+    ///
     /// Returns an error if the GitHub Copilot API request fails or the response cannot be parsed.
-    pub async fn fetch_quota_with_header(
+    pub async fn fetch_chat_quota_with_header(
         cookie_header: &str,
-    ) -> Result<CopilotQuota, Box<dyn std::error::Error>> {
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .build()?;
+    ) -> Result<Option<CopilotQuota>, SeherError> {
+        Self::fetch_chat_quota_via(&ReqwestTransport, cookie_header).await
+    }
 
-        let response = client
-            .get("https://github.com/github-copilot/chat")
+    /// [`Self::fetch_chat_quota_with_header`] over a caller-supplied transport.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the GitHub Copilot API request fails or the response cannot be parsed.
+    pub async fn fetch_chat_quota_via(
+        transport: &dyn HttpTransport,
+        cookie_header: &str,
+    ) -> Result<Option<CopilotQuota>, SeherError> {
+        let url = format!("{}/github-copilot/chat", base_url(Endpoint::Github));
+        let request = HttpRequest::get(url)
             .header("Cookie", cookie_header)
-            .header("User-Agent", USER_AGENT)
+            .header("User-Agent", BrowserHeaders::current().user_agent())
             .header("github-verified-fetch", "true")
             .header("x-requested-with", "XMLHttpRequest")
-            .header("accept", "application/json")
-            .send()
-            .await?;
-
-        let status = response.status();
-        if !status.is_success() {
-            let body = response.text().await?;
-            return Err(format!("GitHub Copilot API error: {status} - {body}").into());
-        }
+            .header("accept", "application/json");
+        let response = Self::send(transport, request).await?;
+        let quota_response: CopilotQuotaResponse = response.json()?;
+        Ok(quota_response.into_quota())
+    }
 
-        let quota_response: CopilotQuotaResponse = response.json().await?;
-        let quotas = quota_response.quotas;
+    /// Fetch the Copilot entitlement for a GitHub OAuth token (e.g. from `gh auth token`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the GitHub API request fails or the response cannot be parsed.
+    pub async fn fetch_user_with_token(token: &str) -> Result<CopilotUserResponse, SeherError> {
+        Self::fetch_user_via(&ReqwestTransport, token).await
+    }
 
-        let _now = Utc::now();
-        let reset_time = NaiveDate::parse_from_str(&quotas.reset_date, "%Y-%m-%d")
-            .ok()
-            .and_then(|d| d.and_hms_opt(0, 0, 0))
-            .map(|dt| dt.and_utc());
+    /// [`Self::fetch_user_with_token`] over a caller-supplied transport.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the GitHub API request fails or the response cannot be parsed.
+    pub async fn fetch_user_via(
+        transport: &dyn HttpTransport,
+        token: &str,
+    ) -> Result<CopilotUserResponse, SeherError> {
+        let url = format!("{}{ENTITLEMENT_PATH}", base_url(Endpoint::GithubApi));
+        let request = HttpRequest::get(url)
+            .header("Authorization", format!("token {token}"))
+            .header("accept", "application/json");
+        let response = Self::send(transport, request).await?;
+        Ok(response.json()?)
+    }
 
-        let chat_utilization = 100.0 - quotas.remaining.chat_percentage;
-        let premium_utilization = 100.0 - quotas.remaining.premium_interactions_percentage;
+    async fn send(
+        transport: &dyn HttpTransport,
+        request: HttpRequest,
+    ) -> Result<HttpResponse, SeherError> {
+        let response = transport.send(request).await?;
+        if is_rate_limited(response.status, |name| response.header(name)) {
+            return Err(SeherError::RateLimited {
+                service: "GitHub Copilot API",
+                retry_after: wait_from_headers(|name| response.header(name), Utc::now()),
+            });
+        }
+        if !response.is_success() {
+            return Err(SeherError::Status {
+                service: "GitHub Copilot API",
+                status: response.status,
+                body: response.body,
+            });
+        }
+        Ok(response)
+    }
 
-        Ok(CopilotQuota {
-            chat_utilization,
-            premium_utilization,
-            reset_time,
-        })
+    /// Fetch quota using a GitHub OAuth token instead of browser cookies.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the GitHub API request fails or the response cannot be parsed.
+    pub async fn fetch_quota_with_token(token: &str) -> Result<CopilotQuota, SeherError> {
+        let user = Self::fetch_user_with_token(token).await?;
+        Ok(CopilotQuota::from(&user))
     }
 
     /// # Errors
     ///
     /// Returns an error if the GitHub Copilot API request fails or the response cannot be parsed.
     #[cfg(feature = "browser")]
-    pub async fn fetch_quota(
+    pub async fn fetch_quota(cookies: &[Cookie]) -> Result<CopilotQuota, SeherError> {
+        Self::fetch_quota_with_cookies_via(&ReqwestTransport, cookies).await
+    }
+
+    /// [`Self::fetch_quota`] over a caller-supplied transport.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the GitHub Copilot API request fails or the response cannot be parsed.
+    #[cfg(feature = "browser")]
+    pub async fn fetch_quota_with_cookies_via(
+        transport: &dyn HttpTransport,
         cookies: &[Cookie],
-    ) -> Result<CopilotQuota, Box<dyn std::error::Error>> {
+    ) -> Result<CopilotQuota, SeherError> {
         let cookie_header = Self::build_cookie_header(cookies);
-        Self::fetch_quota_with_header(&cookie_header).await
+        let mut quota = Self::fetch_quota_via(transport, &cookie_header).await?;
+        quota.login = Self::login(cookies).map(ToString::to_string);
+        Ok(quota)
     }
 
+    /// The GitHub username of a github.com session, from its `dotcom_user` cookie.
     #[cfg(feature = "browser")]
-    fn build_cookie_header(cookies: &[Cookie]) -> String {
+    #[must_use]
+    pub fn login(cookies: &[Cookie]) -> Option<&str> {
         cookies
             .iter()
-            .filter(|c| !c.value.bytes().any(|b| b < 0x20 || b == 0x7f))
-            .map(|c| format!("{}={}", c.name, c.value))
-            .collect::<Vec<_>>()
-            .join("; ")
+            .find(|c| c.name == "dotcom_user" && !c.value.is_empty())
+            .map(|c| c.value.as_str())
+    }
+
+    /// Fetch the quota right away and then once every `interval`, for dashboards that want
+    /// to follow updates.
+    #[cfg(feature = "browser")]
+    pub fn quota_stream(
+        cookies: &[Cookie],
+        interval: Duration,
+    ) -> impl Stream<Item = Result<CopilotQuota, SeherError>> + use<> {
+        let cookie_header = Self::build_cookie_header(cookies);
+        poll(interval, (), move |()| {
+            let cookie_header = cookie_header.clone();
+            async move { (Self::fetch_quota_with_header(&cookie_header).await, ()) }
+        })
+    }
+
+    /// Cookie-based variant of [`Self::fetch_chat_quota_with_header`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the GitHub Copilot API request fails or the response cannot be parsed.
+    #[cfg(feature = "browser")]
+    pub async fn fetch_chat_quota(cookies: &[Cookie]) -> Result<Option<CopilotQuota>, SeherError> {
+        let cookie_header = Self::build_cookie_header(cookies);
+        let quota = Self::fetch_chat_quota_with_header(&cookie_header).await?;
+        Ok(quota.map(|mut quota| {
+            quota.login = Self::login(cookies).map(ToString::to_string);
+            quota
+        }))
+    }
+
+    #[cfg(feature = "browser")]
+    fn build_cookie_header(cookies: &[Cookie]) -> String {
+        crate::browser::cookie_header(cookies, "github.com", "/github-copilot/chat")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::transport::mock::MockTransport;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn chat_payload_with_quotas_converts_to_quota() -> TestResult {
+        let json = r#"{"quotas": {"remaining": {"chatPercentage": 100.0, "premiumInteractionsPercentage": 25.0}, "resetDate": "2025-07-01"}}"#;
+        let response: CopilotQuotaResponse = serde_json::from_str(json)?;
+
+        let quota = response.into_quota().ok_or("expected quota")?;
+        assert!(quota.chat_utilization.abs() < f64::EPSILON);
+        assert!((quota.premium_utilization - 75.0).abs() < f64::EPSILON);
+        Ok(())
+    }
+
+    #[test]
+    fn free_plan_chat_payload_has_no_premium_percentage() -> TestResult {
+        let json = r#"{"quotas": {"remaining": {"chatPercentage": 10.0, "completionsPercentage": 80.0}, "resetDate": "2025-07-01"}}"#;
+        let response: CopilotQuotaResponse = serde_json::from_str(json)?;
+
+        let quota = response.into_quota().ok_or("expected quota")?;
+        assert!((quota.chat_utilization - 90.0).abs() < f64::EPSILON);
+        assert!(quota.premium_utilization.abs() < f64::EPSILON);
+        assert_eq!(quota.plan.as_deref(), Some("Free"));
+        Ok(())
+    }
+
+    #[test]
+    fn chat_payload_without_quotas_is_organization_seat() -> TestResult {
+        let response: CopilotQuotaResponse = serde_json::from_str("{}")?;
+
+        assert!(response.into_quota().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn entitlement_response_exposes_absolute_counts() -> TestResult {
+        let json = r#"{
+            "copilot_plan": "individual",
+            "quota_snapshots": {
+                "chat": {"entitlement": 0, "remaining": 0, "percent_remaining": 100.0, "unlimited": true},
+                "premium_interactions": {"entitlement": 300, "remaining": 111, "percent_remaining": 37.0, "unlimited": false}
+            }
+        }"#;
+        let user: CopilotUserResponse = serde_json::from_str(json)?;
+
+        let quota = CopilotQuota::from(&user);
+        assert_eq!(quota.premium_remaining, Some(111.0));
+        assert_eq!(quota.premium_entitlement, Some(300.0));
+        assert_eq!(quota.chat_remaining, None);
+        assert_eq!(quota.chat_entitlement, None);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn fetch_user_via_sends_token_and_parses_quota() -> TestResult {
+        let body = r#"{
+            "login": "octocat",
+            "copilot_plan": "business",
+            "organization_login_list": ["acme"],
+            "quota_snapshots": {
+                "premium_interactions": {"entitlement": 300, "remaining": 0, "percent_remaining": 0.0, "unlimited": false}
+            }
+        }"#;
+        let url = format!("{}{ENTITLEMENT_PATH}", base_url(Endpoint::GithubApi));
+        let transport = MockTransport::new(vec![(url.as_str(), HttpResponse::new(200, body))]);
+
+        let user = CopilotClient::fetch_user_via(&transport, "gho_abc").await?;
+
+        let quota = CopilotQuota::from(&user);
+        assert!(quota.is_limited());
+        assert_eq!(quota.login.as_deref(), Some("octocat"));
+        let requests = transport.requests.lock().map_err(|e| e.to_string())?;
+        assert!(
+            requests[0]
+                .headers
+                .contains(&("Authorization".to_string(), "token gho_abc".to_string()))
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "browser")]
+    #[test]
+    fn login_reads_dotcom_user_cookie() {
+        let cookie = |name: &str, value: &str| Cookie {
+            name: name.to_string(),
+            value: value.to_string(),
+            domain: ".github.com".to_string(),
+            path: "/".to_string(),
+            expires_utc: 0,
+            is_secure: true,
+            is_httponly: true,
+            same_site: 0,
+        };
+        let cookies = [
+            cookie("user_session", "abc"),
+            cookie("dotcom_user", "octocat"),
+        ];
+        assert_eq!(CopilotClient::login(&cookies), Some("octocat"));
+        assert_eq!(CopilotClient::login(&cookies[..1]), None);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn fetch_user_via_surfaces_api_errors() {
+        let url = format!("{}{ENTITLEMENT_PATH}", base_url(Endpoint::GithubApi));
+        let transport = MockTransport::new(vec![(
+            url.as_str(),
+            HttpResponse::new(401, "Bad credentials"),
+        )]);
+
+        let result = CopilotClient::fetch_user_via(&transport, "bad").await;
+
+        assert!(result.is_err_and(|e| e.to_string().contains("401")));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn secondary_rate_limit_is_not_an_auth_error() -> TestResult {
+        let url = format!("{}{ENTITLEMENT_PATH}", base_url(Endpoint::GithubApi));
+        let mut response = HttpResponse::new(403, "You have exceeded a secondary rate limit");
+        response
+            .headers
+            .push(("Retry-After".to_string(), "60".to_string()));
+        let transport = MockTransport::new(vec![(url.as_str(), response)]);
+
+        let result = CopilotClient::fetch_user_via(&transport, "gho_abc").await;
+
+        let err = result.err().ok_or("expected a rate limit error")?;
+        assert!(!err.is_auth_error());
+        assert!(matches!(
+            err,
+            SeherError::RateLimited {
+                retry_after: Some(wait),
+                ..
+            } if wait == std::time::Duration::from_mins(1)
+        ));
+        Ok(())
     }
 }
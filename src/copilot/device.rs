@@ -0,0 +1,162 @@
+use crate::error::SeherError;
+use crate::http::{Endpoint, base_url};
+use serde::Deserialize;
+use std::time::Duration;
+
+const DEVICE_CODE_PATH: &str = "/login/device/code";
+const ACCESS_TOKEN_PATH: &str = "/login/oauth/access_token";
+const DEVICE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+/// Response from GitHub's device authorization endpoint.
+#[derive(Debug, Deserialize)]
+pub struct DeviceCode {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub expires_in: u64,
+    /// Minimum number of seconds to wait between polls.
+    pub interval: u64,
+}
+
+/// Response from the access token endpoint while polling.
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+    interval: Option<u64>,
+}
+
+/// Outcome of a single poll of the access token endpoint.
+#[derive(Debug, PartialEq)]
+enum PollStatus {
+    Authorized(String),
+    Pending,
+    SlowDown(Option<u64>),
+}
+
+impl TokenResponse {
+    fn into_status(self) -> Result<PollStatus, SeherError> {
+        if let Some(token) = self.access_token {
+            return Ok(PollStatus::Authorized(token));
+        }
+        match self.error.as_deref() {
+            Some("authorization_pending") => Ok(PollStatus::Pending),
+            Some("slow_down") => Ok(PollStatus::SlowDown(self.interval)),
+            Some(error) => Err(format!(
+                "GitHub login failed: {error}{}",
+                self.error_description
+                    .map(|d| format!(" ({d})"))
+                    .unwrap_or_default()
+            )
+            .into()),
+            None => Err("GitHub login failed: empty token response".into()),
+        }
+    }
+}
+
+/// GitHub OAuth device-code flow.
+pub struct GithubDeviceFlow;
+
+impl GithubDeviceFlow {
+    /// OAuth client ID of the GitHub Copilot editor integrations, whose tokens are
+    /// accepted by the Copilot entitlement API.
+    pub const DEFAULT_CLIENT_ID: &str = "Iv1.b507a08c87ecfe98";
+
+    /// Request a device and user code.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the response cannot be parsed.
+    pub async fn request_code(client_id: &str) -> Result<DeviceCode, SeherError> {
+        let response = build_client()?
+            .post(format!("{}{DEVICE_CODE_PATH}", base_url(Endpoint::Github)))
+            .header("accept", "application/json")
+            .json(&serde_json::json!({ "client_id": client_id, "scope": "read:user" }))
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await?;
+            return Err(format!("GitHub device code error: {status} - {body}").into());
+        }
+
+        Ok(response.json().await?)
+    }
+
+    /// Poll until the user authorizes the device, returning the access token.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the code expires, the user denies access, or a request fails.
+    pub async fn poll_token(client_id: &str, code: &DeviceCode) -> Result<String, SeherError> {
+        let client = build_client()?;
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(code.expires_in);
+        let mut interval = code.interval.max(1);
+
+        loop {
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+            if tokio::time::Instant::now() >= deadline {
+                return Err("GitHub login failed: device code expired".into());
+            }
+
+            let response: TokenResponse = client
+                .post(format!("{}{ACCESS_TOKEN_PATH}", base_url(Endpoint::Github)))
+                .header("accept", "application/json")
+                .json(&serde_json::json!({
+                    "client_id": client_id,
+                    "device_code": code.device_code,
+                    "grant_type": DEVICE_GRANT_TYPE,
+                }))
+                .send()
+                .await?
+                .json()
+                .await?;
+
+            match response.into_status()? {
+                PollStatus::Authorized(token) => return Ok(token),
+                PollStatus::Pending => {}
+                // GitHub asks for an extra 5 seconds on slow_down.
+                PollStatus::SlowDown(next) => interval = next.unwrap_or(interval + 5),
+            }
+        }
+    }
+}
+
+fn build_client() -> Result<reqwest::Client, reqwest::Error> {
+    crate::http::shared_client()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    fn status(json: &str) -> Result<PollStatus, SeherError> {
+        serde_json::from_str::<TokenResponse>(json)?.into_status()
+    }
+
+    #[test]
+    fn poll_response_variants_map_to_status() -> TestResult {
+        assert_eq!(
+            status(r#"{"access_token": "gho_abc", "token_type": "bearer"}"#)?,
+            PollStatus::Authorized("gho_abc".to_string())
+        );
+        assert_eq!(
+            status(r#"{"error": "authorization_pending"}"#)?,
+            PollStatus::Pending
+        );
+        assert_eq!(
+            status(r#"{"error": "slow_down", "interval": 10}"#)?,
+            PollStatus::SlowDown(Some(10))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn poll_response_access_denied_is_error() {
+        assert!(status(r#"{"error": "access_denied", "error_description": "denied"}"#).is_err());
+    }
+}
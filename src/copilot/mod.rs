@@ -1,3 +1,9 @@
+pub mod auth;
 pub mod client;
+pub mod device;
+pub mod types;
 
+pub use auth::GhAuth;
 pub use client::{CopilotClient, CopilotQuota};
+pub use device::{DeviceCode, GithubDeviceFlow};
+pub use types::{CopilotUserResponse, QuotaSnapshot};
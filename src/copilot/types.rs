@@ -0,0 +1,211 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// Response from GitHub's Copilot entitlement API (`/copilot_internal/user`).
+#[derive(Debug, Deserialize)]
+pub struct CopilotUserResponse {
+    pub login: Option<String>,
+    /// Plan family, e.g. `"individual"`, `"business"`, `"enterprise"`.
+    pub copilot_plan: Option<String>,
+    pub access_type_sku: Option<String>,
+    pub quota_reset_date: Option<String>,
+    /// Organizations granting a Business/Enterprise seat to this user.
+    #[serde(default)]
+    pub organization_login_list: Vec<String>,
+    /// Per-feature quotas keyed by quota ID (`chat`, `completions`, `premium_interactions`).
+    #[serde(default)]
+    pub quota_snapshots: BTreeMap<String, QuotaSnapshot>,
+    /// Free plan: requests left this month, keyed by quota ID.
+    #[serde(default)]
+    pub limited_user_quotas: BTreeMap<String, f64>,
+    /// Free plan: monthly allowance, keyed by quota ID.
+    #[serde(default)]
+    pub monthly_quotas: BTreeMap<String, f64>,
+    /// Free plan reset date (`YYYY-MM-DD`); used when `quota_reset_date` is absent.
+    pub limited_user_reset_date: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QuotaSnapshot {
+    pub entitlement: Option<f64>,
+    pub remaining: Option<f64>,
+    pub percent_remaining: Option<f64>,
+    #[serde(default)]
+    pub unlimited: bool,
+}
+
+impl QuotaSnapshot {
+    /// Returns usage as a percentage (0–100). Unlimited quotas always report 0.
+    #[must_use]
+    pub fn utilization(&self) -> f64 {
+        if self.unlimited {
+            0.0
+        } else {
+            100.0 - self.percent_remaining.unwrap_or(100.0)
+        }
+    }
+}
+
+impl CopilotUserResponse {
+    #[must_use]
+    pub fn snapshot(&self, quota_id: &str) -> Option<&QuotaSnapshot> {
+        self.quota_snapshots.get(quota_id)
+    }
+
+    /// Whether this is the Copilot Free plan, which reports monthly counts instead of
+    /// quota snapshots.
+    #[must_use]
+    pub fn is_free(&self) -> bool {
+        self.access_type_sku
+            .as_deref()
+            .is_some_and(|sku| sku.contains("free"))
+            || !self.limited_user_quotas.is_empty()
+    }
+
+    /// Free-plan `(remaining, allowance)` for a quota ID.
+    #[must_use]
+    pub fn limited_counts(&self, quota_id: &str) -> (Option<f64>, Option<f64>) {
+        (
+            self.limited_user_quotas.get(quota_id).copied(),
+            self.monthly_quotas.get(quota_id).copied(),
+        )
+    }
+
+    /// Free-plan usage percentage (0–100) for a quota ID.
+    #[must_use]
+    pub fn limited_utilization(&self, quota_id: &str) -> Option<f64> {
+        match self.limited_counts(quota_id) {
+            (Some(remaining), Some(total)) if total > 0.0 => {
+                Some((total - remaining) / total * 100.0)
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether the seat is assigned by an organization (Copilot Business/Enterprise),
+    /// in which case quotas follow the organization's policy.
+    #[must_use]
+    pub fn is_organization_seat(&self) -> bool {
+        matches!(
+            self.copilot_plan.as_deref(),
+            Some("business" | "enterprise")
+        )
+    }
+
+    /// Human-readable plan name, e.g. `"Individual"` or `"Business (acme)"`.
+    #[must_use]
+    pub fn plan_name(&self) -> Option<String> {
+        if self.is_free() {
+            return Some("Free".to_string());
+        }
+        let plan = self.copilot_plan.as_deref()?;
+        let mut chars = plan.chars();
+        let mut name: String = chars
+            .next()
+            .map(|c| c.to_uppercase().chain(chars).collect())
+            .unwrap_or_default();
+        if self.is_organization_seat() && !self.organization_login_list.is_empty() {
+            name = format!("{name} ({})", self.organization_login_list.join(", "));
+        }
+        Some(name)
+    }
+
+    #[must_use]
+    pub fn reset_time(&self) -> Option<DateTime<Utc>> {
+        self.quota_reset_date
+            .as_deref()
+            .or(self.limited_user_reset_date.as_deref())
+            .and_then(parse_reset_date)
+    }
+}
+
+/// Parse a `YYYY-MM-DD` quota reset date as midnight UTC.
+pub(crate) fn parse_reset_date(date: &str) -> Option<DateTime<Utc>> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|dt| dt.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn parses_individual_plan_quota_snapshots() -> TestResult {
+        let json = r#"{
+            "login": "octocat",
+            "copilot_plan": "individual",
+            "access_type_sku": "plus_monthly_subscriber_quota",
+            "quota_reset_date": "2025-07-01",
+            "quota_snapshots": {
+                "chat": {"entitlement": 0, "remaining": 0, "percent_remaining": 100.0, "unlimited": true},
+                "premium_interactions": {"entitlement": 300, "remaining": 111, "percent_remaining": 37.0, "unlimited": false}
+            }
+        }"#;
+        let user: CopilotUserResponse = serde_json::from_str(json)?;
+
+        let chat = user.snapshot("chat").ok_or("missing chat")?;
+        let premium = user
+            .snapshot("premium_interactions")
+            .ok_or("missing premium")?;
+        assert!(chat.utilization().abs() < f64::EPSILON);
+        assert!((premium.utilization() - 63.0).abs() < f64::EPSILON);
+        assert_eq!(
+            user.reset_time(),
+            Some("2025-07-01T00:00:00Z".parse::<DateTime<Utc>>()?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn detects_business_seat_and_reports_org() -> TestResult {
+        let json = r#"{
+            "copilot_plan": "business",
+            "organization_login_list": ["acme"],
+            "quota_reset_date": "2025-07-01",
+            "quota_snapshots": {
+                "premium_interactions": {"entitlement": 300, "remaining": 0, "percent_remaining": 0.0, "unlimited": false}
+            }
+        }"#;
+        let user: CopilotUserResponse = serde_json::from_str(json)?;
+
+        assert!(user.is_organization_seat());
+        assert_eq!(user.plan_name().as_deref(), Some("Business (acme)"));
+        Ok(())
+    }
+
+    #[test]
+    fn individual_plan_is_not_organization_seat() -> TestResult {
+        let user: CopilotUserResponse = serde_json::from_str(r#"{"copilot_plan": "individual"}"#)?;
+
+        assert!(!user.is_organization_seat());
+        assert_eq!(user.plan_name().as_deref(), Some("Individual"));
+        Ok(())
+    }
+
+    #[test]
+    fn free_plan_uses_limited_user_quotas() -> TestResult {
+        let json = r#"{
+            "copilot_plan": "individual",
+            "access_type_sku": "free_limited_copilot",
+            "limited_user_quotas": {"chat": 5, "completions": 1500},
+            "monthly_quotas": {"chat": 50, "completions": 2000},
+            "limited_user_reset_date": "2025-07-01"
+        }"#;
+        let user: CopilotUserResponse = serde_json::from_str(json)?;
+
+        assert!(user.is_free());
+        assert_eq!(user.plan_name().as_deref(), Some("Free"));
+        assert_eq!(user.limited_counts("chat"), (Some(5.0), Some(50.0)));
+        assert!((user.limited_utilization("chat").ok_or("missing chat")? - 90.0).abs() < 1e-9);
+        assert_eq!(
+            user.reset_time(),
+            Some("2025-07-01T00:00:00Z".parse::<DateTime<Utc>>()?)
+        );
+        Ok(())
+    }
+}
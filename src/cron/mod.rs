@@ -0,0 +1,261 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+use std::str::FromStr;
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// How many years ahead [`CronSchedule::next_after`] looks before giving up, so impossible
+/// dates such as `0 0 31 2 *` end the search.
+const SEARCH_YEARS: i32 = 5;
+
+/// A five-field cron expression (`minute hour day-of-month month day-of-week`) evaluated
+/// in local time.
+///
+/// Fields accept `*`, numbers, ranges (`1-5`), steps (`*/15`, `0-30/10`) and lists
+/// (`1,15`); months and weekdays also accept three-letter names, and `7` is Sunday like
+/// `0`. `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly` are shorthands. As in
+/// cron, when both day fields are restricted a day matching either one matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u32,
+    days: u32,
+    months: u16,
+    weekdays: u8,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl FromStr for CronSchedule {
+    type Err = String;
+
+    fn from_str(expr: &str) -> Result<Self, Self::Err> {
+        let expr = match expr.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "expected 5 fields (minute hour day month weekday), got {}",
+                fields.len()
+            ));
+        };
+        let weekdays =
+            parse_field(weekday, 0, 7, &WEEKDAY_NAMES, 0).map_err(|e| format!("weekday: {e}"))?;
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, &[], 0).map_err(|e| format!("minute: {e}"))?,
+            hours: bits(parse_field(hour, 0, 23, &[], 0).map_err(|e| format!("hour: {e}"))?),
+            days: bits(parse_field(day, 1, 31, &[], 0).map_err(|e| format!("day: {e}"))?),
+            months: bits(
+                parse_field(month, 1, 12, &MONTH_NAMES, 1).map_err(|e| format!("month: {e}"))?,
+            ),
+            // Sunday is both 0 and 7.
+            weekdays: bits((weekdays | (weekdays >> 7)) & 0x7f),
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+}
+
+impl CronSchedule {
+    /// `true` if the expression fires in the minute containing `at`.
+    #[must_use]
+    pub fn matches(&self, at: &DateTime<Local>) -> bool {
+        self.matches_naive(&at.naive_local())
+    }
+
+    /// The first minute strictly after `after` at which the expression fires, or `None`
+    /// if it never does within the next few years.
+    #[must_use]
+    pub fn next_after(&self, after: &DateTime<Local>) -> Option<DateTime<Local>> {
+        let start = after.naive_local();
+        let mut t =
+            start.date().and_hms_opt(start.hour(), start.minute(), 0)? + Duration::minutes(1);
+        let limit = start.year() + SEARCH_YEARS;
+        while t.year() <= limit {
+            if !has_bit(u64::from(self.months), t.month()) {
+                t = first_of_next_month(t.date())?.and_hms_opt(0, 0, 0)?;
+            } else if !self.day_matches(t.date()) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if !has_bit(u64::from(self.hours), t.hour()) {
+                t = t.date().and_hms_opt(t.hour(), 0, 0)? + Duration::hours(1);
+            } else if !has_bit(self.minutes, t.minute()) {
+                t += Duration::minutes(1);
+            } else if let Some(local) = Local.from_local_datetime(&t).earliest() {
+                return Some(local);
+            } else {
+                // The minute falls into a daylight-saving gap.
+                t += Duration::minutes(1);
+            }
+        }
+        None
+    }
+
+    fn matches_naive(&self, t: &NaiveDateTime) -> bool {
+        has_bit(self.minutes, t.minute())
+            && has_bit(u64::from(self.hours), t.hour())
+            && has_bit(u64::from(self.months), t.month())
+            && self.day_matches(t.date())
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = has_bit(u64::from(self.days), date.day());
+        let weekday = has_bit(
+            u64::from(self.weekdays),
+            date.weekday().num_days_from_sunday(),
+        );
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+}
+
+/// Parse one comma-separated field into a bitset of the values in `min..=max`. Names are
+/// matched case-insensitively and map to `name_base + index`.
+fn parse_field(
+    field: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    name_base: u32,
+) -> Result<u64, String> {
+    let value = |s: &str| -> Result<u32, String> {
+        let lower = s.to_ascii_lowercase();
+        let n = names
+            .iter()
+            .position(|name| *name == lower)
+            .and_then(|i| u32::try_from(i).ok())
+            .map(|i| i + name_base)
+            .or_else(|| s.parse().ok())
+            .ok_or_else(|| format!("invalid value '{s}'"))?;
+        if (min..=max).contains(&n) {
+            Ok(n)
+        } else {
+            Err(format!("{n} is outside {min}-{max}"))
+        }
+    };
+    let mut set = 0_u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (
+                range,
+                step.parse::<u32>()
+                    .ok()
+                    .filter(|&s| s > 0)
+                    .ok_or_else(|| format!("invalid step '{step}'"))?,
+            ),
+            None => (part, 1),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (value(a)?, value(b)?)
+        } else {
+            let n = value(range)?;
+            // `5/15` means from 5 to the end in steps of 15.
+            (n, if part.contains('/') { max } else { n })
+        };
+        if start > end {
+            return Err(format!("range {start}-{end} is reversed"));
+        }
+        for n in (start..=end).step_by(step as usize) {
+            set |= 1 << n;
+        }
+    }
+    Ok(set)
+}
+
+fn bits<T: TryFrom<u64> + Default>(set: u64) -> T {
+    T::try_from(set).unwrap_or_default()
+}
+
+fn has_bit(set: u64, n: u32) -> bool {
+    set & (1 << n) != 0
+}
+
+fn first_of_next_month(date: NaiveDate) -> Option<NaiveDate> {
+    if date.month() == 12 {
+        NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    fn local(s: &str) -> Result<DateTime<Local>, Box<dyn std::error::Error>> {
+        let naive = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M")?;
+        Local
+            .from_local_datetime(&naive)
+            .earliest()
+            .ok_or_else(|| "nonexistent local time".into())
+    }
+
+    #[test]
+    fn next_after_finds_the_following_fire_time() -> TestResult {
+        let nightly: CronSchedule = "0 2 * * *".parse()?;
+        assert_eq!(
+            nightly.next_after(&local("2026-03-10 01:30")?),
+            Some(local("2026-03-10 02:00")?)
+        );
+        assert_eq!(
+            nightly.next_after(&local("2026-03-10 02:00")?),
+            Some(local("2026-03-11 02:00")?)
+        );
+
+        let weekdays: CronSchedule = "*/15 9-17 * * mon-fri".parse()?;
+        // 2026-03-14 is a Saturday.
+        assert_eq!(
+            weekdays.next_after(&local("2026-03-13 17:50")?),
+            Some(local("2026-03-16 09:00")?)
+        );
+        assert!(weekdays.matches(&local("2026-03-16 09:45")?));
+        assert!(!weekdays.matches(&local("2026-03-16 09:50")?));
+        Ok(())
+    }
+
+    #[test]
+    fn day_fields_match_either_when_both_are_set() -> TestResult {
+        // The 1st of the month, or any Sunday (0 and 7 both mean Sunday).
+        let schedule: CronSchedule = "0 0 1 * 7".parse()?;
+        assert!(schedule.matches(&local("2026-03-01 00:00")?));
+        assert!(schedule.matches(&local("2026-03-08 00:00")?));
+        assert!(!schedule.matches(&local("2026-03-09 00:00")?));
+        assert_eq!(
+            "@monthly"
+                .parse::<CronSchedule>()?
+                .next_after(&local("2026-12-15 00:00")?),
+            Some(local("2027-01-01 00:00")?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_malformed_expressions() -> TestResult {
+        assert!("0 2 * *".parse::<CronSchedule>().is_err());
+        assert!("60 * * * *".parse::<CronSchedule>().is_err());
+        assert!("0 5-1 * * *".parse::<CronSchedule>().is_err());
+        assert!("*/0 * * * *".parse::<CronSchedule>().is_err());
+        assert_eq!(
+            "0 0 31 2 *"
+                .parse::<CronSchedule>()?
+                .next_after(&local("2026-01-01 00:00")?),
+            None
+        );
+        Ok(())
+    }
+}
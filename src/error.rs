@@ -0,0 +1,204 @@
+use thiserror::Error;
+
+/// Error returned by [`crate::Agent`], [`crate::copilot::CopilotClient`] and
+/// [`crate::Settings`].
+///
+/// Each variant wraps the error of the module that failed, so library consumers
+/// can match on the failure category. The type is `Send + Sync` and can be
+/// returned from spawned tasks.
+#[derive(Debug, Error)]
+#[non_exhaustive]
+pub enum SeherError {
+    #[error("HTTP request error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[cfg(feature = "claude")]
+    #[error(transparent)]
+    Claude(#[from] crate::claude::ClaudeApiError),
+
+    #[cfg(feature = "copilot")]
+    #[error(transparent)]
+    GhAuth(#[from] crate::copilot::auth::GhAuthError),
+
+    #[cfg(feature = "browser")]
+    #[error(transparent)]
+    CodexAuth(#[from] crate::codex::auth::CodexAuthError),
+
+    #[cfg(feature = "browser")]
+    #[error(transparent)]
+    CookieReader(#[from] crate::browser::cookie_reader::CookieReaderError),
+
+    #[cfg(feature = "browser")]
+    #[error(transparent)]
+    Crypto(#[from] crate::crypto::CryptoError),
+
+    #[cfg(feature = "browser")]
+    #[error(transparent)]
+    Keyring(#[from] crate::keyring::KeyringError),
+
+    #[cfg(feature = "browser")]
+    #[error(transparent)]
+    OpencodeGoAuth(#[from] crate::opencode_go::OpencodeGoAuthError),
+
+    #[cfg(feature = "browser")]
+    #[error(transparent)]
+    OpencodeGoUsage(#[from] crate::opencode_go::OpencodeGoUsageError),
+
+    /// No browser profile has a signed-in session for the domain.
+    #[cfg(feature = "browser")]
+    #[error("no browser session found for {0}")]
+    SessionNotFound(String),
+
+    /// A provider API answered with an unsuccessful HTTP status.
+    #[error("{service} error: {status} - {body}")]
+    Status {
+        service: &'static str,
+        status: u16,
+        body: String,
+    },
+
+    /// A provider API is rate limiting or blocking requests as abusive; retrying after
+    /// `retry_after` (when known) should succeed.
+    #[error("{service} rate limit exceeded{}", retry_hint(*.retry_after))]
+    RateLimited {
+        service: &'static str,
+        retry_after: Option<std::time::Duration>,
+    },
+
+    /// The settings file is invalid.
+    #[error("invalid settings: {0}")]
+    Config(String),
+
+    /// A failure described only by a message, e.g. a missing API key.
+    #[error("{0}")]
+    Message(String),
+
+    /// A failure from a provider client or an [`crate::http::HttpTransport`].
+    #[error(transparent)]
+    Other(#[from] Box<dyn std::error::Error + Send + Sync>),
+}
+
+fn retry_hint(retry_after: Option<std::time::Duration>) -> String {
+    retry_after.map_or_else(String::new, |wait| {
+        format!("; retry in {}s", wait.as_secs().max(1))
+    })
+}
+
+impl From<String> for SeherError {
+    fn from(message: String) -> Self {
+        Self::Message(message)
+    }
+}
+
+impl From<&str> for SeherError {
+    fn from(message: &str) -> Self {
+        Self::Message(message.to_string())
+    }
+}
+
+impl SeherError {
+    /// `true` if a provider rejected the credentials (HTTP 401 or 403), e.g. because the
+    /// browser has rotated the session cookie since it was read.
+    #[must_use]
+    pub fn is_auth_error(&self) -> bool {
+        match self {
+            #[cfg(feature = "claude")]
+            Self::Claude(crate::claude::ClaudeApiError::ApiError { status, .. }) => {
+                matches!(status, 401 | 403)
+            }
+            Self::Status { status, .. } => matches!(status, 401 | 403),
+            Self::Other(e) => e.downcast_ref::<Self>().is_some_and(Self::is_auth_error),
+            _ => false,
+        }
+    }
+
+    /// `true` if the provider could not be reached at all (no connection, DNS failure or
+    /// timeout), as opposed to answering with an error.
+    #[must_use]
+    pub fn is_network_error(&self) -> bool {
+        match self {
+            Self::Http(e) => e.is_connect() || e.is_timeout(),
+            #[cfg(feature = "claude")]
+            Self::Claude(crate::claude::ClaudeApiError::RequestError(e)) => {
+                e.is_connect() || e.is_timeout()
+            }
+            #[cfg(feature = "claude")]
+            Self::Claude(crate::claude::ClaudeApiError::Transport(e)) => {
+                boxed_is_network_error(e.as_ref())
+            }
+            Self::Other(e) => boxed_is_network_error(e.as_ref()),
+            _ => false,
+        }
+    }
+}
+
+fn boxed_is_network_error(e: &(dyn std::error::Error + Send + Sync + 'static)) -> bool {
+    e.downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_connect() || e.is_timeout())
+        || e.downcast_ref::<SeherError>()
+            .is_some_and(SeherError::is_network_error)
+}
+
+pub type Result<T> = std::result::Result<T, SeherError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send_sync<T: Send + Sync + 'static>() {}
+
+    #[test]
+    fn seher_error_is_send_and_sync() {
+        assert_send_sync::<SeherError>();
+    }
+
+    #[test]
+    fn messages_and_module_errors_keep_their_category() {
+        assert!(matches!(
+            SeherError::from("WARP_API_KEY is required"),
+            SeherError::Message(_)
+        ));
+        let err: SeherError = std::io::Error::other("disk full").into();
+        assert!(matches!(err, SeherError::Io(_)));
+    }
+
+    #[test]
+    fn auth_errors_are_recognized_through_boxing() {
+        let status = |status| SeherError::Status {
+            service: "Codex usage API",
+            status,
+            body: String::new(),
+        };
+        assert!(status(401).is_auth_error());
+        assert!(!status(500).is_auth_error());
+        let boxed: Box<dyn std::error::Error + Send + Sync> = Box::new(status(403));
+        assert!(SeherError::from(boxed).is_auth_error());
+        assert!(!SeherError::from("missing key").is_auth_error());
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn unreachable_providers_are_network_errors() {
+        // Nothing listens on port 1, so the connection is refused.
+        let Err(e) = reqwest::Client::new()
+            .get("http://127.0.0.1:1")
+            .send()
+            .await
+        else {
+            return;
+        };
+        let boxed: Box<dyn std::error::Error + Send + Sync> = Box::new(e);
+        assert!(SeherError::from(boxed).is_network_error());
+        let status = SeherError::Status {
+            service: "GitHub Copilot API",
+            status: 503,
+            body: String::new(),
+        };
+        assert!(!status.is_network_error());
+    }
+}
@@ -0,0 +1,446 @@
+//! seher's event stream: limit changes, reset waits, agent runs and errors.
+//!
+//! Every seher process appends its events as JSON lines to one shared log, which
+//! `seher events --follow` tails. `seher daemon` additionally streams its own events to
+//! clients of a Unix socket (see [`serve`]).
+
+use crate::hooks::{HookEvent, HookPayload};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufRead, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use thiserror::Error;
+
+static INSTALLED: OnceLock<PathBuf> = OnceLock::new();
+static SUBSCRIBERS: OnceLock<tokio::sync::broadcast::Sender<String>> = OnceLock::new();
+
+/// The log is moved to `<path>.1` once it grows past this, keeping one old generation.
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+/// How often `--follow` checks the log for new events.
+const FOLLOW_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+#[derive(Debug, Error)]
+pub enum EventsError {
+    #[error("could not determine home directory for the event log")]
+    HomeDirNotFound,
+
+    #[error("failed to access event log: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to parse event log: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+/// What happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    /// A usage window became limited.
+    Limited,
+    /// A limited usage window is available again.
+    Available,
+    /// Every candidate agent is limited and seher started waiting for a reset.
+    WaitStarted,
+    /// A reset wait is over.
+    WaitFinished,
+    /// An agent was launched.
+    ExecStarted,
+    /// An agent exited.
+    ExecFinished,
+    /// A usage check or scheduled run failed.
+    Error,
+}
+
+/// One line of the event stream.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Event {
+    pub timestamp: DateTime<Utc>,
+    pub event: EventKind,
+    /// The agent's label, e.g. `claude (work@example.com)`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// The usage window, e.g. `five_hour` (`limited`, `available`, `wait_started`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<String>,
+    /// When the window is expected to reset (`limited`, `wait_started`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reset_time: Option<DateTime<Utc>>,
+    /// The full command line being launched (`exec_started`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command_line: Option<String>,
+    /// The agent's exit code, if it exited normally (`exec_finished`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// How the agent's run ended, e.g. `succeeded` (`exec_finished`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<String>,
+    /// What went wrong (`error`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl Event {
+    #[must_use]
+    pub fn new(event: EventKind) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            event,
+            agent: None,
+            provider: None,
+            window: None,
+            reset_time: None,
+            command_line: None,
+            exit_code: None,
+            outcome: None,
+            message: None,
+        }
+    }
+
+    /// An `error` event for `agent` (if the failure belongs to one).
+    #[must_use]
+    pub fn error(agent: Option<String>, message: impl std::fmt::Display) -> Self {
+        Self {
+            agent,
+            message: Some(message.to_string()),
+            ..Self::new(EventKind::Error)
+        }
+    }
+}
+
+impl From<&HookPayload> for Event {
+    fn from(payload: &HookPayload) -> Self {
+        let event = match payload.event {
+            HookEvent::OnLimited => EventKind::WaitStarted,
+            HookEvent::OnReset => EventKind::WaitFinished,
+            HookEvent::PreExec => EventKind::ExecStarted,
+            HookEvent::PostExec => EventKind::ExecFinished,
+        };
+        Self {
+            timestamp: payload.timestamp,
+            event,
+            agent: Some(payload.agent.clone()),
+            provider: payload.provider.clone(),
+            window: payload.window.clone(),
+            reset_time: payload.reset_time,
+            command_line: payload.command_line.clone(),
+            exit_code: payload.exit_code,
+            outcome: payload.outcome.clone(),
+            message: None,
+        }
+    }
+}
+
+/// Turns successive usage polls into `limited` and `available` events.
+#[derive(Debug, Default)]
+pub struct LimitTracker {
+    limited: HashMap<(String, String), bool>,
+}
+
+impl LimitTracker {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the state of `agent`'s `window`, returning an event if it changed. A window
+    /// seen for the first time is reported only if it is limited.
+    pub fn observe(
+        &mut self,
+        agent: &str,
+        provider: Option<&str>,
+        window: &str,
+        limited: bool,
+        reset_time: Option<DateTime<Utc>>,
+    ) -> Option<Event> {
+        let previous = self
+            .limited
+            .insert((agent.to_string(), window.to_string()), limited);
+        if previous.unwrap_or(false) == limited {
+            return None;
+        }
+        Some(Event {
+            agent: Some(agent.to_string()),
+            provider: provider.map(ToString::to_string),
+            window: Some(window.to_string()),
+            reset_time: reset_time.filter(|_| limited),
+            ..Event::new(if limited {
+                EventKind::Limited
+            } else {
+                EventKind::Available
+            })
+        })
+    }
+}
+
+/// `$SEHER_EVENTS_PATH`, else `~/.seher/events.log`.
+///
+/// # Errors
+///
+/// Returns an error if the home directory cannot be determined.
+pub fn default_path() -> Result<PathBuf, EventsError> {
+    if let Some(path) = std::env::var_os("SEHER_EVENTS_PATH").filter(|p| !p.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+    Ok(dirs::home_dir()
+        .ok_or(EventsError::HomeDirNotFound)?
+        .join(".seher")
+        .join("events.log"))
+}
+
+/// `$SEHER_DAEMON_SOCKET`, else `~/.seher/daemon.sock`.
+///
+/// # Errors
+///
+/// Returns an error if the home directory cannot be determined.
+pub fn default_socket_path() -> Result<PathBuf, EventsError> {
+    if let Some(path) = std::env::var_os("SEHER_DAEMON_SOCKET").filter(|p| !p.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+    Ok(dirs::home_dir()
+        .ok_or(EventsError::HomeDirNotFound)?
+        .join(".seher")
+        .join("daemon.sock"))
+}
+
+/// Record this process's events to the log at `path`, rotating it first if it is full.
+///
+/// Returns `false` if logging was already enabled.
+pub fn install(path: PathBuf) -> bool {
+    if std::fs::metadata(&path).is_ok_and(|m| m.len() > MAX_LOG_BYTES) {
+        let mut rotated = path.clone().into_os_string();
+        rotated.push(".1");
+        let _ = std::fs::rename(&path, rotated);
+    }
+    INSTALLED.set(path).is_ok()
+}
+
+/// Publish `event` to the log and to any socket clients.
+///
+/// This is best-effort: failures are reported as [warnings](crate::warning) and never
+/// interrupt the caller.
+pub fn publish(event: &Event) {
+    if let Some(path) = INSTALLED.get()
+        && let Err(e) = append(path, event)
+    {
+        crate::warning::warn(format_args!("failed to write event log: {e}"));
+    }
+    if let Some(subscribers) = SUBSCRIBERS.get()
+        && let Ok(line) = serde_json::to_string(event)
+    {
+        // No connected clients is not an error.
+        let _ = subscribers.send(line);
+    }
+}
+
+/// Append `event` as one JSON line, creating the log (mode 0600) if needed.
+///
+/// # Errors
+///
+/// Returns an error if the log cannot be created or written.
+pub fn append(path: &Path, event: &Event) -> Result<(), EventsError> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).append(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut line = serde_json::to_vec(event)?;
+    line.push(b'\n');
+    options.open(path)?.write_all(&line)?;
+    Ok(())
+}
+
+/// Every event in the log at `path`, oldest first. A missing log has no events.
+///
+/// # Errors
+///
+/// Returns an error if the log exists but cannot be read or holds a malformed line.
+pub fn read(path: &Path) -> Result<Vec<Event>, EventsError> {
+    let file = match std::fs::File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(e) => return Err(e.into()),
+    };
+    let mut events = Vec::new();
+    for line in std::io::BufReader::new(file).lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            events.push(serde_json::from_str(&line)?);
+        }
+    }
+    Ok(events)
+}
+
+/// Call `on_event` for every event appended to the log at `path` from now on, until
+/// reading fails. A log that shrinks (was rotated) is followed from its start.
+///
+/// # Errors
+///
+/// Returns an error if the log cannot be read.
+pub async fn follow(path: &Path, mut on_event: impl FnMut(Event)) -> Result<(), EventsError> {
+    let mut offset = std::fs::metadata(path).map_or(0, |m| m.len());
+    let mut partial = String::new();
+    loop {
+        tokio::time::sleep(FOLLOW_INTERVAL).await;
+        let mut file = match std::fs::File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let len = file.metadata()?.len();
+        if len < offset {
+            offset = 0;
+            partial.clear();
+        }
+        if len == offset {
+            continue;
+        }
+        file.seek(SeekFrom::Start(offset))?;
+        let read = file.read_to_string(&mut partial)?;
+        offset += read as u64;
+        while let Some(end) = partial.find('\n') {
+            let line: String = partial.drain(..=end).collect();
+            // Skip lines from a newer or older seher rather than ending the stream.
+            if let Ok(event) = serde_json::from_str(&line) {
+                on_event(event);
+            }
+        }
+    }
+}
+
+/// Stream every event this process publishes to clients of the Unix socket at `path`, as
+/// JSON lines, until the process exits.
+///
+/// # Errors
+///
+/// Returns an error if the socket cannot be bound or another process is serving it.
+#[cfg(unix)]
+pub async fn serve(path: &Path) -> Result<(), EventsError> {
+    use tokio::io::AsyncWriteExt;
+    use tokio::sync::broadcast::error::RecvError;
+
+    if tokio::net::UnixStream::connect(path).await.is_ok() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::AddrInUse,
+            format!("{} is served by another seher daemon", path.display()),
+        )
+        .into());
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let _ = std::fs::remove_file(path);
+    let listener = tokio::net::UnixListener::bind(path)?;
+    let sender = SUBSCRIBERS.get_or_init(|| tokio::sync::broadcast::channel(256).0);
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let mut events = sender.subscribe();
+        tokio::spawn(async move {
+            loop {
+                let line = match events.recv().await {
+                    Ok(line) => line,
+                    // A slow client misses events rather than holding back the daemon.
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return,
+                };
+                if stream
+                    .write_all(format!("{line}\n").as_bytes())
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn appends_and_reads_events_in_order() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("nested").join("events.log");
+        assert!(read(&path)?.is_empty());
+
+        append(&path, &Event::new(EventKind::ExecStarted))?;
+        append(&path, &Event::error(None, "boom"))?;
+
+        let events = read(&path)?;
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].event, EventKind::ExecStarted);
+        assert_eq!(events[1].message.as_deref(), Some("boom"));
+        Ok(())
+    }
+
+    #[test]
+    fn hook_payloads_map_to_wait_and_exec_events() -> TestResult {
+        let mut payload = HookPayload::new(
+            HookEvent::OnLimited,
+            "claude".to_string(),
+            "claude".to_string(),
+        );
+        payload.window = Some("five_hour".to_string());
+        let event = Event::from(&payload);
+        assert_eq!(event.event, EventKind::WaitStarted);
+        assert_eq!(event.window.as_deref(), Some("five_hour"));
+
+        let json = serde_json::to_value(&event)?;
+        assert_eq!(json["event"], "wait_started");
+        assert!(json.get("exit_code").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn tracker_reports_only_changes() {
+        let mut tracker = LimitTracker::new();
+        assert!(
+            tracker
+                .observe("claude", None, "five_hour", false, None)
+                .is_none()
+        );
+        let limited = tracker.observe("claude", Some("claude"), "five_hour", true, None);
+        assert_eq!(limited.map(|e| e.event), Some(EventKind::Limited));
+        assert!(
+            tracker
+                .observe("claude", None, "five_hour", true, None)
+                .is_none()
+        );
+        let available = tracker.observe("claude", None, "five_hour", false, None);
+        assert_eq!(available.map(|e| e.event), Some(EventKind::Available));
+
+        let first_seen = tracker.observe("copilot", None, "premium", true, None);
+        assert_eq!(first_seen.map(|e| e.event), Some(EventKind::Limited));
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn follow_yields_appended_events() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("events.log");
+        append(&path, &Event::new(EventKind::Limited))?;
+
+        let mut seen = Vec::new();
+        let writer = async {
+            tokio::time::sleep(FOLLOW_INTERVAL / 2).await;
+            append(&path, &Event::new(EventKind::Available))?;
+            tokio::time::sleep(FOLLOW_INTERVAL * 2).await;
+            Ok::<_, EventsError>(())
+        };
+        tokio::select! {
+            result = follow(&path, |event| seen.push(event.event)) => result?,
+            result = writer => result?,
+        }
+        assert_eq!(seen, [EventKind::Available]);
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+//! C ABI for embedding seher's session discovery and usage fetch in other languages.
+//!
+//! Build a shared library with
+//! `cargo rustc --lib --release --no-default-features --features ffi --crate-type cdylib`.
+
+use crate::claude::ClaudeClient;
+use crate::copilot::CopilotClient;
+use crate::error::SeherError;
+use crate::session;
+use std::ffi::{CStr, CString, c_char};
+
+/// Find a signed-in browser session for `domain` (`claude.ai` or `github.com`) and fetch its
+/// usage, returned as a JSON string. Failures are returned as `{"error": "..."}`.
+///
+/// The result must be released with [`seher_string_free`]. Returns null only if the JSON
+/// contains an interior NUL, which cannot happen for serde output.
+///
+/// # Safety
+///
+/// `domain` must be null or point to a NUL-terminated string that stays valid for the call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn seher_fetch_usage_json(domain: *const c_char) -> *mut c_char {
+    let domain = if domain.is_null() {
+        Err(SeherError::from("domain is null"))
+    } else {
+        // SAFETY: the caller guarantees a valid NUL-terminated string.
+        unsafe { CStr::from_ptr(domain) }
+            .to_str()
+            .map_err(|_| SeherError::from("domain is not valid UTF-8"))
+    };
+    let json = match domain.and_then(fetch_usage_json) {
+        Ok(json) => json,
+        Err(e) => serde_json::json!({ "error": e.to_string() }).to_string(),
+    };
+    CString::new(json).map_or(std::ptr::null_mut(), CString::into_raw)
+}
+
+/// Release a string returned by this library.
+///
+/// # Safety
+///
+/// `s` must be null or a pointer returned by [`seher_fetch_usage_json`] that has not been
+/// freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn seher_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        // SAFETY: the caller passes back a pointer from `CString::into_raw`, exactly once.
+        drop(unsafe { CString::from_raw(s) });
+    }
+}
+
+fn fetch_usage_json(domain: &str) -> Result<String, SeherError> {
+    let cookies = session::find_session(domain, session::session_cookie_names(domain));
+    if cookies.is_empty() {
+        return Err(SeherError::SessionNotFound(domain.to_string()));
+    }
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    runtime.block_on(async {
+        match domain {
+            "claude.ai" => Ok(serde_json::to_string(
+                &ClaudeClient::fetch_usage(&cookies).await?,
+            )?),
+            "github.com" => Ok(serde_json::to_string(
+                &CopilotClient::fetch_quota(&cookies).await?,
+            )?),
+            other => Err(SeherError::Message(format!(
+                "usage is not available for {other}"
+            ))),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    fn call(domain: Option<&CStr>) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let ptr = domain.map_or(std::ptr::null(), CStr::as_ptr);
+        // SAFETY: `ptr` is null or a valid C string for the duration of the call.
+        let out = unsafe { seher_fetch_usage_json(ptr) };
+        if out.is_null() {
+            return Err("unexpected null".into());
+        }
+        // SAFETY: `out` was just returned by the library and is freed right after.
+        let json = unsafe { CStr::from_ptr(out) }.to_str()?.to_string();
+        // SAFETY: `out` came from `seher_fetch_usage_json` and is freed once.
+        unsafe { seher_string_free(out) };
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    #[test]
+    fn null_domain_returns_error_json() -> TestResult {
+        let value = call(None)?;
+        assert_eq!(value["error"], "domain is null");
+        Ok(())
+    }
+
+    #[test]
+    fn free_accepts_null() {
+        // SAFETY: null is explicitly allowed.
+        unsafe { seher_string_free(std::ptr::null_mut()) };
+    }
+}
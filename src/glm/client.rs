@@ -1,5 +1,3 @@
-use std::time::Duration;
-
 use super::types::GlmUsageResponse;
 
 const QUOTA_URL: &str = "https://open.bigmodel.cn/api/monitor/usage/quota/limit";
@@ -12,7 +10,7 @@ impl GlmClient {
     /// Returns an error if the API request fails or the response cannot be parsed.
     pub async fn fetch_quota(
         api_key: &str,
-    ) -> Result<GlmUsageResponse, Box<dyn std::error::Error>> {
+    ) -> Result<GlmUsageResponse, Box<dyn std::error::Error + Send + Sync>> {
         let client = Self::build_client()?;
         let response = client
             .get(QUOTA_URL)
@@ -32,8 +30,6 @@ impl GlmClient {
     }
 
     fn build_client() -> Result<reqwest::Client, reqwest::Error> {
-        reqwest::Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
+        crate::http::shared_client()
     }
 }
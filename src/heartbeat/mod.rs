@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+/// Dead-man's-switch pings for `seher watch` and `seher daemon` (`heartbeat` in settings).
+///
+/// Follows the healthchecks.io convention: the URL is pinged after each healthy cycle
+/// and `<url>/fail` after a failed one, so a monitor that stops pinging raises an alert.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HeartbeatConfig {
+    /// Pinged after each successful cycle, e.g. `https://hc-ping.com/<uuid>`.
+    pub url: String,
+    /// Pinged after a failed cycle instead of `<url>/fail`, for other heartbeat services.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail_url: Option<String>,
+}
+
+impl HeartbeatConfig {
+    /// The URL to ping for a cycle that succeeded or failed.
+    #[must_use]
+    pub fn ping_url(&self, success: bool) -> String {
+        if success {
+            return self.url.clone();
+        }
+        self.fail_url
+            .clone()
+            .unwrap_or_else(|| format!("{}/fail", self.url.trim_end_matches('/')))
+    }
+
+    /// Ping the heartbeat URL, sending `log` (e.g. what failed) as the request body.
+    ///
+    /// Pings are best-effort; failures are reported as [warnings](crate::warning) and never
+    /// abort the caller.
+    pub async fn ping(&self, success: bool, log: &str) {
+        if let Err(e) = self.send(success, log).await {
+            crate::warning::warn(format_args!("heartbeat ping failed: {e}"));
+        }
+    }
+
+    async fn send(&self, success: bool, log: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let response = crate::http::shared_client()?
+            .post(self.ping_url(success))
+            .body(log.to_string())
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("heartbeat returned {status}").into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failures_ping_the_fail_endpoint() {
+        let mut heartbeat = HeartbeatConfig {
+            url: "https://hc-ping.com/abc/".to_string(),
+            fail_url: None,
+        };
+        assert_eq!(heartbeat.ping_url(true), "https://hc-ping.com/abc/");
+        assert_eq!(heartbeat.ping_url(false), "https://hc-ping.com/abc/fail");
+
+        heartbeat.fail_url = Some("https://status.example.com/down".to_string());
+        assert_eq!(heartbeat.ping_url(false), "https://status.example.com/down");
+    }
+}
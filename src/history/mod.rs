@@ -0,0 +1,445 @@
+use crate::agent::UsageEntry;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, params};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use thiserror::Error;
+
+static INSTALLED: OnceLock<PathBuf> = OnceLock::new();
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS samples (
+    id INTEGER PRIMARY KEY,
+    recorded_at TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    account TEXT NOT NULL,
+    window TEXT NOT NULL,
+    utilization REAL NOT NULL,
+    limited INTEGER NOT NULL,
+    resets_at TEXT
+);
+CREATE INDEX IF NOT EXISTS samples_recorded_at ON samples (recorded_at);
+";
+
+#[derive(Debug, Error)]
+pub enum HistoryError {
+    #[error("could not determine home directory for the history database")]
+    HomeDirNotFound,
+
+    #[error("failed to create history directory: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("history database error: {0}")]
+    Sql(#[from] rusqlite::Error),
+}
+
+/// One recorded usage window measurement.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Sample {
+    pub recorded_at: DateTime<Utc>,
+    pub provider: String,
+    /// Anonymized account identifier (hash of the credentials).
+    pub account: String,
+    pub window: String,
+    pub utilization: f64,
+    pub limited: bool,
+    pub resets_at: Option<DateTime<Utc>>,
+}
+
+/// Local `SQLite` store of usage samples, used by `seher history`.
+pub struct HistoryStore {
+    conn: Connection,
+}
+
+impl HistoryStore {
+    /// `$SEHER_HISTORY_PATH`, else `$XDG_DATA_HOME/seher/history.sqlite`, else
+    /// `~/.local/share/seher/history.sqlite`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the home directory cannot be determined.
+    pub fn default_path() -> Result<PathBuf, HistoryError> {
+        if let Some(path) = std::env::var_os("SEHER_HISTORY_PATH").filter(|p| !p.is_empty()) {
+            return Ok(PathBuf::from(path));
+        }
+        let dir = match std::env::var_os("XDG_DATA_HOME").filter(|d| !d.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => dirs::home_dir()
+                .ok_or(HistoryError::HomeDirNotFound)?
+                .join(".local")
+                .join("share"),
+        };
+        Ok(dir.join("seher").join("history.sqlite"))
+    }
+
+    /// Open the default history database, creating it if needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be opened or initialized.
+    pub fn open_default() -> Result<Self, HistoryError> {
+        Self::open(&Self::default_path()?)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be opened or initialized.
+    pub fn open(path: &Path) -> Result<Self, HistoryError> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// Record every entry as a sample taken at `recorded_at`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the insert fails.
+    pub fn record(
+        &mut self,
+        recorded_at: DateTime<Utc>,
+        provider: &str,
+        account: &str,
+        entries: &[UsageEntry],
+    ) -> Result<(), HistoryError> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut stmt = tx.prepare(
+                "INSERT INTO samples (recorded_at, provider, account, window, utilization, limited, resets_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            )?;
+            for entry in entries {
+                stmt.execute(params![
+                    recorded_at.to_rfc3339(),
+                    provider,
+                    account,
+                    entry.entry_type,
+                    entry.utilization,
+                    entry.limited,
+                    entry.resets_at.map(|t| t.to_rfc3339()),
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Samples recorded at or after `since`, oldest first, optionally for one provider.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn samples_since(
+        &self,
+        since: DateTime<Utc>,
+        provider: Option<&str>,
+    ) -> Result<Vec<Sample>, HistoryError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT recorded_at, provider, account, window, utilization, limited, resets_at
+             FROM samples
+             WHERE recorded_at >= ?1 AND (?2 IS NULL OR provider = ?2)
+             ORDER BY recorded_at, id",
+        )?;
+        let rows = stmt.query_map(params![since.to_rfc3339(), provider], |row| {
+            Ok(Sample {
+                recorded_at: parse_time(&row.get::<_, String>(0)?).unwrap_or(since),
+                provider: row.get(1)?,
+                account: row.get(2)?,
+                window: row.get(3)?,
+                utilization: row.get(4)?,
+                limited: row.get(5)?,
+                resets_at: row
+                    .get::<_, Option<String>>(6)?
+                    .as_deref()
+                    .and_then(parse_time),
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// Samples of one account's window recorded at or after `since`, oldest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the query fails.
+    pub fn window_samples(
+        &self,
+        provider: &str,
+        account: &str,
+        window: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<Sample>, HistoryError> {
+        Ok(self
+            .samples_since(since, Some(provider))?
+            .into_iter()
+            .filter(|s| s.account == account && s.window == window)
+            .collect())
+    }
+}
+
+/// Enable recording for this process, writing samples to the database at `path`.
+///
+/// Returns `false` if recording was already enabled.
+pub fn install(path: PathBuf) -> bool {
+    INSTALLED.set(path).is_ok()
+}
+
+/// Record a usage sample into the installed history database.
+///
+/// This is best-effort: it does nothing when recording is not enabled, and failures
+/// never interrupt the usage check that produced the sample.
+pub fn record(provider: &str, account: &str, entries: &[UsageEntry]) {
+    let Some(path) = INSTALLED.get() else {
+        return;
+    };
+    if entries.is_empty() {
+        return;
+    }
+    if let Err(e) = HistoryStore::open(path)
+        .and_then(|mut store| store.record(Utc::now(), provider, account, entries))
+    {
+        crate::warning::warn(format_args!("failed to record usage history: {e}"));
+    }
+}
+
+/// Minimum time span covered by samples before a pace is reported.
+const MIN_PACE_SPAN_MINUTES: i64 = 10;
+
+/// Recent consumption pace of one usage window.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BurnRate {
+    /// Utilization percentage points consumed per hour.
+    pub per_hour: f64,
+    /// When the window reaches 100% at this pace, if that happens before it resets.
+    pub eta: Option<DateTime<Utc>>,
+}
+
+/// Estimate the burn rate of one window from its samples (oldest first).
+///
+/// Only the samples since the last reset (the last drop in utilization) are used, and
+/// the slope is a least-squares fit over them.
+#[must_use]
+pub fn burn_rate(samples: &[Sample], now: DateTime<Utc>) -> Option<BurnRate> {
+    let start = samples
+        .windows(2)
+        .rposition(|pair| pair[1].utilization < pair[0].utilization)
+        .map_or(0, |i| i + 1);
+    let period = &samples[start..];
+    let (first, last) = (period.first()?, period.last()?);
+    if (last.recorded_at - first.recorded_at).num_minutes() < MIN_PACE_SPAN_MINUTES {
+        return None;
+    }
+
+    #[expect(clippy::cast_precision_loss, reason = "sample offsets fit in f64")]
+    let points: Vec<(f64, f64)> = period
+        .iter()
+        .map(|s| {
+            let hours = (s.recorded_at - first.recorded_at).num_seconds() as f64 / 3600.0;
+            (hours, s.utilization)
+        })
+        .collect();
+    #[expect(clippy::cast_precision_loss, reason = "sample counts fit in f64")]
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+    let var_x: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let cov: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let per_hour = cov / var_x;
+    if !per_hour.is_finite() {
+        return None;
+    }
+
+    let eta = if per_hour > 0.0 && last.utilization < 100.0 {
+        #[expect(
+            clippy::cast_possible_truncation,
+            reason = "bounded by the reset window"
+        )]
+        let secs = ((100.0 - last.utilization) / per_hour * 3600.0) as i64;
+        let eta = last.recorded_at + chrono::Duration::seconds(secs);
+        Some(eta.max(now)).filter(|eta| last.resets_at.is_none_or(|reset| *eta < reset))
+    } else {
+        None
+    };
+    Some(BurnRate { per_hour, eta })
+}
+
+/// Fill in `limit_eta` on `entries` from the installed history database.
+///
+/// Does nothing when history recording is not enabled.
+pub fn annotate(provider: &str, account: &str, entries: &mut [UsageEntry]) {
+    let Some(path) = INSTALLED.get() else {
+        return;
+    };
+    let Ok(store) = HistoryStore::open(path) else {
+        return;
+    };
+    let now = Utc::now();
+    for entry in entries {
+        let Ok(samples) = store.window_samples(
+            provider,
+            account,
+            &entry.entry_type,
+            now - chrono::Duration::days(8),
+        ) else {
+            return;
+        };
+        entry.limit_eta = burn_rate(&samples, now).and_then(|rate| rate.eta);
+    }
+}
+
+/// Format the time until `eta` as e.g. `~1h 40m`.
+#[must_use]
+pub fn format_eta(eta: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let minutes = (eta - now).num_minutes().max(0);
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("~{m}m"),
+        (h, _) if h >= 24 => format!("~{}d {}h", h / 24, h % 24),
+        (h, m) => format!("~{h}h {m}m"),
+    }
+}
+
+fn parse_time(s: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(s)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+/// Parse a duration such as `30m`, `12h`, `7d` or `2w`.
+///
+/// # Errors
+///
+/// Returns an error if the number or unit is invalid.
+pub fn parse_duration(s: &str) -> Result<chrono::Duration, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(|| format!("missing unit in {s:?} (use m, h, d or w)"))?;
+    let (num, unit) = s.split_at(split);
+    let n: i64 = num
+        .parse()
+        .map_err(|_| format!("invalid number in {s:?}"))?;
+    match unit {
+        "m" => Ok(chrono::Duration::minutes(n)),
+        "h" => Ok(chrono::Duration::hours(n)),
+        "d" => Ok(chrono::Duration::days(n)),
+        "w" => Ok(chrono::Duration::weeks(n)),
+        _ => Err(format!("invalid unit {unit:?} in {s:?} (use m, h, d or w)")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    fn entry(window: &str, utilization: f64) -> UsageEntry {
+        UsageEntry {
+            entry_type: window.to_string(),
+            limited: utilization >= 100.0,
+            utilization,
+            resets_at: None,
+            remaining: None,
+            limit: None,
+            limit_eta: None,
+        }
+    }
+
+    #[test]
+    fn records_and_queries_samples() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let mut store = HistoryStore::open(&dir.path().join("history.sqlite"))?;
+        let t0 = "2026-01-01T00:00:00Z".parse::<DateTime<Utc>>()?;
+        let t1 = "2026-01-02T00:00:00Z".parse::<DateTime<Utc>>()?;
+
+        store.record(t0, "claude", "acct", &[entry("five_hour", 10.0)])?;
+        store.record(
+            t1,
+            "claude",
+            "acct",
+            &[entry("five_hour", 100.0), entry("seven_day", 50.0)],
+        )?;
+        store.record(t1, "copilot", "acct", &[entry("chat_utilization", 5.0)])?;
+
+        let all = store.samples_since(t0, None)?;
+        assert_eq!(all.len(), 4);
+        assert_eq!(all[0].recorded_at, t0);
+
+        let recent_claude = store.samples_since(t1, Some("claude"))?;
+        assert_eq!(recent_claude.len(), 2);
+        assert!(recent_claude[0].limited);
+        Ok(())
+    }
+
+    fn sample_at(minutes: i64, utilization: f64) -> Result<Sample, chrono::ParseError> {
+        let base = "2026-01-01T00:00:00Z".parse::<DateTime<Utc>>()?;
+        Ok(Sample {
+            recorded_at: base + chrono::Duration::minutes(minutes),
+            provider: "claude".to_string(),
+            account: "acct".to_string(),
+            window: "five_hour".to_string(),
+            utilization,
+            limited: utilization >= 100.0,
+            resets_at: Some(base + chrono::Duration::hours(5)),
+        })
+    }
+
+    #[test]
+    fn burn_rate_projects_time_to_limit() -> TestResult {
+        let samples = vec![
+            sample_at(0, 20.0)?,
+            sample_at(30, 30.0)?,
+            sample_at(60, 40.0)?,
+        ];
+        let now = samples[2].recorded_at;
+        let rate = burn_rate(&samples, now).ok_or("expected a burn rate")?;
+        assert!((rate.per_hour - 20.0).abs() < 1e-9);
+        assert_eq!(rate.eta, Some(now + chrono::Duration::hours(3)));
+        Ok(())
+    }
+
+    #[test]
+    fn burn_rate_ignores_samples_before_reset_and_eta_past_reset() -> TestResult {
+        let samples = vec![
+            sample_at(0, 90.0)?,
+            sample_at(10, 2.0)?,
+            sample_at(70, 4.0)?,
+        ];
+        let rate = burn_rate(&samples, samples[2].recorded_at).ok_or("expected a burn rate")?;
+        assert!((rate.per_hour - 2.0).abs() < 1e-9);
+        assert_eq!(rate.eta, None);
+        Ok(())
+    }
+
+    #[test]
+    fn burn_rate_needs_enough_span() -> TestResult {
+        let samples = vec![sample_at(0, 20.0)?, sample_at(5, 30.0)?];
+        assert_eq!(burn_rate(&samples, samples[1].recorded_at), None);
+        Ok(())
+    }
+
+    #[test]
+    fn format_eta_uses_largest_units() -> TestResult {
+        let now = "2026-01-01T00:00:00Z".parse::<DateTime<Utc>>()?;
+        let at = |m| now + chrono::Duration::minutes(m);
+        assert_eq!(format_eta(at(45), now), "~45m");
+        assert_eq!(format_eta(at(100), now), "~1h 40m");
+        assert_eq!(format_eta(at(60 * 50), now), "~2d 2h");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_duration_units() -> Result<(), String> {
+        assert_eq!(parse_duration("30m")?, chrono::Duration::minutes(30));
+        assert_eq!(parse_duration("12h")?, chrono::Duration::hours(12));
+        assert_eq!(parse_duration("7d")?, chrono::Duration::days(7));
+        assert_eq!(parse_duration("2w")?, chrono::Duration::weeks(2));
+        assert!(parse_duration("7").is_err());
+        assert!(parse_duration("7y").is_err());
+        Ok(())
+    }
+}
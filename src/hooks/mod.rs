@@ -0,0 +1,193 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+
+/// Shell commands run on seher's lifecycle events (`hooks` in settings).
+///
+/// Each command runs with `sh -c` (`cmd /C` on Windows), receives a [`HookPayload`] as
+/// JSON on stdin and the event name in `SEHER_HOOK_EVENT`, and is waited for before
+/// seher carries on.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    /// Run when every candidate agent is limited and seher starts waiting for a reset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_limited: Option<String>,
+    /// Run when a reset wait is over and the agent is available again.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_reset: Option<String>,
+    /// Run right before an agent is launched.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pre_exec: Option<String>,
+    /// Run after an agent exits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_exec: Option<String>,
+}
+
+/// A lifecycle event that can trigger a hook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    OnLimited,
+    OnReset,
+    PreExec,
+    PostExec,
+}
+
+impl HookEvent {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::OnLimited => "on_limited",
+            Self::OnReset => "on_reset",
+            Self::PreExec => "pre_exec",
+            Self::PostExec => "post_exec",
+        }
+    }
+}
+
+/// The JSON document a hook reads from stdin.
+#[derive(Debug, Clone, Serialize)]
+pub struct HookPayload {
+    pub event: HookEvent,
+    pub timestamp: DateTime<Utc>,
+    /// The agent's label, e.g. `claude (work@example.com)`.
+    pub agent: String,
+    pub command: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    /// The limited usage window, e.g. `five_hour` (`on_limited`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<String>,
+    /// When the limited agent is expected to reset (`on_limited`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reset_time: Option<DateTime<Utc>>,
+    /// The full command line being launched (`pre_exec`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command_line: Option<String>,
+    /// The agent's exit code, if it exited normally (`post_exec`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// How the agent's run ended, e.g. `succeeded` or `hit a rate limit` (`post_exec`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<String>,
+}
+
+impl HookPayload {
+    #[must_use]
+    pub fn new(event: HookEvent, agent: String, command: String) -> Self {
+        Self {
+            event,
+            timestamp: Utc::now(),
+            agent,
+            command,
+            provider: None,
+            window: None,
+            reset_time: None,
+            command_line: None,
+            exit_code: None,
+            outcome: None,
+        }
+    }
+}
+
+impl HooksConfig {
+    /// The command configured for `event`, if any.
+    #[must_use]
+    pub fn command(&self, event: HookEvent) -> Option<&str> {
+        match event {
+            HookEvent::OnLimited => self.on_limited.as_deref(),
+            HookEvent::OnReset => self.on_reset.as_deref(),
+            HookEvent::PreExec => self.pre_exec.as_deref(),
+            HookEvent::PostExec => self.post_exec.as_deref(),
+        }
+    }
+
+    /// Run the hook for `payload.event`, if one is configured, and wait for it.
+    ///
+    /// Hooks are best-effort; failures are reported as [warnings](crate::warning) and never
+    /// abort the caller.
+    pub async fn run(&self, payload: &HookPayload) {
+        let Some(command) = self.command(payload.event) else {
+            return;
+        };
+        if let Err(e) = run_hook(command, payload).await {
+            crate::warning::warn(format_args!("{} hook failed: {e}", payload.event.as_str()));
+        }
+    }
+}
+
+async fn run_hook(command: &str, payload: &HookPayload) -> std::io::Result<()> {
+    let mut shell = if cfg!(windows) {
+        let mut shell = tokio::process::Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = tokio::process::Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    let mut child = shell
+        .arg(command)
+        .env("SEHER_HOOK_EVENT", payload.event.as_str())
+        .stdin(std::process::Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let json = serde_json::to_vec(payload).map_err(std::io::Error::other)?;
+        // A hook that ignores its input may exit before reading it.
+        let _ = stdin.write_all(&json).await;
+    }
+    let status = child.wait().await?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!("exited with {status}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn parses_hooks_by_event() -> TestResult {
+        let hooks: HooksConfig =
+            serde_json::from_str(r#"{"on_limited": "notify.sh", "post_exec": "backup.sh"}"#)?;
+        assert_eq!(hooks.command(HookEvent::OnLimited), Some("notify.sh"));
+        assert_eq!(hooks.command(HookEvent::OnReset), None);
+        assert_eq!(hooks.command(HookEvent::PostExec), Some("backup.sh"));
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "current_thread")]
+    async fn hooks_receive_the_payload_on_stdin() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let out = dir.path().join("payload.json");
+        let hooks = HooksConfig {
+            post_exec: Some(format!(
+                "cat > '{}' && test \"$SEHER_HOOK_EVENT\" = post_exec",
+                out.display()
+            )),
+            ..HooksConfig::default()
+        };
+        let mut payload = HookPayload::new(
+            HookEvent::PostExec,
+            "claude".to_string(),
+            "claude".to_string(),
+        );
+        payload.exit_code = Some(0);
+        payload.outcome = Some("succeeded".to_string());
+
+        hooks.run(&payload).await;
+
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&out)?)?;
+        assert_eq!(json["event"], "post_exec");
+        assert_eq!(json["agent"], "claude");
+        assert_eq!(json["exit_code"], 0);
+        assert!(json.get("reset_time").is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+static INSTALLED: OnceLock<BrowserHeaders> = OnceLock::new();
+
+#[cfg(target_os = "macos")]
+const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) \
+    AppleWebKit/537.36 (KHTML, like Gecko) Chrome/145.0.0.0 Safari/537.36";
+#[cfg(target_os = "windows")]
+const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) \
+    AppleWebKit/537.36 (KHTML, like Gecko) Chrome/145.0.0.0 Safari/537.36";
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (X11; Linux x86_64) \
+    AppleWebKit/537.36 (KHTML, like Gecko) Chrome/145.0.0.0 Safari/537.36";
+
+/// The browser identity sent to cookie-based endpoints (claude.ai, github.com,
+/// chatgpt.com), from settings (`browser_headers`).
+///
+/// Unset fields follow the User-Agent so the headers stay consistent: the default is
+/// Chrome on the host OS, `sec-ch-ua-platform` is read from the User-Agent's OS, and
+/// `sec-ch-ua` from its Chrome or Edge version. Browsers that do not send client hints
+/// (Firefox, Safari) get none unless they are configured explicitly.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BrowserHeaders {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_agent: Option<String>,
+    /// Full `sec-ch-ua` value, e.g. `"Chromium";v="145", "Google Chrome";v="145"`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sec_ch_ua: Option<String>,
+    /// Platform name for `sec-ch-ua-platform` without quotes, e.g. `Windows`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sec_ch_ua_platform: Option<String>,
+}
+
+impl BrowserHeaders {
+    /// Install these headers process-wide. Returns `false` if already installed.
+    pub fn install(self) -> bool {
+        INSTALLED.set(self).is_ok()
+    }
+
+    /// The installed headers, or the defaults.
+    #[must_use]
+    pub fn current() -> &'static Self {
+        static DEFAULT: BrowserHeaders = BrowserHeaders {
+            user_agent: None,
+            sec_ch_ua: None,
+            sec_ch_ua_platform: None,
+        };
+        INSTALLED.get().unwrap_or(&DEFAULT)
+    }
+
+    #[must_use]
+    pub fn user_agent(&self) -> &str {
+        self.user_agent
+            .as_deref()
+            .filter(|ua| !ua.trim().is_empty())
+            .unwrap_or(DEFAULT_USER_AGENT)
+    }
+
+    /// The `sec-ch-ua` header value, if this browser sends one.
+    #[must_use]
+    pub fn sec_ch_ua(&self) -> Option<String> {
+        if let Some(value) = &self.sec_ch_ua {
+            return Some(value.clone());
+        }
+        let user_agent = self.user_agent();
+        let (brand, version) = match version_after(user_agent, "Edg/") {
+            Some(version) => ("Microsoft Edge", version),
+            None => ("Google Chrome", version_after(user_agent, "Chrome/")?),
+        };
+        Some(format!(
+            "\"Not(A:Brand\";v=\"99\", \"{brand}\";v=\"{version}\", \"Chromium\";v=\"{version}\""
+        ))
+    }
+
+    /// The quoted `sec-ch-ua-platform` header value, if this browser sends one.
+    #[must_use]
+    pub fn sec_ch_ua_platform(&self) -> Option<String> {
+        let platform = match &self.sec_ch_ua_platform {
+            Some(platform) => platform.as_str(),
+            None if self.sec_ch_ua().is_some() => platform_of(self.user_agent())?,
+            None => return None,
+        };
+        Some(format!("\"{platform}\""))
+    }
+}
+
+/// The major version following `marker` (e.g. `Chrome/`) in a User-Agent.
+fn version_after<'a>(user_agent: &'a str, marker: &str) -> Option<&'a str> {
+    let rest = &user_agent[user_agent.find(marker)? + marker.len()..];
+    let version = rest.split(['.', ' ']).next()?;
+    (!version.is_empty() && version.bytes().all(|b| b.is_ascii_digit())).then_some(version)
+}
+
+/// The client-hint platform name for the OS a User-Agent claims.
+fn platform_of(user_agent: &str) -> Option<&'static str> {
+    if user_agent.contains("Windows") {
+        Some("Windows")
+    } else if user_agent.contains("Android") {
+        Some("Android")
+    } else if user_agent.contains("CrOS") {
+        Some("Chrome OS")
+    } else if user_agent.contains("Macintosh") {
+        Some("macOS")
+    } else if user_agent.contains("Linux") {
+        Some("Linux")
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_headers_are_chrome_on_the_host_os() {
+        let headers = BrowserHeaders::default();
+        let platform = if cfg!(target_os = "macos") {
+            "\"macOS\""
+        } else if cfg!(target_os = "windows") {
+            "\"Windows\""
+        } else {
+            "\"Linux\""
+        };
+        assert_eq!(headers.sec_ch_ua_platform().as_deref(), Some(platform));
+        assert!(
+            headers
+                .sec_ch_ua()
+                .is_some_and(|v| v.contains("\"Google Chrome\";v=\"145\""))
+        );
+    }
+
+    #[test]
+    fn client_hints_follow_a_configured_user_agent() {
+        let edge = BrowserHeaders {
+            user_agent: Some(
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) \
+                 Chrome/138.0.0.0 Safari/537.36 Edg/138.0.0.0"
+                    .to_string(),
+            ),
+            ..BrowserHeaders::default()
+        };
+        assert_eq!(edge.sec_ch_ua_platform().as_deref(), Some("\"Windows\""));
+        assert!(
+            edge.sec_ch_ua()
+                .is_some_and(|v| v.contains("\"Microsoft Edge\";v=\"138\""))
+        );
+
+        let firefox = BrowserHeaders {
+            user_agent: Some(
+                "Mozilla/5.0 (X11; Linux x86_64; rv:140.0) Gecko/20100101 Firefox/140.0"
+                    .to_string(),
+            ),
+            ..BrowserHeaders::default()
+        };
+        assert_eq!(firefox.sec_ch_ua(), None);
+        assert_eq!(firefox.sec_ch_ua_platform(), None);
+
+        let pinned = BrowserHeaders {
+            sec_ch_ua_platform: Some("Linux".to_string()),
+            ..firefox
+        };
+        assert_eq!(pinned.sec_ch_ua_platform().as_deref(), Some("\"Linux\""));
+    }
+}
@@ -0,0 +1,117 @@
+use super::{HttpRequest, HttpResponse};
+use chrono::Utc;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+static INSTALLED: OnceLock<Mutex<std::fs::File>> = OnceLock::new();
+
+/// Headers whose values are replaced with [`REDACTED`] in a dump.
+const SECRET_HEADERS: [&str; 6] = [
+    "authorization",
+    "cookie",
+    "proxy-authorization",
+    "set-cookie",
+    "x-api-key",
+    "x-csrf-token",
+];
+
+const REDACTED: &str = "[redacted]";
+
+/// Write every provider request and its raw response to `path` for the rest of the
+/// process (`--dump-response`). The file is truncated and created with mode 0600.
+///
+/// Returns `Ok(false)` if a dump was already installed.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be created.
+pub fn install(path: &Path) -> std::io::Result<bool> {
+    if INSTALLED.get().is_some() {
+        return Ok(false);
+    }
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.create(true).write(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    Ok(INSTALLED.set(Mutex::new(options.open(path)?)).is_ok())
+}
+
+/// Append `request` and `response` to the installed dump. Best-effort: does nothing
+/// without a dump, and write failures are reported as [warnings](crate::warning).
+pub fn record(request: &HttpRequest, response: &HttpResponse) {
+    let Some(file) = INSTALLED.get() else {
+        return;
+    };
+    let Ok(mut file) = file.lock() else {
+        return;
+    };
+    if let Err(e) = file.write_all(format_exchange(request, response).as_bytes()) {
+        crate::warning::warn(format_args!("failed to write response dump: {e}"));
+    }
+}
+
+/// One request/response pair in the dump format, with credentials redacted.
+#[must_use]
+pub fn format_exchange(request: &HttpRequest, response: &HttpResponse) -> String {
+    let mut out = format!("### {}\nGET {}\n", Utc::now().to_rfc3339(), request.url);
+    push_headers(&mut out, &request.headers);
+    let _ = write!(out, "\nHTTP {}\n", response.status);
+    push_headers(&mut out, &response.headers);
+    out.push('\n');
+    out.push_str(&response.body);
+    if !response.body.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push('\n');
+    out
+}
+
+fn push_headers(out: &mut String, headers: &[(String, String)]) {
+    for (name, value) in headers {
+        let value = if SECRET_HEADERS
+            .iter()
+            .any(|secret| name.eq_ignore_ascii_case(secret))
+        {
+            REDACTED
+        } else {
+            value
+        };
+        let _ = writeln!(out, "{name}: {value}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exchange_redacts_cookies_and_credentials() {
+        let request = HttpRequest::get("https://claude.ai/api/organizations")
+            .header("Cookie", "sessionKey=sk-ant-secret")
+            .header("Authorization", "Bearer ghu_token")
+            .header("Accept", "application/json");
+        let mut response = HttpResponse::new(200, r#"[{"uuid":"org"}]"#);
+        response.headers = vec![
+            ("content-type".to_string(), "application/json".to_string()),
+            ("set-cookie".to_string(), "__cf_bm=abc; Secure".to_string()),
+        ];
+
+        let dump = format_exchange(&request, &response);
+
+        assert!(dump.contains("GET https://claude.ai/api/organizations\n"));
+        assert!(dump.contains("Cookie: [redacted]\n"));
+        assert!(dump.contains("Authorization: [redacted]\n"));
+        assert!(dump.contains("Accept: application/json\n"));
+        assert!(dump.contains("\nHTTP 200\ncontent-type: application/json\n"));
+        assert!(dump.contains("set-cookie: [redacted]\n"));
+        assert!(dump.contains("\n[{\"uuid\":\"org\"}]\n"));
+        assert!(!dump.contains("sk-ant-secret"));
+        assert!(!dump.contains("ghu_token"));
+        assert!(!dump.contains("__cf_bm"));
+    }
+}
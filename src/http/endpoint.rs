@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+static BASE_URLS: OnceLock<BaseUrls> = OnceLock::new();
+
+/// Hosts whose base URL can be overridden, e.g. to point at a mock server or gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endpoint {
+    /// `https://claude.ai` (usage and organization APIs).
+    Claude,
+    /// `https://github.com` (Copilot chat page and OAuth device flow).
+    Github,
+    /// `https://api.github.com` (Copilot entitlement API).
+    GithubApi,
+    /// `https://console.anthropic.com` (prepaid API credits).
+    AnthropicConsole,
+    /// `https://api.anthropic.com` (Admin API cost report).
+    AnthropicApi,
+    /// `https://api.openai.com` (rate-limit headers and Admin API costs).
+    OpenAiApi,
+}
+
+impl Endpoint {
+    const fn default_url(self) -> &'static str {
+        match self {
+            Self::Claude => "https://claude.ai",
+            Self::Github => "https://github.com",
+            Self::GithubApi => "https://api.github.com",
+            Self::AnthropicConsole => "https://console.anthropic.com",
+            Self::AnthropicApi => "https://api.anthropic.com",
+            Self::OpenAiApi => "https://api.openai.com",
+        }
+    }
+
+    const fn env_var(self) -> &'static str {
+        match self {
+            Self::Claude => "SEHER_CLAUDE_BASE_URL",
+            Self::Github => "SEHER_GITHUB_BASE_URL",
+            Self::GithubApi => "SEHER_GITHUB_API_BASE_URL",
+            Self::AnthropicConsole => "SEHER_ANTHROPIC_CONSOLE_BASE_URL",
+            Self::AnthropicApi => "SEHER_ANTHROPIC_API_BASE_URL",
+            Self::OpenAiApi => "SEHER_OPENAI_API_BASE_URL",
+        }
+    }
+}
+
+/// Base URL overrides, from settings (`base_urls`) or `--base-url KEY=URL`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaseUrls {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub claude: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub github_api: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anthropic_console: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub anthropic_api: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub openai_api: Option<String>,
+}
+
+impl BaseUrls {
+    fn get(&self, endpoint: Endpoint) -> Option<&str> {
+        match endpoint {
+            Endpoint::Claude => self.claude.as_deref(),
+            Endpoint::Github => self.github.as_deref(),
+            Endpoint::GithubApi => self.github_api.as_deref(),
+            Endpoint::AnthropicConsole => self.anthropic_console.as_deref(),
+            Endpoint::AnthropicApi => self.anthropic_api.as_deref(),
+            Endpoint::OpenAiApi => self.openai_api.as_deref(),
+        }
+    }
+
+    /// Apply a `KEY=URL` override, where `KEY` is `claude`, `github`, `github_api`,
+    /// `anthropic_console`, `anthropic_api` or `openai_api`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the spec is malformed or the key is unknown.
+    pub fn set_from_spec(&mut self, spec: &str) -> Result<(), String> {
+        let (key, url) = spec
+            .split_once('=')
+            .ok_or_else(|| format!("expected KEY=URL, got {spec:?}"))?;
+        let slot = match key {
+            "claude" => &mut self.claude,
+            "github" => &mut self.github,
+            "github_api" => &mut self.github_api,
+            "anthropic_console" => &mut self.anthropic_console,
+            "anthropic_api" => &mut self.anthropic_api,
+            "openai_api" => &mut self.openai_api,
+            _ => return Err(format!("unknown base URL key {key:?}")),
+        };
+        *slot = Some(url.to_string());
+        Ok(())
+    }
+
+    /// Install these overrides process-wide. Returns `false` if already installed.
+    pub fn install(self) -> bool {
+        BASE_URLS.set(self).is_ok()
+    }
+}
+
+/// Resolve the base URL for `endpoint` (without a trailing slash): installed override,
+/// then the `SEHER_*_BASE_URL` environment variable, then the public default.
+#[must_use]
+pub fn base_url(endpoint: Endpoint) -> String {
+    let configured = BASE_URLS
+        .get()
+        .and_then(|urls| urls.get(endpoint))
+        .map(str::to_string)
+        .or_else(|| std::env::var(endpoint.env_var()).ok())
+        .filter(|url| !url.trim().is_empty());
+    configured
+        .as_deref()
+        .unwrap_or(endpoint.default_url())
+        .trim_end_matches('/')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_from_spec_assigns_known_keys() -> Result<(), String> {
+        let mut urls = BaseUrls::default();
+        urls.set_from_spec("claude=http://127.0.0.1:8080/")?;
+        urls.set_from_spec("github_api=http://gw.internal/github")?;
+
+        assert_eq!(urls.get(Endpoint::Claude), Some("http://127.0.0.1:8080/"));
+        assert_eq!(
+            urls.get(Endpoint::GithubApi),
+            Some("http://gw.internal/github")
+        );
+        assert_eq!(urls.get(Endpoint::Github), None);
+        Ok(())
+    }
+
+    #[test]
+    fn set_from_spec_rejects_bad_input() {
+        let mut urls = BaseUrls::default();
+        assert!(urls.set_from_spec("claude").is_err());
+        assert!(urls.set_from_spec("openai=http://x").is_err());
+    }
+}
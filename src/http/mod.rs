@@ -0,0 +1,121 @@
+//! HTTP plumbing shared by the provider clients.
+
+pub mod browser_headers;
+pub mod dump;
+pub mod endpoint;
+pub mod poll;
+pub mod retry;
+pub mod transport;
+
+pub use browser_headers::BrowserHeaders;
+pub use endpoint::{BaseUrls, Endpoint, base_url};
+pub use poll::poll;
+pub use retry::RetryPolicy;
+pub use transport::{
+    ClientTransport, HttpRequest, HttpResponse, HttpTransport, ReqwestTransport, TransportError,
+};
+
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// Request timeout used when none is configured.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+static IMPERSONATE: AtomicBool = AtomicBool::new(false);
+static PROXY: OnceLock<reqwest::Proxy> = OnceLock::new();
+static TIMEOUT: OnceLock<Duration> = OnceLock::new();
+static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+/// User-Agent sent unless a client overrides it per request.
+pub const DEFAULT_USER_AGENT: &str = concat!("seher/", env!("CARGO_PKG_VERSION"));
+
+/// Route all provider requests through `url` (`http://`, `https://`, `socks5://`, `socks5h://`).
+///
+/// Without an explicit proxy, clients honor `HTTPS_PROXY`, `HTTP_PROXY`, `ALL_PROXY` and
+/// `NO_PROXY` from the environment.
+///
+/// This is process-global: only the first call takes effect, and only before the shared
+/// client is built. Returns `false` if the proxy was ignored for either reason; give
+/// [`crate::SeherBuilder::proxy`] a proxy for one session instead.
+///
+/// # Errors
+///
+/// Returns an error if `url` is not a valid proxy URL.
+pub fn set_proxy(url: &str) -> Result<bool, reqwest::Error> {
+    let proxy = reqwest::Proxy::all(url)?;
+    Ok(CLIENT.get().is_none() && PROXY.set(proxy).is_ok())
+}
+
+/// Set the timeout for every provider request.
+///
+/// This is process-global: only the first call takes effect, and only before the shared
+/// client is built. Returns `false` if the timeout was ignored for either reason.
+pub fn set_timeout(timeout: Duration) -> bool {
+    CLIENT.get().is_none() && TIMEOUT.set(timeout).is_ok()
+}
+
+/// The configured request timeout, or [`DEFAULT_TIMEOUT`].
+#[must_use]
+pub fn timeout() -> Duration {
+    TIMEOUT.get().copied().unwrap_or(DEFAULT_TIMEOUT)
+}
+
+/// A `reqwest::ClientBuilder` with the process-wide HTTP settings applied.
+pub fn client_builder() -> reqwest::ClientBuilder {
+    client_builder_with(None, None)
+}
+
+/// A `reqwest::ClientBuilder` with `timeout` and `proxy` in place of the process-wide
+/// settings; either left unset falls back to them.
+pub(crate) fn client_builder_with(
+    timeout: Option<Duration>,
+    proxy: Option<reqwest::Proxy>,
+) -> reqwest::ClientBuilder {
+    let builder = reqwest::Client::builder()
+        .timeout(timeout.unwrap_or_else(self::timeout))
+        .user_agent(DEFAULT_USER_AGENT);
+    match proxy.or_else(|| PROXY.get().cloned()) {
+        Some(proxy) => builder.proxy(proxy),
+        None => builder,
+    }
+}
+
+/// Enable or disable browser impersonation for requests to browser-facing endpoints.
+///
+/// When enabled, clients send the full header set of the browser whose cookies they
+/// use (client hints, fetch metadata, request priority), which lowers the rate of
+/// Cloudflare challenges. The TLS fingerprint is still that of rustls.
+pub fn set_impersonate(enabled: bool) {
+    IMPERSONATE.store(enabled, Ordering::Relaxed);
+}
+
+#[must_use]
+pub fn impersonate() -> bool {
+    IMPERSONATE.load(Ordering::Relaxed)
+}
+
+/// The process-wide client shared by all providers, so repeated checks reuse pooled
+/// connections. Built on first use; configure proxy and timeout before that.
+///
+/// # Errors
+///
+/// Returns an error if the client cannot be built (e.g. TLS backend failure).
+pub fn shared_client() -> Result<reqwest::Client, reqwest::Error> {
+    if let Some(client) = CLIENT.get() {
+        return Ok(client.clone());
+    }
+    let client = client_builder().build()?;
+    Ok(CLIENT.get_or_init(|| client).clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_proxy_rejects_invalid_url() {
+        assert!(set_proxy("not a proxy url").is_err());
+        assert!(PROXY.get().is_none());
+    }
+}
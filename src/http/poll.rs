@@ -0,0 +1,41 @@
+use futures_util::stream::{self, Stream};
+use std::future::Future;
+use std::time::Duration;
+
+/// Yields `fetch`'s result right away and then once every `interval`, threading `state`
+/// from one fetch to the next (e.g. an organization ID resolved on the first poll).
+pub fn poll<S, T, F, Fut>(interval: Duration, state: S, fetch: F) -> impl Stream<Item = T>
+where
+    F: FnMut(S) -> Fut,
+    Fut: Future<Output = (T, S)>,
+{
+    stream::unfold(
+        (state, fetch, true),
+        move |(state, mut fetch, first)| async move {
+            if !first {
+                tokio::time::sleep(interval).await;
+            }
+            let (item, state) = fetch(state).await;
+            Some((item, (state, fetch, false)))
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::StreamExt;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn poll_threads_state_between_fetches() {
+        let items: Vec<u32> = poll(
+            Duration::from_millis(1),
+            0,
+            |n| async move { (n * 10, n + 1) },
+        )
+        .take(3)
+        .collect()
+        .await;
+        assert_eq!(items, [0, 10, 20]);
+    }
+}
@@ -0,0 +1,240 @@
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+static INSTALLED: OnceLock<RetryPolicy> = OnceLock::new();
+
+/// Retry policy for provider API requests.
+///
+/// Retries `429`/`5xx` responses, rate-limited `403`s (see [`is_rate_limited`]) and
+/// transient network errors with exponential backoff and jitter, honoring `Retry-After`
+/// when the server sends one.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryPolicy {
+    /// Total attempts including the first request; `1` disables retries.
+    pub max_attempts: u32,
+    /// Backoff before the first retry, doubled on each subsequent retry.
+    pub initial_backoff_ms: u64,
+    /// Upper bound for a single wait, including waits requested by `Retry-After`.
+    pub max_backoff_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff_ms: 500,
+            max_backoff_ms: 30_000,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Install this policy process-wide. Returns `false` if one was already installed.
+    pub fn install(self) -> bool {
+        INSTALLED.set(self).is_ok()
+    }
+
+    /// The installed policy, or the default if none was installed.
+    #[must_use]
+    pub fn current() -> Self {
+        INSTALLED.get().cloned().unwrap_or_default()
+    }
+
+    /// Jittered exponential backoff before retry number `retry` (0-based).
+    #[must_use]
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let base = self
+            .initial_backoff_ms
+            .saturating_mul(1u64 << retry.min(20))
+            .min(self.max_backoff_ms);
+        // Equal jitter: half fixed, half random, so concurrent clients spread out.
+        let half = base / 2;
+        Duration::from_millis(half + fastrand::u64(0..=base - half))
+    }
+
+    /// Send the request built by `build`, retrying transient failures.
+    ///
+    /// `build` is called once per attempt because a `RequestBuilder` cannot be reused.
+    /// The final response is returned as-is, even when its status is an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns the last transport error if every attempt failed to get a response.
+    pub async fn send<F>(&self, build: F) -> Result<Response, reqwest::Error>
+    where
+        F: Fn() -> RequestBuilder,
+    {
+        let max_attempts = self.max_attempts.max(1);
+        let mut retry = 0;
+        loop {
+            let last_attempt = retry + 1 >= max_attempts;
+            let delay = match build().send().await {
+                Ok(response)
+                    if !last_attempt
+                        && (is_retryable_status(response.status())
+                            || is_rate_limited(response.status().as_u16(), |name| {
+                                header_str(response.headers(), name)
+                            })) =>
+                {
+                    match retry_after(response.headers(), Utc::now()) {
+                        Some(wait) if wait > Duration::from_millis(self.max_backoff_ms) => {
+                            // The server wants us gone for longer than we are willing to block.
+                            return Ok(response);
+                        }
+                        Some(wait) => wait,
+                        None => self.backoff(retry),
+                    }
+                }
+                Err(e) if !last_attempt && is_transient_error(&e) => self.backoff(retry),
+                result => return result,
+            };
+            tokio::time::sleep(delay).await;
+            retry += 1;
+        }
+    }
+}
+
+/// `429 Too Many Requests` and gateway-style `5xx` responses are worth retrying.
+#[must_use]
+pub fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 429 | 500 | 502 | 503 | 504)
+}
+
+/// `true` for a `403`/`429` that carries rate-limit headers rather than an auth failure.
+///
+/// GitHub answers its secondary (abuse-detection) rate limit with a `403` plus either
+/// `Retry-After` or `x-ratelimit-remaining: 0`. `header` looks up a response header by
+/// lowercase name.
+pub fn is_rate_limited<'a>(status: u16, header: impl Fn(&str) -> Option<&'a str>) -> bool {
+    matches!(status, 403 | 429)
+        && (header("retry-after").is_some()
+            || header("x-ratelimit-remaining").map(str::trim) == Some("0"))
+}
+
+fn is_transient_error(e: &reqwest::Error) -> bool {
+    e.is_timeout() || e.is_connect() || e.is_request()
+}
+
+fn header_str<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name)?.to_str().ok()
+}
+
+/// Parse a `Retry-After` header given as delay-seconds or an HTTP-date, falling back to
+/// GitHub's `x-ratelimit-reset` (Unix seconds).
+#[must_use]
+pub fn retry_after(headers: &HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    wait_from_headers(|name| header_str(headers, name), now)
+}
+
+/// [`retry_after`] over a header lookup by lowercase name.
+pub fn wait_from_headers<'a>(
+    header: impl Fn(&str) -> Option<&'a str>,
+    now: DateTime<Utc>,
+) -> Option<Duration> {
+    let until = |at: DateTime<Utc>| (at - now).to_std().unwrap_or(Duration::ZERO);
+    if let Some(value) = header(RETRY_AFTER.as_str()).map(str::trim) {
+        if let Ok(secs) = value.parse::<u64>() {
+            return Some(Duration::from_secs(secs));
+        }
+        return DateTime::parse_from_rfc2822(value)
+            .ok()
+            .map(|at| until(at.with_timezone(&Utc)));
+    }
+    let reset = header("x-ratelimit-reset")?.trim().parse::<i64>().ok()?;
+    DateTime::from_timestamp(reset, 0).map(until)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    fn headers_with_retry_after(value: &str) -> Result<HeaderMap, Box<dyn std::error::Error>> {
+        let mut headers = HeaderMap::new();
+        headers.insert(RETRY_AFTER, HeaderValue::from_str(value)?);
+        Ok(headers)
+    }
+
+    #[test]
+    fn retry_after_accepts_seconds() -> TestResult {
+        let headers = headers_with_retry_after("7")?;
+        assert_eq!(
+            retry_after(&headers, Utc::now()),
+            Some(Duration::from_secs(7))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn retry_after_accepts_http_date() -> TestResult {
+        let headers = headers_with_retry_after("Wed, 21 Oct 2015 07:28:30 GMT")?;
+        let now = "2015-10-21T07:28:00Z".parse::<DateTime<Utc>>()?;
+        assert_eq!(retry_after(&headers, now), Some(Duration::from_secs(30)));
+        Ok(())
+    }
+
+    #[test]
+    fn retry_after_falls_back_to_ratelimit_reset() -> TestResult {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-reset", HeaderValue::from_static("1445412540"));
+        let now = "2015-10-21T07:28:00Z".parse::<DateTime<Utc>>()?;
+        assert_eq!(retry_after(&headers, now), Some(Duration::from_mins(1)));
+        Ok(())
+    }
+
+    #[test]
+    fn rate_limited_403s_are_told_apart_from_auth_failures() {
+        let headers = |pairs: &'static [(&'static str, &'static str)]| {
+            move |name: &str| pairs.iter().find(|(k, _)| *k == name).map(|(_, v)| *v)
+        };
+        assert!(is_rate_limited(403, headers(&[("retry-after", "60")])));
+        assert!(is_rate_limited(
+            403,
+            headers(&[("x-ratelimit-remaining", "0")])
+        ));
+        assert!(!is_rate_limited(
+            403,
+            headers(&[("x-ratelimit-remaining", "12")])
+        ));
+        assert!(!is_rate_limited(403, headers(&[])));
+        assert!(!is_rate_limited(401, headers(&[("retry-after", "60")])));
+    }
+
+    #[test]
+    fn retry_after_missing_or_invalid_is_none() -> TestResult {
+        assert_eq!(retry_after(&HeaderMap::new(), Utc::now()), None);
+        let headers = headers_with_retry_after("soon")?;
+        assert_eq!(retry_after(&headers, Utc::now()), None);
+        Ok(())
+    }
+
+    #[test]
+    fn backoff_grows_and_is_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 5,
+            initial_backoff_ms: 100,
+            max_backoff_ms: 1_000,
+        };
+        for retry in 0..10 {
+            let base = (100u64 << retry).min(1_000);
+            let delay = policy.backoff(retry);
+            assert!(delay >= Duration::from_millis(base / 2));
+            assert!(delay <= Duration::from_millis(base));
+        }
+    }
+
+    #[test]
+    fn retryable_statuses() {
+        assert!(is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+        assert!(is_retryable_status(StatusCode::SERVICE_UNAVAILABLE));
+        assert!(!is_retryable_status(StatusCode::FORBIDDEN));
+        assert!(!is_retryable_status(StatusCode::NOT_FOUND));
+    }
+}
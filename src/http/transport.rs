@@ -0,0 +1,227 @@
+use super::RetryPolicy;
+use std::future::Future;
+use std::pin::Pin;
+
+/// Error returned by an [`HttpTransport`].
+pub type TransportError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Future returned by [`HttpTransport::send`].
+pub type TransportFuture<'a> =
+    Pin<Box<dyn Future<Output = Result<HttpResponse, TransportError>> + Send + 'a>>;
+
+/// A GET request issued by a provider client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpRequest {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl HttpRequest {
+    #[must_use]
+    pub fn get(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            headers: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+/// A fully-read HTTP response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl HttpResponse {
+    /// A response with the given status and body and no headers.
+    #[must_use]
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    #[must_use]
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Case-insensitive header lookup.
+    #[must_use]
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the body is not valid JSON for `T`.
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> Result<T, serde_json::Error> {
+        serde_json::from_str(&self.body)
+    }
+}
+
+/// Sends provider API requests.
+///
+/// [`ReqwestTransport`] is used by default; tests and library users can supply their
+/// own implementation to serve canned responses.
+pub trait HttpTransport: Send + Sync {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_>;
+}
+
+/// Transport backed by the shared `reqwest` client and the installed [`RetryPolicy`].
+pub struct ReqwestTransport;
+
+impl HttpTransport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(async move { send_with(&super::shared_client()?, request).await })
+    }
+}
+
+/// Transport backed by a caller-supplied `reqwest` client, for settings (proxy, timeout)
+/// that should apply to one session rather than the whole process.
+#[derive(Debug, Clone)]
+pub struct ClientTransport {
+    client: reqwest::Client,
+}
+
+impl ClientTransport {
+    #[must_use]
+    pub fn new(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl HttpTransport for ClientTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+        Box::pin(send_with(&self.client, request))
+    }
+}
+
+async fn send_with(
+    client: &reqwest::Client,
+    request: HttpRequest,
+) -> Result<HttpResponse, TransportError> {
+    #[cfg(feature = "cli")]
+    let mut span = {
+        let mut span = crate::telemetry::Span::start("http.request");
+        span.set("http.request.method", "GET");
+        if let Ok(url) = reqwest::Url::parse(&request.url) {
+            span.set("server.address", url.host_str().unwrap_or_default());
+            span.set("url.path", url.path());
+        }
+        span
+    };
+    let response = RetryPolicy::current()
+        .send(|| {
+            request
+                .headers
+                .iter()
+                .fold(client.get(&request.url), |builder, (name, value)| {
+                    builder.header(name, value)
+                })
+        })
+        .await;
+    #[cfg(feature = "cli")]
+    {
+        match &response {
+            Ok(response) => {
+                span.set("http.response.status_code", response.status().as_u16());
+                if !response.status().is_success() {
+                    span.fail(response.status());
+                }
+            }
+            Err(e) => span.fail(e),
+        }
+        span.end();
+    }
+    let response = response?;
+
+    let status = response.status().as_u16();
+    let headers = response
+        .headers()
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .to_str()
+                .ok()
+                .map(|v| (name.as_str().to_string(), v.to_string()))
+        })
+        .collect();
+    let body = response.text().await?;
+    let response = HttpResponse {
+        status,
+        headers,
+        body,
+    };
+    super::dump::record(&request, &response);
+    Ok(response)
+}
+
+#[cfg(test)]
+pub(crate) mod mock {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// Serves a fixed response per URL and records requests.
+    pub struct MockTransport {
+        responses: Vec<(String, HttpResponse)>,
+        pub requests: Mutex<Vec<HttpRequest>>,
+    }
+
+    impl MockTransport {
+        pub fn new(responses: Vec<(&str, HttpResponse)>) -> Self {
+            Self {
+                responses: responses
+                    .into_iter()
+                    .map(|(url, response)| (url.to_string(), response))
+                    .collect(),
+                requests: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    impl HttpTransport for MockTransport {
+        fn send(&self, request: HttpRequest) -> TransportFuture<'_> {
+            let response = self
+                .responses
+                .iter()
+                .find(|(url, _)| *url == request.url)
+                .map(|(_, response)| response.clone());
+            if let Ok(mut requests) = self.requests.lock() {
+                requests.push(request.clone());
+            }
+            Box::pin(async move {
+                response.ok_or_else(|| format!("no mock response for {}", request.url).into())
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn response_header_lookup_is_case_insensitive() {
+        let mut response = HttpResponse::new(403, "");
+        response
+            .headers
+            .push(("CF-Mitigated".to_string(), "challenge".to_string()));
+
+        assert_eq!(response.header("cf-mitigated"), Some("challenge"));
+        assert!(!response.is_success());
+    }
+}
@@ -0,0 +1,47 @@
+use super::{KeyringError, Result};
+use secret_service::EncryptionType;
+use secret_service::blocking::SecretService;
+use std::collections::HashMap;
+
+fn backend_error(context: &str) -> impl Fn(secret_service::Error) -> KeyringError + '_ {
+    move |e| KeyringError::Backend(format!("{context}: {e}"))
+}
+
+pub fn get(service: &str, account: &str) -> Result<Option<String>> {
+    let ss = SecretService::connect(EncryptionType::Dh).map_err(backend_error("connect"))?;
+    let collection = ss
+        .get_default_collection()
+        .map_err(backend_error("get collection"))?;
+    let items = collection
+        .search_items(HashMap::from([("service", service), ("account", account)]))
+        .map_err(backend_error("search items"))?;
+
+    let Some(item) = items.first() else {
+        return Ok(None);
+    };
+    item.ensure_unlocked().map_err(backend_error("unlock"))?;
+    let secret = item.get_secret().map_err(backend_error("get secret"))?;
+    String::from_utf8(secret)
+        .map(Some)
+        .map_err(|e| KeyringError::Backend(format!("secret is not UTF-8: {e}")))
+}
+
+pub fn set(service: &str, account: &str, secret: &str) -> Result<()> {
+    let ss = SecretService::connect(EncryptionType::Dh).map_err(backend_error("connect"))?;
+    let collection = ss
+        .get_default_collection()
+        .map_err(backend_error("get collection"))?;
+    collection
+        .ensure_unlocked()
+        .map_err(backend_error("unlock"))?;
+    collection
+        .create_item(
+            &format!("{service} ({account})"),
+            HashMap::from([("service", service), ("account", account)]),
+            secret.as_bytes(),
+            true,
+            "text/plain",
+        )
+        .map_err(backend_error("create item"))?;
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use super::{KeyringError, Result};
+use security_framework::os::macos::keychain::SecKeychain;
+
+pub fn get(service: &str, account: &str) -> Result<Option<String>> {
+    let keychain = SecKeychain::default()
+        .map_err(|e| KeyringError::Backend(format!("Failed to access keychain: {e}")))?;
+    let Ok((password, _item)) = keychain.find_generic_password(service, account) else {
+        return Ok(None);
+    };
+    String::from_utf8(password.as_ref().to_vec())
+        .map(Some)
+        .map_err(|e| KeyringError::Backend(format!("secret is not UTF-8: {e}")))
+}
+
+pub fn set(service: &str, account: &str, secret: &str) -> Result<()> {
+    let keychain = SecKeychain::default()
+        .map_err(|e| KeyringError::Backend(format!("Failed to access keychain: {e}")))?;
+    keychain
+        .set_generic_password(service, account, secret.as_bytes())
+        .map_err(|e| KeyringError::Backend(format!("Failed to store password: {e}")))
+}
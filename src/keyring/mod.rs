@@ -0,0 +1,184 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "macos")]
+mod macos;
+
+/// Service name under which seher stores its secrets in the OS keyring.
+const SERVICE: &str = "seher";
+
+/// Keyring account holding the GitHub OAuth token from `seher login github`.
+pub const GITHUB_ACCOUNT: &str = "github";
+
+/// Keyring account holding the claude.ai session key from `seher login claude`.
+pub const CLAUDE_ACCOUNT: &str = "claude";
+
+/// Keyring account holding the key that encrypts the session cache.
+pub const SESSION_CACHE_ACCOUNT: &str = "session-cache";
+
+#[derive(Debug, Error)]
+pub enum KeyringError {
+    #[error("home directory not found")]
+    HomeDirNotFound,
+
+    #[error("failed to access credentials file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("failed to parse credentials file: {0}")]
+    Parse(#[from] serde_json::Error),
+
+    #[error("OS keyring error: {0}")]
+    Backend(String),
+}
+
+pub type Result<T> = std::result::Result<T, KeyringError>;
+
+/// Stores secrets (e.g. OAuth tokens) in the OS keyring.
+///
+/// Falls back to `~/.config/seher/credentials.json` (mode 0600) when no keyring is
+/// available, which is common on headless servers.
+pub struct Keyring;
+
+impl Keyring {
+    /// Look up the secret stored for `account`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the fallback credentials file exists but cannot be read.
+    pub fn get(account: &str) -> Result<Option<String>> {
+        if let Ok(Some(secret)) = os_get(account) {
+            return Ok(Some(secret));
+        }
+        let path = Self::fallback_path()?;
+        Ok(read_file(&path)?.remove(account))
+    }
+
+    /// Store `secret` for `account`, replacing any previous value.
+    ///
+    /// Returns `true` if the OS keyring was used, `false` if the fallback file was.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if neither the OS keyring nor the fallback file can be written.
+    pub fn set(account: &str, secret: &str) -> Result<bool> {
+        if os_set(account, secret).is_ok() {
+            return Ok(true);
+        }
+        let path = Self::fallback_path()?;
+        let mut entries = read_file(&path)?;
+        entries.insert(account.to_string(), secret.to_string());
+        write_file(&path, &entries)?;
+        Ok(false)
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if the home directory cannot be determined.
+    pub fn fallback_path() -> Result<PathBuf> {
+        let home = dirs::home_dir().ok_or(KeyringError::HomeDirNotFound)?;
+        Ok(home.join(".config").join("seher").join("credentials.json"))
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn os_get(account: &str) -> Result<Option<String>> {
+    linux::get(SERVICE, account)
+}
+
+#[cfg(target_os = "linux")]
+fn os_set(account: &str, secret: &str) -> Result<()> {
+    linux::set(SERVICE, account, secret)
+}
+
+#[cfg(target_os = "macos")]
+fn os_get(account: &str) -> Result<Option<String>> {
+    macos::get(SERVICE, account)
+}
+
+#[cfg(target_os = "macos")]
+fn os_set(account: &str, secret: &str) -> Result<()> {
+    macos::set(SERVICE, account, secret)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn os_get(_account: &str) -> Result<Option<String>> {
+    Err(KeyringError::Backend("unsupported OS".to_string()))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn os_set(_account: &str, _secret: &str) -> Result<()> {
+    Err(KeyringError::Backend("unsupported OS".to_string()))
+}
+
+fn read_file(path: &Path) -> Result<BTreeMap<String, String>> {
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = std::fs::read_to_string(path)?;
+    Ok(serde_json::from_str(&content)?)
+}
+
+fn write_file(path: &Path, entries: &BTreeMap<String, String>) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let content = serde_json::to_string_pretty(entries)?;
+
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        file.write_all(content.as_bytes())?;
+    }
+
+    #[cfg(not(unix))]
+    std::fs::write(path, content)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = std::result::Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn fallback_file_round_trips_entries() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("nested").join("credentials.json");
+
+        assert!(read_file(&path)?.is_empty());
+
+        let entries = BTreeMap::from([("github".to_string(), "gho_token".to_string())]);
+        write_file(&path, &entries)?;
+
+        assert_eq!(read_file(&path)?, entries);
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn fallback_file_is_private() -> TestResult {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("credentials.json");
+        write_file(&path, &BTreeMap::new())?;
+
+        let mode = std::fs::metadata(&path)?.permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        Ok(())
+    }
+}
@@ -1,5 +1,3 @@
-use std::time::Duration;
-
 use super::types::KimiK2CreditsResponse;
 
 const CREDITS_URL: &str = "https://kimi-k2.ai/api/user/credits";
@@ -12,7 +10,7 @@ impl KimiK2Client {
     /// Returns an error if the API request fails or the response cannot be parsed.
     pub async fn fetch_credits(
         api_key: &str,
-    ) -> Result<KimiK2CreditsResponse, Box<dyn std::error::Error>> {
+    ) -> Result<KimiK2CreditsResponse, Box<dyn std::error::Error + Send + Sync>> {
         let client = Self::build_client()?;
         let response = client
             .get(CREDITS_URL)
@@ -32,8 +30,6 @@ impl KimiK2Client {
     }
 
     fn build_client() -> Result<reqwest::Client, reqwest::Error> {
-        reqwest::Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
+        crate::http::shared_client()
     }
 }
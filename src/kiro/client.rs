@@ -6,7 +6,7 @@ impl KiroClient {
     /// # Errors
     ///
     /// Returns an error if the kiro-cli subprocess fails or its output cannot be parsed.
-    pub async fn fetch_usage() -> Result<KiroUsageInfo, Box<dyn std::error::Error>> {
+    pub async fn fetch_usage() -> Result<KiroUsageInfo, Box<dyn std::error::Error + Send + Sync>> {
         let output = tokio::task::spawn_blocking(|| {
             let output = std::process::Command::new("kiro-cli")
                 .args(["chat", "--no-interactive", "/usage"])
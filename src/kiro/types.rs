@@ -45,7 +45,7 @@ impl KiroUsageInfo {
     /// # Errors
     ///
     /// Returns an error if the output cannot be parsed.
-    pub fn parse(output: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn parse(output: &str) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let output = output.trim();
         if output.is_empty() {
             return Err("empty kiro output".into());
@@ -90,7 +90,7 @@ impl KiroUsageInfo {
 mod tests {
     use super::*;
 
-    type TestResult = Result<(), Box<dyn std::error::Error>>;
+    type TestResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
 
     // =======================================================================
     // Struct construction & predicates (no parsing)
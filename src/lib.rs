@@ -1,35 +1,95 @@
 #[cfg(feature = "browser")]
 pub mod agent;
 #[cfg(feature = "browser")]
+pub mod alert;
+#[cfg(feature = "browser")]
+pub mod audit;
+#[cfg(feature = "browser")]
 pub mod browser;
 #[cfg(feature = "browser")]
+pub mod cache;
+#[cfg(feature = "browser")]
 pub mod codex;
 #[cfg(feature = "browser")]
 pub mod config;
 #[cfg(feature = "browser")]
+pub mod cookie_store;
+#[cfg(feature = "browser")]
 pub mod crypto;
+#[cfg(feature = "browser")]
+pub mod history;
+#[cfg(feature = "browser")]
+pub mod keyring;
 
 // Always available (public API as a library)
+pub mod anthropic;
+pub mod budget;
+#[cfg(feature = "claude")]
 pub mod claude;
+#[cfg(feature = "copilot")]
 pub mod copilot;
+#[cfg(feature = "cli")]
+pub mod cron;
+pub mod error;
+#[cfg(feature = "cli")]
+pub mod events;
+#[cfg(feature = "ffi")]
+pub mod ffi;
 pub mod glm;
+pub mod heartbeat;
+#[cfg(feature = "cli")]
+pub mod hooks;
+pub mod http;
 pub mod kimik2;
 pub mod kiro;
+#[cfg(feature = "cli")]
+pub mod notify;
+pub mod openai;
 #[cfg(feature = "browser")]
 pub mod opencode_go;
 pub mod openrouter;
+#[cfg(feature = "cli")]
+pub mod output;
+#[cfg(feature = "browser")]
+pub mod pending;
+#[cfg(feature = "browser")]
+pub mod queue;
+#[cfg(feature = "browser")]
+pub mod secrets;
+#[cfg(feature = "browser")]
+pub mod session;
+#[cfg(feature = "cli")]
+pub mod statsd;
+#[cfg(feature = "cli")]
+pub mod telemetry;
+pub mod template;
+#[cfg(feature = "cli")]
+pub mod upgrade;
+#[cfg(feature = "cli")]
+pub mod wait;
+pub mod warning;
 pub mod warp;
+#[cfg(feature = "cli")]
 pub mod web;
 pub mod zai;
 
 #[cfg(feature = "browser")]
 pub use agent::{Agent, AgentLimit, AgentStatus, UsageEntry};
 #[cfg(feature = "browser")]
-pub use browser::{BrowserDetector, BrowserType, Cookie, CookieReader, Profile};
+pub use browser::{
+    BrowserDetector, BrowserType, Cookie, CookieEngine, CookieReader, CookieWarning,
+    DecryptedCookies, LazyCookie, Profile,
+};
+#[cfg(feature = "claude")]
 pub use claude::{ClaudeClient, UsageResponse, UsageWindow};
 #[cfg(feature = "browser")]
 pub use codex::{CodexClient, CodexRateLimit, CodexUsageResponse, CodexWindow};
 #[cfg(feature = "browser")]
 pub use config::{AgentConfig, PriorityRule, Settings};
 #[cfg(feature = "browser")]
+pub use cookie_store::{BrowserCookieStore, CookieStore, EnvCookieStore, FileCookieStore};
+pub use error::SeherError;
+#[cfg(feature = "browser")]
 pub use opencode_go::{OpencodeGoAuth, OpencodeGoUsageSnapshot, OpencodeGoUsageStore};
+#[cfg(feature = "browser")]
+pub use session::{Seher, SeherBuilder, find_session};
@@ -3,7 +3,9 @@ mod cli;
 use clap::Parser;
 
 #[tokio::main(flavor = "current_thread")]
-async fn main() {
+async fn main() -> std::process::ExitCode {
     let args = cli::Args::parse();
-    cli::run(args).await;
+    let code = cli::run(args).await;
+    seher::telemetry::flush().await;
+    code
 }
@@ -0,0 +1,376 @@
+use crate::hooks::{HookEvent, HookPayload};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Where seher sends notifications (alerts, reset reminders).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationConfig {
+    /// Show a desktop notification (`notify-send` on Linux, `osascript` on macOS).
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub desktop: bool,
+    /// POST a JSON payload `{"title": ..., "message": ...}` to this URL.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub webhook: Option<String>,
+    /// Slack incoming-webhook URL; also receives limit, reset and execution events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slack: Option<String>,
+    /// Discord webhook URL; also receives limit, reset and execution events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discord: Option<String>,
+}
+
+impl NotificationConfig {
+    /// `true` if at least one channel is configured.
+    #[must_use]
+    pub fn has_channel(&self) -> bool {
+        self.desktop || self.webhook.is_some() || self.has_chat()
+    }
+
+    /// `true` if a Slack or Discord webhook is configured.
+    #[must_use]
+    pub fn has_chat(&self) -> bool {
+        self.slack.is_some() || self.discord.is_some()
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct WebhookPayload<'a> {
+    title: &'a str,
+    message: &'a str,
+}
+
+/// A message rendered as a Slack section or a Discord embed.
+#[derive(Debug, PartialEq)]
+struct ChatMessage {
+    title: String,
+    description: Option<String>,
+    fields: Vec<ChatField>,
+    /// Discord embed color, `0xRRGGBB`.
+    color: u32,
+}
+
+#[derive(Debug, PartialEq)]
+struct ChatField {
+    name: &'static str,
+    /// Plain-text value, used by Slack and as the fallback.
+    text: String,
+    /// Discord-specific value (e.g. a `<t:...:R>` relative timestamp), if different.
+    discord: Option<String>,
+}
+
+const GREY: u32 = 0x0095_a5a6;
+const RED: u32 = 0x00e7_4c3c;
+const GREEN: u32 = 0x002e_cc71;
+const BLUE: u32 = 0x0034_98db;
+
+/// Sends notifications to every configured channel.
+#[derive(Debug, Clone, Default)]
+pub struct Notifier {
+    config: NotificationConfig,
+}
+
+impl Notifier {
+    #[must_use]
+    pub fn new(config: NotificationConfig) -> Self {
+        Self { config }
+    }
+
+    /// `true` if at least one channel is configured.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.config.has_channel()
+    }
+
+    /// `true` if lifecycle events are posted anywhere (Slack or Discord).
+    #[must_use]
+    pub fn wants_events(&self) -> bool {
+        self.config.has_chat()
+    }
+
+    /// Deliver a notification to all channels.
+    ///
+    /// Delivery is best-effort; failures are reported as [warnings](crate::warning) and
+    /// never abort the caller.
+    pub async fn notify(&self, title: &str, message: &str) {
+        if self.config.desktop
+            && let Err(e) = desktop_notify(title, message)
+        {
+            crate::warning::warn(format_args!("desktop notification failed: {e}"));
+        }
+        if let Some(url) = &self.config.webhook
+            && let Err(e) = webhook_notify(url, title, message).await
+        {
+            crate::warning::warn(format_args!("webhook notification failed: {e}"));
+        }
+        self.post_chat(&ChatMessage {
+            title: title.to_string(),
+            description: Some(message.to_string()),
+            fields: vec![],
+            color: GREY,
+        })
+        .await;
+    }
+
+    /// Post a limit, reset or execution event to Slack and Discord. Other events, and
+    /// the desktop and generic webhook channels, are left alone.
+    pub async fn notify_event(&self, event: &HookPayload) {
+        if let Some(message) = event_message(event, Utc::now()) {
+            self.post_chat(&message).await;
+        }
+    }
+
+    async fn post_chat(&self, message: &ChatMessage) {
+        if let Some(url) = &self.config.slack
+            && let Err(e) = post_json(url, &slack_payload(message)).await
+        {
+            crate::warning::warn(format_args!("Slack notification failed: {e}"));
+        }
+        if let Some(url) = &self.config.discord
+            && let Err(e) = post_json(url, &discord_payload(message)).await
+        {
+            crate::warning::warn(format_args!("Discord notification failed: {e}"));
+        }
+    }
+}
+
+/// The chat message for a lifecycle event, or `None` for events not posted to chat.
+fn event_message(event: &HookPayload, now: DateTime<Utc>) -> Option<ChatMessage> {
+    let mut fields = Vec::new();
+    if let Some(provider) = &event.provider {
+        fields.push(field("Provider", provider.clone()));
+    }
+    let (title, color) = match event.event {
+        HookEvent::OnLimited => {
+            if let Some(window) = &event.window {
+                fields.push(field("Window", window.clone()));
+            }
+            if let Some(reset) = event.reset_time {
+                fields.push(ChatField {
+                    name: "Resets",
+                    text: format!(
+                        "in {} ({})",
+                        countdown(reset, now),
+                        reset.format("%Y-%m-%d %H:%M UTC")
+                    ),
+                    discord: Some(format!("<t:{0}:R> (<t:{0}:t>)", reset.timestamp())),
+                });
+            }
+            (format!("{} is rate limited", event.agent), RED)
+        }
+        HookEvent::OnReset => (format!("{} is available again", event.agent), GREEN),
+        HookEvent::PostExec => {
+            if let Some(code) = event.exit_code {
+                fields.push(field("Exit code", code.to_string()));
+            }
+            let outcome = event.outcome.as_deref().unwrap_or("exited");
+            let color = if event.exit_code == Some(0) {
+                GREEN
+            } else {
+                BLUE
+            };
+            (format!("{} {outcome}", event.agent), color)
+        }
+        HookEvent::PreExec => return None,
+    };
+    Some(ChatMessage {
+        title,
+        description: None,
+        fields,
+        color,
+    })
+}
+
+fn field(name: &'static str, text: String) -> ChatField {
+    ChatField {
+        name,
+        text,
+        discord: None,
+    }
+}
+
+/// Time left until `reset`, e.g. `2h 13m`.
+fn countdown(reset: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let minutes = (reset - now).num_minutes().max(0);
+    match (minutes / 60, minutes % 60) {
+        (0, m) => format!("{m}m"),
+        (h, m) => format!("{h}h {m}m"),
+    }
+}
+
+fn slack_payload(message: &ChatMessage) -> serde_json::Value {
+    let mut text = format!("*{}*", message.title);
+    if let Some(description) = &message.description {
+        text.push('\n');
+        text.push_str(description);
+    }
+    let mut blocks = vec![serde_json::json!({
+        "type": "section",
+        "text": { "type": "mrkdwn", "text": text },
+    })];
+    if !message.fields.is_empty() {
+        let fields: Vec<_> = message
+            .fields
+            .iter()
+            .map(|f| serde_json::json!({ "type": "mrkdwn", "text": format!("*{}*\n{}", f.name, f.text) }))
+            .collect();
+        blocks.push(serde_json::json!({ "type": "section", "fields": fields }));
+    }
+    serde_json::json!({ "text": message.title, "blocks": blocks })
+}
+
+fn discord_payload(message: &ChatMessage) -> serde_json::Value {
+    let fields: Vec<_> = message
+        .fields
+        .iter()
+        .map(|f| {
+            serde_json::json!({
+                "name": f.name,
+                "value": f.discord.as_deref().unwrap_or(&f.text),
+                "inline": true,
+            })
+        })
+        .collect();
+    let mut embed = serde_json::json!({
+        "title": message.title,
+        "color": message.color,
+        "fields": fields,
+    });
+    if let Some(description) = &message.description {
+        embed["description"] = description.clone().into();
+    }
+    serde_json::json!({ "username": "seher", "embeds": [embed] })
+}
+
+fn desktop_notify(title: &str, message: &str) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        let script = format!(
+            "display notification {} with title {}",
+            applescript_string(message),
+            applescript_string(title)
+        );
+        let mut command = std::process::Command::new("osascript");
+        command.arg("-e").arg(script);
+        command
+    } else {
+        let mut command = std::process::Command::new("notify-send");
+        command.arg(title).arg(message);
+        command
+    };
+    let status = command.status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "notifier exited with {status}"
+        )))
+    }
+}
+
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+async fn webhook_notify(
+    url: &str,
+    title: &str,
+    message: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    post_json(url, &WebhookPayload { title, message }).await
+}
+
+async fn post_json<T: Serialize + ?Sized>(
+    url: &str,
+    body: &T,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let response = crate::http::shared_client()?
+        .post(url)
+        .json(body)
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("webhook returned {status}").into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applescript_string_escapes_quotes_and_backslashes() {
+        assert_eq!(applescript_string(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+    }
+
+    #[test]
+    fn notifier_is_disabled_by_default() {
+        assert!(!Notifier::default().is_enabled());
+        assert!(
+            Notifier::new(NotificationConfig {
+                webhook: Some("https://example.com/hook".to_string()),
+                ..NotificationConfig::default()
+            })
+            .is_enabled()
+        );
+        let discord = Notifier::new(NotificationConfig {
+            discord: Some("https://discord.com/api/webhooks/1/x".to_string()),
+            ..NotificationConfig::default()
+        });
+        assert!(discord.is_enabled() && discord.wants_events());
+    }
+
+    #[test]
+    fn limit_events_show_provider_window_and_countdown() {
+        let now = Utc::now();
+        let mut event = HookPayload::new(
+            HookEvent::OnLimited,
+            "claude (me@example.com)".to_string(),
+            "claude".to_string(),
+        );
+        event.provider = Some("claude".to_string());
+        event.window = Some("five_hour".to_string());
+        event.reset_time = Some(now + chrono::TimeDelta::minutes(133));
+
+        let Some(message) = event_message(&event, now) else {
+            panic!("limit events are posted");
+        };
+        assert_eq!(message.title, "claude (me@example.com) is rate limited");
+        let names: Vec<_> = message.fields.iter().map(|f| f.name).collect();
+        assert_eq!(names, ["Provider", "Window", "Resets"]);
+        assert!(message.fields[2].text.starts_with("in 2h 13m"));
+
+        let slack = slack_payload(&message);
+        assert_eq!(slack["text"], "claude (me@example.com) is rate limited");
+        assert_eq!(
+            slack["blocks"][1]["fields"][1]["text"],
+            "*Window*\nfive_hour"
+        );
+        let discord = discord_payload(&message);
+        let resets = discord["embeds"][0]["fields"][2]["value"]
+            .as_str()
+            .unwrap_or_default();
+        assert!(resets.starts_with("<t:") && resets.contains(":R>"));
+        assert_eq!(discord["embeds"][0]["color"], RED);
+    }
+
+    #[test]
+    fn pre_exec_events_are_not_posted() {
+        let event = HookPayload::new(HookEvent::PreExec, "codex".to_string(), "codex".to_string());
+        assert!(event_message(&event, Utc::now()).is_none());
+
+        let mut done = HookPayload::new(
+            HookEvent::PostExec,
+            "codex".to_string(),
+            "codex".to_string(),
+        );
+        done.exit_code = Some(0);
+        done.outcome = Some("succeeded".to_string());
+        let message = event_message(&done, Utc::now());
+        assert_eq!(
+            message.map(|m| m.title),
+            Some("codex succeeded".to_string())
+        );
+    }
+}
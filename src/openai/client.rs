@@ -0,0 +1,146 @@
+use chrono::{DateTime, Utc};
+
+use super::types::{CostsPage, RateLimitHeadroom};
+use crate::budget::month_start;
+use crate::error::SeherError;
+use crate::http::{Endpoint, HttpRequest, HttpResponse, HttpTransport, ReqwestTransport, base_url};
+
+/// Pages of daily buckets are capped by the API; a month never needs more than this.
+const MAX_COST_PAGES: usize = 12;
+
+pub struct OpenAiClient;
+
+impl OpenAiClient {
+    /// Rate-limit headroom for `api_key`, read from the `x-ratelimit-*` headers of a
+    /// `/v1/models` request. Empty when the API does not report any.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the key is rejected.
+    pub async fn fetch_rate_limits(api_key: &str) -> Result<Vec<RateLimitHeadroom>, SeherError> {
+        Self::fetch_rate_limits_via(&ReqwestTransport, api_key).await
+    }
+
+    /// [`Self::fetch_rate_limits`] over a caller-supplied transport.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the key is rejected.
+    pub async fn fetch_rate_limits_via(
+        transport: &dyn HttpTransport,
+        api_key: &str,
+    ) -> Result<Vec<RateLimitHeadroom>, SeherError> {
+        let url = format!("{}/v1/models", base_url(Endpoint::OpenAiApi));
+        let response = Self::send(transport, Self::request(url, api_key)).await?;
+        Ok(["requests", "tokens"]
+            .into_iter()
+            .filter_map(|kind| RateLimitHeadroom::from_headers(kind, |name| response.header(name)))
+            .collect())
+    }
+
+    /// Month-to-date organization spend in dollars from the Admin API costs endpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Admin API request fails or the response cannot be parsed.
+    pub async fn fetch_month_cost(admin_key: &str) -> Result<f64, SeherError> {
+        Self::fetch_month_cost_via(&ReqwestTransport, admin_key, Utc::now()).await
+    }
+
+    /// [`Self::fetch_month_cost`] over a caller-supplied transport, for the month of `now`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Admin API request fails or the response cannot be parsed.
+    pub async fn fetch_month_cost_via(
+        transport: &dyn HttpTransport,
+        admin_key: &str,
+        now: DateTime<Utc>,
+    ) -> Result<f64, SeherError> {
+        let base = format!(
+            "{}/v1/organization/costs?start_time={}&bucket_width=1d&limit=31",
+            base_url(Endpoint::OpenAiApi),
+            month_start(now).timestamp()
+        );
+        let mut total = 0.0;
+        let mut url = base.clone();
+        for _ in 0..MAX_COST_PAGES {
+            let page: CostsPage = Self::send(transport, Self::request(url, admin_key))
+                .await?
+                .json()?;
+            total += page.total();
+            match page.next_page.filter(|_| page.has_more) {
+                Some(next) => url = format!("{base}&page={next}"),
+                None => break,
+            }
+        }
+        Ok(total)
+    }
+
+    fn request(url: String, key: &str) -> HttpRequest {
+        HttpRequest::get(url)
+            .header("Authorization", format!("Bearer {key}"))
+            .header("accept", "application/json")
+    }
+
+    async fn send(
+        transport: &dyn HttpTransport,
+        request: HttpRequest,
+    ) -> Result<HttpResponse, SeherError> {
+        let response = transport.send(request).await?;
+        if !response.is_success() {
+            return Err(SeherError::Status {
+                service: "OpenAI API",
+                status: response.status,
+                body: response.body,
+            });
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::transport::mock::MockTransport;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn rate_limits_come_from_response_headers() -> TestResult {
+        let url = format!("{}/v1/models", base_url(Endpoint::OpenAiApi));
+        let mut response = HttpResponse::new(200, r#"{"object": "list", "data": []}"#);
+        for (name, value) in [
+            ("x-ratelimit-limit-tokens", "30000"),
+            ("x-ratelimit-remaining-tokens", "7500"),
+            ("x-ratelimit-reset-tokens", "6m0s"),
+        ] {
+            response.headers.push((name.to_string(), value.to_string()));
+        }
+        let transport = MockTransport::new(vec![(url.as_str(), response)]);
+
+        let limits = OpenAiClient::fetch_rate_limits_via(&transport, "sk-proj").await?;
+
+        assert_eq!(limits.len(), 1);
+        assert_eq!(limits[0].kind, "tokens");
+        assert!((limits[0].utilization() - 75.0).abs() < f64::EPSILON);
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn month_cost_starts_at_the_first_of_the_month() -> TestResult {
+        let now: DateTime<Utc> = "2026-10-15T12:00:00Z".parse()?;
+        let url = format!(
+            "{}/v1/organization/costs?start_time={}&bucket_width=1d&limit=31",
+            base_url(Endpoint::OpenAiApi),
+            month_start(now).timestamp()
+        );
+        let body = r#"{"data": [{"start_time": 0, "end_time": 1, "results": [{"amount": {"value": 4.5}}]}], "has_more": false}"#;
+        let transport = MockTransport::new(vec![(url.as_str(), HttpResponse::new(200, body))]);
+
+        let cost = OpenAiClient::fetch_month_cost_via(&transport, "sk-admin", now).await?;
+
+        assert!((cost - 4.5).abs() < f64::EPSILON);
+        Ok(())
+    }
+}
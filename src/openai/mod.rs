@@ -0,0 +1,5 @@
+pub mod client;
+pub mod types;
+
+pub use client::OpenAiClient;
+pub use types::{CostsPage, RateLimitHeadroom};
@@ -0,0 +1,175 @@
+use serde::Deserialize;
+use std::time::Duration;
+
+/// One page of the Admin API `/v1/organization/costs`.
+#[derive(Debug, Deserialize)]
+pub struct CostsPage {
+    pub data: Vec<CostsBucket>,
+    #[serde(default)]
+    pub has_more: bool,
+    #[serde(default)]
+    pub next_page: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CostsBucket {
+    pub start_time: i64,
+    pub end_time: i64,
+    #[serde(default)]
+    pub results: Vec<CostsResult>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CostsResult {
+    pub amount: CostsAmount,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CostsAmount {
+    /// Cost in dollars.
+    pub value: f64,
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+impl CostsPage {
+    /// Total cost on this page in dollars.
+    #[must_use]
+    pub fn total(&self) -> f64 {
+        self.data
+            .iter()
+            .flat_map(|bucket| &bucket.results)
+            .map(|result| result.amount.value)
+            .sum()
+    }
+}
+
+/// Rate-limit headroom from the `x-ratelimit-*` response headers of one kind
+/// (`requests` or `tokens`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateLimitHeadroom {
+    pub kind: &'static str,
+    pub limit: f64,
+    pub remaining: f64,
+    /// Time until `remaining` is back to `limit`.
+    pub reset_in: Option<Duration>,
+}
+
+impl RateLimitHeadroom {
+    /// Read the headroom of `kind` through `header`, a lookup by lowercase name. `None`
+    /// when the response does not report it.
+    pub fn from_headers<'a>(
+        kind: &'static str,
+        header: impl Fn(&str) -> Option<&'a str>,
+    ) -> Option<Self> {
+        let number = |name: String| header(&name)?.trim().parse::<f64>().ok();
+        Some(Self {
+            kind,
+            limit: number(format!("x-ratelimit-limit-{kind}"))?,
+            remaining: number(format!("x-ratelimit-remaining-{kind}"))?,
+            reset_in: header(&format!("x-ratelimit-reset-{kind}")).and_then(parse_reset),
+        })
+    }
+
+    #[must_use]
+    pub fn is_limited(&self) -> bool {
+        self.remaining <= 0.0
+    }
+
+    /// Share of the limit in use, as a percentage.
+    #[must_use]
+    pub fn utilization(&self) -> f64 {
+        if self.limit > 0.0 {
+            (self.limit - self.remaining) / self.limit * 100.0
+        } else {
+            100.0
+        }
+    }
+}
+
+/// Parse a reset duration such as `1s`, `6m0s`, `20ms` or `1h2m3.5s`.
+#[must_use]
+pub fn parse_reset(value: &str) -> Option<Duration> {
+    let mut rest = value.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    let mut total = 0.0;
+    while !rest.is_empty() {
+        let digits = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .filter(|&i| i > 0)?;
+        let amount: f64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .unwrap_or(rest.len());
+        let seconds = match &rest[..unit_len] {
+            "ms" => 0.001,
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            _ => return None,
+        };
+        total += amount * seconds;
+        rest = &rest[unit_len..];
+    }
+    Duration::try_from_secs_f64(total).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn parse_reset_accepts_go_style_durations() {
+        assert_eq!(parse_reset("1s"), Some(Duration::from_secs(1)));
+        assert_eq!(parse_reset("6m0s"), Some(Duration::from_mins(6)));
+        assert_eq!(parse_reset("20ms"), Some(Duration::from_millis(20)));
+        assert_eq!(
+            parse_reset("1h2m3.5s"),
+            Some(Duration::from_millis(3_723_500))
+        );
+        assert_eq!(parse_reset(""), None);
+        assert_eq!(parse_reset("soon"), None);
+        assert_eq!(parse_reset("5d"), None);
+    }
+
+    #[test]
+    fn headroom_is_read_from_ratelimit_headers() -> TestResult {
+        let headers = [
+            ("x-ratelimit-limit-requests", "500"),
+            ("x-ratelimit-remaining-requests", "0"),
+            ("x-ratelimit-reset-requests", "120ms"),
+        ];
+        let header = |name: &str| headers.iter().find(|(k, _)| *k == name).map(|(_, v)| *v);
+
+        let requests =
+            RateLimitHeadroom::from_headers("requests", header).ok_or("expected headroom")?;
+        assert!(requests.is_limited());
+        assert!((requests.utilization() - 100.0).abs() < f64::EPSILON);
+        assert_eq!(requests.reset_in, Some(Duration::from_millis(120)));
+        assert!(RateLimitHeadroom::from_headers("tokens", header).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn costs_page_sums_dollar_amounts() -> TestResult {
+        let json = r#"{
+            "object": "page",
+            "data": [
+                {"object": "bucket", "start_time": 1790812800, "end_time": 1790899200,
+                 "results": [{"object": "organization.costs.result", "amount": {"value": 1.25, "currency": "usd"}}]},
+                {"object": "bucket", "start_time": 1790899200, "end_time": 1790985600,
+                 "results": [{"object": "organization.costs.result", "amount": {"value": 0.5, "currency": "usd"}}]}
+            ],
+            "has_more": false,
+            "next_page": null
+        }"#;
+        let page: CostsPage = serde_json::from_str(json)?;
+        assert!((page.total() - 1.75).abs() < f64::EPSILON);
+        Ok(())
+    }
+}
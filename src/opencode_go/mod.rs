@@ -2,6 +2,6 @@ mod auth;
 mod local;
 mod types;
 
-pub use auth::OpencodeGoAuth;
-pub use local::OpencodeGoUsageStore;
+pub use auth::{OpencodeGoAuth, OpencodeGoAuthError};
+pub use local::{OpencodeGoUsageError, OpencodeGoUsageStore};
 pub use types::{OpencodeGoUsageSnapshot, OpencodeGoUsageSource, OpencodeGoUsageWindow};
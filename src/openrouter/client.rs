@@ -1,5 +1,3 @@
-use std::time::Duration;
-
 use super::types::CreditsResponse;
 
 const CREDITS_URL: &str = "https://openrouter.ai/api/v1/credits";
@@ -12,7 +10,7 @@ impl OpenRouterClient {
     /// Returns an error if the API request fails or the response cannot be parsed.
     pub async fn fetch_credits(
         management_key: &str,
-    ) -> Result<CreditsResponse, Box<dyn std::error::Error>> {
+    ) -> Result<CreditsResponse, Box<dyn std::error::Error + Send + Sync>> {
         let client = Self::build_client()?;
         let response = client
             .get(CREDITS_URL)
@@ -31,8 +29,6 @@ impl OpenRouterClient {
     }
 
     fn build_client() -> Result<reqwest::Client, reqwest::Error> {
-        reqwest::Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
+        crate::http::shared_client()
     }
 }
@@ -0,0 +1,10 @@
+//! Machine-readable output.
+
+pub mod schema;
+pub mod summary;
+
+pub use schema::{
+    AuditOutput, HistoryOutput, QueueOutput, SCHEMA_VERSION, StatusOutput, SummaryOutput,
+    WaitEvent, WaitEventKind,
+};
+pub use summary::{Summary, SummaryRow};
@@ -0,0 +1,173 @@
+//! JSON documents printed by `--json`.
+//!
+//! Every document is an object carrying [`SCHEMA_VERSION`]. Within a version, fields are
+//! only ever added, so parsers should ignore fields they do not know. Removing, renaming
+//! or changing the meaning of a field bumps the version.
+
+use super::summary::Summary;
+use crate::agent::AgentStatus;
+use crate::audit::AuditEntry;
+use crate::history::Sample;
+use crate::queue::QueueItem;
+use crate::wait::WaitProgress;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Version of the documents in this module.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// `seher --json`: the usage of every configured agent.
+#[derive(Debug, Serialize)]
+pub struct StatusOutput<'a> {
+    pub schema_version: u32,
+    pub agents: &'a [AgentStatus],
+}
+
+impl<'a> StatusOutput<'a> {
+    #[must_use]
+    pub fn new(agents: &'a [AgentStatus]) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            agents,
+        }
+    }
+}
+
+/// `seher history --json`: recorded usage samples, oldest first.
+#[derive(Debug, Serialize)]
+pub struct HistoryOutput<'a> {
+    pub schema_version: u32,
+    pub samples: &'a [Sample],
+}
+
+impl<'a> HistoryOutput<'a> {
+    #[must_use]
+    pub fn new(samples: &'a [Sample]) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            samples,
+        }
+    }
+}
+
+/// `seher audit --json`: cookie database reads, oldest first.
+#[derive(Debug, Serialize)]
+pub struct AuditOutput<'a> {
+    pub schema_version: u32,
+    pub entries: &'a [AuditEntry],
+}
+
+impl<'a> AuditOutput<'a> {
+    #[must_use]
+    pub fn new(entries: &'a [AuditEntry]) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            entries,
+        }
+    }
+}
+
+/// Progress of a reset wait under `--json`, printed as one JSON line per event.
+#[derive(Debug, Serialize)]
+pub struct WaitEvent {
+    pub schema_version: u32,
+    pub event: WaitEventKind,
+    pub reset_time: DateTime<Utc>,
+    pub total_secs: i64,
+    pub remaining_secs: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WaitEventKind {
+    /// Emitted when the wait starts and then on the configured cadence.
+    Waiting,
+    /// Emitted once the reset time has been reached.
+    Finished,
+}
+
+impl WaitEvent {
+    #[must_use]
+    pub fn new(event: WaitEventKind, progress: &WaitProgress) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            event,
+            reset_time: progress.deadline,
+            total_secs: progress.total.num_seconds(),
+            remaining_secs: progress.remaining.num_seconds().max(0),
+        }
+    }
+}
+
+/// `seher queue list --json`: queued prompts and their results.
+#[derive(Debug, Serialize)]
+pub struct QueueOutput<'a> {
+    pub schema_version: u32,
+    pub items: &'a [QueueItem],
+}
+
+impl<'a> QueueOutput<'a> {
+    #[must_use]
+    pub fn new(items: &'a [QueueItem]) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            items,
+        }
+    }
+}
+
+/// `seher summary --json`: every provider's windows in one table, with the index of the
+/// most constrained row.
+#[derive(Debug, Serialize)]
+pub struct SummaryOutput<'a> {
+    pub schema_version: u32,
+    #[serde(flatten)]
+    pub summary: &'a Summary,
+}
+
+impl<'a> SummaryOutput<'a> {
+    #[must_use]
+    pub fn new(summary: &'a Summary) -> Self {
+        Self {
+            schema_version: SCHEMA_VERSION,
+            summary,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn documents_carry_the_schema_version() -> TestResult {
+        let value = serde_json::to_value(StatusOutput::new(&[]))?;
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["agents"], serde_json::json!([]));
+        let value = serde_json::to_value(QueueOutput::new(&[]))?;
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        let value = serde_json::to_value(SummaryOutput::new(&Summary::default()))?;
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["rows"], serde_json::json!([]));
+        assert!(value["most_constrained"].is_null());
+        Ok(())
+    }
+
+    #[test]
+    fn wait_events_report_remaining_seconds() -> TestResult {
+        let deadline = Utc::now();
+        let progress = WaitProgress {
+            deadline,
+            total: chrono::TimeDelta::seconds(600),
+            remaining: chrono::TimeDelta::seconds(90),
+        };
+        let value = serde_json::to_value(WaitEvent::new(WaitEventKind::Waiting, &progress))?;
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        assert_eq!(value["event"], "waiting");
+        assert_eq!(value["remaining_secs"], 90);
+        assert_eq!(value["total_secs"], 600);
+        Ok(())
+    }
+}
@@ -0,0 +1,116 @@
+use crate::agent::AgentStatus;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// One usage window of one provider, normalized across providers.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SummaryRow {
+    pub provider: String,
+    pub window: String,
+    pub utilization: f64,
+    pub resets_at: Option<DateTime<Utc>>,
+    pub limited: bool,
+}
+
+/// Every provider's usage windows in one table.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Summary {
+    pub rows: Vec<SummaryRow>,
+    /// Index into `rows` of the most constrained window: a limited one before any other,
+    /// then the highest utilization, then the latest reset.
+    pub most_constrained: Option<usize>,
+}
+
+impl Summary {
+    #[must_use]
+    pub fn new(statuses: &[AgentStatus]) -> Self {
+        let rows: Vec<SummaryRow> = statuses
+            .iter()
+            .flat_map(|status| {
+                let provider = status.provider.as_deref().unwrap_or(&status.command);
+                status.usage.iter().map(move |entry| SummaryRow {
+                    provider: provider.to_string(),
+                    window: entry.entry_type.clone(),
+                    utilization: entry.utilization,
+                    resets_at: entry.resets_at,
+                    limited: entry.limited,
+                })
+            })
+            .collect();
+        let most_constrained = rows
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| {
+                a.limited
+                    .cmp(&b.limited)
+                    .then(a.utilization.total_cmp(&b.utilization))
+                    .then(a.resets_at.cmp(&b.resets_at))
+            })
+            .map(|(i, _)| i);
+        Self {
+            rows,
+            most_constrained,
+        }
+    }
+
+    /// The most constrained window, if there are any.
+    #[must_use]
+    pub fn most_constrained(&self) -> Option<&SummaryRow> {
+        self.most_constrained.and_then(|i| self.rows.get(i))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::agent::UsageEntry;
+
+    fn entry(entry_type: &str, utilization: f64, limited: bool) -> UsageEntry {
+        UsageEntry {
+            entry_type: entry_type.to_string(),
+            limited,
+            utilization,
+            resets_at: None,
+            remaining: None,
+            limit: None,
+            limit_eta: None,
+        }
+    }
+
+    fn status(provider: &str, usage: Vec<UsageEntry>) -> AgentStatus {
+        AgentStatus {
+            command: provider.to_string(),
+            provider: Some(provider.to_string()),
+            plan: None,
+            identity: None,
+            usage,
+        }
+    }
+
+    #[test]
+    fn most_constrained_prefers_limited_then_utilization() {
+        let summary = Summary::new(&[
+            status(
+                "claude",
+                vec![
+                    entry("five_hour", 80.0, false),
+                    entry("seven_day", 40.0, false),
+                ],
+            ),
+            status("copilot", vec![entry("premium_interactions", 95.0, false)]),
+        ]);
+        assert_eq!(summary.rows.len(), 3);
+        assert_eq!(summary.rows[2].provider, "copilot");
+        assert_eq!(
+            summary.most_constrained().map(|r| r.window.as_str()),
+            Some("premium_interactions")
+        );
+
+        let summary = Summary::new(&[
+            status("claude", vec![entry("five_hour", 100.0, false)]),
+            status("codex", vec![entry("primary", 60.0, true)]),
+        ]);
+        assert_eq!(summary.most_constrained, Some(1));
+        assert_eq!(Summary::new(&[]).most_constrained, None);
+    }
+}
@@ -0,0 +1,119 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// A reset wait in progress, saved so `seher resume` can continue it after a restart.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingWait {
+    pub created_at: DateTime<Utc>,
+    /// When the selected agent's limit is expected to reset.
+    pub reset_time: DateTime<Utc>,
+    /// Command of the selected agent.
+    pub command: String,
+    /// Account of the selected agent, when it has `accounts`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Trailing arguments passed to seher, before agent-specific mapping.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Prompt read from stdin, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    /// Working directory the agent should run in.
+    pub cwd: PathBuf,
+    /// Id of the queue item the wait belongs to, marked finished once `seher resume`
+    /// has run it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue_item: Option<u64>,
+}
+
+impl PendingWait {
+    /// `$XDG_STATE_HOME/seher/pending.json`, falling back to
+    /// `~/.local/state/seher/pending.json`.
+    #[must_use]
+    pub fn default_path() -> Option<PathBuf> {
+        let dir = match std::env::var_os("XDG_STATE_HOME").filter(|d| !d.is_empty()) {
+            Some(dir) => PathBuf::from(dir),
+            None => dirs::home_dir()?.join(".local").join("state"),
+        };
+        Some(dir.join("seher").join("pending.json"))
+    }
+
+    /// Write the wait to `path`, replacing any previous one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state directory or file cannot be written.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_vec_pretty(self).map_err(std::io::Error::other)?;
+        crate::cache::write_atomic(path, &json)
+    }
+
+    /// Load the saved wait, or `None` if there is none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load(path: &Path) -> std::io::Result<Option<Self>> {
+        match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(std::io::Error::other),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Remove the saved wait. Missing files are not an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be removed.
+    pub fn clear(path: &Path) -> std::io::Result<()> {
+        match std::fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn save_load_and_clear_round_trip() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("state").join("pending.json");
+        assert_eq!(PendingWait::load(&path)?, None);
+
+        let wait = PendingWait {
+            created_at: "2026-01-01T00:00:00Z".parse()?,
+            reset_time: "2026-01-01T05:00:00Z".parse()?,
+            command: "claude".to_string(),
+            account: Some("work".to_string()),
+            provider: Some("claude".to_string()),
+            model: Some("high".to_string()),
+            args: vec!["fix bugs".to_string()],
+            prompt: None,
+            cwd: PathBuf::from("/tmp/project"),
+            queue_item: Some(3),
+        };
+        wait.save(&path)?;
+        assert_eq!(PendingWait::load(&path)?, Some(wait));
+
+        PendingWait::clear(&path)?;
+        PendingWait::clear(&path)?;
+        assert_eq!(PendingWait::load(&path)?, None);
+        Ok(())
+    }
+}
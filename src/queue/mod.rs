@@ -0,0 +1,260 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Where a queued task is in its lifecycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+impl std::fmt::Display for ItemStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(match self {
+            ItemStatus::Queued => "queued",
+            ItemStatus::Running => "running",
+            ItemStatus::Done => "done",
+            ItemStatus::Failed => "failed",
+        })
+    }
+}
+
+/// A prompt waiting to be run by `seher queue run`, and its result once it has.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QueueItem {
+    pub id: u64,
+    pub added_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub prompt: String,
+    /// Trailing arguments, before agent-specific mapping.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Only run agents whose name, command or provider matches this name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Working directory the agent should run in.
+    pub cwd: PathBuf,
+    pub status: ItemStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// How the run ended, e.g. "succeeded" or "exited with code 1".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<String>,
+}
+
+/// Persistent queue of prompts for `seher queue`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Queue {
+    #[serde(default)]
+    pub items: Vec<QueueItem>,
+}
+
+impl Queue {
+    /// `queue.json` next to the pending-wait state file.
+    #[must_use]
+    pub fn default_path() -> Option<PathBuf> {
+        Some(crate::pending::PendingWait::default_path()?.with_file_name("queue.json"))
+    }
+
+    /// Load the queue, or an empty one if the file does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file exists but cannot be read or parsed.
+    pub fn load(path: &Path) -> std::io::Result<Self> {
+        match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map_err(std::io::Error::other),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write the queue to `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the state directory or file cannot be written.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_vec_pretty(self).map_err(std::io::Error::other)?;
+        crate::cache::write_atomic(path, &json)
+    }
+
+    /// Append a queued item and return its id.
+    pub fn push(
+        &mut self,
+        prompt: String,
+        args: Vec<String>,
+        agent: Option<String>,
+        model: Option<String>,
+        cwd: PathBuf,
+    ) -> u64 {
+        let id = self.items.iter().map(|i| i.id).max().unwrap_or(0) + 1;
+        self.items.push(QueueItem {
+            id,
+            added_at: Utc::now(),
+            prompt,
+            args,
+            agent,
+            model,
+            cwd,
+            status: ItemStatus::Queued,
+            started_at: None,
+            finished_at: None,
+            outcome: None,
+        });
+        id
+    }
+
+    /// The oldest item still waiting to run.
+    #[must_use]
+    pub fn next_queued(&self) -> Option<&QueueItem> {
+        self.queued().next()
+    }
+
+    /// Every item still waiting to run, oldest first.
+    pub fn queued(&self) -> impl Iterator<Item = &QueueItem> {
+        self.items.iter().filter(|i| i.status == ItemStatus::Queued)
+    }
+
+    /// Put items left `running` by an interrupted `queue run` back in the queue.
+    pub fn requeue_interrupted(&mut self) -> usize {
+        let mut count = 0;
+        for item in &mut self.items {
+            if item.status == ItemStatus::Running {
+                item.status = ItemStatus::Queued;
+                item.started_at = None;
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Mark item `id` as running.
+    pub fn start(&mut self, id: u64) {
+        if let Some(item) = self.items.iter_mut().find(|i| i.id == id) {
+            item.status = ItemStatus::Running;
+            item.started_at = Some(Utc::now());
+        }
+    }
+
+    /// Record the result of item `id`.
+    pub fn finish(&mut self, id: u64, success: bool, outcome: String) {
+        if let Some(item) = self.items.iter_mut().find(|i| i.id == id) {
+            item.status = if success {
+                ItemStatus::Done
+            } else {
+                ItemStatus::Failed
+            };
+            item.finished_at = Some(Utc::now());
+            item.outcome = Some(outcome);
+        }
+    }
+}
+
+/// Split a task file into prompts.
+///
+/// When the file contains fenced blocks (lines starting with three backticks), each block
+/// is one prompt and text outside the blocks is ignored. Otherwise each non-empty line is a
+/// prompt, skipping lines that start with `#`.
+#[must_use]
+pub fn parse_task_list(text: &str) -> Vec<String> {
+    const FENCE: &str = "```";
+    if !text.lines().any(|l| l.trim_start().starts_with(FENCE)) {
+        return text
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(ToString::to_string)
+            .collect();
+    }
+    let mut prompts = Vec::new();
+    let mut block: Option<Vec<&str>> = None;
+    for line in text.lines() {
+        if line.trim_start().starts_with(FENCE) {
+            match block.take() {
+                Some(lines) => {
+                    let prompt = lines.join("\n").trim().to_string();
+                    if !prompt.is_empty() {
+                        prompts.push(prompt);
+                    }
+                }
+                None => block = Some(Vec::new()),
+            }
+        } else if let Some(lines) = block.as_mut() {
+            lines.push(line);
+        }
+    }
+    prompts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_task_list_reads_one_prompt_per_line() {
+        assert_eq!(
+            parse_task_list("fix the parser\n\n# skipped\n  add tests  \n"),
+            vec!["fix the parser", "add tests"]
+        );
+    }
+
+    #[test]
+    fn parse_task_list_reads_fenced_blocks() {
+        let text = "Tasks:\n```\nRefactor the cache.\nKeep the API.\n```\nnotes\n```md\nUpdate docs\n```\n";
+        assert_eq!(
+            parse_task_list(text),
+            vec!["Refactor the cache.\nKeep the API.", "Update docs"]
+        );
+    }
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn push_run_and_finish_round_trip() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("state").join("queue.json");
+        let mut queue = Queue::load(&path)?;
+        assert!(queue.items.is_empty());
+
+        let first = queue.push(
+            "fix bugs".to_string(),
+            vec![],
+            None,
+            None,
+            PathBuf::from("/tmp"),
+        );
+        let second = queue.push(
+            "write docs".to_string(),
+            vec![],
+            Some("claude".to_string()),
+            Some("high".to_string()),
+            PathBuf::from("/tmp"),
+        );
+        assert_eq!((first, second), (1, 2));
+        queue.save(&path)?;
+
+        let mut queue = Queue::load(&path)?;
+        assert_eq!(queue.next_queued().map(|i| i.id), Some(1));
+        queue.start(1);
+        queue.finish(1, false, "exited with code 1".to_string());
+        assert_eq!(queue.items[0].status, ItemStatus::Failed);
+        assert_eq!(queue.next_queued().map(|i| i.id), Some(2));
+        queue.start(2);
+        assert_eq!(queue.next_queued(), None);
+        assert_eq!(queue.requeue_interrupted(), 1);
+        assert_eq!(queue.next_queued().map(|i| i.id), Some(2));
+        Ok(())
+    }
+}
@@ -0,0 +1,121 @@
+//! API keys of token-based providers stored in the OS keyring by `seher secrets set`,
+//! so they don't have to live in settings.json.
+
+use crate::keyring::{Keyring, KeyringError};
+
+/// The secret each API-key provider reads, by provider name. Secrets are named after the
+/// environment variable that supplies the same key.
+pub const PROVIDER_SECRETS: &[(&str, &str)] = &[
+    ("anthropic", "ANTHROPIC_ADMIN_KEY"),
+    ("glm", "GLM_API_KEY"),
+    ("kimi-k2", "KIMI_K2_API_KEY"),
+    ("openai", "OPENAI_API_KEY"),
+    ("openrouter", "OPENROUTER_MANAGEMENT_KEY"),
+    ("warp", "WARP_API_KEY"),
+    ("zai", "Z_AI_API_KEY"),
+];
+
+/// The name of the secret `provider` reads, or `None` for providers that don't use one.
+#[must_use]
+pub fn secret_name(provider: &str) -> Option<&'static str> {
+    PROVIDER_SECRETS
+        .iter()
+        .find(|(p, _)| *p == provider)
+        .map(|(_, name)| *name)
+}
+
+/// The stored value of the secret `name`, if any.
+///
+/// The lookup runs on its own thread: the Secret Service client blocks on an async
+/// runtime of its own, which must not nest inside the caller's.
+#[must_use]
+pub fn get(name: &str) -> Option<String> {
+    let account = account(name);
+    std::thread::scope(|scope| scope.spawn(|| Keyring::get(&account)).join())
+        .ok()
+        .and_then(Result::ok)
+        .flatten()
+        .filter(|value| !value.is_empty())
+}
+
+/// Store `value` as the secret `name`.
+///
+/// Returns `true` if the OS keyring was used, `false` if the fallback file was.
+///
+/// # Errors
+///
+/// Returns an error if neither the OS keyring nor the fallback file can be written.
+pub fn set(name: &str, value: &str) -> Result<bool, KeyringError> {
+    Keyring::set(&account(name), value)
+}
+
+fn account(name: &str) -> String {
+    format!("secret:{name}")
+}
+
+/// Read one line from stdin with terminal echo turned off, so pasted keys don't end up
+/// on screen or in scrollback. Piped input is read as is.
+///
+/// # Errors
+///
+/// Returns an error if stdin cannot be read.
+pub fn read_hidden() -> std::io::Result<String> {
+    let mut line = String::new();
+    #[cfg(unix)]
+    let _echo_off = unix::EchoOff::enable();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+#[cfg(unix)]
+mod unix {
+    /// Turns terminal echo off on stdin until dropped.
+    pub(super) struct EchoOff {
+        saved: libc::termios,
+    }
+
+    impl EchoOff {
+        pub(super) fn enable() -> Option<Self> {
+            use std::io::IsTerminal;
+            if !std::io::stdin().is_terminal() {
+                return None;
+            }
+            // SAFETY: `termios` is plain data and is fully written by `tcgetattr` on success.
+            let mut saved: libc::termios = unsafe { std::mem::zeroed() };
+            // SAFETY: fd 0 is valid for the life of the process and `saved` is a valid pointer.
+            if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &raw mut saved) } != 0 {
+                return None;
+            }
+            let mut hidden = saved;
+            hidden.c_lflag &= !libc::ECHO;
+            // SAFETY: as above; `hidden` is a valid, initialized `termios`.
+            if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw const hidden) } != 0
+            {
+                return None;
+            }
+            Some(Self { saved })
+        }
+    }
+
+    impl Drop for EchoOff {
+        fn drop(&mut self) {
+            // SAFETY: `saved` was filled by `tcgetattr` on the same descriptor.
+            unsafe {
+                libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw const self.saved);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn providers_map_to_their_environment_variable() {
+        assert_eq!(secret_name("zai"), Some("Z_AI_API_KEY"));
+        assert_eq!(secret_name("openrouter"), Some("OPENROUTER_MANAGEMENT_KEY"));
+        assert_eq!(secret_name("claude"), None);
+        assert_eq!(account("WARP_API_KEY"), "secret:WARP_API_KEY");
+    }
+}
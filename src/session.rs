@@ -0,0 +1,453 @@
+use crate::browser::{BrowserDetector, BrowserType, Cookie, CookieReader, LazyCookie, Profile};
+use crate::claude::{ClaudeClient, UsageResponse};
+use crate::copilot::{CopilotClient, CopilotQuota};
+use crate::error::SeherError;
+use crate::http::{ClientTransport, HttpTransport, ReqwestTransport};
+use crate::keyring::{self, Keyring};
+use std::time::Duration;
+
+const DEFAULT_DOMAIN: &str = "claude.ai";
+
+/// A browser session discovered for one site, ready to query that site's usage.
+///
+/// ```no_run
+/// # async fn run() -> Result<(), seher::SeherError> {
+/// use seher::{BrowserType, Seher};
+///
+/// let usage = Seher::builder()
+///     .domain("claude.ai")
+///     .browser(BrowserType::Chrome)
+///     .profile("Work")
+///     .build()?
+///     .usage()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Seher {
+    domain: String,
+    profile: Profile,
+    cookies: Vec<Cookie>,
+    /// The session's own client, when the builder was given HTTP settings.
+    client: Option<reqwest::Client>,
+}
+
+impl Seher {
+    #[must_use]
+    pub fn builder() -> SeherBuilder {
+        SeherBuilder::default()
+    }
+
+    #[must_use]
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// The browser profile the session was read from.
+    #[must_use]
+    pub fn profile(&self) -> &Profile {
+        &self.profile
+    }
+
+    #[must_use]
+    pub fn cookies(&self) -> &[Cookie] {
+        &self.cookies
+    }
+
+    /// Fetch Claude usage with the discovered `claude.ai` session.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Claude API request fails or the response cannot be parsed.
+    pub async fn usage(&self) -> Result<UsageResponse, SeherError> {
+        Ok(ClaudeClient::fetch_usage_with_cookies_via(&*self.transport(), &self.cookies).await?)
+    }
+
+    /// Fetch the GitHub Copilot quota with the discovered `github.com` session.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the Copilot API request fails or the response cannot be parsed.
+    pub async fn copilot_quota(&self) -> Result<CopilotQuota, SeherError> {
+        CopilotClient::fetch_quota_with_cookies_via(&*self.transport(), &self.cookies).await
+    }
+
+    fn transport(&self) -> Box<dyn HttpTransport> {
+        match &self.client {
+            Some(client) => Box::new(ClientTransport::new(client.clone())),
+            None => Box::new(ReqwestTransport),
+        }
+    }
+}
+
+/// Builds a [`Seher`] by detecting browsers, reading their cookies and picking the first
+/// profile with a live session for the domain.
+#[derive(Debug, Clone, Default)]
+pub struct SeherBuilder {
+    domain: Option<String>,
+    browser: Option<BrowserType>,
+    profile: Option<String>,
+    proxy: Option<String>,
+    timeout: Option<Duration>,
+    client: Option<reqwest::Client>,
+}
+
+impl SeherBuilder {
+    /// Site whose session to look up. Defaults to `claude.ai`.
+    #[must_use]
+    pub fn domain(mut self, domain: impl Into<String>) -> Self {
+        self.domain = Some(domain.into());
+        self
+    }
+
+    /// Only look in this browser. Without it, every detected Chromium-based browser is
+    /// searched.
+    #[must_use]
+    pub fn browser(mut self, browser: BrowserType) -> Self {
+        self.browser = Some(browser);
+        self
+    }
+
+    /// Only look in the profile with this name.
+    #[must_use]
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = Some(profile.into());
+        self
+    }
+
+    /// Send this session's requests through `url` (`http://`, `https://`, `socks5://`,
+    /// `socks5h://`) instead of the process-wide proxy.
+    #[must_use]
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.proxy = Some(url.into());
+        self
+    }
+
+    /// Time out this session's requests after `timeout` instead of the process-wide
+    /// timeout.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Send this session's requests with `client`; [`Self::proxy`] and [`Self::timeout`]
+    /// are then ignored.
+    #[must_use]
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Find the session. Reading cookie databases blocks, so call this from a blocking
+    /// context when running inside an async runtime that cares.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SeherError::SessionNotFound`] if no candidate profile has a live session
+    /// cookie for the domain, or an HTTP error if the proxy URL is invalid or the client
+    /// cannot be built.
+    pub fn build(self) -> Result<Seher, SeherError> {
+        let client = match (self.client, &self.proxy, self.timeout) {
+            (Some(client), _, _) => Some(client),
+            (None, None, None) => None,
+            (None, proxy, timeout) => {
+                let proxy = proxy.as_deref().map(reqwest::Proxy::all).transpose()?;
+                Some(crate::http::client_builder_with(timeout, proxy).build()?)
+            }
+        };
+        let domain = self.domain.unwrap_or_else(|| DEFAULT_DOMAIN.to_string());
+        let detector = BrowserDetector::new();
+        let profiles = candidate_profiles(
+            &detector,
+            &detector.detect_browsers(),
+            self.browser,
+            self.profile.as_deref(),
+        );
+        let (profile, cookies) = first_session(profiles, &domain, session_cookie_names(&domain))
+            .ok_or_else(|| SeherError::SessionNotFound(domain.clone()))?;
+        Ok(Seher {
+            domain,
+            profile,
+            cookies,
+            client,
+        })
+    }
+}
+
+/// Cookies that make up a signed-in session on the sites seher knows about; empty for
+/// other sites.
+#[must_use]
+pub fn session_cookie_names(domain: &str) -> &'static [&'static str] {
+    match domain {
+        "claude.ai" => &["sessionKey"],
+        "chatgpt.com" => &["__Secure-next-auth.session-token"],
+        "github.com" => &["user_session"],
+        _ => &[],
+    }
+}
+
+/// `true` if `cookie_name` is `name` or one chunk of it (`name.0`, `name.1`, ...), as sites
+/// split large session tokens across several cookies.
+#[must_use]
+pub fn is_cookie_named(cookie_name: &str, name: &str) -> bool {
+    cookie_name
+        .strip_prefix(name)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+fn holds_session<'a>(
+    cookies: impl IntoIterator<Item = (&'a str, bool)> + Clone,
+    required_cookie_names: &[&str],
+) -> bool {
+    if required_cookie_names.is_empty() {
+        return cookies.into_iter().next().is_some();
+    }
+    required_cookie_names.iter().all(|required| {
+        cookies
+            .clone()
+            .into_iter()
+            .any(|(name, expired)| !expired && is_cookie_named(name, required))
+    })
+}
+
+/// `true` if `cookies` hold a live (unexpired) cookie for every required name. With no
+/// required names, any cookie counts.
+#[must_use]
+pub fn has_session(cookies: &[Cookie], required_cookie_names: &[&str]) -> bool {
+    holds_session(
+        cookies.iter().map(|c| (c.name.as_str(), c.is_expired())),
+        required_cookie_names,
+    )
+}
+
+/// Like [`has_session`], but checks names and expiry without decrypting any value.
+#[must_use]
+pub fn has_lazy_session(cookies: &[LazyCookie], required_cookie_names: &[&str]) -> bool {
+    holds_session(
+        cookies.iter().map(|c| (c.name.as_str(), c.is_expired())),
+        required_cookie_names,
+    )
+}
+
+/// Profiles to search for a session, in priority order.
+///
+/// With `browser`, only that browser's profiles (if it was detected); otherwise the
+/// profiles of every detected Chromium-based browser. `profile` narrows either to
+/// profiles with that name.
+#[must_use]
+pub fn candidate_profiles(
+    detector: &BrowserDetector,
+    browsers: &[BrowserType],
+    browser: Option<BrowserType>,
+    profile: Option<&str>,
+) -> Vec<Profile> {
+    // A detector pinned to a directory searches it even when that is not Chromium.
+    let browser = browser.or(detector.user_data_dir().map(|(browser, _)| browser));
+    candidate_profiles_with(
+        browsers,
+        browser,
+        profile,
+        |browser_type, profile_name| detector.get_profile(browser_type, Some(profile_name)),
+        |browser_type| detector.list_profiles(browser_type),
+    )
+}
+
+fn candidate_profiles_with<GetProfile, ListProfiles>(
+    browsers: &[BrowserType],
+    browser: Option<BrowserType>,
+    profile: Option<&str>,
+    mut get_profile: GetProfile,
+    mut list_profiles: ListProfiles,
+) -> Vec<Profile>
+where
+    GetProfile: FnMut(BrowserType, &str) -> Option<Profile>,
+    ListProfiles: FnMut(BrowserType) -> Vec<Profile>,
+{
+    let searched: Vec<BrowserType> = match browser {
+        Some(browser) if browsers.contains(&browser) => vec![browser],
+        Some(_) => return Vec::new(),
+        None => browsers
+            .iter()
+            .copied()
+            .filter(BrowserType::is_chromium_based)
+            .collect(),
+    };
+    searched
+        .into_iter()
+        .flat_map(|browser| match profile {
+            Some(name) => get_profile(browser, name).into_iter().collect(),
+            None => list_profiles(browser),
+        })
+        .collect()
+}
+
+/// The first profile with a live session for `domain`, and its decrypted cookies.
+pub(crate) fn first_session(
+    profiles: impl IntoIterator<Item = Profile>,
+    domain: &str,
+    required_cookie_names: &[&str],
+) -> Option<(Profile, Vec<Cookie>)> {
+    profiles.into_iter().find_map(|profile| {
+        read_session(&profile, domain, required_cookie_names).map(|cookies| (profile, cookies))
+    })
+}
+
+/// `profile`'s decrypted cookies for `domain`, if they hold a live session.
+pub(crate) fn read_session(
+    profile: &Profile,
+    domain: &str,
+    required_cookie_names: &[&str],
+) -> Option<Vec<Cookie>> {
+    let cookies = CookieReader::read_cookies_lazy(profile, domain).ok()?;
+    if !has_lazy_session(&cookies, required_cookie_names) {
+        return None;
+    }
+    let cookies = CookieReader::decrypt_cookies(cookies).cookies;
+    has_session(&cookies, required_cookie_names).then_some(cookies)
+}
+
+/// Look through every detected browser profile for a signed-in session on `domain` and
+/// return its cookies, falling back to the session saved by `seher login`, or an empty
+/// list if there is neither.
+///
+/// A profile qualifies when it holds a live cookie for each of `required_cookie_names`
+/// (see [`session_cookie_names`] for the sites seher knows about). Reading cookie
+/// databases blocks.
+#[must_use]
+pub fn find_session(domain: &str, required_cookie_names: &[&str]) -> Vec<Cookie> {
+    let detector = BrowserDetector::new();
+    let profiles = candidate_profiles(&detector, &detector.detect_browsers(), None, None);
+    first_session(profiles, domain, required_cookie_names)
+        .map(|(_, cookies)| cookies)
+        .or_else(|| stored_session(domain))
+        .unwrap_or_default()
+}
+
+/// The session saved in the keyring by `seher login` for `domain`, as cookies. Only
+/// claude.ai sessions are saved this way.
+#[must_use]
+pub fn stored_session(domain: &str) -> Option<Vec<Cookie>> {
+    if domain != "claude.ai" {
+        return None;
+    }
+    let key = Keyring::get(keyring::CLAUDE_ACCOUNT).ok().flatten()?;
+    Some(vec![session_key_cookie(key)])
+}
+
+/// A claude.ai `sessionKey` cookie carrying `value`, for sessions obtained outside a
+/// browser.
+#[must_use]
+pub fn session_key_cookie(value: impl Into<String>) -> Cookie {
+    Cookie {
+        name: "sessionKey".to_string(),
+        value: value.into(),
+        domain: ".claude.ai".to_string(),
+        path: "/".to_string(),
+        expires_utc: 0,
+        is_secure: true,
+        is_httponly: true,
+        same_site: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn cookie(name: &str, expires_utc: i64) -> Cookie {
+        Cookie {
+            name: name.to_string(),
+            value: "value".to_string(),
+            domain: ".claude.ai".to_string(),
+            path: "/".to_string(),
+            expires_utc,
+            is_secure: true,
+            is_httponly: true,
+            same_site: 0,
+        }
+    }
+
+    /// 1970-01-01T00:00:01Z in Chrome's microseconds-since-1601 format.
+    const LONG_AGO: i64 = 11_644_473_601 * 1_000_000;
+
+    fn profile(name: &str, browser_type: BrowserType) -> Profile {
+        Profile::new(name.to_string(), PathBuf::from(name), browser_type)
+    }
+
+    #[test]
+    fn builder_rejects_an_invalid_session_proxy() {
+        let result = Seher::builder().proxy("not a proxy url").build();
+        assert!(matches!(result, Err(SeherError::Http(_))));
+    }
+
+    #[test]
+    fn has_session_requires_every_live_named_cookie() {
+        let live = [cookie("__Secure-next-auth.session-token.0", 0)];
+        assert!(has_session(&live, &["__Secure-next-auth.session-token"]));
+        assert!(!has_session(
+            &live,
+            &["__Secure-next-auth.session-token", "other"]
+        ));
+        assert!(!has_session(
+            &[cookie("sessionKey", LONG_AGO)],
+            &["sessionKey"]
+        ));
+        assert!(!has_session(&[cookie("sessionKeyOld", 0)], &["sessionKey"]));
+        assert!(has_session(&[cookie("anything", 0)], &[]));
+        assert!(!has_session(&[], &[]));
+    }
+
+    #[test]
+    fn candidate_profiles_filters_named_profiles_without_browser() {
+        let chrome_default = profile("Default", BrowserType::Chrome);
+        let chrome_profile = profile("Profile 17", BrowserType::Chrome);
+        let edge_profile = profile("Profile 17", BrowserType::Edge);
+        let firefox_profile = profile("Profile 17", BrowserType::Firefox);
+
+        let profiles = candidate_profiles_with(
+            &[BrowserType::Chrome, BrowserType::Edge, BrowserType::Firefox],
+            None,
+            Some("Profile 17"),
+            |browser_type, profile_name| match (browser_type, profile_name) {
+                (BrowserType::Chrome, "Profile 17") => Some(chrome_profile.clone()),
+                (BrowserType::Edge, "Profile 17") => Some(edge_profile.clone()),
+                (BrowserType::Firefox, "Profile 17") => Some(firefox_profile.clone()),
+                _ => None,
+            },
+            |browser_type| match browser_type {
+                BrowserType::Chrome => vec![chrome_default.clone(), chrome_profile.clone()],
+                BrowserType::Edge => vec![edge_profile.clone()],
+                BrowserType::Firefox => vec![firefox_profile.clone()],
+                _ => vec![],
+            },
+        );
+
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0].browser_type, BrowserType::Chrome);
+        assert_eq!(profiles[0].name, "Profile 17");
+        assert_eq!(profiles[1].browser_type, BrowserType::Edge);
+        assert_eq!(profiles[1].name, "Profile 17");
+    }
+
+    #[test]
+    fn session_key_cookie_is_a_live_claude_session() {
+        let cookie = session_key_cookie("sk-ant-sid01-abc");
+        assert_eq!(cookie.domain, ".claude.ai");
+        assert!(has_session(&[cookie], session_cookie_names("claude.ai")));
+    }
+
+    #[test]
+    fn candidate_profiles_ignores_browsers_that_were_not_detected() {
+        let profiles = candidate_profiles_with(
+            &[BrowserType::Chrome],
+            Some(BrowserType::Edge),
+            None,
+            |_, _| None,
+            |browser_type| vec![profile("Default", browser_type)],
+        );
+        assert!(profiles.is_empty());
+    }
+}
@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+
+/// `StatsD`/`DogStatsD` gauges sent over UDP after each `seher watch` poll (`statsd` in
+/// settings).
+///
+/// Each usage window is reported as `<prefix>.usage.utilization` (percent) and
+/// `<prefix>.usage.limited` (`1` or `0`). With `DogStatsD` tags the provider, account and
+/// window are tags; for plain `StatsD` they are folded into the metric name instead.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatsdConfig {
+    /// Agent address as `host:port` (default `127.0.0.1:8125`).
+    #[serde(default = "default_address")]
+    pub address: String,
+    /// Metric name prefix (default `seher`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// Extra `DogStatsD` tags added to every metric, e.g. `env:prod`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Send `DogStatsD` tags (default); `false` for a plain `StatsD` server.
+    #[serde(default = "default_dogstatsd")]
+    pub dogstatsd: bool,
+}
+
+impl Default for StatsdConfig {
+    fn default() -> Self {
+        Self {
+            address: default_address(),
+            prefix: None,
+            tags: Vec::new(),
+            dogstatsd: true,
+        }
+    }
+}
+
+fn default_address() -> String {
+    "127.0.0.1:8125".to_string()
+}
+
+const fn default_dogstatsd() -> bool {
+    true
+}
+
+impl StatsdConfig {
+    /// The utilization and limited gauge lines for one usage window.
+    #[must_use]
+    pub fn gauges(
+        &self,
+        provider: &str,
+        account: Option<&str>,
+        window: &str,
+        utilization: f64,
+        limited: bool,
+    ) -> [String; 2] {
+        let prefix = self.prefix.as_deref().unwrap_or("seher");
+        let values = [
+            ("utilization", utilization.to_string()),
+            ("limited", u8::from(limited).to_string()),
+        ];
+        values.map(|(metric, value)| {
+            if self.dogstatsd {
+                let mut tags = vec![
+                    format!("provider:{}", tag_value(provider)),
+                    format!("window:{}", tag_value(window)),
+                ];
+                if let Some(account) = account {
+                    tags.push(format!("account:{}", tag_value(account)));
+                }
+                tags.extend(self.tags.iter().map(|tag| tag_value(tag)));
+                format!("{prefix}.usage.{metric}:{value}|g|#{}", tags.join(","))
+            } else {
+                let scope = [Some(provider), account, Some(window)]
+                    .into_iter()
+                    .flatten()
+                    .map(name_segment)
+                    .collect::<Vec<_>>()
+                    .join(".");
+                format!("{prefix}.{scope}.usage.{metric}:{value}|g")
+            }
+        })
+    }
+
+    /// Send `lines` in one datagram.
+    ///
+    /// Metrics are best-effort; failures are reported as [warnings](crate::warning) and
+    /// never abort the caller.
+    pub async fn send(&self, lines: &[String]) {
+        if lines.is_empty() {
+            return;
+        }
+        if let Err(e) = self.send_datagram(&lines.join("\n")).await {
+            crate::warning::warn(format_args!(
+                "failed to send StatsD metrics to {}: {e}",
+                self.address
+            ));
+        }
+    }
+
+    async fn send_datagram(&self, payload: &str) -> std::io::Result<()> {
+        let target = tokio::net::lookup_host(&self.address)
+            .await?
+            .next()
+            .ok_or_else(|| std::io::Error::other("address did not resolve"))?;
+        let bind = if target.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = tokio::net::UdpSocket::bind(bind).await?;
+        socket.send_to(payload.as_bytes(), target).await?;
+        Ok(())
+    }
+}
+
+/// A tag value without the characters `DogStatsD` uses as separators.
+fn tag_value(value: &str) -> String {
+    value.replace([',', '|', '#', '\n'], "_")
+}
+
+/// A metric name segment: letters, digits and underscores only.
+fn name_segment(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn dogstatsd_gauges_are_tagged_by_provider_and_window() {
+        let statsd = StatsdConfig {
+            tags: vec!["env:prod".to_string()],
+            ..StatsdConfig::default()
+        };
+        assert_eq!(
+            statsd.gauges("claude", Some("me@example.com"), "five_hour", 87.5, false),
+            [
+                "seher.usage.utilization:87.5|g|#provider:claude,window:five_hour,account:me@example.com,env:prod",
+                "seher.usage.limited:0|g|#provider:claude,window:five_hour,account:me@example.com,env:prod",
+            ]
+        );
+    }
+
+    #[test]
+    fn plain_statsd_folds_tags_into_the_name() {
+        let statsd = StatsdConfig {
+            prefix: Some("ai".to_string()),
+            dogstatsd: false,
+            ..StatsdConfig::default()
+        };
+        assert_eq!(
+            statsd.gauges("copilot", None, "premium_interactions", 100.0, true),
+            [
+                "ai.copilot.premium_interactions.usage.utilization:100|g",
+                "ai.copilot.premium_interactions.usage.limited:1|g",
+            ]
+        );
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn sends_lines_in_one_datagram() -> TestResult {
+        let server = tokio::net::UdpSocket::bind("127.0.0.1:0").await?;
+        let statsd = StatsdConfig {
+            address: server.local_addr()?.to_string(),
+            ..StatsdConfig::default()
+        };
+        statsd
+            .send(&["a:1|g".to_string(), "b:0|g".to_string()])
+            .await;
+
+        let mut buf = [0; 64];
+        let len = server.recv(&mut buf).await?;
+        assert_eq!(&buf[..len], b"a:1|g\nb:0|g");
+        Ok(())
+    }
+}
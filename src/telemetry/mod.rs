@@ -0,0 +1,357 @@
+//! OpenTelemetry export over OTLP/HTTP (JSON encoding).
+//!
+//! Spans and usage gauges are buffered in memory and sent by [`flush`], which the CLI
+//! calls at exit and after each `seher watch` poll and `seher daemon` run. Nothing is
+//! recorded unless an exporter is [installed](install).
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+static INSTALLED: OnceLock<Exporter> = OnceLock::new();
+
+/// Utilization, limited state and sample time, keyed by provider, account and window.
+type Gauges = BTreeMap<(String, String, String), (f64, bool, u128)>;
+
+/// Where to send telemetry (`telemetry` in settings).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    /// OTLP/HTTP base URL, e.g. `http://localhost:4318`; `/v1/traces` and `/v1/metrics`
+    /// are appended.
+    pub endpoint: String,
+    /// Extra request headers, e.g. an API key for a hosted collector.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    /// `service.name` resource attribute (default `seher`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_name: Option<String>,
+}
+
+impl TelemetryConfig {
+    /// The configuration from the standard `OTEL_EXPORTER_OTLP_ENDPOINT` and
+    /// `OTEL_SERVICE_NAME` variables, if an endpoint is set.
+    #[must_use]
+    pub fn from_env() -> Option<Self> {
+        let endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+            .ok()
+            .filter(|e| !e.is_empty())?;
+        Some(Self {
+            endpoint,
+            headers: HashMap::new(),
+            service_name: std::env::var("OTEL_SERVICE_NAME")
+                .ok()
+                .filter(|n| !n.is_empty()),
+        })
+    }
+}
+
+struct Exporter {
+    config: TelemetryConfig,
+    /// One trace per process, so a run's spans group together.
+    trace_id: String,
+    spans: Mutex<Vec<Value>>,
+    /// Latest sample per usage window.
+    gauges: Mutex<Gauges>,
+}
+
+/// Export telemetry to `config` for the rest of the process.
+///
+/// Returns `false` if an exporter was already installed.
+pub fn install(config: TelemetryConfig) -> bool {
+    INSTALLED
+        .set(Exporter {
+            config,
+            trace_id: random_hex(16),
+            spans: Mutex::new(Vec::new()),
+            gauges: Mutex::new(BTreeMap::new()),
+        })
+        .is_ok()
+}
+
+/// `true` if an exporter is installed.
+#[must_use]
+pub fn enabled() -> bool {
+    INSTALLED.get().is_some()
+}
+
+/// A timed operation, exported when [`Span::end`] is called.
+#[derive(Debug)]
+pub struct Span {
+    name: &'static str,
+    start: u128,
+    attributes: Vec<(&'static str, Value)>,
+    error: Option<String>,
+}
+
+impl Span {
+    #[must_use]
+    pub fn start(name: &'static str) -> Self {
+        Self {
+            name,
+            start: now_nanos(),
+            attributes: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Attach an attribute (strings, numbers and booleans).
+    pub fn set(&mut self, key: &'static str, value: impl Into<Value>) {
+        self.attributes.push((key, value.into()));
+    }
+
+    /// Mark the span as failed with `message`.
+    pub fn fail(&mut self, message: impl std::fmt::Display) {
+        self.error = Some(message.to_string());
+    }
+
+    /// Finish the span and buffer it for export. Does nothing without an exporter.
+    pub fn end(self) {
+        let Some(exporter) = INSTALLED.get() else {
+            return;
+        };
+        let span = span_json(&self, &exporter.trace_id, now_nanos());
+        if let Ok(mut spans) = exporter.spans.lock() {
+            spans.push(span);
+        }
+    }
+}
+
+/// Record the utilization of one usage window for the `seher.usage.utilization` and
+/// `seher.usage.limited` gauges.
+pub fn record_usage(provider: &str, account: &str, window: &str, utilization: f64, limited: bool) {
+    let Some(exporter) = INSTALLED.get() else {
+        return;
+    };
+    if let Ok(mut gauges) = exporter.gauges.lock() {
+        gauges.insert(
+            (
+                provider.to_string(),
+                account.to_string(),
+                window.to_string(),
+            ),
+            (utilization, limited, now_nanos()),
+        );
+    }
+}
+
+/// Send the buffered spans and gauges to the collector.
+///
+/// Export is best-effort; failures are reported as [warnings](crate::warning) and the data
+/// is dropped.
+pub async fn flush() {
+    let Some(exporter) = INSTALLED.get() else {
+        return;
+    };
+    let spans = exporter
+        .spans
+        .lock()
+        .map(|mut spans| std::mem::take(&mut *spans))
+        .unwrap_or_default();
+    let gauges = exporter
+        .gauges
+        .lock()
+        .map(|mut gauges| std::mem::take(&mut *gauges))
+        .unwrap_or_default();
+    let resource = resource_json(&exporter.config);
+    if !spans.is_empty() {
+        let body = json!({ "resourceSpans": [{
+            "resource": resource,
+            "scopeSpans": [{ "scope": scope_json(), "spans": spans }],
+        }]});
+        if let Err(e) = export(&exporter.config, "traces", &body).await {
+            crate::warning::warn(format_args!("failed to export traces: {e}"));
+        }
+    }
+    if !gauges.is_empty() {
+        let body = json!({ "resourceMetrics": [{
+            "resource": resource,
+            "scopeMetrics": [{ "scope": scope_json(), "metrics": metrics_json(&gauges) }],
+        }]});
+        if let Err(e) = export(&exporter.config, "metrics", &body).await {
+            crate::warning::warn(format_args!("failed to export metrics: {e}"));
+        }
+    }
+}
+
+async fn export(
+    config: &TelemetryConfig,
+    signal: &str,
+    body: &Value,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let url = format!("{}/v1/{signal}", config.endpoint.trim_end_matches('/'));
+    let response = config
+        .headers
+        .iter()
+        .fold(
+            crate::http::shared_client()?.post(url),
+            |builder, (name, value)| builder.header(name, value),
+        )
+        .json(body)
+        .send()
+        .await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(format!("collector returned {status}").into());
+    }
+    Ok(())
+}
+
+fn span_json(span: &Span, trace_id: &str, end: u128) -> Value {
+    let status = match &span.error {
+        // STATUS_CODE_ERROR
+        Some(message) => json!({ "code": 2, "message": message }),
+        // STATUS_CODE_OK
+        None => json!({ "code": 1 }),
+    };
+    json!({
+        "traceId": trace_id,
+        "spanId": random_hex(8),
+        "name": span.name,
+        // SPAN_KIND_INTERNAL
+        "kind": 1,
+        "startTimeUnixNano": span.start.to_string(),
+        "endTimeUnixNano": end.to_string(),
+        "attributes": span
+            .attributes
+            .iter()
+            .map(|(key, value)| attribute(key, value))
+            .collect::<Vec<_>>(),
+        "status": status,
+    })
+}
+
+fn metrics_json(gauges: &Gauges) -> Value {
+    let points = |value: fn(f64, bool) -> Value| {
+        gauges
+            .iter()
+            .map(
+                |((provider, account, window), &(utilization, limited, at))| {
+                    let mut point = json!({
+                        "timeUnixNano": at.to_string(),
+                        "attributes": [
+                            attribute("provider", &provider.as_str().into()),
+                            attribute("account", &account.as_str().into()),
+                            attribute("window", &window.as_str().into()),
+                        ],
+                    });
+                    if let (Some(point), Value::Object(value)) =
+                        (point.as_object_mut(), value(utilization, limited))
+                    {
+                        point.extend(value);
+                    }
+                    point
+                },
+            )
+            .collect::<Vec<_>>()
+    };
+    json!([
+        {
+            "name": "seher.usage.utilization",
+            "description": "Utilization of a provider's usage window",
+            "unit": "%",
+            "gauge": { "dataPoints": points(|utilization, _| json!({ "asDouble": utilization })) },
+        },
+        {
+            "name": "seher.usage.limited",
+            "description": "1 while a provider's usage window is limited",
+            "unit": "1",
+            "gauge": { "dataPoints": points(|_, limited| json!({ "asInt": i64::from(limited).to_string() })) },
+        },
+    ])
+}
+
+fn resource_json(config: &TelemetryConfig) -> Value {
+    json!({ "attributes": [
+        attribute("service.name", &config.service_name.as_deref().unwrap_or("seher").into()),
+        attribute("service.version", &env!("CARGO_PKG_VERSION").into()),
+    ]})
+}
+
+fn scope_json() -> Value {
+    json!({ "name": "seher", "version": env!("CARGO_PKG_VERSION") })
+}
+
+/// An OTLP `KeyValue`; integers are sent as strings, as the JSON encoding requires.
+fn attribute(key: &str, value: &Value) -> Value {
+    let value = match value {
+        Value::Bool(b) => json!({ "boolValue": b }),
+        Value::Number(n) if n.is_i64() || n.is_u64() => json!({ "intValue": n.to_string() }),
+        Value::Number(n) => json!({ "doubleValue": n }),
+        Value::String(s) => json!({ "stringValue": s }),
+        other => json!({ "stringValue": other.to_string() }),
+    };
+    json!({ "key": key, "value": value })
+}
+
+fn now_nanos() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos())
+}
+
+fn random_hex(bytes: usize) -> String {
+    use std::fmt::Write as _;
+    (0..bytes).fold(String::with_capacity(bytes * 2), |mut hex, _| {
+        let _ = write!(hex, "{:02x}", fastrand::u8(..));
+        hex
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_encode_as_otlp_json() {
+        let mut span = Span::start("cookies.read");
+        span.set("browser", "Chrome");
+        span.set("cookies", 3);
+        span.fail("database is locked");
+
+        let json = span_json(&span, "0af7651916cd43dd8448eb211c80319c", span.start + 5);
+        assert_eq!(json["name"], "cookies.read");
+        assert_eq!(json["spanId"].as_str().map(str::len), Some(16));
+        assert_eq!(
+            json["endTimeUnixNano"],
+            (span.start + 5).to_string().as_str()
+        );
+        assert_eq!(
+            json["attributes"][0],
+            json!({ "key": "browser", "value": { "stringValue": "Chrome" } })
+        );
+        assert_eq!(
+            json["attributes"][1],
+            json!({ "key": "cookies", "value": { "intValue": "3" } })
+        );
+        assert_eq!(
+            json["status"],
+            json!({ "code": 2, "message": "database is locked" })
+        );
+    }
+
+    #[test]
+    fn usage_gauges_carry_provider_and_window() {
+        let mut gauges = BTreeMap::new();
+        gauges.insert(
+            (
+                "claude".to_string(),
+                "me@example.com".to_string(),
+                "five_hour".to_string(),
+            ),
+            (87.5, false, 42),
+        );
+
+        let metrics = metrics_json(&gauges);
+        let utilization = &metrics[0]["gauge"]["dataPoints"][0];
+        assert_eq!(metrics[0]["name"], "seher.usage.utilization");
+        assert_eq!(utilization["asDouble"], 87.5);
+        assert_eq!(utilization["timeUnixNano"], "42");
+        assert_eq!(
+            utilization["attributes"][2],
+            json!({ "key": "window", "value": { "stringValue": "five_hour" } })
+        );
+        assert_eq!(metrics[1]["gauge"]["dataPoints"][0]["asInt"], "0");
+    }
+}
@@ -0,0 +1,244 @@
+//! Optional front-matter at the top of an editor-composed prompt.
+//!
+//! Only the small YAML subset needed for launcher options is understood:
+//!
+//! ```text
+//! ---
+//! agent: claude
+//! model: high
+//! args: [--permission-mode, plan]
+//! files:
+//!   - src/lib.rs
+//! ---
+//! Fix the failing tests.
+//! ```
+
+use thiserror::Error;
+
+/// Launcher options read from a prompt's front-matter.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrontMatter {
+    /// Only run agents whose command or provider matches this name.
+    pub agent: Option<String>,
+    /// Model key, overriding `--model`.
+    pub model: Option<String>,
+    /// Extra arguments passed to the agent.
+    pub args: Vec<String>,
+    /// Files referenced from the prompt as `@path`.
+    pub files: Vec<String>,
+}
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum FrontMatterError {
+    #[error("front-matter is missing its closing `---`")]
+    Unterminated,
+
+    #[error("front-matter line {line}: expected `key: value`")]
+    Syntax { line: usize },
+
+    #[error("front-matter line {line}: unknown key `{key}`")]
+    UnknownKey { line: usize, key: String },
+
+    #[error("front-matter line {line}: `{key}` takes a single value, not a list")]
+    ExpectedScalar { line: usize, key: String },
+}
+
+const DELIMITER: &str = "---";
+
+/// Split `text` into its front-matter and the remaining prompt body.
+///
+/// Text that does not start with a `---` line has no front-matter and is returned whole.
+///
+/// # Errors
+///
+/// Returns an error if the block is unterminated or contains a line or key that is not
+/// understood.
+pub fn parse(text: &str) -> Result<(FrontMatter, &str), FrontMatterError> {
+    let Some(rest) = strip_delimiter_line(text) else {
+        return Ok((FrontMatter::default(), text));
+    };
+    let mut header_len = 0;
+    let mut body = None;
+    for line in rest.split_inclusive('\n') {
+        if line.trim_end() == DELIMITER {
+            body = Some(&rest[header_len + line.len()..]);
+            break;
+        }
+        header_len += line.len();
+    }
+    let body = body.ok_or(FrontMatterError::Unterminated)?;
+    Ok((parse_header(&rest[..header_len])?, body))
+}
+
+impl FrontMatter {
+    /// Append the `files` references to `body` as `@path` mentions.
+    #[must_use]
+    pub fn apply_files(&self, body: &str) -> String {
+        if self.files.is_empty() {
+            return body.to_string();
+        }
+        let mentions = self
+            .files
+            .iter()
+            .map(|f| format!("@{f}"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if body.is_empty() {
+            mentions
+        } else {
+            format!("{body}\n\n{mentions}")
+        }
+    }
+}
+
+fn strip_delimiter_line(text: &str) -> Option<&str> {
+    let (first, rest) = text.split_once('\n')?;
+    (first.trim_end() == DELIMITER).then_some(rest)
+}
+
+enum Value {
+    Scalar(String),
+    List(Vec<String>),
+}
+
+fn parse_header(header: &str) -> Result<FrontMatter, FrontMatterError> {
+    let mut front = FrontMatter::default();
+    let mut entries: Vec<(usize, String, Value)> = Vec::new();
+    for (i, raw) in header.lines().enumerate() {
+        let line = i + 2; // 1-based, after the opening delimiter
+        let trimmed = raw.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if let Some(item) = trimmed.strip_prefix("- ") {
+            match entries.last_mut() {
+                Some((_, _, Value::List(items))) => items.push(unquote(item)),
+                _ => return Err(FrontMatterError::Syntax { line }),
+            }
+            continue;
+        }
+        let (key, value) = trimmed
+            .split_once(':')
+            .ok_or(FrontMatterError::Syntax { line })?;
+        let value = value.trim();
+        let value = if value.is_empty() {
+            Value::List(vec![])
+        } else if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            Value::List(
+                inner
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(unquote)
+                    .collect(),
+            )
+        } else {
+            Value::Scalar(unquote(value))
+        };
+        entries.push((line, key.trim().to_string(), value));
+    }
+
+    for (line, key, value) in entries {
+        match key.as_str() {
+            "agent" => front.agent = Some(scalar(line, &key, value)?),
+            "model" => front.model = Some(scalar(line, &key, value)?),
+            "args" => front.args = list(value),
+            "files" => front.files = list(value),
+            _ => return Err(FrontMatterError::UnknownKey { line, key }),
+        }
+    }
+    Ok(front)
+}
+
+fn scalar(line: usize, key: &str, value: Value) -> Result<String, FrontMatterError> {
+    match value {
+        Value::Scalar(s) => Ok(s),
+        Value::List(_) => Err(FrontMatterError::ExpectedScalar {
+            line,
+            key: key.to_string(),
+        }),
+    }
+}
+
+fn list(value: Value) -> Vec<String> {
+    match value {
+        Value::Scalar(s) => vec![s],
+        Value::List(items) => items,
+    }
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+        {
+            return inner.to_string();
+        }
+    }
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[test]
+    fn parse_without_front_matter_returns_text_unchanged() -> TestResult {
+        let (front, body) = parse("fix bugs\n---\n")?;
+        assert_eq!(front, FrontMatter::default());
+        assert_eq!(body, "fix bugs\n---\n");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_reads_scalars_and_lists() -> TestResult {
+        let text = "---\nagent: claude\nmodel: \"high\"\nargs: [--permission-mode, plan]\n\
+                    files:\n  - src/lib.rs\n  - 'README.md'\n---\nFix the tests.\n";
+        let (front, body) = parse(text)?;
+        assert_eq!(front.agent.as_deref(), Some("claude"));
+        assert_eq!(front.model.as_deref(), Some("high"));
+        assert_eq!(front.args, vec!["--permission-mode", "plan"]);
+        assert_eq!(front.files, vec!["src/lib.rs", "README.md"]);
+        assert_eq!(body, "Fix the tests.\n");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_rejects_unknown_keys_and_unterminated_blocks() {
+        assert_eq!(
+            parse("---\nagnet: claude\n---\nbody"),
+            Err(FrontMatterError::UnknownKey {
+                line: 2,
+                key: "agnet".to_string()
+            })
+        );
+        assert_eq!(
+            parse("---\nagent: claude\nbody"),
+            Err(FrontMatterError::Unterminated)
+        );
+        assert_eq!(
+            parse("---\nmodel: [a, b]\n---\n"),
+            Err(FrontMatterError::ExpectedScalar {
+                line: 2,
+                key: "model".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn apply_files_appends_mentions() {
+        let front = FrontMatter {
+            files: vec!["src/lib.rs".to_string(), "Cargo.toml".to_string()],
+            ..FrontMatter::default()
+        };
+        assert_eq!(
+            front.apply_files("Review"),
+            "Review\n\n@src/lib.rs @Cargo.toml"
+        );
+        assert_eq!(FrontMatter::default().apply_files("Review"), "Review");
+    }
+}
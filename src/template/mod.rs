@@ -0,0 +1,106 @@
+//! Prompt templates with `{placeholder}` expansion.
+
+pub mod front_matter;
+
+/// Values substituted into a prompt template.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TemplateContext {
+    /// `{args}`: the trailing command-line arguments joined by spaces.
+    pub args: String,
+    /// `{cwd}`: the current working directory.
+    pub cwd: String,
+    /// `{git_branch}`: the checked-out branch, empty outside a git repository.
+    pub git_branch: String,
+    /// `{stdin}`: the prompt piped into seher, if any.
+    pub stdin: String,
+}
+
+impl TemplateContext {
+    /// Build a context from the current process state.
+    #[must_use]
+    pub fn current(args: &[String], stdin: Option<&str>) -> Self {
+        Self {
+            args: args.join(" "),
+            cwd: std::env::current_dir()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default(),
+            git_branch: git_branch().unwrap_or_default(),
+            stdin: stdin.unwrap_or_default().to_string(),
+        }
+    }
+}
+
+/// `true` if `template` consumes the trailing arguments through `{args}`.
+#[must_use]
+pub fn uses_args(template: &str) -> bool {
+    template.contains("{args}")
+}
+
+/// `true` if `template` consumes the piped prompt through `{stdin}`.
+#[must_use]
+pub fn uses_stdin(template: &str) -> bool {
+    template.contains("{stdin}")
+}
+
+/// Replace every known placeholder in `template`; unknown `{...}` text is left as is.
+#[must_use]
+pub fn expand(template: &str, ctx: &TemplateContext) -> String {
+    template
+        .replace("{args}", &ctx.args)
+        .replace("{cwd}", &ctx.cwd)
+        .replace("{git_branch}", &ctx.git_branch)
+        .replace("{stdin}", &ctx.stdin)
+}
+
+fn git_branch() -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["rev-parse", "--abbrev-ref", "HEAD"])
+        .stderr(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let branch = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!branch.is_empty()).then_some(branch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> TemplateContext {
+        TemplateContext {
+            args: "src/lib.rs".to_string(),
+            cwd: "/work/app".to_string(),
+            git_branch: "feature/x".to_string(),
+            stdin: String::new(),
+        }
+    }
+
+    #[test]
+    fn expand_replaces_known_placeholders() {
+        assert_eq!(
+            expand(
+                "Fix the tests in {args} on {git_branch} ({cwd})",
+                &context()
+            ),
+            "Fix the tests in src/lib.rs on feature/x (/work/app)"
+        );
+    }
+
+    #[test]
+    fn expand_keeps_unknown_placeholders() {
+        assert_eq!(
+            expand("{args} {unknown}", &context()),
+            "src/lib.rs {unknown}"
+        );
+    }
+
+    #[test]
+    fn uses_placeholders() {
+        assert!(uses_args("Review {args}"));
+        assert!(!uses_args("Review everything"));
+        assert!(uses_stdin("Summarize:\n{stdin}"));
+    }
+}
@@ -0,0 +1,530 @@
+//! Self-update from the GitHub releases built by `dist`.
+//!
+//! Releases are always fetched from github.com, whatever `base_url` overrides are set,
+//! and an archive is installed only after its checksum and GitHub build attestation
+//! check out.
+
+use crate::error::SeherError;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// GitHub repository the release binaries are published to.
+pub const REPOSITORY: &str = "smartcrabai/seher";
+
+/// The version of the running binary.
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A published release, from `GET /repos/{owner}/{repo}/releases/latest`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub html_url: String,
+    #[serde(default)]
+    pub assets: Vec<Asset>,
+}
+
+/// A file attached to a release.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    /// The release version without the tag's `v` prefix.
+    #[must_use]
+    pub fn version(&self) -> &str {
+        self.tag_name.trim_start_matches('v')
+    }
+
+    /// `true` if this release is newer than the running binary.
+    #[must_use]
+    pub fn is_newer(&self) -> bool {
+        is_newer(self.version(), CURRENT_VERSION)
+    }
+
+    /// The archive for `target` and the `.sha256` file published beside it.
+    #[must_use]
+    pub fn archive_for(&self, target: &str) -> Option<(&Asset, &Asset)> {
+        let suffix = format!("-{target}.tar.xz");
+        let archive = self.assets.iter().find(|a| a.name.ends_with(&suffix))?;
+        let checksum_name = format!("{}.sha256", archive.name);
+        let checksum = self.assets.iter().find(|a| a.name == checksum_name)?;
+        Some((archive, checksum))
+    }
+}
+
+/// Fetch the latest release of [`REPOSITORY`] from the GitHub API. The API override
+/// is deliberately ignored: it must not be able to choose the binary that gets installed.
+///
+/// # Errors
+///
+/// Returns an error if the request fails or GitHub answers with an unsuccessful status.
+pub async fn latest_release() -> Result<Release, SeherError> {
+    let response = crate::http::shared_client()?
+        .get(format!(
+            "https://api.github.com/repos/{REPOSITORY}/releases/latest"
+        ))
+        .header("accept", "application/vnd.github+json")
+        .send()
+        .await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        return Err(SeherError::Status {
+            service: "GitHub releases",
+            status: status.as_u16(),
+            body: response.text().await?,
+        });
+    }
+    Ok(response.json().await?)
+}
+
+/// `true` if the dotted version `candidate` is greater than `current`. Pre-release
+/// suffixes (`-rc.1`) and build metadata are ignored.
+#[must_use]
+pub fn is_newer(candidate: &str, current: &str) -> bool {
+    fn parts(version: &str) -> Vec<u64> {
+        version
+            .trim_start_matches('v')
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default()
+            .split('.')
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    }
+    let (candidate, current) = (parts(candidate), parts(current));
+    let len = candidate.len().max(current.len());
+    let pad = |v: &[u64]| {
+        (0..len)
+            .map(|i| v.get(i).copied().unwrap_or(0))
+            .collect::<Vec<_>>()
+    };
+    pad(&candidate) > pad(&current)
+}
+
+/// Daily check for a newer release, remembered in a small state file so the hint can be
+/// printed on later runs without waiting for the network.
+#[derive(Debug, Clone)]
+pub struct UpdateCheck {
+    path: PathBuf,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct CheckState {
+    checked_at: Option<DateTime<Utc>>,
+    latest: Option<String>,
+}
+
+impl UpdateCheck {
+    /// Time between checks.
+    pub const INTERVAL: chrono::TimeDelta = chrono::TimeDelta::days(1);
+
+    #[must_use]
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+
+    /// A one-line hint if the last check found a release newer than this binary.
+    #[must_use]
+    pub fn hint(&self) -> Option<String> {
+        let latest = self.state().latest?;
+        is_newer(&latest, CURRENT_VERSION).then(|| {
+            format!(
+                "seher {latest} is available (installed: {CURRENT_VERSION}); run `seher upgrade` to update"
+            )
+        })
+    }
+
+    /// `true` if no check has been started in the last [`Self::INTERVAL`]. Records `now`
+    /// as the check time before the check runs, so concurrent runs don't check again and a
+    /// failed check backs off for the interval instead of being retried on every run.
+    #[must_use]
+    pub fn claim(&self, now: DateTime<Utc>) -> bool {
+        let mut state = self.state();
+        if state.checked_at.is_some_and(|at| now - at < Self::INTERVAL) {
+            return false;
+        }
+        state.checked_at = Some(now);
+        self.store(&state).is_ok()
+    }
+
+    /// Fetch the latest release and remember its version for [`Self::hint`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the release cannot be fetched or the state file written.
+    pub async fn refresh(&self) -> Result<(), SeherError> {
+        let release = latest_release().await?;
+        self.record_latest(release.version())
+    }
+
+    fn record_latest(&self, latest: &str) -> Result<(), SeherError> {
+        let mut state = self.state();
+        state.latest = Some(latest.to_string());
+        self.store(&state)
+    }
+
+    fn state(&self) -> CheckState {
+        std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn store(&self, state: &CheckState) -> Result<(), SeherError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.path, serde_json::to_string(state)?)?;
+        Ok(())
+    }
+}
+
+/// The `dist` target triple of the running binary, if release archives are built for it.
+#[must_use]
+pub const fn current_target() -> Option<&'static str> {
+    if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+        Some("x86_64-unknown-linux-gnu")
+    } else if cfg!(all(target_os = "linux", target_arch = "aarch64")) {
+        Some("aarch64-unknown-linux-gnu")
+    } else if cfg!(all(target_os = "macos", target_arch = "aarch64")) {
+        Some("aarch64-apple-darwin")
+    } else if cfg!(all(target_os = "macos", target_arch = "x86_64")) {
+        Some("x86_64-apple-darwin")
+    } else {
+        None
+    }
+}
+
+/// The package manager that owns the executable at `exe`, if any, with the command that
+/// upgrades it. Replacing such a binary behind the package manager's back breaks its
+/// bookkeeping.
+#[must_use]
+pub fn package_manager(exe: &Path) -> Option<(&'static str, &'static str)> {
+    let path = exe.to_string_lossy();
+    if path.contains("/Cellar/") || path.contains("/homebrew/") || path.contains("/linuxbrew/") {
+        Some(("Homebrew", "brew upgrade seher"))
+    } else if path.starts_with("/nix/store/") {
+        Some(("Nix", "your Nix configuration"))
+    } else if exe.ends_with(".cargo/bin/seher") {
+        Some((
+            "cargo",
+            "cargo install --git https://github.com/smartcrabai/seher",
+        ))
+    } else {
+        None
+    }
+}
+
+/// Download `release`'s archive for this platform, check it against the published
+/// SHA-256 and its GitHub build attestation, and replace the running executable with the
+/// `seher` binary inside it. Returns the path of the replaced executable.
+///
+/// # Errors
+///
+/// Returns an error if the executable is managed by a package manager, no archive is
+/// published for this platform, a download fails, the checksum or attestation does not
+/// verify, or the executable cannot be replaced.
+pub async fn install(release: &Release) -> Result<PathBuf, SeherError> {
+    let exe = std::env::current_exe()?;
+    let resolved = std::fs::canonicalize(&exe).unwrap_or_else(|_| exe.clone());
+    if let Some((manager, upgrade)) = package_manager(&resolved) {
+        return Err(format!(
+            "{} is managed by {manager}; upgrade it with {upgrade} instead",
+            exe.display()
+        )
+        .into());
+    }
+    let target =
+        current_target().ok_or("no prebuilt seher binary is published for this platform")?;
+    let (archive, checksum) = release
+        .archive_for(target)
+        .ok_or_else(|| format!("release {} has no archive for {target}", release.tag_name))?;
+
+    let bytes = download(&archive.browser_download_url).await?;
+    let expected = download(&checksum.browser_download_url).await?;
+    verify_checksum(&bytes, &String::from_utf8_lossy(&expected))?;
+
+    let dir = tempfile::tempdir()?;
+    let archive_path = dir.path().join(&archive.name);
+    std::fs::write(&archive_path, &bytes)?;
+    verify_attestation(&archive_path)?;
+    let status = std::process::Command::new("tar")
+        .arg("-xJf")
+        .arg(&archive_path)
+        .arg("-C")
+        .arg(dir.path())
+        .status()?;
+    if !status.success() {
+        return Err(format!("could not unpack {} ({status})", archive.name).into());
+    }
+    let binary = find_binary(dir.path())
+        .ok_or_else(|| format!("{} does not contain a seher binary", archive.name))?;
+
+    replace_executable(&binary, &exe)?;
+    Ok(exe)
+}
+
+/// Download a release asset. Only github.com release downloads of [`REPOSITORY`] are
+/// fetched.
+async fn download(url: &str) -> Result<Vec<u8>, SeherError> {
+    if !is_release_download(url) {
+        return Err(format!("refusing to download {url}: not a {REPOSITORY} release asset").into());
+    }
+    let response = crate::http::shared_client()?.get(url).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(SeherError::Status {
+            service: "GitHub releases",
+            status: status.as_u16(),
+            body: response.text().await?,
+        });
+    }
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// `true` for `https://github.com/<REPOSITORY>/releases/download/<tag>/<asset>`, judged on
+/// the parsed URL so look-alike hosts, credentials, ports and `..` segments don't pass.
+fn is_release_download(url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(url) else {
+        return false;
+    };
+    if url.scheme() != "https"
+        || url.host_str() != Some("github.com")
+        || url.port().is_some()
+        || !url.username().is_empty()
+        || url.password().is_some()
+    {
+        return false;
+    }
+    let Some(segments) = url.path_segments() else {
+        return false;
+    };
+    let segments: Vec<&str> = segments.collect();
+    let prefix: Vec<&str> = REPOSITORY
+        .split('/')
+        .chain(["releases", "download"])
+        .collect();
+    segments.len() == prefix.len() + 2
+        && segments.starts_with(&prefix)
+        && segments[prefix.len()..]
+            .iter()
+            .all(|segment| !matches!(*segment, "" | "." | ".."))
+}
+
+/// Check that `archive` was built by [`REPOSITORY`]'s release workflow, using the GitHub
+/// CLI to verify its signed build provenance.
+fn verify_attestation(archive: &Path) -> Result<(), SeherError> {
+    let output = std::process::Command::new("gh")
+        .args(["attestation", "verify"])
+        .arg(archive)
+        .args(["--repo", REPOSITORY])
+        .output()
+        .map_err(|e| {
+            format!("the GitHub CLI (gh) is needed to verify the release's build attestation: {e}")
+        })?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "build attestation did not verify: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+        .into())
+    }
+}
+
+/// Check `bytes` against a `sha256sum`-style line (`<hex>  <file name>`).
+fn verify_checksum(bytes: &[u8], checksum_file: &str) -> Result<(), SeherError> {
+    let expected = checksum_file
+        .split_whitespace()
+        .next()
+        .ok_or("the published checksum is empty")?;
+    let actual = Sha256::digest(bytes)
+        .iter()
+        .fold(String::new(), |mut hex, b| {
+            let _ = write!(hex, "{b:02x}");
+            hex
+        });
+    if actual.eq_ignore_ascii_case(expected) {
+        Ok(())
+    } else {
+        Err(format!("checksum mismatch: expected {expected}, downloaded {actual}").into())
+    }
+}
+
+/// The `seher` executable anywhere under `dir` (archives nest it in a directory).
+fn find_binary(dir: &Path) -> Option<PathBuf> {
+    for entry in std::fs::read_dir(dir).ok()?.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if let Some(found) = find_binary(&path) {
+                return Some(found);
+            }
+        } else if entry.file_name() == "seher" {
+            return Some(path);
+        }
+    }
+    None
+}
+
+/// Copy `binary` next to `exe` and rename it over `exe`, so a failed copy leaves the
+/// installed binary untouched.
+fn replace_executable(binary: &Path, exe: &Path) -> Result<(), SeherError> {
+    let parent = exe.parent().unwrap_or_else(|| Path::new("."));
+    let mut staged = tempfile::NamedTempFile::new_in(parent)?;
+    std::io::copy(&mut std::fs::File::open(binary)?, &mut staged)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        staged
+            .as_file()
+            .set_permissions(std::fs::Permissions::from_mode(0o755))?;
+    }
+    staged.persist(exe).map_err(|e| e.error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    fn asset(name: &str) -> Asset {
+        Asset {
+            name: name.to_string(),
+            browser_download_url: format!("https://example.com/{name}"),
+        }
+    }
+
+    #[test]
+    fn downloads_only_release_assets_of_the_repository() {
+        assert!(is_release_download(&format!(
+            "https://github.com/{REPOSITORY}/releases/download/v0.0.33/seher.tar.xz"
+        )));
+        assert!(!is_release_download(
+            "http://127.0.0.1:8080/releases/download/v0.0.33/seher.tar.xz"
+        ));
+        assert!(!is_release_download(
+            "https://github.com/someone/seher/releases/download/v0.0.33/seher.tar.xz"
+        ));
+        for url in [
+            "https://github.com.evil.example/{REPOSITORY}/releases/download/v1/seher.tar.xz",
+            "https://evil.example/github.com/{REPOSITORY}/releases/download/v1/seher.tar.xz",
+            "https://github.com@evil.example/{REPOSITORY}/releases/download/v1/seher.tar.xz",
+            "https://github.com:8443/{REPOSITORY}/releases/download/v1/seher.tar.xz",
+            "https://github.com/{REPOSITORY}/releases/download/../../../evil/seher/x/y",
+            "https://github.com/{REPOSITORY}/releases/download/v1/%2e%2e/seher.tar.xz",
+            "https://github.com/{REPOSITORY}/releases/download/v1/",
+            "https://github.com/{REPOSITORY}/releases/download/v1/nested/seher.tar.xz",
+        ] {
+            let url = url.replace("{REPOSITORY}", REPOSITORY);
+            assert!(!is_release_download(&url), "{url}");
+        }
+    }
+
+    #[test]
+    fn leaves_package_manager_installs_alone() {
+        let manager = |path: &str| package_manager(Path::new(path)).map(|(name, _)| name);
+        assert_eq!(
+            manager("/opt/homebrew/Cellar/seher/0.0.32/bin/seher"),
+            Some("Homebrew")
+        );
+        assert_eq!(
+            manager("/home/linuxbrew/.linuxbrew/Cellar/seher/0.0.32/bin/seher"),
+            Some("Homebrew")
+        );
+        assert_eq!(
+            manager("/nix/store/abc-seher-0.0.32/bin/seher"),
+            Some("Nix")
+        );
+        assert_eq!(manager("/home/me/.cargo/bin/seher"), Some("cargo"));
+        assert_eq!(manager("/home/me/.local/bin/seher"), None);
+    }
+
+    #[test]
+    fn compares_versions_numerically() {
+        assert!(is_newer("0.0.33", "0.0.32"));
+        assert!(is_newer("v0.1.0", "0.0.99"));
+        assert!(is_newer("0.0.10", "0.0.9"));
+        assert!(is_newer("1.0", "0.9.9"));
+        assert!(!is_newer("0.0.32", "0.0.32"));
+        assert!(!is_newer("0.0.32-rc.1", "0.0.32"));
+        assert!(!is_newer("0.0.31", "0.0.32"));
+    }
+
+    #[test]
+    fn picks_the_archive_with_its_checksum() {
+        let release = Release {
+            tag_name: "v0.0.33".to_string(),
+            html_url: String::new(),
+            assets: vec![
+                asset("sehercode-x86_64-unknown-linux-gnu.tar.xz"),
+                asset("sehercode-x86_64-unknown-linux-gnu.tar.xz.sha256"),
+                asset("sehercode-aarch64-apple-darwin.tar.xz"),
+            ],
+        };
+
+        let (archive, checksum) = release
+            .archive_for("x86_64-unknown-linux-gnu")
+            .map(|(a, c)| (a.name.as_str(), c.name.as_str()))
+            .unzip();
+        assert_eq!(archive, Some("sehercode-x86_64-unknown-linux-gnu.tar.xz"));
+        assert_eq!(
+            checksum,
+            Some("sehercode-x86_64-unknown-linux-gnu.tar.xz.sha256")
+        );
+        // No checksum published: refuse rather than install unverified.
+        assert!(release.archive_for("aarch64-apple-darwin").is_none());
+        assert_eq!(release.version(), "0.0.33");
+    }
+
+    #[test]
+    fn verifies_sha256_checksums() {
+        let digest = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        assert!(verify_checksum(b"hello", &format!("{digest}  seher.tar.xz\n")).is_ok());
+        assert!(verify_checksum(b"hello!", &format!("{digest}  seher.tar.xz\n")).is_err());
+        assert!(verify_checksum(b"hello", "").is_err());
+    }
+
+    #[test]
+    fn update_check_runs_once_per_interval_and_hints_newer_releases() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        let check = UpdateCheck::new(dir.path().join("update-check.json"));
+        let now = Utc::now();
+
+        assert!(check.hint().is_none());
+        // Claimed before the request, so a failed check is not retried until the interval.
+        assert!(check.claim(now));
+        assert!(!check.claim(now + chrono::TimeDelta::hours(23)));
+        assert!(check.claim(now + chrono::TimeDelta::hours(25)));
+
+        check.record_latest(CURRENT_VERSION)?;
+        assert!(check.hint().is_none());
+        check.record_latest("999.0.0")?;
+        assert!(check.hint().is_some_and(|hint| hint.contains("999.0.0")));
+        assert!(!check.claim(now + chrono::TimeDelta::hours(26)));
+        Ok(())
+    }
+
+    #[test]
+    fn replaces_the_executable_in_place() -> TestResult {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir(dir.path().join("sehercode-x86_64-unknown-linux-gnu"))?;
+        let binary = dir.path().join("sehercode-x86_64-unknown-linux-gnu/seher");
+        std::fs::write(&binary, "new")?;
+        let exe = dir.path().join("installed");
+        std::fs::write(&exe, "old")?;
+
+        assert_eq!(find_binary(dir.path()), Some(binary.clone()));
+        replace_executable(&binary, &exe)?;
+        assert_eq!(std::fs::read_to_string(&exe)?, "new");
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+//! Single-key input while waiting, without requiring Enter.
+
+use std::time::Duration;
+
+/// Puts the terminal into non-canonical, no-echo mode until dropped.
+///
+/// Signals (Ctrl+C) keep working. Does nothing when stdin is not a terminal.
+pub struct KeyInput {
+    #[cfg(unix)]
+    saved: Option<libc::termios>,
+}
+
+impl KeyInput {
+    /// Enable single-key input on stdin, or return `None` if stdin is not a terminal.
+    #[must_use]
+    pub fn enable() -> Option<Self> {
+        use std::io::IsTerminal;
+        if !std::io::stdin().is_terminal() {
+            return None;
+        }
+        #[cfg(unix)]
+        {
+            let saved = unix::enter_key_mode()?;
+            Some(Self { saved: Some(saved) })
+        }
+        #[cfg(not(unix))]
+        {
+            None
+        }
+    }
+
+    /// Wait up to `timeout` for a key press on a blocking thread.
+    pub async fn next_key(&self, timeout: Duration) -> Option<char> {
+        #[cfg(unix)]
+        {
+            tokio::task::spawn_blocking(move || unix::read_key(timeout))
+                .await
+                .ok()
+                .flatten()
+        }
+        #[cfg(not(unix))]
+        {
+            tokio::time::sleep(timeout).await;
+            None
+        }
+    }
+}
+
+impl Drop for KeyInput {
+    fn drop(&mut self) {
+        #[cfg(unix)]
+        if let Some(saved) = self.saved.take() {
+            unix::restore(&saved);
+        }
+    }
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::time::Duration;
+
+    pub(super) fn enter_key_mode() -> Option<libc::termios> {
+        // SAFETY: `termios` is plain data and is fully written by `tcgetattr` on success.
+        let mut saved: libc::termios = unsafe { std::mem::zeroed() };
+        // SAFETY: fd 0 is valid for the life of the process and `saved` is a valid pointer.
+        if unsafe { libc::tcgetattr(libc::STDIN_FILENO, &raw mut saved) } != 0 {
+            return None;
+        }
+        let mut raw = saved;
+        raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+        raw.c_cc[libc::VMIN] = 1;
+        raw.c_cc[libc::VTIME] = 0;
+        // SAFETY: as above; `raw` is a valid, initialized `termios`.
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw const raw) } != 0 {
+            return None;
+        }
+        Some(saved)
+    }
+
+    pub(super) fn restore(saved: &libc::termios) {
+        // SAFETY: `saved` was filled by `tcgetattr` on the same descriptor.
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, saved);
+        }
+    }
+
+    pub(super) fn read_key(timeout: Duration) -> Option<char> {
+        let mut fds = libc::pollfd {
+            fd: libc::STDIN_FILENO,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let millis = libc::c_int::try_from(timeout.as_millis()).unwrap_or(libc::c_int::MAX);
+        // SAFETY: `fds` points to exactly one valid `pollfd`.
+        if unsafe { libc::poll(&raw mut fds, 1, millis) } <= 0 {
+            return None;
+        }
+        let mut byte = 0u8;
+        // SAFETY: reading at most one byte into a valid one-byte buffer.
+        let read = unsafe { libc::read(libc::STDIN_FILENO, (&raw mut byte).cast(), 1) };
+        (read == 1).then(|| char::from(byte))
+    }
+}
@@ -0,0 +1,80 @@
+pub mod keys;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use std::time::Duration;
+
+/// Longest single sleep between wall-clock checks.
+///
+/// Monotonic timers stop while the machine is suspended, so the remaining time is
+/// recomputed from the wall clock at least this often.
+pub const TICK: Duration = Duration::from_secs(1);
+
+/// Snapshot of a wait passed to the progress callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaitProgress {
+    pub deadline: DateTime<Utc>,
+    /// Length of the whole wait; grows if the clock is set back past the start.
+    pub total: TimeDelta,
+    pub remaining: TimeDelta,
+}
+
+impl WaitProgress {
+    /// Time waited so far.
+    #[must_use]
+    pub fn elapsed(&self) -> TimeDelta {
+        self.total - self.remaining
+    }
+}
+
+/// Sleep until the wall clock reaches `deadline`, calling `on_progress` about once per tick.
+///
+/// The remaining time is derived from the wall clock on every tick, so laptop sleep and
+/// system clock adjustments are picked up within one tick instead of desynchronizing
+/// the countdown.
+pub async fn sleep_until<F>(deadline: DateTime<Utc>, mut on_progress: F)
+where
+    F: FnMut(&WaitProgress),
+{
+    let mut total = (deadline - Utc::now()).max(TimeDelta::zero());
+    loop {
+        let remaining = deadline - Utc::now();
+        if remaining <= TimeDelta::zero() {
+            return;
+        }
+        total = total.max(remaining);
+        on_progress(&WaitProgress {
+            deadline,
+            total,
+            remaining,
+        });
+        let step = remaining.to_std().map_or(TICK, |r| r.min(TICK));
+        tokio::time::sleep(step).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type TestResult = Result<(), Box<dyn std::error::Error>>;
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn returns_immediately_for_past_deadline() {
+        let mut calls = 0;
+        sleep_until(Utc::now() - TimeDelta::seconds(1), |_| calls += 1).await;
+        assert_eq!(calls, 0);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn reports_progress_until_deadline() -> TestResult {
+        let deadline = Utc::now() + TimeDelta::milliseconds(50);
+        let mut last = None;
+        sleep_until(deadline, |p| last = Some(*p)).await;
+        assert!(Utc::now() >= deadline);
+        let last = last.ok_or("expected a progress callback")?;
+        assert_eq!(last.deadline, deadline);
+        assert!(last.remaining <= last.total);
+        assert_eq!(last.elapsed(), last.total - last.remaining);
+        Ok(())
+    }
+}
@@ -0,0 +1,25 @@
+//! Warnings from best-effort work (logs, hooks, notifications, metric exports) whose
+//! failures must not abort the caller.
+//!
+//! The library never prints them itself: they go to the handler installed with
+//! [`set_handler`], and are dropped when none is installed.
+
+use std::sync::OnceLock;
+
+type Handler = Box<dyn Fn(&str) + Send + Sync>;
+
+static HANDLER: OnceLock<Handler> = OnceLock::new();
+
+/// Send warnings to `handler` for the rest of the process.
+///
+/// Returns `false` if a handler was already installed.
+pub fn set_handler(handler: impl Fn(&str) + Send + Sync + 'static) -> bool {
+    HANDLER.set(Box::new(handler)).is_ok()
+}
+
+/// Report `message` to the installed handler, if any.
+pub fn warn(message: impl std::fmt::Display) {
+    if let Some(handler) = HANDLER.get() {
+        handler(&message.to_string());
+    }
+}
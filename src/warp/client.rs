@@ -1,5 +1,3 @@
-use std::time::Duration;
-
 use super::types::WarpLimitInfoResponse;
 
 const GRAPHQL_URL: &str = "https://api.warp.dev/graphql";
@@ -14,7 +12,7 @@ impl WarpClient {
     /// Returns an error if the GraphQL request fails or the response cannot be parsed.
     pub async fn fetch_limit_info(
         api_key: &str,
-    ) -> Result<WarpLimitInfoResponse, Box<dyn std::error::Error>> {
+    ) -> Result<WarpLimitInfoResponse, Box<dyn std::error::Error + Send + Sync>> {
         let client = Self::build_client()?;
         let response = client
             .post(GRAPHQL_URL)
@@ -35,8 +33,6 @@ impl WarpClient {
     }
 
     fn build_client() -> Result<reqwest::Client, reqwest::Error> {
-        reqwest::Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
+        crate::http::shared_client()
     }
 }
@@ -20,7 +20,7 @@ use tokio::net::TcpListener;
 
 use chrono::Local;
 
-use crate::config::{AgentConfig, ProviderConfig, Settings};
+use crate::config::{AgentConfig, PromptInput, ProviderConfig, Settings};
 
 // -- shared state --------------------------------------------------------------
 
@@ -759,6 +759,7 @@ async fn add_agent_handler(State(state): State<SharedState>) -> HandlerResult {
     let mut settings = lock_settings(&state)?;
     settings.agents.push(AgentConfig {
         command: "new-agent".to_string(),
+        name: None,
         args: vec![],
         models: None,
         arg_maps: HashMap::new(),
@@ -769,6 +770,13 @@ async fn add_agent_handler(State(state): State<SharedState>) -> HandlerResult {
         pre_command: vec![],
         active: None,
         inactive: None,
+        detect_rate_limit: false,
+        rate_limit_patterns: vec![],
+        input: PromptInput::Arg,
+        resume_args: vec![],
+        accounts: vec![],
+        retry: None,
+        cooldown: None,
     });
     let model_keys = collect_model_keys(&settings);
     let now = Local::now();
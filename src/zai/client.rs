@@ -1,5 +1,3 @@
-use std::time::Duration;
-
 use super::types::ZaiUsageResponse;
 
 const DEFAULT_QUOTA_URL: &str = "https://api.z.ai/api/paas/quota/limit";
@@ -13,7 +11,7 @@ impl ZaiClient {
     pub async fn fetch_quota(
         api_key: &str,
         quota_url: Option<&str>,
-    ) -> Result<ZaiUsageResponse, Box<dyn std::error::Error>> {
+    ) -> Result<ZaiUsageResponse, Box<dyn std::error::Error + Send + Sync>> {
         let url = quota_url.unwrap_or(DEFAULT_QUOTA_URL);
         let client = Self::build_client()?;
         let response = client
@@ -34,8 +32,6 @@ impl ZaiClient {
     }
 
     fn build_client() -> Result<reqwest::Client, reqwest::Error> {
-        reqwest::Client::builder()
-            .timeout(Duration::from_secs(30))
-            .build()
+        crate::http::shared_client()
     }
 }